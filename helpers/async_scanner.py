@@ -27,6 +27,13 @@ try:
 except ImportError:
     HAS_REQUESTS = False
 
+try:
+    import netscan_core as _rust
+    HAS_RUST = True
+except ImportError:
+    HAS_RUST = False
+    _rust = None
+
 
 @dataclass
 class Device:
@@ -307,8 +314,17 @@ class AsyncScanner:
         """Resolve hostnames for multiple devices"""
         self.log(f"Resolving hostnames for {len(devices)} devices...")
         
-        tasks = [self.resolve_hostname(d.ip) for d in devices]
-        hostnames = await asyncio.gather(*tasks)
+        if HAS_RUST:
+            # Rust path shares a TTL/negative cache across scan cycles
+            loop = asyncio.get_event_loop()
+            names = await loop.run_in_executor(
+                self._executor,
+                lambda: _rust.reverse_dns_batch([d.ip for d in devices])
+            )
+            hostnames = [names.get(d.ip) or "" for d in devices]
+        else:
+            tasks = [self.resolve_hostname(d.ip) for d in devices]
+            hostnames = await asyncio.gather(*tasks)
         
         for device, hostname in zip(devices, hostnames):
             if hostname and not device.hostname:
@@ -15,6 +15,7 @@ Otherwise, pure Python implementations are used.
 
 import re
 import ipaddress
+import warnings
 from typing import List, Dict, Optional, Tuple
 from pathlib import Path
 
@@ -27,6 +28,14 @@ except ImportError:
     _rust = None
 
 
+def _legacy(func, *args):
+    """Call a Rust parser for its pre-ScanResult layout, which the Python
+    fallbacks here also return"""
+    with warnings.catch_warnings():
+        warnings.simplefilter("ignore", DeprecationWarning)
+        return func(*args, legacy=True)
+
+
 # =============================================================================
 # MAC Address Functions
 # =============================================================================
@@ -55,6 +64,23 @@ def normalize_mac(mac: str) -> str:
     return _py_normalize_mac(mac)
 
 
+def _py_validate_mac(mac: str) -> bool:
+    """Pure Python MAC validation"""
+    mac = mac.strip()
+    return bool(
+        re.fullmatch(r'[0-9A-Fa-f]{1,4}\.[0-9A-Fa-f]{1,4}\.[0-9A-Fa-f]{1,4}', mac)
+        or re.fullmatch(r'[0-9A-Fa-f]{2}([:\-])(?:[0-9A-Fa-f]{2}\1){4}[0-9A-Fa-f]{2}', mac)
+        or re.fullmatch(r'[0-9A-Fa-f]{12}', mac)
+    )
+
+
+def validate_mac(mac: str) -> bool:
+    """Whether mac is a complete MAC address (colon, hyphen, bare or Cisco dotted)"""
+    if HAS_RUST:
+        return _rust.validate_mac(mac)
+    return _py_validate_mac(mac)
+
+
 def normalize_macs(macs: List[str]) -> List[str]:
     """Batch normalize MAC addresses"""
     if HAS_RUST:
@@ -74,27 +100,78 @@ def extract_oui(mac: str) -> str:
 # OUI Database Functions
 # =============================================================================
 
-def _py_parse_oui_file(filepath: str) -> Dict[str, str]:
-    """Pure Python OUI file parser"""
+# Defaults of the Rust parser's parse quality check
+OUI_MIN_ENTRIES = 1000
+OUI_MIN_LINE_RATIO = 0.1
+
+if HAS_RUST:
+    ParseQualityError = _rust.ParseQualityError
+else:
+    class ParseQualityError(ValueError):
+        """A database file parsed into far fewer entries than a healthy copy has"""
+
+# IEEE oui.txt "(hex)" lines or nmap-mac-prefixes "XXXXXX Vendor" lines
+_OUI_LINE = re.compile(
+    r'^(?:([0-9A-Fa-f]{2}[:\-]?[0-9A-Fa-f]{2}[:\-]?[0-9A-Fa-f]{2})\s+\(hex\)\s+'
+    r'|([0-9A-Fa-f]{6})[ \t]+)([^(\s].*)$'
+)
+
+
+def _py_parse_oui_file(filepath: str, min_entries: int = OUI_MIN_ENTRIES,
+                       min_line_ratio: float = OUI_MIN_LINE_RATIO,
+                       strict: bool = True) -> Dict[str, str]:
+    """Pure Python OUI file parser, with the same quality check as Rust"""
     oui_db = {}
-    pattern = re.compile(r'^([0-9A-Fa-f]{2}[:\-]?[0-9A-Fa-f]{2}[:\-]?[0-9A-Fa-f]{2})\s+\(hex\)\s+(.+)$')
+    nonempty = 0
+    unmatched = []
+    # Failing "(hex)" lines, lines that open with a hex pair
+    hex_led = []
     
-    with open(filepath, 'r', errors='ignore') as f:
+    with open(filepath, 'r', errors='replace') as f:
         for line in f:
-            match = pattern.match(line)
+            line = line.strip()
+            if not line:
+                continue
+            nonempty += 1
+            match = _OUI_LINE.match(line)
             if match:
-                prefix = match.group(1).upper().replace('-', ':')
-                vendor = match.group(2).strip()
-                oui_db[prefix] = vendor
+                hex_digits = re.sub(r'[:\-]', '', match.group(1) or match.group(2)).upper()
+                prefix = ':'.join(hex_digits[i:i+2] for i in range(0, 6, 2))
+                oui_db[prefix] = match.group(3).strip()
+            elif '(hex)' in line.lower():
+                if len(unmatched) < 3:
+                    unmatched.append(line)
+            elif (len(hex_led) < 3 and re.match(r'^[0-9A-Fa-f]{2}', line)
+                  and '(base 16)' not in line):
+                hex_led.append(line)
+    
+    unmatched = unmatched or hex_led
+    ratio = len(oui_db) / max(nonempty, 1)
+    if len(oui_db) < min_entries or ratio < min_line_ratio:
+        message = (
+            f"{filepath} parsed into {len(oui_db)} OUI entries from {nonempty} non-empty lines "
+            f"(expected at least {min_entries} entries and {min_line_ratio * 100:.0f}% of lines); "
+            f"the file format may have changed. Unmatched lines: {unmatched}"
+        )
+        if strict:
+            raise ParseQualityError(message)
+        warnings.warn(message, RuntimeWarning, stacklevel=3)
     
     return oui_db
 
 
-def parse_oui_file(filepath: str) -> Dict[str, str]:
-    """Parse OUI database file"""
+def parse_oui_file(filepath: str, min_entries: int = OUI_MIN_ENTRIES,
+                   min_line_ratio: float = OUI_MIN_LINE_RATIO,
+                   strict: bool = True) -> Dict[str, str]:
+    """Parse OUI database file (IEEE oui.txt or nmap-mac-prefixes)
+
+    Raises ParseQualityError when the file parses into fewer than
+    min_entries entries or from less than min_line_ratio of its lines;
+    with strict=False only warns.
+    """
     if HAS_RUST:
-        return _rust.parse_oui_file(filepath)
-    return _py_parse_oui_file(filepath)
+        return _rust.parse_oui_file(filepath, min_entries, min_line_ratio, strict)
+    return _py_parse_oui_file(filepath, min_entries, min_line_ratio, strict)
 
 
 def lookup_oui(oui_db: Dict[str, str], mac: str) -> Optional[str]:
@@ -192,14 +269,14 @@ def _py_parse_arp_output(output: str) -> List[Tuple[str, str, str]]:
 def parse_arp_output(output: str) -> List[Tuple[str, str, str]]:
     """Parse ARP table output"""
     if HAS_RUST:
-        return _rust.parse_arp_output(output)
+        return _legacy(_rust.parse_arp_output, output)
     return _py_parse_arp_output(output)
 
 
 def parse_pipe_file(filepath: str) -> List[Dict[str, str]]:
     """Parse pipe-delimited file"""
     if HAS_RUST:
-        return _rust.parse_pipe_file(filepath)
+        return _legacy(_rust.parse_pipe_file, filepath)
     
     with open(filepath, 'r') as f:
         lines = f.readlines()
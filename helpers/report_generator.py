@@ -46,6 +46,7 @@ class ReportGenerator:
         self.title = title
         self.devices: List[Dict] = []
         self.previous_devices: List[Dict] = []
+        self.compliance: Dict[str, Any] = {}
         self.scan_time = datetime.now()
         self.stats: Dict[str, Any] = {}
     
@@ -58,6 +59,10 @@ class ReportGenerator:
         """Load previous scan for comparison"""
         self.previous_devices = devices
     
+    def load_compliance(self, compliance: Dict[str, Any]):
+        """Load a compliance report (netscan_core.check_compliance output)"""
+        self.compliance = compliance
+    
     def load_from_json(self, filepath: str):
         """Load devices from JSON file"""
         with open(filepath, 'r') as f:
@@ -508,6 +513,9 @@ class ReportGenerator:
         if self.previous_devices:
             report['comparison'] = self._get_comparison()
         
+        if self.compliance:
+            report['compliance'] = self.compliance
+        
         indent = 2 if pretty else None
         return json.dumps(report, indent=indent, default=str)
     
@@ -566,6 +574,35 @@ class ReportGenerator:
                 for d in comparison['gone_devices']:
                     lines.append(f"| {d.get('ip', '')} | {d.get('mac', '')} | {d.get('vendor', '')} |")
         
+        # Compliance
+        if self.compliance:
+            summary = self.compliance.get('summary', {})
+            lines.extend([
+                "",
+                "## Port Compliance",
+                "",
+                f"- **Compliant hosts:** {summary.get('compliant', 0)}",
+                f"- **Violations:** {summary.get('violations', 0)}",
+                f"- **Not covered by baseline:** {summary.get('uncovered', 0)}",
+            ])
+            
+            if self.compliance.get('violations'):
+                lines.extend(["", "### Violations", ""])
+                lines.append("| IP | Hostname | Rule | Unexpected Ports | Missing Ports |")
+                lines.append("| --- | --- | --- | --- | --- |")
+                for v in self.compliance['violations']:
+                    unexpected = ', '.join(str(p) for p in v.get('unexpected_ports', []))
+                    missing = ', '.join(str(p) for p in v.get('missing_ports', []))
+                    lines.append(
+                        f"| {v.get('ip', '')} | {v.get('hostname', '') or ''} | "
+                        f"{v.get('rule', '')} ({v.get('match_type', '')}) | {unexpected} | {missing} |"
+                    )
+            
+            if self.compliance.get('uncovered'):
+                lines.extend(["", "### Hosts Without a Baseline Rule", ""])
+                for ip in self.compliance['uncovered']:
+                    lines.append(f"- {ip}")
+        
         # Device list
         lines.extend([
             "",
@@ -0,0 +1,43 @@
+//! Batch forward resolution: one blocking getaddrinfo per name on Rayon's
+//! pool versus `resolve_names` (Tokio tasks, resolver calls capped by a
+//! semaphore). Names come from /etc/hosts, so no network is needed and the
+//! numbers measure scheduling, not DNS latency.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, ToSocketAddrs};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use netscan_core::bench_api::resolve_names;
+use rayon::prelude::*;
+
+const TIMEOUT_MS: u64 = 2000;
+const MAX_CONCURRENT: usize = 256;
+
+fn resolve_names_rayon(names: &[String]) -> HashMap<String, String> {
+    names
+        .par_iter()
+        .filter_map(|name| {
+            let addrs: Vec<IpAddr> = (name.as_str(), 0).to_socket_addrs().ok()?.map(|a| a.ip()).collect();
+            let address = addrs.iter().find(|a| a.is_ipv4()).or(addrs.first())?;
+            Some((name.clone(), address.to_string()))
+        })
+        .collect()
+}
+
+fn batch_resolution(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch_dns_resolve");
+    group.sample_size(10);
+    for size in [10, 100, 1000, 10000] {
+        let names = vec!["localhost".to_string(); size];
+        group.bench_with_input(BenchmarkId::new("rayon", size), &names, |b, names| {
+            b.iter(|| resolve_names_rayon(names))
+        });
+        group.bench_with_input(BenchmarkId::new("tokio", size), &names, |b, names| {
+            b.iter(|| resolve_names(names.clone(), TIMEOUT_MS, MAX_CONCURRENT))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, batch_resolution);
+criterion_main!(benches);
@@ -0,0 +1,99 @@
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::{Duration, Instant};
+use pnet::datalink::{self, Channel, NetworkInterface};
+use pnet::packet::arp::{ArpHardwareTypes, ArpOperations, ArpPacket, MutableArpPacket};
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket, MutableEthernetPacket};
+use pnet::packet::Packet;
+use pnet::util::MacAddr;
+
+// =============================================================================
+// ARP Ping (local subnets only)
+// =============================================================================
+
+/// Interface with an IPv4 network containing `ip`, and our address on it
+pub fn local_interface_for(ip: Ipv4Addr) -> Option<(NetworkInterface, Ipv4Addr)> {
+    datalink::interfaces().into_iter().find_map(|iface| {
+        if !iface.is_up() || iface.is_loopback() || iface.mac.is_none() {
+            return None;
+        }
+        let source = iface.ips.iter().find_map(|net| match net.ip() {
+            IpAddr::V4(addr) if net.contains(IpAddr::V4(ip)) => Some(addr),
+            _ => None,
+        })?;
+        Some((iface, source))
+    })
+}
+
+fn arp_request(source_mac: MacAddr, source_ip: Ipv4Addr, target: Ipv4Addr) -> [u8; 42] {
+    let mut buffer = [0u8; 42];
+    {
+        let mut ethernet = MutableEthernetPacket::new(&mut buffer).expect("buffer fits ethernet header");
+        ethernet.set_destination(MacAddr::broadcast());
+        ethernet.set_source(source_mac);
+        ethernet.set_ethertype(EtherTypes::Arp);
+    }
+    let mut arp = MutableArpPacket::new(&mut buffer[14..]).expect("buffer fits ARP packet");
+    arp.set_hardware_type(ArpHardwareTypes::Ethernet);
+    arp.set_protocol_type(EtherTypes::Ipv4);
+    arp.set_hw_addr_len(6);
+    arp.set_proto_addr_len(4);
+    arp.set_operation(ArpOperations::Request);
+    arp.set_sender_hw_addr(source_mac);
+    arp.set_sender_proto_addr(source_ip);
+    arp.set_target_hw_addr(MacAddr::zero());
+    arp.set_target_proto_addr(target);
+    buffer
+}
+
+/// Broadcast an ARP request for `ip` and wait for its reply
+///
+/// Returns Ok(Some((mac, rtt_ms))) on reply, Ok(None) on timeout, and Err if
+/// the target isn't on a directly attached subnet or capture isn't permitted.
+pub fn arp_ping(ip: Ipv4Addr, timeout_ms: u64) -> Result<Option<(String, f64)>, String> {
+    let (iface, source_ip) = local_interface_for(ip)
+        .ok_or_else(|| format!("{} is not on a directly attached subnet", ip))?;
+    let source_mac = iface.mac.unwrap_or_else(MacAddr::zero);
+
+    let config = datalink::Config {
+        read_timeout: Some(Duration::from_millis(50)),
+        ..Default::default()
+    };
+    let (mut tx, mut rx) = match datalink::channel(&iface, config) {
+        Ok(Channel::Ethernet(tx, rx)) => (tx, rx),
+        Ok(_) => return Err(format!("Unsupported channel type on {}", iface.name)),
+        Err(e) => {
+            return Err(format!(
+                "Cannot open {} for ARP ({}); {}",
+                iface.name, e, crate::capabilities::grant_hint("raw_packet")
+            ))
+        }
+    };
+
+    let start = Instant::now();
+    let deadline = start + Duration::from_millis(timeout_ms);
+    match tx.send_to(&arp_request(source_mac, source_ip, ip), None) {
+        Some(Ok(())) => {}
+        Some(Err(e)) => return Err(format!("Cannot send ARP request: {}", e)),
+        None => return Err("Cannot send ARP request".to_string()),
+    }
+
+    while Instant::now() < deadline {
+        let Ok(frame) = rx.next() else {
+            continue;
+        };
+        let Some(ethernet) = EthernetPacket::new(frame) else {
+            continue;
+        };
+        if ethernet.get_ethertype() != EtherTypes::Arp {
+            continue;
+        }
+        let Some(arp) = ArpPacket::new(ethernet.payload()) else {
+            continue;
+        };
+        if arp.get_operation() == ArpOperations::Reply && arp.get_sender_proto_addr() == ip {
+            let mac = crate::normalize_mac(&arp.get_sender_hw_addr().to_string());
+            return Ok(Some((mac, start.elapsed().as_secs_f64() * 1000.0)));
+        }
+    }
+    Ok(None)
+}
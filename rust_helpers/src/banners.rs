@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rayon::prelude::*;
+use regex::Regex;
+
+use crate::fingerprint::sha256_hex;
+use crate::probes::service_name;
+
+// =============================================================================
+// Banner Deduplication
+// =============================================================================
+//
+// Banners are grouped by the SHA-256 of a normalized form: the parts that
+// differ between hosts running the same software (timestamps, addresses,
+// hostnames, session tokens) are replaced by placeholders, then whitespace
+// is collapsed. "220 mail1.corp.example ESMTP Postfix" and "220
+// mail2.corp.example ESMTP Postfix" become one entry, "220 <host> ESMTP
+// Postfix". Extra patterns are applied after the defaults, in order.
+
+/// (regex, replacement) applied by default, in order
+pub const DEFAULT_BANNER_PATTERNS: &[(&str, &str)] = &[
+    // ISO 8601 date, optionally with time and zone
+    (r"\b\d{4}-\d{2}-\d{2}(?:[T ]\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?(?:Z|[+-]\d{2}:?\d{2})?)?\b", "<date>"),
+    // RFC 822 / ctime style: "Mon, 01 Jan 2024", "Thu Jan 13"
+    (r"(?i)\b(?:mon|tue|wed|thu|fri|sat|sun)[a-z]*,?\s+(?:\d{1,2}\s+)?(?:jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*(?:\s+\d{1,2}\b)?(?:\s+\d{4}\b)?", "<date>"),
+    (r"\b\d{1,2}:\d{2}:\d{2}(?:\.\d+)?(?:\s*(?:[A-Z]{3,4}|[+-]\d{4}))?\b", "<time>"),
+    (r"\b\d{1,3}(?:\.\d{1,3}){3}\b", "<ip>"),
+    (r"\b[0-9A-Fa-f]{2}(?:[:-][0-9A-Fa-f]{2}){5}\b", "<mac>"),
+    (r"(?i)\b(?:[a-z0-9](?:[a-z0-9-]*[a-z0-9])?\.)+[a-z]{2,}\b", "<host>"),
+    // Session IDs, nonces, key hashes
+    (r"\b[0-9A-Fa-f]{16,}\b", "<hex>"),
+];
+
+fn compile(pattern: &str) -> PyResult<Regex> {
+    Regex::new(pattern).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid pattern '{}': {}", pattern, e))
+    })
+}
+
+/// Normalization patterns: the defaults (with `use_defaults`) then `extra`
+fn banner_patterns(extra: Option<Vec<(String, String)>>, use_defaults: bool) -> PyResult<Vec<(Regex, String)>> {
+    let defaults = DEFAULT_BANNER_PATTERNS
+        .iter()
+        .filter(|_| use_defaults)
+        .map(|(pattern, replacement)| (pattern.to_string(), replacement.to_string()));
+    defaults
+        .chain(extra.unwrap_or_default())
+        .map(|(pattern, replacement)| Ok((compile(&pattern)?, replacement)))
+        .collect()
+}
+
+fn normalize(banner: &str, patterns: &[(Regex, String)]) -> String {
+    let mut text = banner.to_string();
+    for (regex, replacement) in patterns {
+        text = regex.replace_all(&text, replacement.as_str()).into_owned();
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// A banner as `dedupe_banners` normalizes it (for checking patterns)
+#[pyfunction]
+#[pyo3(signature = (banner, patterns=None, use_defaults=true))]
+pub fn normalize_banner(banner: &str, patterns: Option<Vec<(String, String)>>, use_defaults: bool) -> PyResult<String> {
+    Ok(normalize(banner, &banner_patterns(patterns, use_defaults)?))
+}
+
+/// Group identical banners across hosts
+///
+/// `results` maps each IP to its per-port probe output, as
+/// `{ip: scan_host_services(ip, ...)}`; ports whose dict has no `field`
+/// ("banner" by default; "server" groups HTTP Server headers) are skipped.
+/// Returns one dict per distinct normalized banner, most common first:
+/// {hash, banner (normalized), example (the first raw banner seen), count,
+/// hosts: [(ip, port), ...]}. `patterns` are extra (regex, replacement)
+/// pairs run after DEFAULT_BANNER_PATTERNS, or instead of them with
+/// `use_defaults=False`.
+#[pyfunction]
+#[pyo3(signature = (results, patterns=None, use_defaults=true, field="banner"))]
+pub fn dedupe_banners(
+    py: Python,
+    results: HashMap<String, HashMap<u16, HashMap<String, String>>>,
+    patterns: Option<Vec<(String, String)>>,
+    use_defaults: bool,
+    field: &str,
+) -> PyResult<Vec<HashMap<String, PyObject>>> {
+    let patterns = banner_patterns(patterns, use_defaults)?;
+    let mut seen: Vec<(&str, u16, &str)> = results
+        .iter()
+        .flat_map(|(ip, ports)| {
+            ports.iter().filter_map(move |(port, info)| {
+                info.get(field).filter(|b| !b.trim().is_empty()).map(|b| (ip.as_str(), *port, b.as_str()))
+            })
+        })
+        .collect();
+    // Input order is a dict's; sort so `example` and `hosts` are stable
+    seen.sort_unstable_by_key(|(ip, port, _)| (ip.parse::<std::net::IpAddr>().ok(), *ip, *port));
+
+    let normalized: Vec<String> = py.allow_threads(|| {
+        seen.par_iter().map(|(_, _, banner)| normalize(banner, &patterns)).collect()
+    });
+
+    struct Group<'a> {
+        banner: String,
+        example: &'a str,
+        hosts: Vec<(String, u16)>,
+    }
+    let mut groups: HashMap<String, Group> = HashMap::new();
+    for ((ip, port, raw), banner) in seen.iter().zip(normalized) {
+        let group = groups.entry(sha256_hex(&banner)).or_insert_with(|| Group { banner, example: raw, hosts: Vec::new() });
+        group.hosts.push((ip.to_string(), *port));
+    }
+
+    let mut groups: Vec<(String, Group)> = groups.into_iter().collect();
+    groups.sort_by(|(_, a), (_, b)| b.hosts.len().cmp(&a.hosts.len()).then_with(|| a.banner.cmp(&b.banner)));
+    Ok(groups
+        .into_iter()
+        .map(|(hash, group)| {
+            let mut map = HashMap::new();
+            map.insert("hash".to_string(), hash.into_py(py));
+            map.insert("banner".to_string(), group.banner.into_py(py));
+            map.insert("example".to_string(), group.example.into_py(py));
+            map.insert("count".to_string(), group.hosts.len().into_py(py));
+            map.insert("hosts".to_string(), group.hosts.into_py(py));
+            map
+        })
+        .collect())
+}
+
+// =============================================================================
+// Fingerprint Matching
+// =============================================================================
+
+/// Fill in `product` and `version` of each `dedupe_banners` entry
+///
+/// `patterns` is a table of (regex, product, version), tried in order
+/// against each entry's raw `example` (its `banner` if there is none); the
+/// first match wins. Product and version may refer to captures, e.g.
+/// (r"OpenSSH_(?P<v>[\w.]+)", "OpenSSH", "${v}") or (r"ESMTP (\w+)", "$1",
+/// ""). Unmatched entries get both set to "". Entries are copied, not
+/// modified; a pattern that doesn't compile raises ValueError.
+#[pyfunction]
+pub fn match_fingerprints<'py>(
+    py: Python<'py>,
+    banners: Vec<&'py PyDict>,
+    patterns: Vec<(String, String, String)>,
+) -> PyResult<Vec<&'py PyDict>> {
+    let table = patterns
+        .iter()
+        .map(|(pattern, product, version)| Ok((compile(pattern)?, product.as_str(), version.as_str())))
+        .collect::<PyResult<Vec<(Regex, &str, &str)>>>()?;
+    let texts = banners
+        .iter()
+        .map(|entry| match entry.get_item("example")?.or(entry.get_item("banner")?) {
+            Some(text) => text.extract::<String>(),
+            None => Ok(String::new()),
+        })
+        .collect::<PyResult<Vec<String>>>()?;
+
+    let matched: Vec<(String, String)> = py.allow_threads(|| {
+        texts
+            .par_iter()
+            .map(|text| {
+                table
+                    .iter()
+                    .find_map(|(regex, product, version)| {
+                        let caps = regex.captures(text)?;
+                        let (mut name, mut number) = (String::new(), String::new());
+                        caps.expand(product, &mut name);
+                        caps.expand(version, &mut number);
+                        Some((name.trim().to_string(), number.trim().to_string()))
+                    })
+                    .unwrap_or_default()
+            })
+            .collect()
+    });
+
+    banners
+        .into_iter()
+        .zip(matched)
+        .map(|(entry, (product, version))| {
+            let entry = entry.copy()?;
+            entry.set_item("product", product)?;
+            entry.set_item("version", version)?;
+            Ok(entry)
+        })
+        .collect()
+}
+
+// =============================================================================
+// Service Detection
+// =============================================================================
+//
+// Layered, most specific first: a banner pattern names the service with
+// high confidence; failing that, a TLS handshake shows the port speaks TLS;
+// failing that, the port's registered service is a medium-confidence
+// guess, and an unregistered port with nothing recognisable is "unknown"
+// (low).
+
+/// (regex, service, product, version); product and version may use the
+/// regex's captures. Tried in order, so specific patterns precede generic ones.
+const SERVICE_PATTERNS: &[(&str, &str, &str, &str)] = &[
+    // SSH identification strings
+    (r"^SSH-[\d.]+-OpenSSH[_-]([\w.]+)", "ssh", "OpenSSH", "$1"),
+    (r"^SSH-[\d.]+-dropbear_([\w.]+)", "ssh", "Dropbear sshd", "$1"),
+    (r"^SSH-[\d.]+-libssh[_-]([\w.]+)", "ssh", "libssh", "$1"),
+    (r"^SSH-[\d.]+-Cisco-([\w.]+)", "ssh", "Cisco SSH", "$1"),
+    (r"^SSH-[\d.]+-RomSShell_([\w.]+)", "ssh", "Allegro RomSShell", "$1"),
+    (r"^SSH-[\d.]+-([\w.-]+)", "ssh", "$1", ""),
+    // FTP greetings
+    (r"^220[- ].*\bProFTPD ([\w.]+)", "ftp", "ProFTPD", "$1"),
+    (r"^220[- ].*\(vsFTPd ([\w.]+)\)", "ftp", "vsftpd", "$1"),
+    (r"^220[- ].*Pure-FTPd", "ftp", "Pure-FTPd", ""),
+    (r"^220[- ].*FileZilla Server(?: version)? ?([\d][\w.]*)?", "ftp", "FileZilla ftpd", "$1"),
+    (r"^220[- ].*Serv-U FTP Server v([\w.]+)", "ftp", "Serv-U ftpd", "$1"),
+    (r"^220[- ].*Microsoft FTP Service", "ftp", "Microsoft ftpd", ""),
+    (r"^220[- ].*VMware Authentication Daemon Version ([\d.]+)", "vmware-auth", "VMware Authentication Daemon", "$1"),
+    // SMTP greetings
+    (r"^220[- ].*\bESMTP Postfix", "smtp", "Postfix smtpd", ""),
+    (r"^220[- ].*\bExim ([\w.]+)", "smtp", "Exim smtpd", "$1"),
+    (r"^220[- ].*\bSendmail ([\w.]+)", "smtp", "Sendmail", "$1"),
+    (r"^220[- ].*Microsoft ESMTP MAIL Service(?:, Version: ([\d.]+))?", "smtp", "Microsoft Exchange smtpd", "$1"),
+    (r"(?i)^220[- ].*\bE?SMTP\b", "smtp", "", ""),
+    (r"(?i)^220[- ].*\bFTP\b", "ftp", "", ""),
+    // Mail retrieval
+    (r"^\+OK.*\bDovecot\b", "pop3", "Dovecot pop3d", ""),
+    (r"^\+OK", "pop3", "", ""),
+    (r"^\* OK.*\bDovecot\b", "imap", "Dovecot imapd", ""),
+    (r"^\* OK.*Cyrus IMAP.*?v([\d.]+)", "imap", "Cyrus imapd", "$1"),
+    (r"^\* OK", "imap", "", ""),
+    // HTTP response heads
+    (r"(?im)^Server: Apache/?([\d.]*)", "http", "Apache httpd", "$1"),
+    (r"(?im)^Server: nginx/?([\d.]*)", "http", "nginx", "$1"),
+    (r"(?im)^Server: Microsoft-IIS/([\d.]+)", "http", "Microsoft IIS httpd", "$1"),
+    (r"(?im)^Server: lighttpd/?([\d.]*)", "http", "lighttpd", "$1"),
+    (r"(?im)^Server: ([^\s/]+)/?([\w.]*)", "http", "$1", "$2"),
+    (r"^HTTP/[12](?:\.\d)? \d{3}", "http", "", ""),
+    // Databases and caches
+    (r"(\d+\.\d+\.\d+)-MariaDB", "mysql", "MariaDB", "$1"),
+    (r"^.{0,4}?(\d+\.\d+\.\d+)[\w.-]*.*(?:mysql_native_password|caching_sha2_password)", "mysql", "MySQL", "$1"),
+    (r"redis_version:([\d.]+)", "redis", "Redis", "$1"),
+    (r"^-(?:ERR|NOAUTH|DENIED)\b", "redis", "Redis", ""),
+    (r"^VERSION ([\d.]+)", "memcache", "memcached", "$1"),
+    (r"SFATAL.*(?:unsupported frontend protocol|C0A000)", "postgresql", "PostgreSQL", ""),
+    // Other line protocols
+    (r"^RFB (\d{3}\.\d{3})", "vnc", "VNC", "$1"),
+    (r"^@RSYNCD: ([\d.]+)", "rsync", "rsync", "$1"),
+    (r"^AMQP", "amqp", "", ""),
+    (r"^SIP/2\.0 \d{3}", "sip", "", ""),
+    (r"(?i)^(?:\S+ )?NOTICE (?:AUTH|\*)", "irc", "", ""),
+    (r"(?i)(?:^|\s)(?:login|username):\s*$", "telnet", "", ""),
+];
+
+fn service_patterns() -> &'static [(Regex, &'static str, &'static str, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(Regex, &str, &str, &str)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        SERVICE_PATTERNS
+            .iter()
+            .map(|(pattern, service, product, version)| {
+                (Regex::new(pattern).expect("built-in service pattern"), *service, *product, *version)
+            })
+            .collect()
+    })
+}
+
+/// Registered services that are TLS from the first byte
+const TLS_SERVICES: &[&str] = &["https", "https-alt", "imaps", "pop3s", "ldaps", "submissions"];
+
+/// Whether a response starts like a TLS record (handshake or alert)
+fn looks_like_tls(banner: &str) -> bool {
+    let bytes = banner.as_bytes();
+    matches!(bytes, [0x15 | 0x16, 0x03, ..])
+}
+
+/// {service, product, version, confidence} for what `port` answered;
+/// `tls` when a TLS probe already saw a handshake
+pub fn detect_service(port: u16, banner: &str, tls: bool) -> HashMap<String, String> {
+    let registered = service_name(port);
+    let matched = service_patterns().iter().find_map(|(regex, service, product, version)| {
+        let caps = regex.captures(banner)?;
+        let (mut name, mut number) = (String::new(), String::new());
+        caps.expand(product, &mut name);
+        caps.expand(version, &mut number);
+        Some((service.to_string(), name, number, "high"))
+    });
+    let (service, product, version, confidence) = match matched {
+        Some(found) => found,
+        None if tls || looks_like_tls(banner) => match registered {
+            "unknown" => ("ssl".to_string(), String::new(), String::new(), "low"),
+            // The registered name of a TLS port already says so
+            name if TLS_SERVICES.contains(&name) => (name.to_string(), String::new(), String::new(), "medium"),
+            name => (format!("ssl/{}", name), String::new(), String::new(), "medium"),
+        },
+        None if registered != "unknown" => (registered.to_string(), String::new(), String::new(), "medium"),
+        None => ("unknown".to_string(), String::new(), String::new(), "low"),
+    };
+    HashMap::from([
+        ("service".to_string(), service),
+        ("product".to_string(), product.trim().to_string()),
+        ("version".to_string(), version.trim().to_string()),
+        ("confidence".to_string(), confidence.to_string()),
+    ])
+}
+
+/// Identify the service on each port from what it sent
+///
+/// Layers, first hit wins: one of the built-in banner patterns (SSH, FTP,
+/// SMTP, POP3/IMAP, HTTP heads, MySQL, Redis, VNC, ...; confidence
+/// "high"), a TLS record at the start of the banner ("ssl/<service>",
+/// "medium"), the port's registered service ("medium"), else "unknown"
+/// ("low"). Returns {service, product, version, confidence} per port, with
+/// product and version "" when the banner doesn't give them.
+#[pyfunction]
+pub fn detect_services_from_banners(py: Python, banners: HashMap<u16, String>) -> HashMap<u16, HashMap<String, String>> {
+    py.allow_threads(|| {
+        banners
+            .par_iter()
+            .map(|(port, banner)| (*port, detect_service(*port, banner, false)))
+            .collect()
+    })
+}
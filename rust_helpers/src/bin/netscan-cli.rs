@@ -0,0 +1,201 @@
+//! netscan-cli: the netscan_core scanning and parsing paths without Python
+//!
+//! Build with `cargo build --release --no-default-features --features cli --bin netscan-cli`
+
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use netscan_core::cli_api::*;
+
+const USAGE: &str = "\
+usage: netscan-cli <command> [options]
+
+commands:
+  scan <targets...> [-p PORTS]     TCP connect scan (PORTS like 22,80,8000-8100 or common)
+  ping <targets...>                TCP liveness sweep on common service ports
+  expand <targets...>              expand CIDRs, ranges and wildcards to IPs
+  oui-lookup <mac...> --oui-file F look up MAC vendors in an IEEE oui.txt
+  parse-arp                        parse `arp -a` output from stdin
+
+options:
+  -f, --format json|pipe   output format (default json)
+  -t, --timeout MS         probe timeout in milliseconds (default 1000)
+  -c, --concurrency N      max probes in flight (default 500)
+  -h, --help               show this help";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Json,
+    Pipe,
+}
+
+struct Args {
+    command: String,
+    positional: Vec<String>,
+    ports: Option<String>,
+    format: Format,
+    timeout_ms: u64,
+    concurrency: usize,
+    oui_file: Option<String>,
+}
+
+fn parse_args(mut argv: impl Iterator<Item = String>) -> Result<Args, String> {
+    let command = argv.next().ok_or("missing command")?;
+    let mut args = Args {
+        command,
+        positional: Vec::new(),
+        ports: None,
+        format: Format::Json,
+        timeout_ms: 1000,
+        concurrency: 500,
+        oui_file: std::env::var("NETSCAN_OUI_FILE").ok(),
+    };
+
+    while let Some(arg) = argv.next() {
+        let mut value = |name: &str| argv.next().ok_or(format!("{} needs a value", name));
+        match arg.as_str() {
+            "-p" | "--ports" => args.ports = Some(value(&arg)?),
+            "-f" | "--format" => {
+                args.format = match value(&arg)?.as_str() {
+                    "json" => Format::Json,
+                    "pipe" => Format::Pipe,
+                    other => return Err(format!("unknown format '{}'", other)),
+                }
+            }
+            "-t" | "--timeout" => {
+                args.timeout_ms = value(&arg)?.parse().map_err(|_| "invalid --timeout")?
+            }
+            "-c" | "--concurrency" => {
+                args.concurrency = value(&arg)?.parse().map_err(|_| "invalid --concurrency")?
+            }
+            "--oui-file" => args.oui_file = Some(value(&arg)?),
+            flag if flag.starts_with('-') && flag.len() > 1 => {
+                return Err(format!("unknown option '{}'", flag))
+            }
+            _ => args.positional.push(arg),
+        }
+    }
+    Ok(args)
+}
+
+/// Print records as a JSON array or pipe-delimited text with a header row
+fn emit(records: Vec<HashMap<String, String>>, fields: &[&str], format: Format) -> Result<(), String> {
+    match format {
+        Format::Json => {
+            println!("{}", serde_json::to_string_pretty(&records).map_err(|e| e.to_string())?);
+        }
+        Format::Pipe => {
+            let fields: Vec<String> = fields.iter().map(|f| f.to_string()).collect();
+            write_delimited(&mut io::stdout().lock(), &records, "|", &fields).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+fn emit_results(results: Vec<ScanResult>, format: Format) -> Result<(), String> {
+    match format {
+        // Same serde representation the Python module's ScanResult dicts mirror
+        Format::Json => {
+            println!("{}", serde_json::to_string_pretty(&results).map_err(|e| e.to_string())?);
+            Ok(())
+        }
+        Format::Pipe => emit(results.iter().map(ScanResult::to_record).collect(), RECORD_FIELDS, format),
+    }
+}
+
+fn run_scan(args: &Args, ports: Vec<u16>) -> Result<Vec<ScanResult>, String> {
+    if args.positional.is_empty() {
+        return Err("no targets given".to_string());
+    }
+    let ips = expand_targets(&args.positional)?;
+    let config = ScanConfig {
+        ports,
+        timeout_ms: args.timeout_ms,
+        max_concurrent: args.concurrency,
+        ..ScanConfig::default()
+    };
+    let (scanned, task_errors) =
+        runtime().block_on(scan_hosts(ips, config.ports, config.timeout_ms, config.max_concurrent.max(1)));
+    for error in &task_errors {
+        eprintln!("netscan-cli: warning: {}", error);
+    }
+    let scan_timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+    Ok(results_from_scan(scanned, "tcp_connect", scan_timestamp, config.liveness_threshold))
+}
+
+fn run(args: Args) -> Result<(), String> {
+    match args.command.as_str() {
+        "scan" => {
+            let ports = match &args.ports {
+                Some(spec) => parse_port_spec(spec)?,
+                None => parse_port_spec("common")?,
+            };
+            emit_results(run_scan(&args, ports)?, args.format)
+        }
+        "ping" => emit_results(run_scan(&args, TCP_PING_PORTS.to_vec())?, args.format),
+        "expand" => {
+            let ips = expand_targets(&args.positional)?;
+            let records = ips.into_iter().map(|ip| HashMap::from([("ip".to_string(), ip)])).collect();
+            emit(records, &["ip"], args.format)
+        }
+        "oui-lookup" => {
+            let path = args.oui_file.as_deref().ok_or("oui-lookup needs --oui-file (or NETSCAN_OUI_FILE)")?;
+            let content = std::fs::read_to_string(path).map_err(|e| format!("Cannot open {}: {}", path, e))?;
+            let db = parse_oui_content(&content);
+            let records = args
+                .positional
+                .iter()
+                .map(|mac| {
+                    HashMap::from([
+                        ("mac".to_string(), normalize_mac(mac)),
+                        ("vendor".to_string(), db.get(&extract_oui(mac)).cloned().unwrap_or_default()),
+                    ])
+                })
+                .collect();
+            emit(records, &["mac", "vendor"], args.format)
+        }
+        "parse-arp" => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map_err(|e| e.to_string())?;
+            let records = parse_arp_output(&input)
+                .into_iter()
+                .map(|(ip, mac, hostname)| {
+                    HashMap::from([
+                        ("ip".to_string(), ip),
+                        ("mac".to_string(), mac),
+                        ("hostname".to_string(), hostname),
+                    ])
+                })
+                .collect();
+            emit(records, &["ip", "mac", "hostname"], args.format)
+        }
+        other => Err(format!("unknown command '{}'", other)),
+    }
+}
+
+fn main() -> ExitCode {
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    if argv.is_empty() || argv.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return if argv.is_empty() { ExitCode::from(2) } else { ExitCode::SUCCESS };
+    }
+
+    let args = match parse_args(argv.into_iter()) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("netscan-cli: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("netscan-cli: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
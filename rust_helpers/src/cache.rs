@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use pyo3::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::scanner::unix_now;
+
+// =============================================================================
+// Port Scan Cache
+// =============================================================================
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    ip: String,
+    port: u16,
+    timestamp: f64,
+    is_open: bool,
+}
+
+/// Per-(ip, port) probe outcomes with the time they were observed
+#[pyclass]
+#[derive(Debug, Clone, Default)]
+pub struct ScanCache {
+    pub entries: HashMap<(String, u16), (f64, bool)>,
+}
+
+impl ScanCache {
+    /// Cached open/closed state if observed within `ttl_seconds`
+    pub fn lookup(&self, ip: &str, port: u16, ttl_seconds: u64, now: f64) -> Option<bool> {
+        self.entries
+            .get(&(ip.to_string(), port))
+            .filter(|(timestamp, _)| now - timestamp <= ttl_seconds as f64)
+            .map(|(_, is_open)| *is_open)
+    }
+
+    pub fn record(&mut self, ip: &str, port: u16, is_open: bool, now: f64) {
+        self.entries.insert((ip.to_string(), port), (now, is_open));
+    }
+}
+
+fn io_error(action: &str, e: impl std::fmt::Display) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot {} scan cache: {}", action, e))
+}
+
+#[pymethods]
+impl ScanCache {
+    #[new]
+    fn new() -> Self {
+        ScanCache::default()
+    }
+
+    /// Cached state for (ip, port) if younger than `ttl_seconds`, else None
+    fn get(&self, ip: &str, port: u16, ttl_seconds: u64) -> Option<bool> {
+        self.lookup(ip, port, ttl_seconds, unix_now())
+    }
+
+    /// Store a probe result stamped with the current time
+    fn put(&mut self, ip: &str, port: u16, is_open: bool) {
+        self.record(ip, port, is_open, unix_now());
+    }
+
+    /// Write the cache to `path` as JSON
+    pub fn save(&self, path: &str) -> PyResult<()> {
+        let entries: Vec<CacheEntry> = self
+            .entries
+            .iter()
+            .map(|((ip, port), (timestamp, is_open))| CacheEntry {
+                ip: ip.clone(),
+                port: *port,
+                timestamp: *timestamp,
+                is_open: *is_open,
+            })
+            .collect();
+        let file = File::create(path).map_err(|e| io_error("write", e))?;
+        serde_json::to_writer(BufWriter::new(file), &entries).map_err(|e| io_error("write", e))
+    }
+
+    /// Read a cache previously written by `save`
+    #[staticmethod]
+    pub fn load(path: &str) -> PyResult<ScanCache> {
+        let file = File::open(path).map_err(|e| io_error("read", e))?;
+        let entries: Vec<CacheEntry> = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| io_error("read", e))?;
+        Ok(ScanCache {
+            entries: entries
+                .into_iter()
+                .map(|e| ((e.ip, e.port), (e.timestamp, e.is_open)))
+                .collect(),
+        })
+    }
+
+    /// Drop every entry for `ip`; returns how many were removed
+    fn invalidate_host(&mut self, ip: &str) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|(cached_ip, _), _| cached_ip != ip);
+        before - self.entries.len()
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn __len__(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ScanCache(<{} entries>)", self.entries.len())
+    }
+}
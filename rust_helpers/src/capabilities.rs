@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use pyo3::prelude::*;
+use socket2::{Domain, Protocol, Socket, Type};
+
+// =============================================================================
+// Privileged Feature Detection
+// =============================================================================
+
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    pub raw_icmp: bool,
+    pub unprivileged_icmp: bool,
+    /// Raw TCP socket, as SYN probes need
+    pub raw_tcp: bool,
+    pub raw_packet: bool,
+    pub pcap: bool,
+    pub privileged: bool,
+    /// Soft limit on open file descriptors (None if unknown)
+    pub fd_limit: Option<u64>,
+}
+
+impl Capabilities {
+    pub fn has(&self, name: &str) -> Option<bool> {
+        match name {
+            "raw_icmp" => Some(self.raw_icmp),
+            "unprivileged_icmp" => Some(self.unprivileged_icmp),
+            "icmp" => Some(self.raw_icmp || self.unprivileged_icmp),
+            "raw_tcp" => Some(self.raw_tcp),
+            "raw_packet" => Some(self.raw_packet),
+            "pcap" => Some(self.pcap),
+            "privileged" => Some(self.privileged),
+            _ => None,
+        }
+    }
+
+    pub fn to_py_dict(&self, py: Python) -> HashMap<String, PyObject> {
+        let mut map = HashMap::new();
+        map.insert("raw_icmp".to_string(), self.raw_icmp.into_py(py));
+        map.insert("unprivileged_icmp".to_string(), self.unprivileged_icmp.into_py(py));
+        map.insert("raw_tcp".to_string(), self.raw_tcp.into_py(py));
+        map.insert("raw_packet".to_string(), self.raw_packet.into_py(py));
+        map.insert("pcap".to_string(), self.pcap.into_py(py));
+        map.insert("privileged".to_string(), self.privileged.into_py(py));
+        map.insert("fd_limit".to_string(), self.fd_limit.into_py(py));
+        map.insert("platform".to_string(), std::env::consts::OS.into_py(py));
+        map
+    }
+}
+
+fn can_open(domain: Domain, ty: Type, protocol: Protocol) -> bool {
+    Socket::new(domain, ty, Some(protocol)).is_ok()
+}
+
+/// Try to open a datalink channel on the first usable interface
+fn can_open_raw_packet() -> bool {
+    pnet::datalink::interfaces()
+        .into_iter()
+        .find(|iface| iface.is_up() && !iface.is_loopback() && !iface.ips.is_empty())
+        .map(|iface| pnet::datalink::channel(&iface, Default::default()).is_ok())
+        .unwrap_or(false)
+}
+
+/// Whether the dynamic loader can find libpcap, wherever the distribution
+/// or LD_LIBRARY_PATH put it
+#[cfg(unix)]
+fn pcap_available() -> bool {
+    const NAMES: &[&str] = &["libpcap.so.1", "libpcap.so.0.8", "libpcap.so", "libpcap.A.dylib", "libpcap.dylib"];
+    NAMES.iter().any(|name| {
+        let Ok(name) = std::ffi::CString::new(*name) else {
+            return false;
+        };
+        // SAFETY: `name` is NUL-terminated and the handle is closed before returning
+        unsafe {
+            let handle = libc::dlopen(name.as_ptr(), libc::RTLD_LAZY | libc::RTLD_LOCAL);
+            !handle.is_null() && libc::dlclose(handle) == 0
+        }
+    })
+}
+
+/// Npcap installs wpcap.dll under System32\Npcap, WinPcap into System32;
+/// either may also be on PATH
+#[cfg(windows)]
+fn pcap_available() -> bool {
+    let system = std::env::var_os("SystemRoot").unwrap_or_else(|| r"C:\Windows".into());
+    let system = std::path::Path::new(&system).join("System32");
+    let path_dirs = std::env::var_os("PATH").map(|p| std::env::split_paths(&p).collect()).unwrap_or_else(Vec::new);
+    [system.join("Npcap"), system]
+        .into_iter()
+        .chain(path_dirs)
+        .any(|dir| dir.join("wpcap.dll").exists())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn pcap_available() -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn fd_limit() -> Option<u64> {
+    let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    // SAFETY: getrlimit only writes into the provided struct
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } == 0 {
+        Some(limit.rlim_cur)
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn fd_limit() -> Option<u64> {
+    None
+}
+
+#[cfg(unix)]
+fn is_privileged() -> bool {
+    // SAFETY: geteuid has no preconditions
+    unsafe { libc::geteuid() == 0 }
+}
+
+#[cfg(not(unix))]
+fn is_privileged() -> bool {
+    // Raw sockets are admin-only on Windows
+    can_open(Domain::IPV4, Type::RAW, Protocol::ICMPV4)
+}
+
+/// Probe the current process for privileged networking features
+pub fn detect() -> Capabilities {
+    Capabilities {
+        raw_icmp: can_open(Domain::IPV4, Type::RAW, Protocol::ICMPV4),
+        unprivileged_icmp: can_open(Domain::IPV4, Type::DGRAM, Protocol::ICMPV4),
+        raw_tcp: can_open(Domain::IPV4, Type::RAW, Protocol::TCP),
+        raw_packet: can_open_raw_packet(),
+        pcap: pcap_available(),
+        privileged: is_privileged(),
+        fd_limit: fd_limit(),
+    }
+}
+
+/// Capabilities detected once per process
+pub fn cached() -> &'static Capabilities {
+    static CAPS: OnceLock<Capabilities> = OnceLock::new();
+    CAPS.get_or_init(detect)
+}
+
+/// How to obtain a missing capability on this platform
+pub(crate) fn grant_hint(name: &str) -> &'static str {
+    match (name, std::env::consts::OS) {
+        ("raw_icmp" | "icmp" | "raw_tcp" | "raw_packet" | "privileged", "windows") => "run as Administrator",
+        ("pcap", "windows") => "install Npcap from https://npcap.com",
+        ("pcap", _) => "install libpcap (e.g. apt install libpcap0.8)",
+        ("unprivileged_icmp", "linux") => {
+            "allow ping sockets: sudo sysctl -w net.ipv4.ping_group_range='0 2147483647'"
+        }
+        ("raw_icmp" | "icmp" | "raw_tcp", "linux") => {
+            "run as root or grant CAP_NET_RAW: sudo setcap cap_net_raw+ep $(readlink -f $(which python3))"
+        }
+        ("raw_packet", "linux") => {
+            "run as root or grant raw capture: sudo setcap cap_net_raw,cap_net_admin+eip $(readlink -f $(which python3))"
+        }
+        _ => "run with sudo",
+    }
+}
+
+/// Report which privileged features are usable: raw_icmp,
+/// unprivileged_icmp, raw_tcp (SYN probes), raw_packet (ARP), pcap,
+/// privileged, fd_limit and platform. `Scanner` picks its probe and
+/// discovery methods from this report.
+#[pyfunction]
+#[pyo3(signature = (refresh=false))]
+pub fn capability_report(py: Python, refresh: bool) -> HashMap<String, PyObject> {
+    if refresh {
+        detect().to_py_dict(py)
+    } else {
+        cached().to_py_dict(py)
+    }
+}
+
+/// Raise PermissionError listing every missing capability and how to grant it
+#[pyfunction]
+pub fn require(capabilities: Vec<String>) -> PyResult<()> {
+    let caps = detect();
+    let mut missing = Vec::new();
+
+    for name in &capabilities {
+        match caps.has(name) {
+            Some(true) => {}
+            Some(false) => missing.push(format!("{} ({})", name, grant_hint(name))),
+            None => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    format!("Unknown capability: {}", name)
+                ));
+            }
+        }
+    }
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(PyErr::new::<pyo3::exceptions::PyPermissionError, _>(format!(
+            "Missing capabilities: {}",
+            missing.join("; ")
+        )))
+    }
+}
+
+// =============================================================================
+// Self Test
+// =============================================================================
+//
+// "Scan finds nothing" is usually a local firewall, a missing privilege or a
+// low descriptor limit rather than the network. The self test runs the real
+// scan paths against loopback, where the answer is known, so a failure points
+// at the machine and not the targets.
+
+/// Outcome of one self-test check
+struct Check {
+    name: &'static str,
+    /// "pass", "fail", "warn" or "skip"
+    status: &'static str,
+    detail: String,
+    /// What to do about a failure; empty when nothing is needed
+    hint: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: &'static str, detail: String, hint: &str) -> Self {
+        Check { name, status, detail, hint: hint.to_string() }
+    }
+
+    fn to_py_dict(&self, py: Python) -> HashMap<String, PyObject> {
+        let mut map = HashMap::new();
+        map.insert("name".to_string(), self.name.into_py(py));
+        map.insert("status".to_string(), self.status.into_py(py));
+        map.insert("detail".to_string(), self.detail.clone().into_py(py));
+        map.insert("hint".to_string(), self.hint.clone().into_py(py));
+        map
+    }
+}
+
+const FIREWALL_HINT: &str =
+    "a local firewall or security agent is interfering with loopback connections; allow outbound TCP to 127.0.0.1";
+
+/// A listener on an ephemeral port must show up open through tcp_scan_batch
+fn check_tcp_open(py: Python, timeout_ms: u64) -> Check {
+    let listener = match std::net::TcpListener::bind("127.0.0.1:0") {
+        Ok(listener) => listener,
+        Err(e) => {
+            return Check::new("tcp_open", "fail", format!("cannot listen on 127.0.0.1: {}", e), FIREWALL_HINT);
+        }
+    };
+    // Unaccepted connections still complete the handshake from the backlog
+    let port = match listener.local_addr() {
+        Ok(addr) => addr.port(),
+        Err(e) => return Check::new("tcp_open", "fail", e.to_string(), FIREWALL_HINT),
+    };
+    let targets = pyo3::types::PyList::new(py, ["127.0.0.1"]);
+    // Our own listener, so authorized scopes don't apply
+    let scanned = crate::scanner::tcp_scan_batch(py, targets, vec![port], timeout_ms, 4, true, false, 0, true, false)
+        .and_then(|results| results.extract::<Vec<crate::scanner::ScanResult>>(py));
+    match scanned {
+        Ok(results) if results.iter().any(|r| r.open_ports.contains(&port)) => {
+            Check::new("tcp_open", "pass", format!("listener on 127.0.0.1:{} found open", port), "")
+        }
+        Ok(_) => Check::new(
+            "tcp_open",
+            "fail",
+            format!("listener on 127.0.0.1:{} was not reported open within {} ms", port, timeout_ms),
+            FIREWALL_HINT,
+        ),
+        Err(e) => Check::new("tcp_open", "fail", format!("scan failed: {}", e), "report this as a bug, with this output"),
+    }
+}
+
+/// A port nobody listens on must come back "closed" (refused), not "filtered"
+fn check_tcp_closed(py: Python, timeout_ms: u64) -> Check {
+    // Bind and release to find a port that is free right now
+    let port = match std::net::TcpListener::bind("127.0.0.1:0").and_then(|l| l.local_addr()) {
+        Ok(addr) => addr.port(),
+        Err(e) => return Check::new("tcp_closed", "fail", format!("cannot pick a free port: {}", e), FIREWALL_HINT),
+    };
+    match crate::scanner::check_port(py, "127.0.0.1", port, timeout_ms, true) {
+        Ok(state) if state == "closed" => {
+            Check::new("tcp_closed", "pass", format!("127.0.0.1:{} reported closed", port), "")
+        }
+        Ok(state) => Check::new(
+            "tcp_closed",
+            "fail",
+            format!("127.0.0.1:{} reported {} instead of closed", port, state),
+            "connection refusals are being dropped; closed ports will look filtered and scans will run slowly",
+        ),
+        Err(e) => Check::new("tcp_closed", "fail", format!("probe failed: {}", e), "report this as a bug, with this output"),
+    }
+}
+
+/// ICMP echo to loopback when the process may send ICMP at all
+fn check_icmp(py: Python, caps: &Capabilities, timeout_ms: u64) -> Check {
+    if !caps.raw_icmp && !caps.unprivileged_icmp {
+        return Check::new(
+            "icmp",
+            "skip",
+            "no raw or ping socket available; ICMP discovery is disabled".to_string(),
+            grant_hint("icmp"),
+        );
+    }
+    let target = [std::net::Ipv4Addr::LOCALHOST];
+    let probes = [crate::icmp::IcmpProbe::Echo];
+    match py.allow_threads(|| crate::icmp::icmp_sweep(&target, &probes, timeout_ms)) {
+        Ok(replies) if replies.contains_key(&target[0]) => {
+            Check::new("icmp", "pass", "echo reply from 127.0.0.1".to_string(), "")
+        }
+        Ok(_) => Check::new(
+            "icmp",
+            "fail",
+            format!("no echo reply from 127.0.0.1 within {} ms", timeout_ms),
+            "ICMP echo is blocked locally (firewall or net.ipv4.icmp_echo_ignore_all); hosts will only be found by TCP",
+        ),
+        Err(e) => Check::new("icmp", "fail", e, grant_hint("icmp")),
+    }
+}
+
+/// Scans open one descriptor per in-flight probe
+fn check_fd_limit(caps: &Capabilities) -> Check {
+    const RECOMMENDED: u64 = 1024;
+    match caps.fd_limit {
+        Some(limit) if limit < RECOMMENDED => Check::new(
+            "fd_limit",
+            "warn",
+            format!("open file limit is {}; scans are throttled to stay below it", limit),
+            "raise it before scanning, e.g. ulimit -n 4096",
+        ),
+        Some(limit) => Check::new("fd_limit", "pass", format!("open file limit is {}", limit), ""),
+        None => Check::new("fd_limit", "skip", "limit unknown on this platform".to_string(), ""),
+    }
+}
+
+/// Verify the scanner works on this machine, using loopback as a known target
+///
+/// Runs: `tcp_open` (an ephemeral listener found through tcp_scan_batch),
+/// `tcp_closed` (a free port reported closed, not filtered), `icmp` (echo to
+/// 127.0.0.1, skipped without ICMP privileges) and `fd_limit`. Returns
+/// {passed, checks, capabilities, platform, version}: `passed` is False only
+/// when a check failed, and each check is {name, status, detail, hint} with
+/// status "pass", "fail", "warn" or "skip" and `hint` saying what to change.
+/// The report is meant to be pasted into bug reports as is.
+#[pyfunction]
+#[pyo3(signature = (timeout_ms=1000))]
+pub fn self_test(py: Python, timeout_ms: u64) -> HashMap<String, PyObject> {
+    let caps = detect();
+    let checks = [
+        check_tcp_open(py, timeout_ms),
+        check_tcp_closed(py, timeout_ms),
+        check_icmp(py, &caps, timeout_ms),
+        check_fd_limit(&caps),
+    ];
+
+    let mut report = HashMap::new();
+    report.insert("passed".to_string(), checks.iter().all(|c| c.status != "fail").into_py(py));
+    report.insert(
+        "checks".to_string(),
+        checks.iter().map(|c| c.to_py_dict(py)).collect::<Vec<_>>().into_py(py),
+    );
+    report.insert("capabilities".to_string(), caps.to_py_dict(py).into_py(py));
+    report.insert("platform".to_string(), std::env::consts::OS.into_py(py));
+    report.insert("version".to_string(), env!("CARGO_PKG_VERSION").into_py(py));
+    report
+}
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use ipnetwork::IpNetwork;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::scanner::ScanResult;
+
+// =============================================================================
+// Port Baseline Compliance
+// =============================================================================
+//
+// Baseline keys are matched with well-defined precedence:
+//   1. exact IP            ("10.0.0.5")
+//   2. CIDR, longest prefix ("10.0.0.0/24" beats "10.0.0.0/8"), ties
+//                           (host bits set: "10.0.0.9/24") broken by the key
+//   3. hostname glob       ("db-*.corp"), most literal characters wins,
+//                           ties broken by the pattern string
+// Values are either a port list (allowed ports) or a dict with optional
+// "allowed" and "required" port lists. A rule without "allowed" permits any
+// port and only checks the required set.
+
+#[derive(Debug, Clone, Default)]
+pub struct BaselineRule {
+    pub allowed: Option<Vec<u16>>,
+    pub required: Vec<u16>,
+}
+
+impl<'source> FromPyObject<'source> for BaselineRule {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        if let Ok(dict) = ob.downcast::<PyDict>() {
+            let allowed = match dict.get_item("allowed")? {
+                Some(v) if !v.is_none() => Some(v.extract()?),
+                _ => None,
+            };
+            let required = match dict.get_item("required")? {
+                Some(v) if !v.is_none() => v.extract()?,
+                _ => Vec::new(),
+            };
+            Ok(BaselineRule { allowed, required })
+        } else {
+            Ok(BaselineRule { allowed: Some(ob.extract()?), required: Vec::new() })
+        }
+    }
+}
+
+/// Case-insensitive glob match supporting `*` and `?`
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.to_lowercase().chars().collect();
+    let t: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+enum RuleKey {
+    Ip(IpAddr),
+    Cidr(IpNetwork),
+    Pattern(String),
+}
+
+fn classify_key(key: &str) -> RuleKey {
+    let key = key.trim();
+    if let Ok(ip) = key.parse::<IpAddr>() {
+        RuleKey::Ip(ip)
+    } else if let Some(net) = key.contains('/').then(|| key.parse::<IpNetwork>().ok()).flatten() {
+        RuleKey::Cidr(net)
+    } else {
+        RuleKey::Pattern(key.to_string())
+    }
+}
+
+/// Find the baseline rule for a host; returns (rule key, match type)
+pub fn match_rule<'a>(
+    baseline: &'a [(String, BaselineRule)],
+    ip: &str,
+    hostname: &str,
+) -> Option<(&'a str, &'static str, &'a BaselineRule)> {
+    let addr = ip.trim().parse::<IpAddr>().ok();
+    let mut best_cidr: Option<(u8, &str, usize)> = None;
+    let mut best_pattern: Option<(usize, &str, usize)> = None;
+
+    for (idx, (key, _)) in baseline.iter().enumerate() {
+        match classify_key(key) {
+            RuleKey::Ip(rule_ip) => {
+                if Some(rule_ip) == addr {
+                    let (key, rule) = &baseline[idx];
+                    return Some((key, "ip", rule));
+                }
+            }
+            RuleKey::Cidr(net) => {
+                let better = match best_cidr {
+                    None => true,
+                    Some((p, k, _)) => net.prefix() > p || (net.prefix() == p && key.as_str() < k),
+                };
+                if addr.map(|a| net.contains(a)).unwrap_or(false) && better {
+                    best_cidr = Some((net.prefix(), key.as_str(), idx));
+                }
+            }
+            RuleKey::Pattern(pattern) => {
+                if !hostname.is_empty() && glob_match(&pattern, hostname) {
+                    let literal = pattern.chars().filter(|c| *c != '*' && *c != '?').count();
+                    let better = match best_pattern {
+                        None => true,
+                        Some((l, k, _)) => literal > l || (literal == l && key.as_str() < k),
+                    };
+                    if better {
+                        best_pattern = Some((literal, key.as_str(), idx));
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some((_, _, idx)) = best_cidr {
+        let (key, rule) = &baseline[idx];
+        return Some((key, "cidr", rule));
+    }
+    best_pattern.map(|(_, _, idx)| {
+        let (key, rule) = &baseline[idx];
+        (key.as_str(), "pattern", rule)
+    })
+}
+
+/// Compare each host's open ports to its baseline rule
+///
+/// Returns {"violations": [{ip, hostname, rule, match_type, unexpected_ports,
+/// missing_ports}], "uncovered": [ip], "compliant": [ip], "summary": {...}}
+#[pyfunction]
+pub fn check_compliance(
+    py: Python,
+    results: Vec<ScanResult>,
+    baseline: HashMap<String, BaselineRule>,
+) -> PyResult<HashMap<String, PyObject>> {
+    let baseline: Vec<(String, BaselineRule)> = baseline.into_iter().collect();
+
+    let mut violations: Vec<PyObject> = Vec::new();
+    let mut uncovered: Vec<String> = Vec::new();
+    let mut compliant: Vec<String> = Vec::new();
+
+    for result in &results {
+        let Some((key, match_type, rule)) = match_rule(&baseline, &result.ip, &result.hostname) else {
+            uncovered.push(result.ip.clone());
+            continue;
+        };
+
+        let mut unexpected: Vec<u16> = match &rule.allowed {
+            Some(allowed) => result
+                .open_ports
+                .iter()
+                .filter(|p| !allowed.contains(p))
+                .copied()
+                .collect(),
+            None => Vec::new(),
+        };
+        let mut missing: Vec<u16> = rule
+            .required
+            .iter()
+            .filter(|p| !result.open_ports.contains(p))
+            .copied()
+            .collect();
+        unexpected.sort_unstable();
+        missing.sort_unstable();
+
+        if unexpected.is_empty() && missing.is_empty() {
+            compliant.push(result.ip.clone());
+            continue;
+        }
+
+        let entry = PyDict::new(py);
+        entry.set_item("ip", &result.ip)?;
+        entry.set_item("hostname", &result.hostname)?;
+        entry.set_item("rule", key)?;
+        entry.set_item("match_type", match_type)?;
+        entry.set_item("unexpected_ports", unexpected)?;
+        entry.set_item("missing_ports", missing)?;
+        violations.push(entry.into());
+    }
+
+    let summary = PyDict::new(py);
+    summary.set_item("hosts", results.len())?;
+    summary.set_item("violations", violations.len())?;
+    summary.set_item("uncovered", uncovered.len())?;
+    summary.set_item("compliant", compliant.len())?;
+
+    let mut report = HashMap::new();
+    report.insert("violations".to_string(), violations.into_py(py));
+    report.insert("uncovered".to_string(), uncovered.into_py(py));
+    report.insert("compliant".to_string(), compliant.into_py(py));
+    report.insert("summary".to_string(), summary.into());
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_precedence_is_ip_then_longest_cidr_then_most_literal_pattern() {
+        let keys = ["10.0.0.0/8", "10.1.0.0/16", "10.1.0.9/16", "10.1.2.3", "db-*", "db-*.corp", "*.corp", "*"];
+        let mut baseline: Vec<(String, BaselineRule)> =
+            keys.iter().map(|k| (k.to_string(), BaselineRule::default())).collect();
+        let cases = [
+            ("10.1.2.3", "db-1.corp", ("10.1.2.3", "ip")),
+            ("10.1.2.4", "db-1.corp", ("10.1.0.0/16", "cidr")),
+            ("10.2.0.1", "db-1.corp", ("10.0.0.0/8", "cidr")),
+            ("192.168.0.1", "db-1.corp", ("db-*.corp", "pattern")),
+            ("192.168.0.1", "web.corp", ("*.corp", "pattern")),
+            ("", "db-1", ("db-*", "pattern")),
+            ("192.168.0.1", "", ("", "")),
+        ];
+        // Baselines arrive as a dict, so the answer must not depend on order
+        for _ in 0..2 {
+            for (ip, hostname, expected) in cases {
+                let found = match_rule(&baseline, ip, hostname).map(|(key, kind, _)| (key, kind));
+                assert_eq!(found.unwrap_or(("", "")), expected, "{} {}", ip, hostname);
+            }
+            baseline.reverse();
+        }
+    }
+}
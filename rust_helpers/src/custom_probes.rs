@@ -0,0 +1,183 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use parking_lot::Mutex;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::query::Filter;
+use crate::scanner::ScanResult;
+use crate::schema::canonical_from_dict;
+use crate::secret::redact;
+
+// =============================================================================
+// Custom Python Probes
+// =============================================================================
+//
+// Probes registered with `Scanner.add_probe` run after the async scan has
+// finished, so Python never holds the GIL against the scanner. Calls go to a
+// small pool of OS threads, each taking the GIL only for its call; probes
+// that wait on sockets release it and overlap. A Python call cannot be
+// interrupted, so a probe that overruns its timeout is abandoned: its
+// worker is replaced and whatever it eventually returns is discarded.
+
+/// Threads calling probes at once
+const PROBE_WORKERS: usize = 16;
+
+/// A Python callable run on each host the scan finds
+#[derive(Debug, Clone)]
+pub struct CustomProbe {
+    pub name: String,
+    pub callable: PyObject,
+    /// Only hosts matching this filter expression are probed
+    pub only_if: Option<Filter>,
+    pub timeout_ms: u64,
+}
+
+struct Job {
+    id: usize,
+    name: String,
+    callable: PyObject,
+    record: ScanResult,
+}
+
+/// What a probe call produced: a record to merge (None when it returned
+/// None), or why it failed
+type Outcome = Result<Option<ScanResult>, String>;
+
+enum Event {
+    Started(usize),
+    Finished(usize, Box<Outcome>),
+}
+
+fn call_probe(py: Python, job: Job) -> Outcome {
+    let returned = job
+        .callable
+        .call1(py, (job.record.into_py(py),))
+        .map_err(|e| redact(&e.to_string()))?;
+    let returned = returned.as_ref(py);
+    if returned.is_none() {
+        return Ok(None);
+    }
+    let dict = returned.downcast::<PyDict>().map_err(|_| {
+        let type_name = returned.get_type().name().unwrap_or("object");
+        format!("returned {} instead of a dict", type_name)
+    })?;
+    let mut found = canonical_from_dict(dict, &job.name).map_err(|e| e.to_string())?;
+    for value in found.attributes.values_mut() {
+        *value = redact(value);
+    }
+    Ok(Some(found))
+}
+
+fn worker(queue: Arc<Mutex<VecDeque<Job>>>, events: mpsc::Sender<Event>) {
+    loop {
+        let Some(job) = queue.lock().pop_front() else {
+            return;
+        };
+        let id = job.id;
+        if events.send(Event::Started(id)).is_err() {
+            return;
+        }
+        let outcome = Python::with_gil(|py| call_probe(py, job));
+        // The scan may have stopped waiting for this probe long ago
+        let _ = events.send(Event::Finished(id, Box::new(outcome)));
+    }
+}
+
+/// Run every applicable probe on every result and merge what they return
+///
+/// Each probe sees the record as the scan left it. Returned dicts are read
+/// like `to_canonical` input and merged with `ScanResult::merge`, so fields
+/// the scan already set are kept and the probe's name joins `sources`.
+/// Exceptions, timeouts and non-dict returns are appended to the host's
+/// `error` as "probe <name>: <reason>".
+pub fn run_custom_probes(py: Python, probes: &[CustomProbe], results: &mut [ScanResult]) {
+    let mut jobs = VecDeque::new();
+    let mut targets: Vec<(usize, usize)> = Vec::new();
+    for (r, result) in results.iter().enumerate() {
+        for (p, probe) in probes.iter().enumerate() {
+            if probe.only_if.as_ref().is_none_or(|filter| filter.matches(result)) {
+                jobs.push_back(Job {
+                    id: targets.len(),
+                    name: probe.name.clone(),
+                    callable: probe.callable.clone_ref(py),
+                    record: result.clone(),
+                });
+                targets.push((r, p));
+            }
+        }
+    }
+    if targets.is_empty() {
+        return;
+    }
+    let timeouts: Vec<Duration> = targets
+        .iter()
+        .map(|(_, p)| Duration::from_millis(probes[*p].timeout_ms))
+        .collect();
+
+    let outcomes = py.allow_threads(|| {
+        let queue = Arc::new(Mutex::new(jobs));
+        let (tx, rx) = mpsc::channel();
+        let spawn_worker = || {
+            let (queue, tx) = (queue.clone(), tx.clone());
+            std::thread::spawn(move || worker(queue, tx));
+        };
+        for _ in 0..PROBE_WORKERS.min(timeouts.len()) {
+            spawn_worker();
+        }
+
+        let mut outcomes: Vec<Option<Outcome>> = vec![None; timeouts.len()];
+        let mut deadlines: HashMap<usize, Instant> = HashMap::new();
+        let mut remaining = timeouts.len();
+        while remaining > 0 {
+            let wait = deadlines
+                .values()
+                .min()
+                .map_or(Duration::from_millis(100), |d| d.saturating_duration_since(Instant::now()));
+            match rx.recv_timeout(wait) {
+                Ok(Event::Started(id)) => {
+                    deadlines.insert(id, Instant::now() + timeouts[id]);
+                }
+                // Results of probes already given up on are dropped
+                Ok(Event::Finished(id, outcome)) => {
+                    if deadlines.remove(&id).is_some() {
+                        outcomes[id] = Some(*outcome);
+                        remaining -= 1;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            let now = Instant::now();
+            let expired: Vec<usize> = deadlines.iter().filter(|(_, d)| **d <= now).map(|(id, _)| *id).collect();
+            for id in expired {
+                deadlines.remove(&id);
+                outcomes[id] = Some(Err(format!("timed out after {} ms", timeouts[id].as_millis())));
+                remaining -= 1;
+                // The stuck worker keeps its thread; keep the pool at strength
+                spawn_worker();
+            }
+        }
+        outcomes
+    });
+
+    for ((r, p), outcome) in targets.into_iter().zip(outcomes) {
+        let result = &mut results[r];
+        match outcome {
+            Some(Ok(Some(found))) => result.merge(&found),
+            Some(Ok(None)) => {}
+            Some(Err(reason)) => {
+                let message = format!("probe {}: {}", probes[p].name, reason);
+                if result.error.is_empty() {
+                    result.error = message;
+                } else {
+                    result.error = format!("{}; {}", result.error, message);
+                }
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,213 @@
+// =============================================================================
+// DNS Wire Format Helpers
+// =============================================================================
+
+pub const TYPE_A: u16 = 1;
+pub const TYPE_PTR: u16 = 12;
+pub const TYPE_TXT: u16 = 16;
+pub const TYPE_AAAA: u16 = 28;
+pub const TYPE_SRV: u16 = 33;
+
+pub const CLASS_IN: u16 = 1;
+pub const CLASS_CH: u16 = 3;
+
+/// A resource record from a DNS response
+#[derive(Debug, Clone)]
+pub struct DnsRecord {
+    pub rtype: u16,
+    /// Offset of the RDATA within the packet (needed to decompress names)
+    pub rdata_offset: usize,
+    pub rdata: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DnsResponse {
+    pub rcode: u8,
+    pub answers: Vec<DnsRecord>,
+}
+
+/// Encode a dotted name as length-prefixed labels
+pub fn encode_name(name: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(name.len() + 2);
+    for label in name.trim_end_matches('.').split('.').filter(|l| !l.is_empty()) {
+        let bytes = label.as_bytes();
+        out.push(bytes.len().min(63) as u8);
+        out.extend_from_slice(&bytes[..bytes.len().min(63)]);
+    }
+    out.push(0);
+    out
+}
+
+/// Build a single-question query packet
+pub fn build_query(id: u16, name: &str, qtype: u16, qclass: u16, recursion: bool) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(32 + name.len());
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&(if recursion { 0x0100u16 } else { 0 }).to_be_bytes());
+    packet.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]); // 1 question
+    packet.extend(encode_name(name));
+    packet.extend_from_slice(&qtype.to_be_bytes());
+    packet.extend_from_slice(&qclass.to_be_bytes());
+    packet
+}
+
+/// Read a (possibly compressed) name; returns the name and the offset after it
+pub fn read_name(packet: &[u8], mut offset: usize) -> Option<(String, usize)> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut end: Option<usize> = None;
+    let mut jumps = 0;
+
+    loop {
+        let len = *packet.get(offset)? as usize;
+        if len == 0 {
+            offset += 1;
+            break;
+        }
+        if len & 0xC0 == 0xC0 {
+            let pointer = ((len & 0x3F) << 8) | *packet.get(offset + 1)? as usize;
+            if end.is_none() {
+                end = Some(offset + 2);
+            }
+            jumps += 1;
+            if jumps > 16 {
+                return None;
+            }
+            offset = pointer;
+            continue;
+        }
+        let label = packet.get(offset + 1..offset + 1 + len)?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        offset += 1 + len;
+    }
+
+    Some((labels.join("."), end.unwrap_or(offset)))
+}
+
+/// Parse a response packet's header and answer section
+pub fn parse_response(packet: &[u8]) -> Option<DnsResponse> {
+    if packet.len() < 12 {
+        return None;
+    }
+    let rcode = packet[3] & 0x0F;
+    let qdcount = u16::from_be_bytes([packet[4], packet[5]]);
+    let ancount = u16::from_be_bytes([packet[6], packet[7]]);
+
+    let mut offset = 12;
+    for _ in 0..qdcount {
+        let (_, next) = read_name(packet, offset)?;
+        offset = next + 4;
+    }
+
+    let mut answers = Vec::with_capacity(ancount as usize);
+    for _ in 0..ancount {
+        let (_, next) = read_name(packet, offset)?;
+        let header = packet.get(next..next + 10)?;
+        let rtype = u16::from_be_bytes([header[0], header[1]]);
+        let rdlen = u16::from_be_bytes([header[8], header[9]]) as usize;
+        let rdata_offset = next + 10;
+        let rdata = packet.get(rdata_offset..rdata_offset + rdlen)?.to_vec();
+        answers.push(DnsRecord { rtype, rdata_offset, rdata });
+        offset = rdata_offset + rdlen;
+    }
+
+    Some(DnsResponse { rcode, answers })
+}
+
+/// Decode TXT RDATA (length-prefixed character strings)
+pub fn txt_strings(rdata: &[u8]) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < rdata.len() {
+        let len = rdata[i] as usize;
+        let end = (i + 1 + len).min(rdata.len());
+        out.push(String::from_utf8_lossy(&rdata[i + 1..end]).into_owned());
+        i = end;
+    }
+    out
+}
+
+/// Reverse-lookup name for an IPv4 address (d.c.b.a.in-addr.arpa)
+pub fn reverse_name_v4(ip: std::net::Ipv4Addr) -> String {
+    let o = ip.octets();
+    format!("{}.{}.{}.{}.in-addr.arpa", o[3], o[2], o[1], o[0])
+}
+
+/// Reverse-lookup name for any address: in-addr.arpa for IPv4, and for IPv6
+/// the 32 nibbles in reverse under ip6.arpa
+pub fn reverse_name(ip: std::net::IpAddr) -> String {
+    match ip {
+        std::net::IpAddr::V4(v4) => reverse_name_v4(v4),
+        std::net::IpAddr::V6(v6) => {
+            let mut name = String::with_capacity(72);
+            for byte in v6.octets().iter().rev() {
+                name.push_str(&format!("{:x}.{:x}.", byte & 0x0f, byte >> 4));
+            }
+            name.push_str("ip6.arpa");
+            name
+        }
+    }
+}
+
+/// Address a full in-addr.arpa or ip6.arpa name stands for; a trailing dot
+/// and letter case are ignored
+pub fn parse_reverse_name(name: &str) -> Result<std::net::IpAddr, String> {
+    let lowered = name.trim().trim_end_matches('.').to_ascii_lowercase();
+    let invalid = |why: &str| format!("Invalid PTR name '{}': {}", name.trim(), why);
+
+    if let Some(labels) = lowered.strip_suffix(".in-addr.arpa") {
+        let octets: Vec<&str> = labels.split('.').collect();
+        if octets.len() != 4 {
+            return Err(invalid("expected 4 octets before in-addr.arpa"));
+        }
+        let mut bytes = [0u8; 4];
+        for (i, octet) in octets.iter().rev().enumerate() {
+            bytes[i] = octet.parse().map_err(|_| invalid("octets must be 0-255"))?;
+        }
+        return Ok(std::net::Ipv4Addr::from(bytes).into());
+    }
+    if let Some(labels) = lowered.strip_suffix(".ip6.arpa") {
+        let nibbles: Vec<&str> = labels.split('.').collect();
+        if nibbles.len() != 32 {
+            return Err(invalid("expected 32 nibbles before ip6.arpa"));
+        }
+        let mut bytes = [0u8; 16];
+        for (i, nibble) in nibbles.iter().rev().enumerate() {
+            if nibble.len() != 1 {
+                return Err(invalid("nibbles must be single hex digits"));
+            }
+            let value = u8::from_str_radix(nibble, 16).map_err(|_| invalid("nibbles must be single hex digits"))?;
+            bytes[i / 2] |= if i % 2 == 0 { value << 4 } else { value };
+        }
+        return Ok(std::net::Ipv6Addr::from(bytes).into());
+    }
+    Err(invalid("expected an in-addr.arpa or ip6.arpa name"))
+}
+
+/// SRV record fields
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrvRecord {
+    pub priority: u16,
+    pub weight: u16,
+    pub port: u16,
+    pub target: String,
+}
+
+/// Decode SRV RDATA; the target name may be compressed, hence the packet
+pub fn srv_record(packet: &[u8], record: &DnsRecord) -> Option<SrvRecord> {
+    let rdata = record.rdata.get(..6)?;
+    let (target, _) = read_name(packet, record.rdata_offset + 6)?;
+    Some(SrvRecord {
+        priority: u16::from_be_bytes([rdata[0], rdata[1]]),
+        weight: u16::from_be_bytes([rdata[2], rdata[3]]),
+        port: u16::from_be_bytes([rdata[4], rdata[5]]),
+        target,
+    })
+}
+
+/// Decode A / AAAA RDATA
+pub fn address_record(record: &DnsRecord) -> Option<std::net::IpAddr> {
+    match (record.rtype, record.rdata.len()) {
+        (TYPE_A, 4) => Some(std::net::IpAddr::from(<[u8; 4]>::try_from(record.rdata.as_slice()).ok()?)),
+        (TYPE_AAAA, 16) => Some(std::net::IpAddr::from(<[u8; 16]>::try_from(record.rdata.as_slice()).ok()?)),
+        _ => None,
+    }
+}
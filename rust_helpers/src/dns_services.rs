@@ -0,0 +1,323 @@
+use std::collections::{BTreeSet, HashMap};
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::time::Duration;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rayon::prelude::*;
+
+use crate::dns;
+use crate::resolv_conf::ResolvConf;
+
+// =============================================================================
+// DNS Service (SRV) Discovery
+// =============================================================================
+
+fn parse_server(server: &str) -> Result<SocketAddr, String> {
+    server
+        .parse::<SocketAddr>()
+        .or_else(|_| server.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 53)))
+        .map_err(|_| format!("Invalid DNS server: {}", server))
+}
+
+/// One recursive query over UDP; Ok(None) for NXDOMAIN / empty answers
+fn query(server: SocketAddr, name: &str, qtype: u16, timeout_ms: u64) -> Result<Option<(Vec<u8>, dns::DnsResponse)>, String> {
+    let bind: SocketAddr = if server.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }.parse().expect("valid bind address");
+    let socket = UdpSocket::bind(bind).map_err(|e| e.to_string())?;
+    socket
+        .set_read_timeout(Some(Duration::from_millis(timeout_ms.max(1))))
+        .map_err(|e| e.to_string())?;
+
+    let id = (std::process::id() as u16) ^ (crate::scanner::unix_now().fract() * 65536.0) as u16;
+    socket
+        .send_to(&dns::build_query(id, name, qtype, dns::CLASS_IN, true), server)
+        .map_err(|e| format!("DNS query to {} failed: {}", server, e))?;
+
+    let mut buf = vec![0u8; 4096];
+    loop {
+        let (n, from) = socket
+            .recv_from(&mut buf)
+            .map_err(|_| format!("DNS server {} did not answer for {}", server, name))?;
+        if from != server || n < 2 || u16::from_be_bytes([buf[0], buf[1]]) != id {
+            continue;
+        }
+        let packet = buf[..n].to_vec();
+        let Some(response) = dns::parse_response(&packet) else {
+            return Err(format!("Malformed DNS response for {}", name));
+        };
+        return match response.rcode {
+            0 => Ok(Some((packet, response))),
+            3 => Ok(None),
+            rcode => Err(format!("DNS server {} returned rcode {} for {}", server, rcode, name)),
+        };
+    }
+}
+
+/// "_ldap._tcp" stays as is; a bare "ldap" becomes "_ldap._tcp"
+fn service_label(service: &str) -> String {
+    let service = service.trim().trim_matches('.');
+    if service.starts_with('_') {
+        service.to_string()
+    } else {
+        format!("_{}._tcp", service)
+    }
+}
+
+/// Role name for a label: "_kerberos._tcp" -> "kerberos"
+fn service_role(label: &str) -> String {
+    label.split('.').next().unwrap_or(label).trim_start_matches('_').to_lowercase()
+}
+
+fn resolve_target(server: SocketAddr, target: &str, timeout_ms: u64) -> Vec<String> {
+    let mut addresses = Vec::new();
+    for qtype in [dns::TYPE_A, dns::TYPE_AAAA] {
+        if let Ok(Some((_, response))) = query(server, target, qtype, timeout_ms) {
+            addresses.extend(response.answers.iter().filter_map(dns::address_record).map(|ip| ip.to_string()));
+        }
+    }
+    addresses
+}
+
+/// Query SRV records for each service under `domain`
+///
+/// `services` are labels such as "_ldap._tcp" (or bare "ldap", taken as
+/// TCP). Returns one dict per SRV target: {service, role, target, port,
+/// priority, weight, addresses}, with the target's A/AAAA addresses resolved
+/// through the same server. `server` defaults to the first system
+/// nameserver (resolv.conf, or `ipconfig /all` on Windows). Services without records are skipped.
+#[pyfunction]
+#[pyo3(signature = (domain, services, server=None, timeout_ms=2000))]
+pub fn dns_service_lookup(
+    py: Python,
+    domain: &str,
+    services: Vec<String>,
+    server: Option<&str>,
+    timeout_ms: u64,
+) -> PyResult<Vec<HashMap<String, PyObject>>> {
+    let server = match server {
+        Some(server) => parse_server(server),
+        None => ResolvConf::system()
+            .nameservers
+            .first()
+            .map(|ip| SocketAddr::new(*ip, 53))
+            .ok_or_else(|| "No nameserver configured; pass server=".to_string()),
+    }
+    .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+    let domain = domain.trim().trim_matches('.').to_string();
+
+    let found = py
+        .allow_threads(|| -> Result<Vec<(String, dns::SrvRecord, Vec<String>)>, String> {
+            let mut found = Vec::new();
+            for service in &services {
+                let label = service_label(service);
+                let Some((packet, response)) = query(server, &format!("{}.{}", label, domain), dns::TYPE_SRV, timeout_ms)? else {
+                    continue;
+                };
+                let mut records: Vec<dns::SrvRecord> = response
+                    .answers
+                    .iter()
+                    .filter(|a| a.rtype == dns::TYPE_SRV)
+                    .filter_map(|a| dns::srv_record(&packet, a))
+                    // "." as the target means the service is explicitly not offered
+                    .filter(|srv| !srv.target.is_empty() && srv.target != ".")
+                    .collect();
+                records.sort_by(|a, b| a.priority.cmp(&b.priority).then(b.weight.cmp(&a.weight)));
+                for srv in records {
+                    let addresses = resolve_target(server, &srv.target, timeout_ms);
+                    found.push((label.clone(), srv, addresses));
+                }
+            }
+            Ok(found)
+        })
+        .map_err(PyErr::new::<pyo3::exceptions::PyIOError, _>)?;
+
+    Ok(found
+        .into_iter()
+        .map(|(label, srv, addresses)| {
+            let mut map = HashMap::new();
+            map.insert("role".to_string(), service_role(&label).into_py(py));
+            map.insert("service".to_string(), label.into_py(py));
+            map.insert("target".to_string(), srv.target.trim_end_matches('.').into_py(py));
+            map.insert("port".to_string(), srv.port.into_py(py));
+            map.insert("priority".to_string(), srv.priority.into_py(py));
+            map.insert("weight".to_string(), srv.weight.into_py(py));
+            map.insert("addresses".to_string(), addresses.into_py(py));
+            map
+        })
+        .collect())
+}
+
+/// Copy each device dict with a sorted `dns_roles` list naming every SRV
+/// role (from `dns_service_lookup` output) whose addresses include its `ip`
+#[pyfunction]
+pub fn mark_dns_roles<'py>(
+    py: Python<'py>,
+    devices: Vec<&'py PyDict>,
+    srv_records: Vec<&'py PyDict>,
+) -> PyResult<Vec<&'py PyDict>> {
+    let mut roles: HashMap<IpAddr, BTreeSet<String>> = HashMap::new();
+    for record in srv_records {
+        let role: String = match record.get_item("role")? {
+            Some(role) => role.extract()?,
+            None => continue,
+        };
+        let addresses: Vec<String> = match record.get_item("addresses")? {
+            Some(addresses) => addresses.extract()?,
+            None => continue,
+        };
+        for address in addresses {
+            if let Ok(ip) = address.trim().parse::<IpAddr>() {
+                roles.entry(ip).or_default().insert(role.clone());
+            }
+        }
+    }
+
+    devices
+        .into_iter()
+        .map(|device| {
+            let device = device.copy()?;
+            let ip = match device.get_item("ip")? {
+                Some(ip) => ip.extract::<String>().ok().and_then(|ip| ip.trim().parse::<IpAddr>().ok()),
+                None => None,
+            };
+            let device_roles: Vec<String> = ip
+                .and_then(|ip| roles.get(&ip))
+                .map(|r| r.iter().cloned().collect())
+                .unwrap_or_default();
+            device.set_item("dns_roles", device_roles.into_py(py))?;
+            Ok(device)
+        })
+        .collect()
+}
+
+// =============================================================================
+// Forward Resolution
+// =============================================================================
+
+/// How one name resolved: the qualified name that answered, its addresses,
+/// and the server whose answer was used (or that last answered, when none
+/// had addresses)
+#[derive(Debug, Default)]
+struct Resolution {
+    fqdn: String,
+    addresses: Vec<IpAddr>,
+    server: Option<SocketAddr>,
+    error: String,
+}
+
+/// A and AAAA answers for one qualified name from the first server that
+/// answers; a server that times out or fails is skipped, but an empty or
+/// NXDOMAIN answer is final, as in the system resolver
+fn query_addresses(servers: &[SocketAddr], fqdn: &str, timeout_ms: u64) -> Result<(SocketAddr, Vec<IpAddr>), String> {
+    let mut last_error = String::new();
+    for &server in servers {
+        let mut addresses = Vec::new();
+        let mut answered = false;
+        for qtype in [dns::TYPE_A, dns::TYPE_AAAA] {
+            match query(server, fqdn, qtype, timeout_ms) {
+                Ok(Some((_, response))) => {
+                    answered = true;
+                    addresses.extend(response.answers.iter().filter_map(dns::address_record));
+                }
+                Ok(None) => answered = true,
+                Err(e) => last_error = e,
+            }
+        }
+        if answered {
+            return Ok((server, addresses));
+        }
+    }
+    Err(last_error)
+}
+
+fn resolve_name(conf: &ResolvConf, servers: &[SocketAddr], name: &str, timeout_ms: u64) -> Resolution {
+    let mut resolution = Resolution::default();
+    if let Ok(ip) = name.trim().parse::<IpAddr>() {
+        resolution.fqdn = ip.to_string();
+        resolution.addresses.push(ip);
+        return resolution;
+    }
+    for fqdn in conf.candidates(name) {
+        match query_addresses(servers, &fqdn, timeout_ms) {
+            Ok((server, addresses)) => {
+                resolution.server = Some(server);
+                if !addresses.is_empty() {
+                    resolution.fqdn = fqdn;
+                    resolution.addresses = addresses;
+                    resolution.error.clear();
+                    return resolution;
+                }
+                resolution.error = format!("{} not found", name.trim());
+            }
+            Err(e) => resolution.error = e,
+        }
+    }
+    resolution
+}
+
+/// Forward-resolve names the way the system resolver would, reporting
+/// which nameserver answered
+///
+/// Nameservers, search domains and ndots come from /etc/resolv.conf
+/// (`ipconfig /all` on Windows); `servers`, `search` and `ndots` override
+/// them. Short names are qualified with the search domains, so "printer-3"
+/// can resolve as "printer-3.corp.example.com". Servers are tried in order
+/// and only a server that fails to answer passes the name to the next one.
+///
+/// Returns one dict per name, in order: {name, fqdn, addresses, server,
+/// error}. `server` ("ip:port") is the server whose answer was used, or the
+/// last one that answered at all, which helps spot split-horizon answers;
+/// it is None for IP literals and when no server answered.
+#[pyfunction]
+#[pyo3(signature = (names, servers=None, search=None, ndots=None, timeout_ms=2000))]
+pub fn resolve_names(
+    py: Python,
+    names: Vec<String>,
+    servers: Option<Vec<String>>,
+    search: Option<Vec<String>>,
+    ndots: Option<usize>,
+    timeout_ms: u64,
+) -> PyResult<Vec<HashMap<String, PyObject>>> {
+    let mut conf = ResolvConf::system();
+    if let Some(search) = search {
+        conf.search = search
+            .iter()
+            .map(|d| d.trim().trim_matches('.').to_lowercase())
+            .filter(|d| !d.is_empty())
+            .collect();
+    }
+    if let Some(ndots) = ndots {
+        conf.ndots = ndots;
+    }
+    let servers: Vec<SocketAddr> = match servers {
+        Some(servers) => servers
+            .iter()
+            .map(|s| parse_server(s.trim()))
+            .collect::<Result<_, _>>()
+            .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?,
+        None => conf.nameservers.iter().map(|ip| SocketAddr::new(*ip, 53)).collect(),
+    };
+    if servers.is_empty() && names.iter().any(|n| n.trim().parse::<IpAddr>().is_err()) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "No nameserver configured; pass servers=",
+        ));
+    }
+
+    let resolutions: Vec<Resolution> = py.allow_threads(|| {
+        names.par_iter().map(|name| resolve_name(&conf, &servers, name, timeout_ms)).collect()
+    });
+
+    Ok(names
+        .into_iter()
+        .zip(resolutions)
+        .map(|(name, resolution)| {
+            let mut map = HashMap::new();
+            map.insert("name".to_string(), name.into_py(py));
+            map.insert("fqdn".to_string(), resolution.fqdn.into_py(py));
+            let addresses: Vec<String> = resolution.addresses.iter().map(|ip| ip.to_string()).collect();
+            map.insert("addresses".to_string(), addresses.into_py(py));
+            map.insert("server".to_string(), resolution.server.map(|s| s.to_string()).into_py(py));
+            map.insert("error".to_string(), resolution.error.into_py(py));
+            map
+        })
+        .collect())
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::scanner::ScanResult;
+
+// =============================================================================
+// Result Enrichment
+// =============================================================================
+
+/// Hostname sources in priority order: reverse DNS, NetBIOS/mDNS, ARP, DHCP
+pub const HOSTNAME_PRIORITY: &[&str] = &["dns", "netbios", "mdns", "arp", "dhcp"];
+
+/// Record every non-empty source value and set `hostname` to the highest
+/// priority one. The existing hostname is kept if no source has a value.
+pub fn apply_hostname_sources(result: &mut ScanResult, sources: &HashMap<String, String>) {
+    for (source, name) in sources {
+        let name = name.trim();
+        if !name.is_empty() {
+            result.hostname_sources.insert(source.to_lowercase(), name.to_string());
+        }
+    }
+
+    if let Some(best) = HOSTNAME_PRIORITY
+        .iter()
+        .find_map(|source| result.hostname_sources.get(*source))
+    {
+        result.hostname = best.clone();
+    }
+}
+
+/// Set a result's hostname from multiple sources by priority
+/// (dns > netbios > mdns > arp > dhcp), keeping all values in `hostname_sources`
+#[pyfunction]
+pub fn enrich_hostname_priority(mut result: ScanResult, sources: HashMap<String, String>) -> ScanResult {
+    apply_hostname_sources(&mut result, &sources);
+    result
+}
+
+/// Batch variant: `sources` maps IP -> {source: hostname}
+#[pyfunction]
+pub fn enrich_hostnames_batch(
+    results: Vec<ScanResult>,
+    sources: HashMap<String, HashMap<String, String>>,
+) -> Vec<ScanResult> {
+    results
+        .into_par_iter()
+        .map(|mut result| {
+            if let Some(host_sources) = sources.get(&result.ip) {
+                apply_hostname_sources(&mut result, host_sources);
+            }
+            result
+        })
+        .collect()
+}
+
+// =============================================================================
+// DHCP Lease Enrichment
+// =============================================================================
+
+fn dhcp_lease(ip: &str, mac: &str, hostname: &str) -> Option<HashMap<String, String>> {
+    let ip: std::net::IpAddr = ip.trim().parse().ok()?;
+    let mut lease = HashMap::new();
+    lease.insert("ip".to_string(), ip.to_string());
+    lease.insert("mac".to_string(), if mac.is_empty() { String::new() } else { crate::normalize_mac(mac) });
+    lease.insert("hostname".to_string(), hostname.trim_matches('"').to_string());
+    Some(lease)
+}
+
+/// ISC dhcpd.leases: `lease <ip> { hardware ethernet <mac>; client-hostname "<name>"; ... }`
+///
+/// Leases are appended as they change, so later blocks supersede earlier
+/// ones; blocks whose binding state is not active are dropped.
+fn parse_isc_leases(content: &str) -> Vec<HashMap<String, String>> {
+    let mut leases = Vec::new();
+    let mut current: Option<(String, String, String, bool)> = None;
+
+    for line in content.lines() {
+        let line = line.trim().trim_end_matches(';');
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["lease", ip, "{"] => current = Some((ip.to_string(), String::new(), String::new(), true)),
+            ["}"] => {
+                if let Some((ip, mac, hostname, active)) = current.take() {
+                    if active {
+                        leases.extend(dhcp_lease(&ip, &mac, &hostname));
+                    }
+                }
+            }
+            ["hardware", "ethernet", mac] => {
+                if let Some(lease) = current.as_mut() {
+                    lease.1 = mac.to_string();
+                }
+            }
+            ["client-hostname", name @ ..] => {
+                if let Some(lease) = current.as_mut() {
+                    lease.2 = name.join(" ");
+                }
+            }
+            ["binding", "state", state] => {
+                if let Some(lease) = current.as_mut() {
+                    lease.3 = *state == "active";
+                }
+            }
+            _ => {}
+        }
+    }
+    leases
+}
+
+/// dnsmasq.leases: `<expiry> <mac> <ip> <hostname|*> <client-id>`
+fn parse_dnsmasq_leases(content: &str) -> Vec<HashMap<String, String>> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            if tokens.len() < 4 || tokens[0].parse::<u64>().is_err() {
+                return None;
+            }
+            let hostname = if tokens[3] == "*" { "" } else { tokens[3] };
+            dhcp_lease(tokens[2], tokens[1], hostname)
+        })
+        .collect()
+}
+
+/// Leases from an ISC dhcpd or dnsmasq file as {ip, mac, hostname}, in file
+/// order; invalid UTF-8 is replaced, with a RuntimeWarning
+fn read_lease_file(py: Python, filepath: &str) -> PyResult<Vec<HashMap<String, String>>> {
+    let bytes = std::fs::read(filepath).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot read lease file: {}", e))
+    })?;
+    let (content, replaced) = crate::decode_lossy(&bytes);
+    crate::warn_replaced_bytes(py, filepath, replaced)?;
+    let content = crate::clean_text(&content);
+
+    if content.lines().any(|line| line.trim_start().starts_with("lease ")) {
+        Ok(parse_isc_leases(&content))
+    } else {
+        Ok(parse_dnsmasq_leases(&content))
+    }
+}
+
+/// Parse a DHCP lease file (ISC dhcpd or dnsmasq format) into ScanResults
+/// with source "dhcp_lease", in file order
+///
+/// `legacy=True` returns the old [{ip, mac, hostname}] dicts.
+#[pyfunction]
+#[pyo3(signature = (filepath, legacy=false))]
+pub fn parse_dhcp_lease_file(py: Python, filepath: &str, legacy: bool) -> PyResult<PyObject> {
+    crate::schema::emit_records(py, "parse_dhcp_lease_file", read_lease_file(py, filepath)?, "dhcp_lease", legacy)
+}
+
+/// Fill empty `mac` and `hostname` fields from a DHCP lease file
+///
+/// Existing values are never overwritten; the lease hostname is also
+/// recorded under `hostname_sources["dhcp"]`. Useful when ARP-based MAC
+/// discovery fails (routed segments, hosts behind L3 boundaries).
+#[pyfunction]
+pub fn enrich_scan_results_from_dhcp(py: Python, results: Vec<ScanResult>, lease_path: &str) -> PyResult<Vec<ScanResult>> {
+    // Later leases win for the same IP
+    let leases: HashMap<String, (String, String)> = read_lease_file(py, lease_path)?
+        .into_iter()
+        .map(|mut lease| {
+            let field = |lease: &mut HashMap<String, String>, key: &str| lease.remove(key).unwrap_or_default();
+            (field(&mut lease, "ip"), (field(&mut lease, "mac"), field(&mut lease, "hostname")))
+        })
+        .collect();
+
+    Ok(results
+        .into_par_iter()
+        .map(|mut result| {
+            let ip = result.ip.trim().parse::<std::net::IpAddr>().map(|ip| ip.to_string());
+            if let Some((mac, hostname)) = ip.ok().and_then(|ip| leases.get(&ip)) {
+                if result.mac.trim().is_empty() && !mac.is_empty() {
+                    result.mac = mac.clone();
+                }
+                if !hostname.is_empty() {
+                    result.hostname_sources.entry("dhcp".to_string()).or_insert_with(|| hostname.clone());
+                    if result.hostname.trim().is_empty() {
+                        result.hostname = hostname.clone();
+                    }
+                }
+            }
+            result
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dnsmasq_leases_survive_crlf_nbsp_and_invalid_utf8() {
+        let bytes = std::fs::read(crate::tests::fixture("dnsmasq_mixed.leases")).unwrap();
+        let (content, replaced) = crate::decode_lossy(&bytes);
+        assert_eq!(replaced, 1);
+        let leases = parse_dnsmasq_leases(&crate::clean_text(&content));
+        let ips: Vec<&str> = leases.iter().map(|lease| lease["ip"].as_str()).collect();
+        assert_eq!(ips, ["10.0.0.1", "10.0.0.2", "10.0.0.3"]);
+        assert_eq!(leases[1]["hostname"], "phone");
+    }
+}
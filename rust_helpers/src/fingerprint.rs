@@ -0,0 +1,169 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyFrozenSet, PyList, PySet, PyTuple};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+
+// =============================================================================
+// Device Fingerprints
+// =============================================================================
+//
+// Canonicalization rules (any change here changes every fingerprint):
+//   - Field names are trimmed and lowercased, deduplicated, then sorted; the
+//     device's keys are matched case-insensitively.
+//   - Missing fields and None hash the same as an empty string, so a device
+//     without a vendor equals one with vendor "".
+//   - Strings are trimmed and lowercased; "mac" is normalized first, so
+//     "aa-bb-cc-dd-ee-ff" and "AA:BB:CC:DD:EE:FF" are identical.
+//   - Lists, tuples and sets are canonicalized element-wise, deduplicated and
+//     sorted (numerically when every element is an integer) and joined by ",".
+//   - Booleans become "true"/"false"; other values use their str().
+//   - The digest input is one "name=value\n" line per field; output is hex SHA-256.
+
+pub const DEFAULT_FINGERPRINT_FIELDS: &[&str] = &["device_type", "mac", "open_ports", "vendor"];
+
+fn canonical_scalar(field: &str, value: &PyAny) -> PyResult<String> {
+    if value.is_none() {
+        return Ok(String::new());
+    }
+    if let Ok(b) = value.extract::<bool>() {
+        return Ok(b.to_string());
+    }
+    let text = match value.extract::<&str>() {
+        Ok(s) => s.to_string(),
+        Err(_) => value.str()?.to_str()?.to_string(),
+    };
+    let text = text.trim();
+    Ok(if field == "mac" && !text.is_empty() {
+        crate::normalize_mac(text).to_lowercase()
+    } else {
+        text.to_lowercase()
+    })
+}
+
+fn canonical_value(field: &str, value: &PyAny) -> PyResult<String> {
+    let items: Option<Vec<&PyAny>> = if let Ok(list) = value.downcast::<PyList>() {
+        Some(list.iter().collect())
+    } else if let Ok(tuple) = value.downcast::<PyTuple>() {
+        Some(tuple.iter().collect())
+    } else if let Ok(set) = value.downcast::<PySet>() {
+        Some(set.iter().collect())
+    } else if let Ok(set) = value.downcast::<PyFrozenSet>() {
+        Some(set.iter().collect())
+    } else {
+        None
+    };
+
+    let Some(items) = items else {
+        return canonical_scalar(field, value);
+    };
+    let mut parts: Vec<String> = items
+        .into_iter()
+        .map(|item| canonical_scalar(field, item))
+        .collect::<PyResult<_>>()?;
+    parts.sort();
+    parts.dedup();
+    if parts.iter().all(|p| p.parse::<i64>().is_ok()) {
+        parts.sort_by_key(|p| p.parse::<i64>().unwrap_or_default());
+    }
+    Ok(parts.join(","))
+}
+
+fn canonical_fields(fields: Option<Vec<String>>) -> Vec<String> {
+    let mut fields: Vec<String> = match fields {
+        Some(fields) => fields.iter().map(|f| f.trim().to_lowercase()).collect(),
+        None => DEFAULT_FINGERPRINT_FIELDS.iter().map(|f| f.to_string()).collect(),
+    };
+    fields.sort();
+    fields.dedup();
+    fields
+}
+
+/// The exact text that gets hashed for a device
+fn canonical_form(device: &PyDict, fields: &[String]) -> PyResult<String> {
+    let mut out = String::new();
+    for field in fields {
+        let value = device
+            .iter()
+            .find(|(k, _)| {
+                k.extract::<&str>()
+                    .map(|k| k.trim().eq_ignore_ascii_case(field))
+                    .unwrap_or(false)
+            })
+            .map(|(_, v)| canonical_value(field, v))
+            .transpose()?
+            .unwrap_or_default();
+        out.push_str(field);
+        out.push('=');
+        out.push_str(&value);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+pub fn sha256_hex(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Stable hex SHA-256 over a canonical subset of device fields
+///
+/// Defaults to device_type, mac, open_ports and vendor; see the rules above.
+#[pyfunction]
+#[pyo3(signature = (device, fields=None))]
+pub fn fingerprint_device(device: &PyDict, fields: Option<Vec<String>>) -> PyResult<String> {
+    let fields = canonical_fields(fields);
+    Ok(sha256_hex(&canonical_form(device, &fields)?))
+}
+
+/// Fingerprint many devices (same fields for all), preserving order
+#[pyfunction]
+#[pyo3(signature = (devices, fields=None))]
+pub fn fingerprint_devices(devices: Vec<&PyDict>, fields: Option<Vec<String>>) -> PyResult<Vec<String>> {
+    let fields = canonical_fields(fields);
+    let forms: Vec<String> = devices
+        .iter()
+        .map(|device| canonical_form(device, &fields))
+        .collect::<PyResult<_>>()?;
+    Ok(forms.par_iter().map(|form| sha256_hex(form)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device<'py>(py: Python<'py>, literal: &str) -> &'py PyDict {
+        py.eval(literal, None, None).unwrap().downcast().unwrap()
+    }
+
+    #[test]
+    fn canonicalization_ignores_order_case_and_absence() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let a = device(py, "{'mac': 'aa-bb-cc-dd-ee-ff', 'open_ports': [443, 22, 80, 22], 'vendor': 'Cisco', 'device_type': None}");
+            let b = device(py, "{' Vendor': 'CISCO ', 'open_ports': {80, 443, 22}, 'MAC': 'AA:BB:CC:DD:EE:FF'}");
+            let fields = canonical_fields(None);
+            assert_eq!(
+                canonical_form(a, &fields).unwrap(),
+                "device_type=\nmac=aa:bb:cc:dd:ee:ff\nopen_ports=22,80,443\nvendor=cisco\n"
+            );
+            assert_eq!(fingerprint_device(a, None).unwrap(), fingerprint_device(b, None).unwrap());
+
+            let reordered = Some(vec!["VENDOR".to_string(), " mac".to_string(), "vendor".to_string()]);
+            let ordered = Some(vec!["mac".to_string(), "vendor".to_string()]);
+            assert_eq!(fingerprint_device(a, reordered).unwrap(), fingerprint_device(a, ordered).unwrap());
+
+            let empty = device(py, "{'vendor': ''}");
+            let missing = device(py, "{}");
+            assert_eq!(fingerprint_device(empty, None).unwrap(), fingerprint_device(missing, None).unwrap());
+
+            let changed = device(py, "{'mac': 'aa:bb:cc:dd:ee:ff', 'open_ports': [22, 80], 'vendor': 'cisco'}");
+            assert_ne!(fingerprint_device(a, None).unwrap(), fingerprint_device(changed, None).unwrap());
+            assert_eq!(
+                fingerprint_devices(vec![a, changed], None).unwrap(),
+                [fingerprint_device(a, None).unwrap(), fingerprint_device(changed, None).unwrap()]
+            );
+        });
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use pyo3::prelude::*;
+
+use crate::scanner::ScanResult;
+
+// =============================================================================
+// Honeypot / Tarpit Detection
+// =============================================================================
+//
+// Honeypots and tarpits (and IDS appliances that answer for a subnet once a
+// scan is noticed) accept connections on every port, so a result claiming
+// dozens of open services is more likely an artifact than a real host.
+// Results keep a single response time per host, so timing consistency is
+// judged across hosts: several hosts exposing the identical large port set,
+// scanned within seconds and answering within the same millisecond or so,
+// look like one responder rather than independent machines.
+
+/// Open ports above which a host is flagged outright
+const MAX_PLAUSIBLE_OPEN_PORTS: usize = 50;
+/// Open ports below which the volume signal is zero
+const VOLUME_FLOOR: usize = 10;
+/// Tested ports needed before "every tested port is open" means anything
+const MIN_COVERAGE_PORTS: usize = 20;
+/// Score from which `detect_port_scan_from_results` reports a host
+const SUSPICION_THRESHOLD: f64 = 0.8;
+/// Hosts sharing an open port set needed to call it a cluster
+const CLUSTER_MIN_HOSTS: usize = 3;
+/// Open ports a clustered host must expose
+const CLUSTER_MIN_PORTS: usize = 10;
+/// Longest scan_timestamp spread within a cluster, in seconds
+const CLUSTER_WINDOW_S: f64 = 10.0;
+
+/// Suspicion that a result is a honeypot or tarpit rather than a real host,
+/// from 0.0 (nothing unusual) to 1.0
+///
+/// Two signals are combined as independent evidence (1 - Π(1 - s)):
+///   volume:   0.9 above 50 open ports, scaling up from 0 at 10 to 0.6 at 50
+///   coverage: 0.8 when `port_state_detail` lists at least 20 tested ports
+///             and every one of them is open
+/// Either signal alone reaches the detection threshold only in its strong
+/// form; both together give 0.98.
+pub fn compute_port_scan_suspicion_score(result: &ScanResult) -> f64 {
+    let open: HashSet<u16> = result.open_ports.iter().copied().collect();
+
+    let volume = if open.len() > MAX_PLAUSIBLE_OPEN_PORTS {
+        0.9
+    } else {
+        let span = (MAX_PLAUSIBLE_OPEN_PORTS - VOLUME_FLOOR) as f64;
+        (open.len().saturating_sub(VOLUME_FLOOR) as f64 / span).min(1.0) * 0.6
+    };
+
+    let tested = &result.port_state_detail;
+    let coverage = if tested.len() >= MIN_COVERAGE_PORTS
+        && tested.iter().all(|(port, state)| state == "open" || open.contains(port))
+    {
+        0.8
+    } else {
+        0.0
+    };
+
+    1.0 - (1.0 - volume) * (1.0 - coverage)
+}
+
+/// Python-facing `compute_port_scan_suspicion_score`
+#[pyfunction]
+#[pyo3(name = "compute_port_scan_suspicion_score")]
+pub fn compute_port_scan_suspicion_score_py(result: ScanResult) -> f64 {
+    compute_port_scan_suspicion_score(&result)
+}
+
+/// Hosts in `results` that share one large open port set, were scanned
+/// within CLUSTER_WINDOW_S of each other and answered with near-identical
+/// response times (spread within 1 ms or 5% of the mean)
+fn consistent_clusters(results: &[ScanResult]) -> HashSet<String> {
+    let mut groups: HashMap<BTreeSet<u16>, Vec<&ScanResult>> = HashMap::new();
+    for result in results {
+        let ports: BTreeSet<u16> = result.open_ports.iter().copied().collect();
+        // Unknown timestamps can't show the hosts answered together
+        if ports.len() >= CLUSTER_MIN_PORTS && result.scan_timestamp > 0.0 {
+            groups.entry(ports).or_default().push(result);
+        }
+    }
+
+    let mut flagged = HashSet::new();
+    for members in groups.values().filter(|m| m.len() >= CLUSTER_MIN_HOSTS) {
+        let spread = |value: fn(&ScanResult) -> f64| {
+            let values = members.iter().map(|r| value(r));
+            let max = values.clone().fold(f64::MIN, f64::max);
+            let min = values.fold(f64::MAX, f64::min);
+            max - min
+        };
+        let mean_rtt = members.iter().map(|r| r.response_time_ms).sum::<f64>() / members.len() as f64;
+        let rtt_tolerance = (mean_rtt * 0.05).max(1.0);
+        if spread(|r| r.scan_timestamp) <= CLUSTER_WINDOW_S && spread(|r| r.response_time_ms) <= rtt_tolerance {
+            flagged.extend(members.iter().map(|r| r.ip.clone()));
+        }
+    }
+    flagged
+}
+
+/// IPs of suspected honeypot / tarpit hosts, in input order
+///
+/// A host is reported when its `compute_port_scan_suspicion_score` reaches
+/// 0.8 (more than 50 open ports, or every tested port open), when it has
+/// every port tested anywhere in the batch open (at least 20 of them: the
+/// union of all open ports and `port_state_detail` entries stands in for the
+/// scan's port list), or when it belongs to a cluster of three or more hosts
+/// with the same open port set (at least 10 ports), scan timestamps within
+/// 10 seconds and near-identical response times.
+#[pyfunction]
+pub fn detect_port_scan_from_results(results: Vec<ScanResult>) -> Vec<String> {
+    let tested: HashSet<u16> = results
+        .iter()
+        .flat_map(|r| r.open_ports.iter().chain(r.port_state_detail.keys()).copied())
+        .collect();
+    let clustered = consistent_clusters(&results);
+
+    let mut seen = HashSet::new();
+    results
+        .iter()
+        .filter(|result| {
+            let open: HashSet<u16> = result.open_ports.iter().copied().collect();
+            compute_port_scan_suspicion_score(result) >= SUSPICION_THRESHOLD
+                || (tested.len() >= MIN_COVERAGE_PORTS && open.len() == tested.len())
+                || clustered.contains(&result.ip)
+        })
+        .filter(|result| seen.insert(result.ip.clone()))
+        .map(|result| result.ip.clone())
+        .collect()
+}
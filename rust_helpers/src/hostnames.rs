@@ -0,0 +1,264 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+// =============================================================================
+// Hostname Naming Conventions
+// =============================================================================
+//
+// Names are split on '-', '_' and '.' into segments; an all-digit segment
+// becomes a numeric slot, so "prt-bld2-01" and "prt-bld2-44" share the
+// template "prt-bld2-{n}". Segments with letters stay literal ("bld2" is a
+// site code, not a number). Templates that differ in a single literal
+// segment ("prt-bld2-{n}", "prt-hq-{n}") reveal a field that varies while
+// the rest of the convention holds: site codes, buildings, roles.
+
+/// Members listed per discovered pattern
+const EXAMPLES: usize = 3;
+/// Digit runs longer than this stay literal (serials, not sequence numbers)
+const MAX_SLOT_DIGITS: usize = 18;
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Piece {
+    Lit(String),
+    Delim(char),
+    Slot,
+    Wild,
+}
+
+fn is_delim(c: char) -> bool {
+    matches!(c, '-' | '_' | '.')
+}
+
+/// Template pieces and the slot values of one name
+fn tokenize(name: &str) -> (Vec<Piece>, Vec<(u64, usize, bool)>) {
+    let mut pieces = Vec::new();
+    let mut slots = Vec::new();
+    let mut segment = String::new();
+    let mut flush = |segment: &mut String, pieces: &mut Vec<Piece>| {
+        if segment.is_empty() {
+            return;
+        }
+        let numeric = segment.len() <= MAX_SLOT_DIGITS && segment.chars().all(|c| c.is_ascii_digit());
+        match segment.parse::<u64>() {
+            Ok(value) if numeric => {
+                // (value, digits, zero-padded)
+                slots.push((value, segment.len(), segment.len() > 1 && segment.starts_with('0')));
+                pieces.push(Piece::Slot);
+            }
+            _ => pieces.push(Piece::Lit(segment.clone())),
+        }
+        segment.clear();
+    };
+    for c in name.chars() {
+        if is_delim(c) {
+            flush(&mut segment, &mut pieces);
+            pieces.push(Piece::Delim(c));
+        } else {
+            segment.push(c);
+        }
+    }
+    flush(&mut segment, &mut pieces);
+    (pieces, slots)
+}
+
+/// "{n}", or "{n:02}" when the values are zero-padded to a fixed width
+fn slot_text(values: &[(u64, usize, bool)]) -> String {
+    match values.iter().filter(|(_, _, padded)| *padded).map(|(_, digits, _)| *digits).max() {
+        Some(width) => format!("{{n:{:02}}}", width),
+        None => "{n}".to_string(),
+    }
+}
+
+fn render(pieces: &[Piece], slot_values: &[Vec<(u64, usize, bool)>]) -> String {
+    let mut out = String::new();
+    let mut slot = 0;
+    for piece in pieces {
+        match piece {
+            Piece::Lit(text) => out.push_str(text),
+            Piece::Delim(c) => out.push(*c),
+            Piece::Slot => {
+                out.push_str(&slot_values.get(slot).map(|v| slot_text(v)).unwrap_or_else(|| "{n}".to_string()));
+                slot += 1;
+            }
+            Piece::Wild => out.push_str("{*}"),
+        }
+    }
+    out
+}
+
+struct Template {
+    /// (slot values, name), sorted so examples come out in sequence order
+    members: Vec<(Vec<u64>, String)>,
+    slots: Vec<Vec<(u64, usize, bool)>>,
+}
+
+/// Infer naming conventions from a list of hostnames
+///
+/// Returns patterns sorted by member count, each a dict with "kind",
+/// "pattern", "count" and "examples":
+///   sequence  names differing only in numbers, e.g. "prt-bld2-{n:02}";
+///             adds "ranges" (min, max) per slot and "gaps", the values
+///             missing between them
+///   field     a segment that varies while the rest is fixed, e.g.
+///             "prt-{*}-{n}"; adds "values" {segment: count}
+///   prefix    a shared first segment of the host label, "prt-*"
+///   suffix    a shared last segment of the host label, "*-gw"
+///   domain    a shared domain, "*.corp.example.com"
+/// Names are lowercased and de-duplicated; patterns with fewer than
+/// `min_count` members are dropped. Sequence patterns feed straight into
+/// `expand_hostname_pattern`.
+#[pyfunction]
+#[pyo3(signature = (names, min_count=2))]
+pub fn analyze_hostnames(py: Python, names: Vec<String>, min_count: usize) -> PyResult<Vec<PyObject>> {
+    let names: BTreeSet<String> = names
+        .iter()
+        .map(|n| n.trim().trim_end_matches('.').to_lowercase())
+        .filter(|n| !n.is_empty())
+        .collect();
+
+    let mut templates: HashMap<Vec<Piece>, Template> = HashMap::new();
+    let mut prefixes: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    let mut suffixes: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    let mut domains: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+
+    for name in &names {
+        let (pieces, slots) = tokenize(name);
+
+        if let Some((_, domain)) = name.split_once('.') {
+            domains.entry(domain.to_string()).or_default().push(name);
+        }
+        let segments: Vec<&Piece> = pieces
+            .iter()
+            .take_while(|p| **p != Piece::Delim('.'))
+            .filter(|p| !matches!(p, Piece::Delim(_)))
+            .collect();
+        if segments.len() > 1 {
+            if let Piece::Lit(first) = segments[0] {
+                prefixes.entry(first.clone()).or_default().push(name);
+            }
+            if let Some(Piece::Lit(last)) = segments.last() {
+                suffixes.entry(last.clone()).or_default().push(name);
+            }
+        }
+
+        let template = templates.entry(pieces).or_insert_with(|| Template { members: Vec::new(), slots: Vec::new() });
+        if template.slots.is_empty() {
+            template.slots = vec![Vec::new(); slots.len()];
+        }
+        for (i, value) in slots.iter().enumerate() {
+            template.slots[i].push(*value);
+        }
+        template.members.push((slots.iter().map(|(v, _, _)| *v).collect(), name.clone()));
+    }
+
+    let mut patterns: Vec<(usize, String, PyObject)> = Vec::new();
+    let mut push = |count: usize, pattern: String, kind: &str, examples: Vec<String>, extra: &PyDict| -> PyResult<()> {
+        let entry = PyDict::new(py);
+        entry.set_item("kind", kind)?;
+        entry.set_item("pattern", &pattern)?;
+        entry.set_item("count", count)?;
+        entry.set_item("examples", examples)?;
+        entry.update(extra.as_mapping())?;
+        patterns.push((count, pattern, entry.to_object(py)));
+        Ok(())
+    };
+
+    // Sequences, and literal segments to compare across templates
+    let mut fields: HashMap<Vec<Piece>, BTreeMap<String, Vec<String>>> = HashMap::new();
+    for (pieces, template) in templates.iter_mut() {
+        template.members.sort();
+        let count = template.members.len();
+        let literals: Vec<usize> = (0..pieces.len()).filter(|&i| matches!(pieces[i], Piece::Lit(_))).collect();
+        if literals.len() > 1 {
+            for &i in &literals {
+                let Piece::Lit(value) = &pieces[i] else { continue };
+                let mut key = pieces.clone();
+                key[i] = Piece::Wild;
+                let members = fields.entry(key).or_default().entry(value.clone()).or_default();
+                members.extend(template.members.iter().map(|(_, name)| name.clone()));
+            }
+        }
+        if template.slots.is_empty() || count < min_count {
+            continue;
+        }
+
+        let extra = PyDict::new(py);
+        let mut ranges = Vec::new();
+        let mut gaps: u64 = 0;
+        for values in &template.slots {
+            let min = values.iter().map(|v| v.0).min().unwrap_or(0);
+            let max = values.iter().map(|v| v.0).max().unwrap_or(0);
+            ranges.push((min, max));
+            let distinct: BTreeSet<u64> = values.iter().map(|v| v.0).collect();
+            gaps = gaps.max((max - min + 1).saturating_sub(distinct.len() as u64));
+        }
+        extra.set_item("ranges", ranges)?;
+        extra.set_item("gaps", gaps)?;
+        let examples = template.members.iter().take(EXAMPLES).map(|(_, name)| name.clone()).collect();
+        push(count, render(pieces, &template.slots), "sequence", examples, extra)?;
+    }
+
+    for (key, values) in &fields {
+        let count: usize = values.values().map(Vec::len).sum();
+        if values.len() < 2 || count < min_count {
+            continue;
+        }
+        let extra = PyDict::new(py);
+        let counts: BTreeMap<&str, usize> = values.iter().map(|(v, members)| (v.as_str(), members.len())).collect();
+        extra.set_item("values", counts)?;
+        let examples = values.values().filter_map(|members| members.first().cloned()).take(EXAMPLES).collect();
+        push(count, render(key, &[]), "field", examples, extra)?;
+    }
+
+    for (kind, groups, shape) in [
+        ("prefix", &prefixes, "{}-*"),
+        ("suffix", &suffixes, "*-{}"),
+        ("domain", &domains, "*.{}"),
+    ] {
+        for (segment, members) in groups {
+            if members.len() < min_count {
+                continue;
+            }
+            let examples = members.iter().take(EXAMPLES).map(|n| n.to_string()).collect();
+            push(members.len(), shape.replace("{}", segment), kind, examples, PyDict::new(py))?;
+        }
+    }
+
+    patterns.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    Ok(patterns.into_iter().map(|(_, _, pattern)| pattern).collect())
+}
+
+/// Generate candidate names from a pattern with one numeric slot
+///
+/// The slot is "{n}", or "{n:W}" for values zero-padded to W digits, as in
+/// the sequence patterns `analyze_hostnames` returns; `range` is (start,
+/// end) inclusive. `expand_hostname_pattern("prt-bld2-{n:02}", (1, 3))`
+/// gives prt-bld2-01, prt-bld2-02 and prt-bld2-03.
+#[pyfunction]
+pub fn expand_hostname_pattern(pattern: &str, range: (u64, u64)) -> PyResult<Vec<String>> {
+    let invalid = |msg: String| PyErr::new::<pyo3::exceptions::PyValueError, _>(msg);
+    let (start, end) = range;
+    if end < start {
+        return Err(invalid(format!("Invalid range ({}, {}): end must be >= start", start, end)));
+    }
+
+    let open = pattern.find('{').ok_or_else(|| invalid(format!("Hostname pattern '{}' has no {{n}} slot", pattern)))?;
+    let close = pattern[open..]
+        .find('}')
+        .map(|i| open + i)
+        .ok_or_else(|| invalid(format!("Hostname pattern '{}' has an unclosed slot", pattern)))?;
+    let (before, slot, after) = (&pattern[..open], &pattern[open + 1..close], &pattern[close + 1..]);
+    if after.contains('{') || after.contains('}') || before.contains('}') {
+        return Err(invalid(format!("Hostname pattern '{}' must have exactly one slot", pattern)));
+    }
+    let width = match slot {
+        "n" => 0,
+        _ => slot
+            .strip_prefix("n:")
+            .and_then(|w| w.parse::<usize>().ok())
+            .ok_or_else(|| invalid(format!("Unsupported slot '{{{}}}': expected {{n}} or {{n:W}}", slot)))?,
+    };
+
+    Ok((start..=end).map(|n| format!("{}{:0width$}{}", before, n, after, width = width)).collect())
+}
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::io;
+use std::mem::MaybeUninit;
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use dashmap::DashMap;
+use pyo3::prelude::*;
+use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+
+use crate::scanner::{unix_now, ScanResult};
+use crate::scope::authorize_targets;
+
+// =============================================================================
+// ICMP Discovery (echo, timestamp, address mask)
+// =============================================================================
+//
+// Echo works over unprivileged ping sockets where the OS allows them;
+// timestamp (type 13) and address-mask (type 17) requests need a raw socket.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IcmpProbe {
+    Echo,
+    Timestamp,
+    AddressMask,
+}
+
+impl IcmpProbe {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "echo" => Some(IcmpProbe::Echo),
+            "timestamp" => Some(IcmpProbe::Timestamp),
+            "mask" | "address_mask" => Some(IcmpProbe::AddressMask),
+            _ => None,
+        }
+    }
+
+    fn request_type(&self) -> u8 {
+        match self {
+            IcmpProbe::Echo => 8,
+            IcmpProbe::Timestamp => 13,
+            IcmpProbe::AddressMask => 17,
+        }
+    }
+
+    fn from_reply_type(icmp_type: u8) -> Option<Self> {
+        match icmp_type {
+            0 => Some(IcmpProbe::Echo),
+            14 => Some(IcmpProbe::Timestamp),
+            18 => Some(IcmpProbe::AddressMask),
+            _ => None,
+        }
+    }
+
+    /// Value recorded in ScanResult.discovery_method
+    pub fn method(&self) -> &'static str {
+        match self {
+            IcmpProbe::Echo => "icmp_echo",
+            IcmpProbe::Timestamp => "icmp_timestamp",
+            IcmpProbe::AddressMask => "icmp_mask",
+        }
+    }
+}
+
+/// RFC 1071 Internet checksum
+pub(crate) fn checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]) as u32)
+        .sum();
+    while sum >> 16 != 0 {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// Milliseconds since midnight UTC, as carried in timestamp requests
+fn ms_since_midnight() -> u32 {
+    ((unix_now() * 1000.0) as u64 % 86_400_000) as u32
+}
+
+fn build_request(probe: IcmpProbe, id: u16, seq: u16) -> Vec<u8> {
+    let mut packet = vec![probe.request_type(), 0, 0, 0];
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&seq.to_be_bytes());
+    match probe {
+        IcmpProbe::Echo => packet.extend_from_slice(b"netscan-icmp-prb"),
+        IcmpProbe::Timestamp => {
+            packet.extend_from_slice(&ms_since_midnight().to_be_bytes());
+            packet.extend_from_slice(&[0; 8]);
+        }
+        IcmpProbe::AddressMask => packet.extend_from_slice(&[0; 4]),
+    }
+    let sum = checksum(&packet);
+    packet[2..4].copy_from_slice(&sum.to_be_bytes());
+    packet
+}
+
+fn recv_from(socket: &Socket, buf: &mut [u8]) -> io::Result<(usize, Option<Ipv4Addr>)> {
+    // SAFETY: u8 has no invalid bit patterns, so viewing initialized bytes as
+    // MaybeUninit<u8> is sound; recv_from only writes into the buffer
+    let uninit = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+    let (n, addr) = socket.recv_from(uninit)?;
+    Ok((n, addr.as_socket_ipv4().map(|a| *a.ip())))
+}
+
+/// Split a received datagram into (source, icmp bytes); raw sockets (and
+/// BSD ping sockets) deliver the IPv4 header too
+fn split_reply(packet: &[u8], from: Option<Ipv4Addr>) -> Option<(Ipv4Addr, &[u8])> {
+    if packet.len() >= 20 && packet[0] >> 4 == 4 {
+        let ihl = (packet[0] & 0x0F) as usize * 4;
+        let src = Ipv4Addr::new(packet[12], packet[13], packet[14], packet[15]);
+        Some((src, packet.get(ihl..)?))
+    } else {
+        Some((from?, packet))
+    }
+}
+
+/// Send each probe type to every target and collect the first reply per host
+///
+/// Returns {ip: (probe that elicited the reply, rtt ms)}. Timestamp and mask
+/// probes return an error unless a raw ICMP socket is available.
+pub fn icmp_sweep(
+    targets: &[Ipv4Addr],
+    probes: &[IcmpProbe],
+    timeout_ms: u64,
+) -> Result<HashMap<Ipv4Addr, (IcmpProbe, f64)>, String> {
+    let needs_raw = probes.iter().any(|p| *p != IcmpProbe::Echo);
+    let (socket, raw) = match Socket::new(Domain::IPV4, Type::RAW, Some(Protocol::ICMPV4)) {
+        Ok(socket) => (socket, true),
+        Err(_) if needs_raw => {
+            return Err(format!(
+                "ICMP timestamp/mask probes need a raw socket; {}",
+                crate::capabilities::grant_hint("raw_icmp")
+            ));
+        }
+        Err(_) => Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::ICMPV4))
+            .map(|socket| (socket, false))
+            .map_err(|e| format!("Cannot open ICMP socket ({}); {}", e, crate::capabilities::grant_hint("icmp")))?,
+    };
+    let _ = socket.set_recv_buffer_size(4 * 1024 * 1024);
+    socket
+        .set_read_timeout(Some(Duration::from_millis(50)))
+        .map_err(|e| e.to_string())?;
+    let receiver_socket = socket.try_clone().map_err(|e| e.to_string())?;
+
+    let id = (std::process::id() & 0xFFFF) as u16;
+    let sent: Arc<DashMap<(Ipv4Addr, IcmpProbe), Instant>> = Arc::new(DashMap::new());
+    let deadline: Arc<OnceLock<Instant>> = Arc::new(OnceLock::new());
+    let expected = targets.len();
+
+    let receiver = {
+        let sent = sent.clone();
+        let deadline = deadline.clone();
+        std::thread::spawn(move || {
+            let mut replies: HashMap<Ipv4Addr, (IcmpProbe, f64)> = HashMap::new();
+            let mut buf = vec![0u8; 1500];
+            loop {
+                if deadline.get().map(|d| Instant::now() >= *d).unwrap_or(false) || replies.len() >= expected {
+                    break;
+                }
+                let Ok((n, from)) = recv_from(&receiver_socket, &mut buf) else {
+                    continue;
+                };
+                let Some((src, icmp)) = split_reply(&buf[..n], from) else {
+                    continue;
+                };
+                if icmp.len() < 8 {
+                    continue;
+                }
+                let Some(probe) = IcmpProbe::from_reply_type(icmp[0]) else {
+                    continue;
+                };
+                // Ping sockets rewrite the identifier and filter replies themselves
+                if raw && u16::from_be_bytes([icmp[4], icmp[5]]) != id {
+                    continue;
+                }
+                if let Some(start) = sent.get(&(src, probe)) {
+                    let rtt = start.elapsed().as_secs_f64() * 1000.0;
+                    replies.entry(src).or_insert((probe, rtt));
+                }
+            }
+            replies
+        })
+    };
+
+    for probe in probes {
+        for (seq, ip) in targets.iter().enumerate() {
+            let packet = build_request(*probe, id, seq as u16);
+            sent.insert((*ip, *probe), Instant::now());
+            let _ = socket.send_to(&packet, &SockAddr::from(SocketAddrV4::new(*ip, 0)));
+        }
+    }
+    let _ = deadline.set(Instant::now() + Duration::from_millis(timeout_ms));
+
+    receiver.join().map_err(|_| "ICMP receiver thread panicked".to_string())
+}
+
+/// ICMP discovery sweep; returns a ScanResult for every host that replied
+///
+/// `probes` is any of "echo", "timestamp", "mask" (default ["echo"]). The
+/// probe that got the first reply is recorded in `discovery_method`
+/// ("icmp_echo", "icmp_timestamp", "icmp_mask"), catching hosts that drop
+/// echo but still answer timestamp or mask requests. Targets outside
+/// `set_authorized_scopes` are refused unless `force=True`.
+#[pyfunction]
+#[pyo3(signature = (ips, timeout_ms=1000, probes=None, force=false))]
+pub fn icmp_discovery(
+    py: Python,
+    ips: Vec<String>,
+    timeout_ms: u64,
+    probes: Option<Vec<String>>,
+    force: bool,
+) -> PyResult<Vec<ScanResult>> {
+    let probes: Vec<IcmpProbe> = match probes {
+        Some(names) => names
+            .iter()
+            .map(|name| {
+                IcmpProbe::parse(name).ok_or_else(|| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Unknown ICMP probe: {}", name))
+                })
+            })
+            .collect::<PyResult<_>>()?,
+        None => vec![IcmpProbe::Echo],
+    };
+    let targets: Vec<Ipv4Addr> = ips
+        .iter()
+        .map(|ip| ip.trim().parse::<Ipv4Addr>())
+        .collect::<Result<_, _>>()
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IP: {}", e)))?;
+    authorize_targets(py, "icmp_discovery", &ips, force)?;
+
+    let replies = py
+        .allow_threads(|| icmp_sweep(&targets, &probes, timeout_ms))
+        .map_err(PyErr::new::<pyo3::exceptions::PyPermissionError, _>)?;
+
+    let scan_timestamp = unix_now();
+    Ok(targets
+        .iter()
+        .filter_map(|ip| {
+            let (probe, rtt) = replies.get(ip)?;
+            Some(ScanResult {
+                ip: ip.to_string(),
+                status: "up".to_string(),
+                response_time_ms: *rtt,
+                discovery_method: probe.method().to_string(),
+                scan_timestamp,
+                sources: vec![probe.method().to_string()],
+                probes_sent: probes.len() as u64,
+                ..Default::default()
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,742 @@
+use std::collections::HashMap;
+use pyo3::prelude::*;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+
+use crate::scanner::ScanResult;
+
+// =============================================================================
+// Third-party Scan Imports
+// =============================================================================
+
+/// Days since 1970-01-01 for a proleptic Gregorian date
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Proleptic Gregorian (year, month, day) for days since 1970-01-01
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// UTC seconds as a ctime-style timestamp ("Thu Jan 13 10:23:45 2022"),
+/// the inverse of `parse_ctime_timestamp`
+pub fn format_ctime_timestamp(seconds: f64) -> String {
+    const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let seconds = seconds.floor() as i64;
+    let (days, secs) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{} {} {} {:02}:{:02}:{:02} {}",
+        DAYS[days.rem_euclid(7) as usize],
+        MONTHS[month as usize - 1],
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+        year
+    )
+}
+
+/// Parse a ctime-style timestamp ("Thu Jan 13 10:23:45 2022") as UTC seconds
+pub fn parse_ctime_timestamp(value: &str) -> Option<f64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    if parts.len() != 5 {
+        return None;
+    }
+    let month = MONTHS.iter().position(|m| *m == parts[1])? as u32 + 1;
+    let day: u32 = parts[2].parse().ok()?;
+    let year: i64 = parts[4].parse().ok()?;
+    let hms: Vec<i64> = parts[3]
+        .split(':')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+    if hms.len() != 3 {
+        return None;
+    }
+    let days = days_from_civil(year, month, day);
+    Some((days * 86400 + hms[0] * 3600 + hms[1] * 60 + hms[2]) as f64)
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|a| a.key.as_ref() == name)
+        .and_then(|a| a.unescape_value().ok().map(|v| v.into_owned()))
+}
+
+fn xml_error(e: impl std::fmt::Display) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid Nessus XML: {}", e))
+}
+
+/// Build a ScanResult from collected Nessus host properties and ports
+fn nessus_host(properties: &HashMap<String, String>, report_name: &str, mut ports: Vec<u16>) -> ScanResult {
+    ports.sort_unstable();
+    ports.dedup();
+
+    let prop = |key: &str| properties.get(key).cloned().unwrap_or_default();
+    let ip = properties
+        .get("host-ip")
+        .cloned()
+        .unwrap_or_else(|| report_name.to_string());
+    let hostname = match prop("host-fqdn") {
+        fqdn if !fqdn.is_empty() => fqdn,
+        _ => prop("netbios-name"),
+    };
+    // Nessus lists every interface MAC, one per line
+    let mac = prop("mac-address")
+        .lines()
+        .map(str::trim)
+        .find(|m| !m.is_empty())
+        .map(crate::normalize_mac)
+        .unwrap_or_default();
+
+    ScanResult {
+        ip,
+        mac,
+        hostname,
+        status: "up".to_string(),
+        open_ports: ports,
+        discovery_method: "nessus".to_string(),
+        sources: vec!["nessus".to_string()],
+        os: prop("operating-system").lines().next().unwrap_or_default().to_string(),
+        scan_timestamp: properties
+            .get("host-start")
+            .and_then(|v| parse_ctime_timestamp(v))
+            .unwrap_or(0.0),
+        ..Default::default()
+    }
+}
+
+/// Parse a Nessus v2 (.nessus) export into scan results
+///
+/// Open TCP ports are collected from `ReportItem` port/protocol attributes;
+/// port 0 (host-level findings) and non-TCP items are skipped.
+#[pyfunction]
+pub fn parse_nessus_xml(xml_str: &str) -> PyResult<Vec<ScanResult>> {
+    let mut reader = Reader::from_str(xml_str);
+    reader.trim_text(true);
+
+    let mut results = Vec::new();
+    let mut in_host = false;
+    let mut report_name = String::new();
+    let mut properties: HashMap<String, String> = HashMap::new();
+    let mut ports: Vec<u16> = Vec::new();
+    let mut current_tag: Option<String> = None;
+    let mut saw_report = false;
+
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) => match e.name().as_ref() {
+                b"NessusClientData_v2" | b"Report" => saw_report = true,
+                b"ReportHost" => {
+                    in_host = true;
+                    report_name = attribute(&e, b"name").unwrap_or_default();
+                    properties.clear();
+                    ports.clear();
+                }
+                b"tag" if in_host => current_tag = attribute(&e, b"name"),
+                b"ReportItem" if in_host => collect_report_item(&e, &mut ports),
+                _ => {}
+            },
+            Event::Empty(e) if in_host && e.name().as_ref() == b"ReportItem" => {
+                collect_report_item(&e, &mut ports);
+            }
+            Event::Text(t) => {
+                if let Some(tag) = current_tag.as_ref() {
+                    let text = t.unescape().map_err(xml_error)?;
+                    properties.insert(tag.clone(), text.trim().to_string());
+                }
+            }
+            Event::End(e) => match e.name().as_ref() {
+                b"tag" => current_tag = None,
+                b"ReportHost" => {
+                    results.push(nessus_host(&properties, &report_name, std::mem::take(&mut ports)));
+                    in_host = false;
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !saw_report {
+        return Err(xml_error("no NessusClientData_v2/Report element found"));
+    }
+    Ok(results)
+}
+
+fn collect_report_item(element: &BytesStart, ports: &mut Vec<u16>) {
+    let protocol = attribute(element, b"protocol").unwrap_or_default();
+    if !protocol.eq_ignore_ascii_case("tcp") {
+        return;
+    }
+    if let Some(port) = attribute(element, b"port").and_then(|p| p.parse::<u16>().ok()) {
+        if port != 0 {
+            ports.push(port);
+        }
+    }
+}
+
+// =============================================================================
+// nmap XML
+// =============================================================================
+//
+// Enough of nmap's `-oX` layout for tools that import it (Faraday,
+// vulnerability scanners): nmaprun, host, status, address, hostnames, ports
+// and runstats, with times as Unix seconds the way nmap writes them. The
+// document claims scanner="nmap" because some importers check for it.
+
+fn nmap_error(e: impl std::fmt::Display) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid nmap XML: {}", e))
+}
+
+/// Version of nmap's XML output this layout follows
+const NMAP_XML_VERSION: &str = "1.05";
+
+fn write_event<'a>(writer: &mut Writer<Vec<u8>>, event: impl Into<Event<'a>>) -> PyResult<()> {
+    writer.write_event(event.into()).map_err(nmap_error)
+}
+
+fn element<'a>(name: &'a str, attributes: &[(&'a str, &'a str)]) -> BytesStart<'a> {
+    BytesStart::new(name).with_attributes(attributes.iter().copied())
+}
+
+/// One <host> element
+fn write_nmap_host(writer: &mut Writer<Vec<u8>>, result: &ScanResult) -> PyResult<()> {
+    let time = format!("{}", result.scan_timestamp.max(0.0) as u64);
+    let state = if result.status.is_empty() || result.status == "up" { "up" } else { "down" };
+    let addrtype = match result.ip.parse::<std::net::IpAddr>() {
+        Ok(std::net::IpAddr::V6(_)) => "ipv6",
+        _ => "ipv4",
+    };
+    let reason = if result.discovery_method.is_empty() { "user-set" } else { result.discovery_method.as_str() };
+
+    write_event(writer, Event::Start(element("host", &[("starttime", &time), ("endtime", &time)])))?;
+    write_event(writer, Event::Empty(element("status", &[("state", state), ("reason", reason), ("reason_ttl", "0")])))?;
+    write_event(writer, Event::Empty(element("address", &[("addr", &result.ip), ("addrtype", addrtype)])))?;
+    if !result.mac.is_empty() {
+        let mac = crate::normalize_mac(&result.mac);
+        let mut attributes = vec![("addr", mac.as_str()), ("addrtype", "mac")];
+        if !result.vendor.is_empty() {
+            attributes.push(("vendor", &result.vendor));
+        }
+        write_event(writer, Event::Empty(element("address", &attributes)))?;
+    }
+    write_event(writer, Event::Start(BytesStart::new("hostnames")))?;
+    if !result.hostname.is_empty() {
+        write_event(writer, Event::Empty(element("hostname", &[("name", &result.hostname), ("type", "PTR")])))?;
+    }
+    write_event(writer, Event::End(BytesEnd::new("hostnames")))?;
+
+    let mut ports: Vec<(u16, &str)> = result.open_ports.iter().map(|p| (*p, "open")).collect();
+    for (port, port_state) in &result.port_state_detail {
+        if !result.open_ports.contains(port) {
+            ports.push((*port, port_state.as_str()));
+        }
+    }
+    ports.sort_unstable();
+    write_event(writer, Event::Start(BytesStart::new("ports")))?;
+    for (port, port_state) in ports {
+        let portid = port.to_string();
+        let reason = match port_state {
+            "open" => "syn-ack",
+            "closed" => "reset",
+            _ => "no-response",
+        };
+        write_event(writer, Event::Start(element("port", &[("protocol", "tcp"), ("portid", &portid)])))?;
+        write_event(writer, Event::Empty(element("state", &[("state", port_state), ("reason", reason), ("reason_ttl", "0")])))?;
+        write_event(
+            writer,
+            Event::Empty(element("service", &[("name", crate::probes::service_name(port)), ("method", "table"), ("conf", "3")])),
+        )?;
+        write_event(writer, Event::End(BytesEnd::new("port")))?;
+    }
+    write_event(writer, Event::End(BytesEnd::new("ports")))?;
+
+    if !result.os.is_empty() {
+        write_event(writer, Event::Start(BytesStart::new("os")))?;
+        write_event(writer, Event::Empty(element("osmatch", &[("name", &result.os), ("accuracy", "100"), ("line", "0")])))?;
+        write_event(writer, Event::End(BytesEnd::new("os")))?;
+    }
+    if result.response_time_ms > 0.0 {
+        // nmap gives round-trip times in microseconds
+        let srtt = format!("{}", (result.response_time_ms * 1000.0).round() as u64);
+        write_event(writer, Event::Empty(element("times", &[("srtt", &srtt), ("rttvar", "0"), ("to", "100000")])))?;
+    }
+    write_event(writer, Event::End(BytesEnd::new("host")))
+}
+
+/// Render results as an nmap XML (`-oX`) document
+///
+/// Open ports and the closed or filtered ones in `port_state_detail` become
+/// TCP <port> elements with nmap's table service names; MACs carry their
+/// vendor, and `os` becomes an <osmatch>. The run's start and end times are
+/// the earliest and latest `scan_timestamp`. `parse_nmap_xml` reads the
+/// document back.
+#[pyfunction]
+#[pyo3(signature = (results, scan_args="netscan", pretty=true))]
+pub fn results_to_nmap_xml(results: Vec<ScanResult>, scan_args: &str, pretty: bool) -> PyResult<String> {
+    let mut writer = if pretty { Writer::new_with_indent(Vec::new(), b' ', 2) } else { Writer::new(Vec::new()) };
+    let times: Vec<f64> = results.iter().map(|r| r.scan_timestamp).filter(|t| *t > 0.0).collect();
+    let start = times.iter().copied().fold(f64::INFINITY, f64::min);
+    let start = if start.is_finite() { start } else { crate::scanner::unix_now() };
+    let end = times.iter().copied().fold(start, f64::max);
+    let (start_str, end_str) = (format!("{}", start as u64), format!("{}", end as u64));
+    let up = results.iter().filter(|r| r.status.is_empty() || r.status == "up").count();
+
+    write_event(&mut writer, Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+    write_event(&mut writer, Event::DocType(BytesText::from_escaped("nmaprun")))?;
+    let startstr = format_ctime_timestamp(start);
+    write_event(
+        &mut writer,
+        Event::Start(element(
+            "nmaprun",
+            &[
+                ("scanner", "nmap"),
+                ("args", scan_args),
+                ("start", &start_str),
+                ("startstr", &startstr),
+                ("version", env!("CARGO_PKG_VERSION")),
+                ("xmloutputversion", NMAP_XML_VERSION),
+            ],
+        )),
+    )?;
+    for result in &results {
+        write_nmap_host(&mut writer, result)?;
+    }
+
+    let endstr = format_ctime_timestamp(end);
+    let elapsed = format!("{:.2}", end - start);
+    let summary = format!("Scan done: {} IP addresses ({} hosts up)", results.len(), up);
+    let (up_str, down_str, total_str) =
+        (up.to_string(), (results.len() - up).to_string(), results.len().to_string());
+    write_event(&mut writer, Event::Start(BytesStart::new("runstats")))?;
+    write_event(
+        &mut writer,
+        Event::Empty(element(
+            "finished",
+            &[
+                ("time", &end_str),
+                ("timestr", &endstr),
+                ("elapsed", &elapsed),
+                ("summary", &summary),
+                ("exit", "success"),
+            ],
+        )),
+    )?;
+    write_event(&mut writer, Event::Empty(element("hosts", &[("up", &up_str), ("down", &down_str), ("total", &total_str)])))?;
+    write_event(&mut writer, Event::End(BytesEnd::new("runstats")))?;
+    write_event(&mut writer, Event::End(BytesEnd::new("nmaprun")))?;
+    String::from_utf8(writer.into_inner()).map_err(nmap_error)
+}
+
+/// Apply one element inside <host> to the result being built
+fn read_nmap_host_element(element: &BytesStart, result: &mut ScanResult, port: &mut Option<(u16, bool)>) {
+    match element.name().as_ref() {
+        b"status" => result.status = attribute(element, b"state").unwrap_or_default(),
+        b"address" => {
+            let addr = attribute(element, b"addr").unwrap_or_default();
+            match attribute(element, b"addrtype").as_deref() {
+                Some("mac") => {
+                    result.mac = crate::normalize_mac(&addr);
+                    result.vendor = attribute(element, b"vendor").unwrap_or_default();
+                }
+                _ => result.ip = addr,
+            }
+        }
+        b"hostname" if result.hostname.is_empty() => {
+            result.hostname = attribute(element, b"name").unwrap_or_default();
+        }
+        b"port" => {
+            let tcp = attribute(element, b"protocol").is_some_and(|p| p == "tcp");
+            *port = attribute(element, b"portid").and_then(|p| p.parse().ok()).map(|p| (p, tcp));
+        }
+        b"state" => {
+            if let (Some((number, true)), Some(state)) = (*port, attribute(element, b"state")) {
+                if state == "open" {
+                    result.open_ports.push(number);
+                }
+                result.port_state_detail.insert(number, state);
+            }
+        }
+        b"osmatch" if result.os.is_empty() => result.os = attribute(element, b"name").unwrap_or_default(),
+        b"times" => {
+            let srtt = attribute(element, b"srtt").and_then(|t| t.parse::<f64>().ok()).unwrap_or(0.0);
+            result.response_time_ms = srtt / 1000.0;
+        }
+        _ => {}
+    }
+}
+
+/// Parse an nmap XML (`-oX`) report into scan results
+///
+/// TCP ports in any state go to `port_state_detail`, the open ones also to
+/// `open_ports`; the MAC address carries its vendor, the first hostname
+/// and OS match are kept, and `times` srtt (microseconds) becomes
+/// `response_time_ms`. Hosts without a start time take the run's.
+#[pyfunction]
+pub fn parse_nmap_xml(xml_str: &str) -> PyResult<Vec<ScanResult>> {
+    let mut reader = Reader::from_str(xml_str);
+    reader.trim_text(true);
+
+    let mut results = Vec::new();
+    let mut run_start = 0.0;
+    let mut host: Option<ScanResult> = None;
+    let mut port: Option<(u16, bool)> = None;
+    let mut saw_run = false;
+
+    loop {
+        let (element, is_start) = match reader.read_event().map_err(nmap_error)? {
+            Event::Start(e) => (e, true),
+            Event::Empty(e) => (e, false),
+            Event::End(e) => {
+                match e.name().as_ref() {
+                    b"host" => {
+                        if let Some(mut result) = host.take() {
+                            result.open_ports.sort_unstable();
+                            result.open_ports.dedup();
+                            if result.scan_timestamp <= 0.0 {
+                                result.scan_timestamp = run_start;
+                            }
+                            results.push(result);
+                        }
+                    }
+                    b"port" => port = None,
+                    _ => {}
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+        match (element.name().as_ref(), host.as_mut()) {
+            (b"nmaprun", _) => {
+                saw_run = true;
+                run_start = attribute(&element, b"start").and_then(|t| t.parse().ok()).unwrap_or(0.0);
+            }
+            (b"host", None) if is_start => {
+                host = Some(ScanResult {
+                    discovery_method: "nmap".to_string(),
+                    sources: vec!["nmap".to_string()],
+                    scan_timestamp: attribute(&element, b"starttime").and_then(|t| t.parse().ok()).unwrap_or(0.0),
+                    ..Default::default()
+                });
+            }
+            (_, Some(result)) => read_nmap_host_element(&element, result, &mut port),
+            _ => {}
+        }
+    }
+
+    if !saw_run {
+        return Err(nmap_error("no nmaprun element found"));
+    }
+    Ok(results)
+}
+
+// =============================================================================
+// Format Auto-detection
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanFormat {
+    NmapNormal,
+    NmapGrepable,
+    OuiDatabase,
+    Arp,
+    Pipe,
+    JsonLines,
+}
+
+impl ScanFormat {
+    fn name(&self) -> &'static str {
+        match self {
+            ScanFormat::NmapNormal => "nmap normal output",
+            ScanFormat::NmapGrepable => "nmap grepable output",
+            ScanFormat::OuiDatabase => "OUI database",
+            ScanFormat::Arp => "arp -a output",
+            ScanFormat::Pipe => "pipe-delimited",
+            ScanFormat::JsonLines => "JSON lines",
+        }
+    }
+}
+
+fn arp_line_regex() -> &'static regex::Regex {
+    static RE: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    RE.get_or_init(|| {
+        regex::Regex::new(r"\(\d+\.\d+\.\d+\.\d+\)\s+at\s+(?:[0-9a-fA-F]{1,2}[:-]){5}[0-9a-fA-F]{1,2}").unwrap()
+    })
+}
+
+/// Decide the format from the first three non-empty lines
+fn sniff_format(text: &str) -> Result<ScanFormat, String> {
+    let sample: Vec<&str> = text.lines().map(str::trim).filter(|l| !l.is_empty()).take(3).collect();
+    if sample.is_empty() {
+        return Err("No scan output to parse".to_string());
+    }
+
+    let any = |pred: &dyn Fn(&str) -> bool| sample.iter().any(|line| pred(line));
+    let mut matches = Vec::new();
+    if any(&|l| l.contains("Nmap scan report")) {
+        matches.push(ScanFormat::NmapNormal);
+    }
+    if any(&|l| l.starts_with("Host:") && (l.contains("Ports:") || l.contains("Status:"))) {
+        matches.push(ScanFormat::NmapGrepable);
+    }
+    if any(&|l| l.contains("(hex)")) {
+        matches.push(ScanFormat::OuiDatabase);
+    }
+    if any(&|l| arp_line_regex().is_match(l)) {
+        matches.push(ScanFormat::Arp);
+    }
+    if sample[0].split('|').count() >= 2 && !sample[0].starts_with('{') {
+        matches.push(ScanFormat::Pipe);
+    }
+
+    match matches.as_slice() {
+        [ScanFormat::OuiDatabase] => Err("Input is an OUI database, not scan output; use parse_oui_file".to_string()),
+        [format] => Ok(*format),
+        [] if sample[0].starts_with('{') || sample[0].starts_with('[') => Ok(ScanFormat::JsonLines),
+        [] => Err("Unrecognized scan output format".to_string()),
+        _ => Err(format!(
+            "Ambiguous scan output: looks like {}",
+            matches.iter().map(ScanFormat::name).collect::<Vec<_>>().join(" and ")
+        )),
+    }
+}
+
+/// `Host: 10.0.0.1 (name)\tStatus: Up` / `Host: ...\tPorts: 22/open/tcp//ssh///, ...`
+fn parse_gnmap(text: &str) -> Vec<ScanResult> {
+    let mut results: Vec<ScanResult> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for line in text.lines() {
+        let mut fields = line.split('\t');
+        let Some(host) = fields.next().and_then(|f| f.strip_prefix("Host:")) else {
+            continue;
+        };
+        let mut host = host.split_whitespace();
+        let Some(ip) = host.next() else {
+            continue;
+        };
+        let idx = *index.entry(ip.to_string()).or_insert_with(|| {
+            results.push(ScanResult {
+                ip: ip.to_string(),
+                status: "up".to_string(),
+                discovery_method: "nmap".to_string(),
+                sources: vec!["nmap".to_string()],
+                ..Default::default()
+            });
+            results.len() - 1
+        });
+        let result = &mut results[idx];
+        if let Some(name) = host.next().map(|n| n.trim_matches(|c| c == '(' || c == ')')) {
+            if !name.is_empty() {
+                result.hostname = name.to_string();
+            }
+        }
+
+        for field in fields {
+            if let Some(status) = field.strip_prefix("Status:") {
+                result.status = status.trim().to_lowercase();
+            } else if let Some(ports) = field.strip_prefix("Ports:") {
+                for entry in ports.split(',') {
+                    let parts: Vec<&str> = entry.trim().split('/').collect();
+                    if parts.len() >= 3 && parts[1] == "open" && parts[2] == "tcp" {
+                        if let Ok(port) = parts[0].parse::<u16>() {
+                            if !result.open_ports.contains(&port) {
+                                result.open_ports.push(port);
+                            }
+                        }
+                    }
+                }
+            } else if let Some(os) = field.strip_prefix("OS:") {
+                result.os = os.trim().to_string();
+            }
+        }
+    }
+    results
+}
+
+/// `nmap -oN` reports: one block per "Nmap scan report for ..." line
+fn parse_nmap_normal(text: &str) -> Vec<ScanResult> {
+    let mut results: Vec<ScanResult> = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if let Some(target) = line.strip_prefix("Nmap scan report for ") {
+            let (hostname, ip) = match target.rsplit_once(" (") {
+                Some((name, ip)) => (name.to_string(), ip.trim_end_matches(')').to_string()),
+                None => (String::new(), target.to_string()),
+            };
+            results.push(ScanResult {
+                ip,
+                hostname,
+                status: "up".to_string(),
+                discovery_method: "nmap".to_string(),
+                sources: vec!["nmap".to_string()],
+                ..Default::default()
+            });
+            continue;
+        }
+        let Some(result) = results.last_mut() else {
+            continue;
+        };
+
+        if let Some(latency) = line.strip_prefix("Host is up (").and_then(|l| l.split('s').next()) {
+            result.response_time_ms = latency.parse::<f64>().map(|s| s * 1000.0).unwrap_or(0.0);
+        } else if let Some(mac) = line.strip_prefix("MAC Address: ") {
+            let (mac, vendor) = mac.split_once(' ').unwrap_or((mac, ""));
+            result.mac = crate::normalize_mac(mac);
+            result.vendor = vendor.trim_matches(|c| c == '(' || c == ')').to_string();
+        } else if let Some(os) = line.strip_prefix("OS details: ").or_else(|| line.strip_prefix("Running: ")) {
+            if result.os.is_empty() {
+                result.os = os.to_string();
+            }
+        } else {
+            // 22/tcp   open  ssh
+            let mut parts = line.split_whitespace();
+            if let (Some(port), Some("open")) = (parts.next(), parts.next()) {
+                if let Some(Ok(port)) = port.strip_suffix("/tcp").map(str::parse::<u16>) {
+                    result.open_ports.push(port);
+                }
+            }
+        }
+    }
+    results
+}
+
+fn parse_arp(text: &str) -> Vec<ScanResult> {
+    crate::parse_arp_output(text)
+        .into_iter()
+        .map(|(ip, mac, hostname)| ScanResult {
+            ip,
+            mac,
+            hostname: if hostname == "?" { String::new() } else { hostname },
+            status: "up".to_string(),
+            discovery_method: "arp".to_string(),
+            sources: vec!["arp_table".to_string()],
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Header line of field names, then one record per line
+fn parse_pipe(text: &str) -> Vec<ScanResult> {
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let headers: Vec<String> = header.split('|').map(|h| h.trim().to_lowercase()).collect();
+    lines
+        .map(|line| {
+            let record: HashMap<String, String> = headers
+                .iter()
+                .cloned()
+                .zip(line.split('|').map(|v| v.trim().to_string()))
+                .collect();
+            ScanResult::from_record(&record)
+        })
+        .filter(|r| !r.ip.is_empty())
+        .collect()
+}
+
+/// One ScanResult object per line, or a single JSON array
+fn parse_json_lines(text: &str) -> Result<Vec<ScanResult>, String> {
+    if text.trim_start().starts_with('[') {
+        return serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e));
+    }
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| serde_json::from_str(line).map_err(|e| format!("Invalid JSON on line {}: {}", n + 1, e)))
+        .collect()
+}
+
+/// Parse scan output without knowing its format
+///
+/// The first three non-empty lines decide: nmap normal or grepable output,
+/// `arp -a` output, a pipe-delimited table with a header row, or else JSON
+/// lines. Raises ValueError for OUI databases, ambiguous samples and
+/// unrecognized input. CRLF or stray CR line endings and non-breaking
+/// spaces are read as plain newlines and spaces.
+#[pyfunction]
+pub fn parse_scan_output_auto(text: &str) -> PyResult<Vec<ScanResult>> {
+    let value_error = PyErr::new::<pyo3::exceptions::PyValueError, _>;
+    let text = &*crate::clean_text(text);
+    match sniff_format(text).map_err(value_error)? {
+        ScanFormat::NmapNormal => Ok(parse_nmap_normal(text)),
+        ScanFormat::NmapGrepable => Ok(parse_gnmap(text)),
+        ScanFormat::Arp => Ok(parse_arp(text)),
+        ScanFormat::Pipe => Ok(parse_pipe(text)),
+        ScanFormat::JsonLines => parse_json_lines(text).map_err(value_error),
+        ScanFormat::OuiDatabase => unreachable!("rejected by sniff_format"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nmap_xml_round_trips_essential_fields() {
+        let host = ScanResult {
+            ip: "192.168.1.10".to_string(),
+            mac: "aa-bb-cc-dd-ee-ff".to_string(),
+            vendor: "Procter & Gamble <Labs>".to_string(),
+            hostname: "printer.lan".to_string(),
+            status: "up".to_string(),
+            os: "Linux 5.x".to_string(),
+            open_ports: vec![443, 22],
+            port_state_detail: HashMap::from([(22, "open".to_string()), (23, "closed".to_string()), (25, "filtered".to_string())]),
+            response_time_ms: 1.5,
+            scan_timestamp: 1_700_000_000.0,
+            ..Default::default()
+        };
+        let bare = ScanResult { ip: "2001:db8::1".to_string(), status: "down".to_string(), scan_timestamp: 1_700_000_100.0, ..Default::default() };
+
+        for pretty in [true, false] {
+            let xml = results_to_nmap_xml(vec![host.clone(), bare.clone()], "netscan -p 22", pretty).unwrap();
+            let parsed = parse_nmap_xml(&xml).unwrap();
+            assert_eq!(parsed.len(), 2);
+            let (first, second) = (&parsed[0], &parsed[1]);
+            assert_eq!(first.ip, host.ip);
+            assert_eq!(first.mac, "AA:BB:CC:DD:EE:FF");
+            assert_eq!(first.vendor, host.vendor);
+            assert_eq!(first.hostname, host.hostname);
+            assert_eq!(first.status, "up");
+            assert_eq!(first.os, host.os);
+            assert_eq!(first.open_ports, [22, 443]);
+            assert_eq!(first.port_state_detail.get(&23).map(String::as_str), Some("closed"));
+            assert_eq!(first.port_state_detail.get(&25).map(String::as_str), Some("filtered"));
+            assert_eq!(first.port_state_detail.get(&443).map(String::as_str), Some("open"));
+            assert_eq!(first.response_time_ms, 1.5);
+            assert_eq!(first.scan_timestamp, host.scan_timestamp);
+
+            assert_eq!((second.ip.as_str(), second.status.as_str()), ("2001:db8::1", "down"));
+            assert!(second.open_ports.is_empty() && second.mac.is_empty());
+            assert_eq!(second.scan_timestamp, bare.scan_timestamp);
+        }
+    }
+}
@@ -0,0 +1,444 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use pyo3::prelude::*;
+
+use crate::scanner::{unix_now, ScanResult};
+
+// =============================================================================
+// Incremental Device Inventory
+// =============================================================================
+//
+// A monitor that re-runs `dedupe_devices` over its whole inventory plus each
+// new scan pays for the inventory on every cycle. Inventory keeps devices in
+// a map keyed the way `dedupe_devices` keys them, so applying a scan touches
+// only the devices in it. Each device carries `attributes["first_seen"]` and
+// `attributes["seen_count"]`; `scan_timestamp` is when it was last seen.
+//
+// Each device also keeps the last few open/closed observations of every port
+// it has shown, to spot ports that flap between scans (a crashing service,
+// an unstable host). The history lives in memory only; `save` doesn't keep
+// it.
+
+/// Attributes the inventory maintains itself; not reported as changes
+const BOOKKEEPING: &[&str] = &["first_seen", "seen_count"];
+
+/// Which side wins a field both the stored device and the new result fill
+const PREFER: &[&str] = &["new", "existing"];
+
+/// Inventory key for a result: its address, or failing that "mac:<mac>" or
+/// "host:<hostname>"; None for a result with none of them
+fn device_key(result: &ScanResult) -> Option<String> {
+    let ip = result.ip.trim();
+    if !ip.is_empty() {
+        return Some(match ip.parse::<IpAddr>() {
+            Ok(addr) => addr.to_string(),
+            Err(_) => format!("host:{}", ip.to_lowercase()),
+        });
+    }
+    if !result.mac.trim().is_empty() {
+        return Some(format!("mac:{}", crate::normalize_mac(&result.mac)));
+    }
+    let hostname = result.hostname.trim();
+    (!hostname.is_empty()).then(|| format!("host:{}", hostname.to_lowercase()))
+}
+
+/// The fields a change is reported on: the flat record minus the last-seen
+/// time, plus every attribute but the bookkeeping ones
+fn comparable(result: &ScanResult) -> HashMap<String, String> {
+    let mut fields = result.to_record();
+    fields.remove("scan_timestamp");
+    for (key, value) in &result.attributes {
+        if !BOOKKEEPING.contains(&key.as_str()) {
+            fields.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+    fields
+}
+
+/// {field: (old, new)} for the fields that differ
+fn field_diff(before: &ScanResult, after: &ScanResult) -> HashMap<String, (String, String)> {
+    let before = comparable(before);
+    let mut after = comparable(after);
+    let mut diff: HashMap<String, (String, String)> = before
+        .into_iter()
+        .filter_map(|(field, old)| {
+            let new = after.remove(&field).unwrap_or_default();
+            (old != new).then_some((field, (old, new)))
+        })
+        .collect();
+    diff.extend(after.into_iter().filter(|(_, new)| !new.is_empty()).map(|(field, new)| (field, (String::new(), new))));
+    diff
+}
+
+/// Ports a result shows open, and those it shows closed or filtered
+fn port_observations(result: &ScanResult) -> (Vec<u16>, Vec<u16>) {
+    let mut open = result.open_ports.clone();
+    let mut shut = Vec::new();
+    for (port, state) in &result.port_state_detail {
+        if state == "open" {
+            open.push(*port);
+        } else if state != "open|filtered" {
+            shut.push(*port);
+        }
+    }
+    (open, shut)
+}
+
+/// Open/closed changes between consecutive observations among the last
+/// `window` (all kept ones when None)
+fn transitions(history: &VecDeque<bool>, window: Option<usize>) -> usize {
+    let skip = window.map_or(0, |w| history.len().saturating_sub(w));
+    let recent: Vec<bool> = history.iter().skip(skip).copied().collect();
+    recent.windows(2).filter(|pair| pair[0] != pair[1]).count()
+}
+
+/// What one `apply_scan` did
+#[derive(Debug, Default)]
+struct ScanChanges {
+    joined: Vec<String>,
+    updated: HashMap<String, HashMap<String, (String, String)>>,
+    unchanged: usize,
+    skipped: usize,
+    flapping: HashMap<String, Vec<u16>>,
+}
+
+/// Known devices, updated one scan at a time
+///
+/// `prefer` picks the winner when a new result and the stored device both
+/// fill a field: "new" (the default) lets the latest scan correct a changed
+/// hostname or vendor and replaces the open ports with the ones it found,
+/// "existing" keeps the first value seen and unions the ports. Either way
+/// empty fields are filled, and sources and attributes are unioned as in
+/// `ScanResult` merging.
+///
+/// Per port the last `history_depth` observations are kept. A port that
+/// changed state at least `flap_threshold` times among them is flapping:
+/// `apply_scan` reports it under "flapping" and drops an `open_ports`
+/// change that only opens or closes flapping ports.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct Inventory {
+    devices: HashMap<String, ScanResult>,
+    /// {device key: {port: observations, oldest first, true = open}}
+    history: HashMap<String, HashMap<u16, VecDeque<bool>>>,
+    #[pyo3(get)]
+    pub prefer: String,
+    #[pyo3(get)]
+    pub history_depth: usize,
+    #[pyo3(get, set)]
+    pub flap_threshold: usize,
+}
+
+impl Inventory {
+    /// Add this scan's observation of each port the device has shown;
+    /// a port known to the history but absent from the result counts as
+    /// closed. Returns the device's ports now flapping.
+    fn record_ports(&mut self, key: &str, result: &ScanResult) -> Vec<u16> {
+        let (open, shut) = port_observations(result);
+        let depth = self.history_depth;
+        let ports = self.history.entry(key.to_string()).or_default();
+        for port in open.iter().chain(&shut) {
+            ports.entry(*port).or_default();
+        }
+        let mut flapping = Vec::new();
+        ports.retain(|port, history| {
+            history.push_back(open.contains(port));
+            while history.len() > depth {
+                history.pop_front();
+            }
+            if transitions(history, None) >= self.flap_threshold {
+                flapping.push(*port);
+            }
+            // A port closed for the whole history has nothing left to say
+            history.len() < depth || history.iter().any(|open| *open)
+        });
+        flapping.sort_unstable();
+        flapping
+    }
+
+    fn apply(&mut self, results: Vec<ScanResult>) -> ScanChanges {
+        let mut changes = ScanChanges::default();
+        let now = unix_now();
+        for mut result in results {
+            let Some(key) = device_key(&result) else {
+                changes.skipped += 1;
+                continue;
+            };
+            if result.scan_timestamp <= 0.0 {
+                result.scan_timestamp = now;
+            }
+            let flapping = self.record_ports(&key, &result);
+            match self.devices.get_mut(&key) {
+                Some(device) => {
+                    let seen_count = device.attributes.get("seen_count").and_then(|c| c.parse::<u64>().ok()).unwrap_or(1);
+                    let first_seen = device.attributes.get("first_seen").cloned();
+                    let merged = if self.prefer == "new" {
+                        let open_ports = result.open_ports.clone();
+                        let mut merged = result;
+                        merged.merge(device);
+                        // The latest scan's ports replace the stored ones, so a port
+                        // that closed shows up as a change instead of lingering
+                        merged.open_ports = open_ports;
+                        merged.sort_ports();
+                        let open = &merged.open_ports;
+                        merged.port_state_detail.retain(|port, state| state != "open" || open.contains(port));
+                        // Keep the stored order so a rescan doesn't read as a change
+                        let mut sources = device.sources.clone();
+                        sources.extend(merged.sources.into_iter().filter(|s| !device.sources.contains(s)));
+                        merged.sources = sources;
+                        merged
+                    } else {
+                        let mut merged = device.clone();
+                        merged.merge(&result);
+                        merged
+                    };
+                    let mut diff = field_diff(device, &merged);
+                    // Ports that opened or closed since the stored record
+                    let only_flapping = |(old, new): &(String, String)| {
+                        let ports = |list: &str| -> Vec<u16> { list.split(',').filter_map(|p| p.parse().ok()).collect() };
+                        let (old, new) = (ports(old), ports(new));
+                        let mut toggled = new.iter().filter(|p| !old.contains(p)).chain(old.iter().filter(|p| !new.contains(p))).peekable();
+                        toggled.peek().is_some() && toggled.all(|p| flapping.contains(p))
+                    };
+                    if diff.get("open_ports").is_some_and(only_flapping) {
+                        diff.remove("open_ports");
+                    }
+                    *device = merged;
+                    if let Some(first_seen) = first_seen {
+                        device.attributes.insert("first_seen".to_string(), first_seen);
+                    }
+                    device.attributes.insert("seen_count".to_string(), (seen_count + 1).to_string());
+                    if diff.is_empty() {
+                        changes.unchanged += 1;
+                    } else {
+                        changes.updated.insert(key.clone(), diff);
+                    }
+                }
+                None => {
+                    result.attributes.insert("first_seen".to_string(), format!("{:.3}", result.scan_timestamp));
+                    result.attributes.insert("seen_count".to_string(), "1".to_string());
+                    self.devices.insert(key.clone(), result);
+                    changes.joined.push(key.clone());
+                }
+            }
+            if !flapping.is_empty() {
+                changes.flapping.insert(key, flapping);
+            }
+        }
+        changes
+    }
+}
+
+#[pymethods]
+impl Inventory {
+    /// Empty inventory; raises ValueError for an unknown `prefer` or a
+    /// `history_depth` of 0
+    #[new]
+    #[pyo3(signature = (prefer="new", history_depth=10, flap_threshold=3))]
+    pub fn new(prefer: &str, history_depth: usize, flap_threshold: usize) -> PyResult<Self> {
+        if history_depth == 0 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("history_depth must be at least 1"));
+        }
+        let prefer = prefer.trim().to_lowercase();
+        if !PREFER.contains(&prefer.as_str()) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid prefer '{}': expected 'new' or 'existing'",
+                prefer
+            )));
+        }
+        Ok(Inventory { devices: HashMap::new(), history: HashMap::new(), prefer, history_depth, flap_threshold })
+    }
+
+    /// Fold a scan's results into the inventory; returns a change summary
+    ///
+    /// The summary has counts "joined", "updated", "unchanged" and
+    /// "skipped" (results with no IP, MAC or hostname to key them by),
+    /// "joined_keys" for the new devices, "changes" as
+    /// {key: {field: (old, new)}} for the updated ones and "flapping" as
+    /// {key: [ports]} for devices in the scan with flapping ports. A device
+    /// seen again with nothing but its last-seen time moved, or only
+    /// flapping ports opened or closed, counts as unchanged.
+    fn apply_scan(&mut self, py: Python, results: Vec<ScanResult>) -> HashMap<String, PyObject> {
+        let changes = py.allow_threads(|| self.apply(results));
+        let mut summary = HashMap::new();
+        summary.insert("joined".to_string(), changes.joined.len().into_py(py));
+        summary.insert("updated".to_string(), changes.updated.len().into_py(py));
+        summary.insert("unchanged".to_string(), changes.unchanged.into_py(py));
+        summary.insert("skipped".to_string(), changes.skipped.into_py(py));
+        summary.insert("joined_keys".to_string(), changes.joined.into_py(py));
+        summary.insert("changes".to_string(), changes.updated.into_py(py));
+        summary.insert("flapping".to_string(), changes.flapping.into_py(py));
+        summary
+    }
+
+    /// Device stored under `key`: an IP address, or the MAC or hostname of a
+    /// device that has no address
+    fn get(&self, key: &str) -> Option<ScanResult> {
+        let key = key.trim();
+        let by_ip_or_name = device_key(&ScanResult { ip: key.to_string(), ..Default::default() });
+        let by_mac = format!("mac:{}", crate::normalize_mac(key));
+        let device = [by_ip_or_name.as_deref(), Some(by_mac.as_str()), Some(key)]
+            .into_iter()
+            .flatten()
+            .find_map(|k| self.devices.get(k));
+        device.cloned()
+    }
+
+    /// {key: [ports]} for ports that changed state at least `min_transitions`
+    /// times in their last `window` observations (default: all kept)
+    #[pyo3(signature = (min_transitions=None, window=None))]
+    fn flapping_ports(&self, min_transitions: Option<usize>, window: Option<usize>) -> HashMap<String, Vec<u16>> {
+        let min_transitions = min_transitions.unwrap_or(self.flap_threshold);
+        self.history
+            .iter()
+            .filter_map(|(key, ports)| {
+                let mut flapping: Vec<u16> = ports
+                    .iter()
+                    .filter(|(_, history)| transitions(history, window) >= min_transitions)
+                    .map(|(port, _)| *port)
+                    .collect();
+                flapping.sort_unstable();
+                (!flapping.is_empty()).then(|| (key.clone(), flapping))
+            })
+            .collect()
+    }
+
+    /// Number of devices; without `include_infrastructure`, those marked
+    /// as gateways by `identify_gateways` are left out
+    #[pyo3(signature = (include_infrastructure=true))]
+    fn count(&self, include_infrastructure: bool) -> usize {
+        self.devices
+            .values()
+            .filter(|device| include_infrastructure || !crate::routes::is_infrastructure(device))
+            .count()
+    }
+
+    /// Inventory keys, sorted
+    fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.devices.keys().cloned().collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// All devices, addresses first in numeric order, then the rest by key
+    fn to_list(&self) -> Vec<ScanResult> {
+        let mut entries: Vec<(&String, &ScanResult)> = self.devices.iter().collect();
+        entries.sort_by(|(a, _), (b, _)| match (a.parse::<IpAddr>(), b.parse::<IpAddr>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => std::cmp::Ordering::Less,
+            (Err(_), Ok(_)) => std::cmp::Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        });
+        entries.into_iter().map(|(_, device)| device.clone()).collect()
+    }
+
+    /// Write the inventory as JSON lines (see `write_scan_results_jsonl`);
+    /// returns the number of devices written
+    fn save(&self, path: &str) -> PyResult<usize> {
+        crate::jsonl::write_scan_results_jsonl(self.to_list(), path, false)
+    }
+
+    /// Inventory from a file written by `save`, keeping each device's
+    /// first-seen time and count; any ScanResult JSON lines file loads, with
+    /// rows for the same device merged
+    #[staticmethod]
+    #[pyo3(signature = (path, prefer="new", history_depth=10, flap_threshold=3))]
+    fn load(py: Python, path: &str, prefer: &str, history_depth: usize, flap_threshold: usize) -> PyResult<Self> {
+        let mut inventory = Inventory::new(prefer, history_depth, flap_threshold)?;
+        let now = unix_now();
+        for result in crate::jsonl::read_scan_results_jsonl(py, path, None)? {
+            let Some(key) = device_key(&result) else {
+                continue;
+            };
+            let device = inventory.devices.entry(key).or_default();
+            device.merge(&result);
+            let first_seen = if result.scan_timestamp > 0.0 { result.scan_timestamp } else { now };
+            device.attributes.entry("first_seen".to_string()).or_insert_with(|| format!("{:.3}", first_seen));
+            device.attributes.entry("seen_count".to_string()).or_insert_with(|| "1".to_string());
+        }
+        Ok(inventory)
+    }
+
+    fn __len__(&self) -> usize {
+        self.devices.len()
+    }
+
+    fn __contains__(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Inventory(<{} devices>, prefer='{}')", self.devices.len(), self.prefer)
+    }
+}
+
+/// Fold `results` into `inventory` (same as `Inventory.apply_scan`)
+#[pyfunction]
+pub fn apply_scan_to_inventory(
+    py: Python,
+    inventory: &PyCell<Inventory>,
+    results: Vec<ScanResult>,
+) -> HashMap<String, PyObject> {
+    inventory.borrow_mut().apply_scan(py, results)
+}
+
+/// Ports flapping in `inventory` (same as `Inventory.flapping_ports`)
+#[pyfunction]
+#[pyo3(signature = (inventory, min_transitions=None, window=None))]
+pub fn flapping_ports(
+    inventory: &Inventory,
+    min_transitions: Option<usize>,
+    window: Option<usize>,
+) -> HashMap<String, Vec<u16>> {
+    inventory.flapping_ports(min_transitions, window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(ports: &[u16]) -> ScanResult {
+        ScanResult { ip: "10.0.0.5".to_string(), status: "up".to_string(), open_ports: ports.to_vec(), ..Default::default() }
+    }
+
+    #[test]
+    fn closed_port_is_dropped_and_reported() {
+        let mut inventory = Inventory::new("new", 10, 3).unwrap();
+        inventory.apply(vec![host(&[22])]);
+        inventory.apply(vec![host(&[22, 80])]);
+        let changes = inventory.apply(vec![host(&[22])]);
+
+        assert_eq!(inventory.devices["10.0.0.5"].open_ports, vec![22]);
+        assert_eq!(changes.updated.len(), 1);
+        assert_eq!(changes.updated["10.0.0.5"]["open_ports"], ("22,80".to_string(), "22".to_string()));
+    }
+
+    #[test]
+    fn flapping_port_closing_and_reopening_is_not_a_change() {
+        let mut inventory = Inventory::new("new", 10, 2).unwrap();
+        inventory.apply(vec![host(&[22, 80])]);
+        // open -> closed: one transition, below the threshold, so reported
+        let closed = inventory.apply(vec![host(&[22])]);
+        assert!(closed.updated["10.0.0.5"].contains_key("open_ports"));
+        assert!(closed.flapping.is_empty());
+        // closed -> open: 80 is now flapping and its reopening is suppressed
+        let reopened = inventory.apply(vec![host(&[22, 80])]);
+        assert_eq!(reopened.flapping["10.0.0.5"], vec![80]);
+        assert_eq!(reopened.unchanged, 1);
+        // open -> closed again: the close is a flap event too
+        let closed_again = inventory.apply(vec![host(&[22])]);
+        assert_eq!(closed_again.flapping["10.0.0.5"], vec![80]);
+        assert_eq!(closed_again.unchanged, 1);
+        assert_eq!(inventory.devices["10.0.0.5"].open_ports, vec![22]);
+    }
+
+    #[test]
+    fn prefer_existing_keeps_port_union() {
+        let mut inventory = Inventory::new("existing", 10, 3).unwrap();
+        inventory.apply(vec![host(&[22, 80])]);
+        let changes = inventory.apply(vec![host(&[22])]);
+
+        assert_eq!(inventory.devices["10.0.0.5"].open_ports, vec![22, 80]);
+        assert_eq!(changes.unchanged, 1);
+    }
+}
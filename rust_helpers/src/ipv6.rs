@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use ipnetwork::Ipv6Network;
+use pyo3::prelude::*;
+
+use crate::classify_ip_addr;
+use crate::scanner::ScanResult;
+
+// =============================================================================
+// IPv6 Addresses and Prefixes
+// =============================================================================
+
+fn value_error(message: String) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>(message)
+}
+
+/// Address and optional zone ("fe80::1%eth0"); brackets are accepted
+fn parse_ipv6(addr: &str) -> PyResult<(Ipv6Addr, Option<&str>)> {
+    let text = addr.trim().trim_start_matches('[').trim_end_matches(']');
+    let (text, zone) = match text.split_once('%') {
+        Some((text, zone)) if !zone.is_empty() => (text, Some(zone)),
+        Some(_) => return Err(value_error(format!("Empty zone in IPv6 address: {}", addr))),
+        None => (text, None),
+    };
+    let ip = text
+        .parse::<Ipv6Addr>()
+        .map_err(|e| value_error(format!("Invalid IPv6 address '{}': {}", addr, e)))?;
+    Ok((ip, zone))
+}
+
+/// Network with host bits cleared ("2001:db8::1/32" -> 2001:db8::/32)
+fn parse_prefix(prefix: &str) -> PyResult<Ipv6Network> {
+    let net: Ipv6Network = prefix
+        .trim()
+        .parse()
+        .map_err(|e| value_error(format!("Invalid IPv6 prefix '{}': {}", prefix, e)))?;
+    Ipv6Network::new(net.network(), net.prefix()).map_err(|e| value_error(e.to_string()))
+}
+
+/// 2^bits as a Python int (a /0 holds more addresses than u128 can count)
+fn power_of_two(py: Python, bits: u8) -> PyResult<PyObject> {
+    1u8.into_py(py).call_method1(py, "__lshift__", (bits,))
+}
+
+/// RFC 5952 text form: lowercase, longest zero run as "::", no leading
+/// zeros; IPv4-mapped addresses keep dotted notation, zones are preserved
+#[pyfunction]
+pub fn canonicalize_ipv6(addr: &str) -> PyResult<String> {
+    let (ip, zone) = parse_ipv6(addr)?;
+    Ok(match zone {
+        Some(zone) => format!("{}%{}", ip, zone),
+        None => ip.to_string(),
+    })
+}
+
+/// Facts about a prefix: {network, prefix_length, first, last,
+/// num_addresses, num_64_subnets, classification}
+///
+/// Host bits in the input are ignored; `num_64_subnets` is 0 for prefixes
+/// longer than /64.
+#[pyfunction]
+pub fn ipv6_subnet_info(py: Python, prefix: &str) -> PyResult<HashMap<String, PyObject>> {
+    let net = parse_prefix(prefix)?;
+    let len = net.prefix();
+    let first = net.network();
+    let last = Ipv6Addr::from(u128::from(first) | u128::MAX.checked_shr(len as u32).unwrap_or(0));
+
+    let mut info = HashMap::new();
+    info.insert("network".to_string(), net.to_string().into_py(py));
+    info.insert("prefix_length".to_string(), len.into_py(py));
+    info.insert("first".to_string(), first.to_string().into_py(py));
+    info.insert("last".to_string(), last.to_string().into_py(py));
+    info.insert("num_addresses".to_string(), power_of_two(py, 128 - len)?);
+    info.insert(
+        "num_64_subnets".to_string(),
+        if len <= 64 { power_of_two(py, 64 - len)? } else { 0.into_py(py) },
+    );
+    info.insert("classification".to_string(), classify_ip_addr(IpAddr::V6(first)).into_py(py));
+    Ok(info)
+}
+
+/// Split `prefix` into /`new_len` subnets, returning at most `limit` of them
+/// in address order
+#[pyfunction]
+#[pyo3(signature = (prefix, new_len, limit=256))]
+pub fn split_ipv6(prefix: &str, new_len: u8, limit: usize) -> PyResult<Vec<String>> {
+    let net = parse_prefix(prefix)?;
+    if new_len < net.prefix() || new_len > 128 {
+        return Err(value_error(format!(
+            "New prefix length must be between {} and 128, got {}",
+            net.prefix(),
+            new_len
+        )));
+    }
+
+    let base = u128::from(net.network());
+    let step = 1u128.checked_shl(128 - new_len as u32).unwrap_or(0);
+    let count = 1u128.checked_shl((new_len - net.prefix()) as u32);
+    let take = count.map_or(limit, |c| c.min(limit as u128) as usize);
+    Ok((0..take as u128)
+        .map(|i| format!("{}/{}", Ipv6Addr::from(base + i * step), new_len))
+        .collect())
+}
+
+/// Interface identifier (low 64 bits) of an address
+fn interface_id(ip: &Ipv6Addr) -> [u8; 8] {
+    let octets = ip.octets();
+    octets[8..].try_into().expect("8 bytes")
+}
+
+fn is_eui64(ip: &Ipv6Addr) -> bool {
+    let iid = interface_id(ip);
+    iid[3] == 0xff && iid[4] == 0xfe
+}
+
+/// Whether the interface identifier is modified EUI-64 (MAC-derived,
+/// "ff:fe" in the middle), as used by classic SLAAC
+#[pyfunction]
+pub fn is_eui64_address(addr: &str) -> PyResult<bool> {
+    Ok(is_eui64(&parse_ipv6(addr)?.0))
+}
+
+/// Heuristic for privacy addresses (RFC 4941 temporary, RFC 7217 opaque):
+/// a global, unique local or documentation address whose interface
+/// identifier looks random
+///
+/// Excluded: EUI-64 and ISATAP identifiers, manually numbered ones (upper
+/// 32 bits of the identifier zero, e.g. ::1 or ::a:b), and identifiers with
+/// the universal bit set. RFC 7217 stable addresses look the same as
+/// temporary ones, so True means "not MAC-derived", not necessarily
+/// short-lived.
+#[pyfunction]
+pub fn is_privacy_address(addr: &str) -> PyResult<bool> {
+    let (ip, _) = parse_ipv6(addr)?;
+    let class = classify_ip_addr(IpAddr::V6(ip));
+    if !matches!(class, "public" | "private" | "documentation") || ip.to_ipv4_mapped().is_some() {
+        return Ok(false);
+    }
+    let iid = interface_id(&ip);
+    let isatap = iid[1..4] == [0x00, 0x5e, 0xfe] && iid[0] & 0xfd == 0;
+    let manual = iid[..4] == [0, 0, 0, 0];
+    let universal = iid[0] & 0x02 != 0;
+    Ok(!is_eui64(&ip) && !isatap && !manual && !universal)
+}
+
+// =============================================================================
+// Dual-Stack Correlation
+// =============================================================================
+//
+// A dual-stack device shows up once in the ARP table and once in the IPv6
+// neighbor cache. The link between the two is the MAC address: the one the
+// v6 record carries, or the one embedded in an EUI-64 address. Privacy
+// addresses embed nothing, so a v6 record with no MAC of its own and only
+// such an address stays on its own.
+
+/// MAC embedded in a modified EUI-64 interface identifier
+fn eui64_mac(ip: &Ipv6Addr) -> Option<String> {
+    if !is_eui64(ip) {
+        return None;
+    }
+    let iid = interface_id(ip);
+    let mac = [iid[0] ^ 0x02, iid[1], iid[2], iid[5], iid[6], iid[7]];
+    Some(mac.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(":"))
+}
+
+/// Normalized MAC of a record, unless missing or a placeholder
+pub fn record_mac(mac: &str) -> Option<String> {
+    let mac = crate::normalize_mac(mac.trim());
+    let usable = mac.len() == 17 && mac != "00:00:00:00:00:00" && mac != "FF:FF:FF:FF:FF:FF";
+    usable.then_some(mac)
+}
+
+/// Merge the IPv6 records of dual-stack devices into their IPv4 records
+///
+/// Returns (devices, links). A v6 record is linked to a v4 record with the
+/// same MAC, its own or (evidence "eui64") the one its EUI-64 address
+/// embeds; when several v4 records share the MAC, the lowest address takes
+/// the v6 records. The v4 record keeps its `ip`, gains the v6 addresses in
+/// `ipv6_addresses` and the v6 record's
+/// sources and missing fields. `links` lists each merge as (ipv4, ipv6,
+/// evidence), evidence being "mac" or "eui64". Unlinked records come back
+/// as they were, in input order.
+#[pyfunction]
+pub fn correlate_dual_stack(devices: Vec<ScanResult>) -> (Vec<ScanResult>, Vec<(String, String, String)>) {
+    let addresses: Vec<Option<IpAddr>> = devices.iter().map(|d| d.ip.trim().parse().ok()).collect();
+
+    // Lowest IPv4 address per MAC
+    let mut primaries: HashMap<String, (Ipv4Addr, usize)> = HashMap::new();
+    for (index, device) in devices.iter().enumerate() {
+        if let (Some(IpAddr::V4(ip)), Some(mac)) = (addresses[index], record_mac(&device.mac)) {
+            let primary = primaries.entry(mac).or_insert((ip, index));
+            if ip < primary.0 {
+                *primary = (ip, index);
+            }
+        }
+    }
+
+    // v6 record index -> (primary index, evidence)
+    let mut merge_into: HashMap<usize, (usize, &str)> = HashMap::new();
+    for (index, device) in devices.iter().enumerate() {
+        let Some(IpAddr::V6(ip)) = addresses[index] else {
+            continue;
+        };
+        let link = match record_mac(&device.mac) {
+            Some(mac) => primaries.get(&mac).map(|p| (p.1, "mac")),
+            None => eui64_mac(&ip).and_then(|mac| primaries.get(&mac)).map(|p| (p.1, "eui64")),
+        };
+        if let Some(link) = link {
+            merge_into.insert(index, link);
+        }
+    }
+
+    let mut merged = devices.clone();
+    let mut links = Vec::new();
+    let mut v6_indexes: Vec<&usize> = merge_into.keys().collect();
+    v6_indexes.sort_unstable();
+    for index in v6_indexes {
+        let (primary, evidence) = merge_into[index];
+        let v6 = &devices[*index];
+        let device = &mut merged[primary];
+        let ipv6 = v6.ip.trim().to_string();
+        if !device.ipv6_addresses.contains(&ipv6) {
+            device.ipv6_addresses.push(ipv6.clone());
+        }
+        device.merge(v6);
+        links.push((device.ip.clone(), ipv6, evidence.to_string()));
+    }
+
+    let devices = merged
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| !merge_into.contains_key(index))
+        .map(|(_, device)| device)
+        .collect();
+    (devices, links)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(ip: &str, mac: &str) -> ScanResult {
+        ScanResult { ip: ip.to_string(), mac: mac.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn dual_stack_addresses_are_a_list() {
+        let devices = vec![
+            record("192.168.1.10", "00:11:22:33:44:55"),
+            record("fe80::211:22ff:fe33:4455", ""),
+            record("2001:db8::10", "00-11-22-33-44-55"),
+            record("2001:db8::10", "00:11:22:33:44:55"),
+        ];
+        let (devices, links) = correlate_dual_stack(devices);
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].ip, "192.168.1.10");
+        assert_eq!(devices[0].ipv6_addresses, ["fe80::211:22ff:fe33:4455", "2001:db8::10"]);
+        assert!(!devices[0].attributes.contains_key("ipv6_addresses"));
+        assert_eq!(links[0].2, "eui64");
+        assert_eq!(links.len(), 3);
+    }
+}
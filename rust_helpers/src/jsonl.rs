@@ -0,0 +1,110 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use pyo3::prelude::*;
+
+use crate::scanner::ScanResult;
+
+// =============================================================================
+// JSON Lines Output
+// =============================================================================
+//
+// One ScanResult object per line, so files can be appended to while a scan
+// runs and read back without loading the whole file. Blank lines are skipped.
+
+fn io_error(action: &str, path: &str, e: impl std::fmt::Display) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot {} {}: {}", action, path, e))
+}
+
+fn parse_line(line: &str, number: usize) -> PyResult<ScanResult> {
+    serde_json::from_str(line).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid JSON on line {}: {}", number, e))
+    })
+}
+
+/// Write results as JSON lines, replacing the file or appending to it;
+/// returns the number of lines written
+#[pyfunction]
+#[pyo3(signature = (results, path, append=false))]
+pub fn write_scan_results_jsonl(results: Vec<ScanResult>, path: &str, append: bool) -> PyResult<usize> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .map_err(|e| io_error("open", path, e))?;
+    let mut writer = BufWriter::new(file);
+
+    for result in &results {
+        serde_json::to_writer(&mut writer, result).map_err(|e| io_error("write", path, e))?;
+        writer.write_all(b"\n").map_err(|e| io_error("write", path, e))?;
+    }
+    writer.flush().map_err(|e| io_error("write", path, e))?;
+    Ok(results.len())
+}
+
+/// Read a JSON lines file into results
+///
+/// `filter_fn`, if given, is called with each raw line before it is parsed;
+/// lines it returns a falsy value for are skipped (and never validated).
+#[pyfunction]
+#[pyo3(signature = (path, filter_fn=None))]
+pub fn read_scan_results_jsonl(py: Python, path: &str, filter_fn: Option<PyObject>) -> PyResult<Vec<ScanResult>> {
+    let file = File::open(path).map_err(|e| io_error("open", path, e))?;
+    let mut results = Vec::new();
+
+    for (n, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| io_error("read", path, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(filter) = &filter_fn {
+            if !filter.call1(py, (line.as_str(),))?.is_true(py)? {
+                continue;
+            }
+        }
+        results.push(parse_line(&line, n + 1)?);
+    }
+    Ok(results)
+}
+
+/// Lazy iterator over a JSON lines file, one ScanResult per step
+#[pyclass]
+pub struct ScanResultJsonlIterator {
+    lines: Lines<BufReader<File>>,
+    path: String,
+    line_number: usize,
+}
+
+#[pymethods]
+impl ScanResultJsonlIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> PyResult<Option<ScanResult>> {
+        for line in self.lines.by_ref() {
+            self.line_number += 1;
+            let line = line.map_err(|e| io_error("read", &self.path, e))?;
+            if !line.trim().is_empty() {
+                return parse_line(&line, self.line_number).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ScanResultJsonlIterator(path='{}', line={})", self.path, self.line_number)
+    }
+}
+
+/// Iterate over a JSON lines file without reading it all into memory
+#[pyfunction]
+pub fn stream_scan_results_jsonl(path: &str) -> PyResult<ScanResultJsonlIterator> {
+    let file = File::open(path).map_err(|e| io_error("open", path, e))?;
+    Ok(ScanResultJsonlIterator {
+        lines: BufReader::new(file).lines(),
+        path: path.to_string(),
+        line_number: 0,
+    })
+}
@@ -1,23 +1,89 @@
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufWriter, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::sync::Arc;
 use dashmap::DashMap;
-use ipnetwork::Ipv4Network;
+use ipnetwork::{IpNetwork, Ipv4Network};
 use regex::Regex;
 use memmap2::Mmap;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 
+mod arp;
+mod banners;
+mod cache;
+mod capabilities;
+mod compliance;
+mod custom_probes;
+mod dns;
+mod dns_services;
+mod enrich;
+mod fingerprint;
+mod honeypot;
+mod hostnames;
+mod icmp;
+mod importers;
+mod inventory;
+mod ipv6;
+mod jsonl;
+mod liveness;
+mod metrics;
+mod monitor;
+mod multicast;
+mod ndp;
+mod oui;
+mod parsers;
+mod probes;
+mod query;
+#[cfg(feature = "rdap")]
+mod rdap;
+mod reconcile;
+mod resolv_conf;
+mod resolve;
+mod routes;
 mod scanner;
+mod schedule;
+mod schema;
+mod scope;
+mod secret;
+mod service_probes;
+mod slack;
+mod syn;
+mod targets;
+mod udp;
+
+/// Rust-level entry points shared with the `netscan-cli` binary
+#[cfg(feature = "cli")]
+pub mod cli_api {
+    pub use crate::{extract_oui, normalize_mac, parse_arp_output, parse_oui_content, write_delimited};
+    pub use crate::scanner::{
+        parse_port_spec, results_from_scan, runtime, scan_hosts, ScanConfig, ScanResult,
+        RECORD_FIELDS, TCP_PING_PORTS,
+    };
+    pub use crate::targets::expand_targets;
+}
+
+/// Rust-level entry points for the benchmarks in benches/
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench_api {
+    pub use crate::resolve::resolve_names;
+}
 
 // =============================================================================
 // MAC Address Normalization (10-50x faster than Python)
 // =============================================================================
 
 /// Normalize a MAC address to uppercase colon-separated format
+///
+/// Accepts colon, hyphen, Cisco dotted ("aabb.ccdd.eeff") and bare forms.
 #[pyfunction]
-fn normalize_mac(mac: &str) -> String {
+pub fn normalize_mac(mac: &str) -> String {
     // Fast path: already normalized
     if mac.len() == 17 && mac.chars().nth(2) == Some(':') {
         let upper = mac.to_uppercase();
@@ -25,6 +91,12 @@ fn normalize_mac(mac: &str) -> String {
             return upper;
         }
     }
+    // Cisco dotted notation: group widths matter, so don't just strip the dots
+    if mac.contains('.') {
+        if let Some(normalized) = dotted_mac(mac) {
+            return normalized;
+        }
+    }
     
     // Remove all separators and convert to uppercase
     let clean: String = mac
@@ -39,15 +111,83 @@ fn normalize_mac(mac: &str) -> String {
     }
     
     // Format as XX:XX:XX:XX:XX:XX
-    let bytes: Vec<&str> = (0..clean.len().min(12))
-        .step_by(2)
-        .map(|i| &clean[i..i.min(clean.len()).max(i + 2).min(clean.len())])
+    format_mac_groups(&clean).unwrap_or_else(|| mac.to_uppercase())
+}
+
+/// Normalize Cisco dotted notation ("aabb.ccdd.eeff"): three groups of up
+/// to four hex digits, each a pair of bytes with its leading zeros dropped
+/// ("1.2.3" is 00:01:00:02:00:03)
+fn dotted_mac(mac: &str) -> Option<String> {
+    let groups: Vec<&str> = mac.trim().split('.').collect();
+    let valid = |group: &&str| (1..=4).contains(&group.len()) && group.chars().all(|c| c.is_ascii_hexdigit());
+    if groups.len() != 3 || !groups.iter().all(valid) {
+        return None;
+    }
+    let clean: String = groups.iter().map(|group| format!("{:0>4}", group.to_uppercase())).collect();
+    format_mac_groups(&clean)
+}
+
+/// Format up to 12 uppercase hex chars as six 2-char groups, zero-padding a
+/// trailing single-char group. Returns None if fewer than six groups result.
+fn format_mac_groups(clean: &str) -> Option<String> {
+    let groups: Vec<String> = clean
+        .as_bytes()
+        .chunks(2)
+        .take(6)
+        .map(|pair| match pair {
+            [hi, lo] => format!("{}{}", *hi as char, *lo as char),
+            [single] => format!("0{}", *single as char),
+            _ => unreachable!(),
+        })
         .collect();
     
-    if bytes.len() >= 6 {
-        bytes[..6].join(":")
+    if groups.len() == 6 {
+        Some(groups.join(":"))
     } else {
-        mac.to_uppercase()
+        None
+    }
+}
+
+/// Normalize a MAC address, raising ValueError unless it has exactly 12 hex digits
+#[pyfunction]
+fn normalize_mac_strict(mac: &str) -> PyResult<String> {
+    let stripped: String = mac
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ':' | '-' | '.' | ' '))
+        .collect();
+    
+    if let Some(bad) = stripped.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid MAC address '{}': unexpected character '{}'", mac, bad)
+        ));
+    }
+    if stripped.len() != 12 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid MAC address '{}': expected 12 hex digits, found {}", mac, stripped.len())
+        ));
+    }
+    
+    Ok(format_mac_groups(&stripped.to_uppercase()).unwrap_or_default())
+}
+
+/// Whether `mac` is a complete MAC address: six hex pairs separated by ":"
+/// or "-" (or not at all), or Cisco dotted notation ("aabb.ccdd.eeff")
+#[pyfunction]
+pub fn validate_mac(mac: &str) -> bool {
+    let mac = mac.trim();
+    if mac.contains('.') {
+        return dotted_mac(mac).is_some();
+    }
+    let all_hex = |s: &str| s.chars().all(|c| c.is_ascii_hexdigit());
+    match mac.len() {
+        12 => all_hex(mac),
+        17 => {
+            let separator = mac.as_bytes()[2] as char;
+            let pairs: Vec<&str> = mac.split(separator).collect();
+            matches!(separator, ':' | '-') && pairs.len() == 6 && pairs.iter().all(|p| p.len() == 2 && all_hex(p))
+        }
+        _ => false,
     }
 }
 
@@ -59,9 +199,75 @@ fn normalize_macs(macs: Vec<String>) -> Vec<String> {
         .collect()
 }
 
+/// Batch normalize MAC addresses and count what the input looked like
+///
+/// Returns (normalized, stats), where normalized is what `normalize_macs`
+/// returns and stats holds `total`, `already_normalized`,
+/// `needed_normalization` and `invalid` (which add up to `total`), plus
+/// `too_short` (invalid with fewer than 12 hex digits) and `had_colons`,
+/// `had_dashes`, `had_dots` (separators present in the input). Invalid means
+/// not exactly 12 hex digits once separators are removed, as for
+/// `normalize_mac_strict`; many of those point at upstream data problems
+/// and explain failed OUI lookups.
+#[pyfunction]
+fn normalize_macs_with_stats(macs: Vec<String>) -> PyResult<(Vec<String>, HashMap<String, usize>)> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let already_normalized = AtomicUsize::new(0);
+    let needed_normalization = AtomicUsize::new(0);
+    let invalid = AtomicUsize::new(0);
+    let too_short = AtomicUsize::new(0);
+    let had_colons = AtomicUsize::new(0);
+    let had_dashes = AtomicUsize::new(0);
+    let had_dots = AtomicUsize::new(0);
+    let count = |counter: &AtomicUsize| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    };
+
+    let normalized: Vec<String> = macs
+        .par_iter()
+        .map(|mac| {
+            for (separator, counter) in [(':', &had_colons), ('-', &had_dashes), ('.', &had_dots)] {
+                if mac.contains(separator) {
+                    count(counter);
+                }
+            }
+            let stripped: Vec<char> = mac
+                .trim()
+                .chars()
+                .filter(|c| !matches!(c, ':' | '-' | '.' | ' '))
+                .collect();
+            let normalized = normalize_mac(mac);
+            if stripped.len() != 12 || !stripped.iter().all(char::is_ascii_hexdigit) {
+                count(&invalid);
+                if stripped.iter().filter(|c| c.is_ascii_hexdigit()).count() < 12 {
+                    count(&too_short);
+                }
+            } else if normalized == *mac {
+                count(&already_normalized);
+            } else {
+                count(&needed_normalization);
+            }
+            normalized
+        })
+        .collect();
+
+    let stats = HashMap::from([
+        ("total".to_string(), macs.len()),
+        ("already_normalized".to_string(), already_normalized.into_inner()),
+        ("needed_normalization".to_string(), needed_normalization.into_inner()),
+        ("invalid".to_string(), invalid.into_inner()),
+        ("too_short".to_string(), too_short.into_inner()),
+        ("had_colons".to_string(), had_colons.into_inner()),
+        ("had_dashes".to_string(), had_dashes.into_inner()),
+        ("had_dots".to_string(), had_dots.into_inner()),
+    ]);
+    Ok((normalized, stats))
+}
+
 /// Extract OUI prefix from MAC address
 #[pyfunction]
-fn extract_oui(mac: &str) -> String {
+pub fn extract_oui(mac: &str) -> String {
     let normalized = normalize_mac(mac);
     if normalized.len() >= 8 {
         normalized[..8].to_string()
@@ -74,9 +280,76 @@ fn extract_oui(mac: &str) -> String {
 // OUI Database Parser (100x+ faster than Python for large files)
 // =============================================================================
 
-/// Parse OUI database file and return HashMap
-#[pyfunction]
-fn parse_oui_file(filepath: &str) -> PyResult<HashMap<String, String>> {
+pyo3::create_exception!(
+    netscan_core,
+    ParseQualityError,
+    pyo3::exceptions::PyValueError,
+    "A database file parsed into far fewer entries than a healthy copy has"
+);
+
+/// Entry count below which an OUI file is taken to be misparsed; the IEEE
+/// registry has over 35,000
+pub const OUI_MIN_ENTRIES: usize = 1000;
+
+/// Share of non-empty lines that must parse; each `oui.txt` entry spans
+/// about five lines, so a healthy file sits near 0.2 (nmap-mac-prefixes,
+/// one entry per line, near 1)
+pub const OUI_MIN_LINE_RATIO: f64 = 0.1;
+
+/// One OUI entry: an IEEE `oui.txt` "(hex)" line or an nmap-mac-prefixes
+/// "XXXXXX Vendor" line; "(base 16)" lines match neither
+const OUI_LINE_PATTERN: &str =
+    r"^(?:([0-9A-Fa-f]{2}[:\-]?[0-9A-Fa-f]{2}[:\-]?[0-9A-Fa-f]{2})\s+\(hex\)\s+|([0-9A-Fa-f]{6})[ \t]+)([^(\s].*)$";
+
+/// (oui key, vendor) from an `OUI_LINE_PATTERN` match
+fn oui_entry(caps: &regex::Captures) -> (String, String) {
+    let prefix = caps.get(1).or_else(|| caps.get(2)).unwrap().as_str();
+    (oui_key(prefix), caps[3].trim().to_string())
+}
+
+/// Entries of an OUI file with what it takes to judge the parse
+#[derive(Debug, Default)]
+pub struct OuiParse {
+    pub entries: HashMap<String, String>,
+    pub nonempty_lines: usize,
+    /// Up to three lines that look like entries but didn't parse
+    pub unmatched_samples: Vec<String>,
+    /// Invalid UTF-8 bytes replaced while reading the file
+    pub replaced_bytes: usize,
+}
+
+impl OuiParse {
+    /// Why the parse looks broken, if it does
+    pub fn quality_problem(&self, path: &str, min_entries: usize, min_line_ratio: f64) -> Option<String> {
+        let ratio = self.entries.len() as f64 / self.nonempty_lines.max(1) as f64;
+        if self.entries.len() >= min_entries && ratio >= min_line_ratio {
+            return None;
+        }
+        Some(format!(
+            "{} parsed into {} OUI entries from {} non-empty lines (expected at least {} entries and {:.0}% of lines); \
+             the file format may have changed. Unmatched lines: {:?}",
+            path,
+            self.entries.len(),
+            self.nonempty_lines,
+            min_entries,
+            min_line_ratio * 100.0,
+            self.unmatched_samples
+        ))
+    }
+}
+
+/// Raise ParseQualityError for a broken-looking parse, or with `strict`
+/// off only warn (RuntimeWarning)
+pub fn check_parse_quality(py: Python, problem: Option<String>, strict: bool) -> PyResult<()> {
+    match problem {
+        Some(message) if strict => Err(ParseQualityError::new_err(message)),
+        Some(message) => PyErr::warn(py, py.get_type::<pyo3::exceptions::PyRuntimeWarning>(), &message, 1),
+        None => Ok(()),
+    }
+}
+
+/// Read and parse an OUI file without judging the result
+pub fn read_oui_file(filepath: &str) -> PyResult<OuiParse> {
     let file = File::open(filepath).map_err(|e| {
         PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot open file: {}", e))
     })?;
@@ -86,24 +359,143 @@ fn parse_oui_file(filepath: &str) -> PyResult<HashMap<String, String>> {
         PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot mmap file: {}", e))
     })?;
     
-    let content = std::str::from_utf8(&mmap).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid UTF-8: {}", e))
+    let (content, replaced_bytes) = decode_lossy(&mmap);
+    Ok(OuiParse { replaced_bytes, ..parse_oui_content_with_stats(&content) })
+}
+
+/// Parse OUI database file and return HashMap
+///
+/// Fewer than `min_entries` entries, or entries from less than
+/// `min_line_ratio` of the non-empty lines, means the format has probably
+/// drifted: raises ParseQualityError with the counts and sample unmatched
+/// lines, or with `strict=False` warns and returns what parsed. Pass
+/// `min_entries=0, min_line_ratio=0` for small in-house files. Invalid
+/// UTF-8 is replaced with U+FFFD, with a RuntimeWarning giving the number
+/// of bytes replaced.
+#[pyfunction]
+#[pyo3(signature = (filepath, min_entries=OUI_MIN_ENTRIES, min_line_ratio=OUI_MIN_LINE_RATIO, strict=true))]
+pub fn parse_oui_file(
+    py: Python,
+    filepath: &str,
+    min_entries: usize,
+    min_line_ratio: f64,
+    strict: bool,
+) -> PyResult<HashMap<String, String>> {
+    let parse = py.allow_threads(|| read_oui_file(filepath))?;
+    warn_replaced_bytes(py, filepath, parse.replaced_bytes)?;
+    check_parse_quality(py, parse.quality_problem(filepath, min_entries, min_line_ratio), strict)?;
+    Ok(parse.entries)
+}
+
+/// Parse an OUI file as `parse_oui_file` does and say how the parse went
+///
+/// Returns (entries, stats), where stats holds `entries`, `nonempty_lines`,
+/// `unmatched` (non-empty lines that gave no entry) and `replaced_bytes`
+/// (invalid UTF-8 bytes replaced with U+FFFD). The quality check is the
+/// same as `parse_oui_file`'s; no RuntimeWarning is given for replaced
+/// bytes since the count is returned.
+#[pyfunction]
+#[pyo3(signature = (filepath, min_entries=OUI_MIN_ENTRIES, min_line_ratio=OUI_MIN_LINE_RATIO, strict=true))]
+fn parse_oui_file_with_stats(
+    py: Python,
+    filepath: &str,
+    min_entries: usize,
+    min_line_ratio: f64,
+    strict: bool,
+) -> PyResult<(HashMap<String, String>, HashMap<String, usize>)> {
+    let parse = py.allow_threads(|| read_oui_file(filepath))?;
+    check_parse_quality(py, parse.quality_problem(filepath, min_entries, min_line_ratio), strict)?;
+    let stats = HashMap::from([
+        ("entries".to_string(), parse.entries.len()),
+        ("nonempty_lines".to_string(), parse.nonempty_lines),
+        ("unmatched".to_string(), parse.nonempty_lines.saturating_sub(parse.entries.len())),
+        ("replaced_bytes".to_string(), parse.replaced_bytes),
+    ]);
+    Ok((parse.entries, stats))
+}
+
+/// Read an OUI file only as far as needed to find `ouis` (keys as
+/// `parse_oui_file` writes them); entries for other prefixes are skipped
+pub fn read_oui_file_for(filepath: &str, ouis: &HashSet<String>) -> PyResult<HashMap<String, String>> {
+    let file = File::open(filepath).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot open file: {}", e))
     })?;
-    
+    let mmap = unsafe { Mmap::map(&file) }.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot mmap file: {}", e))
+    })?;
+
+    let oui_regex = Regex::new(OUI_LINE_PATTERN).unwrap();
+    let mut found = HashMap::new();
+    // Line by line so the pages past the last wanted entry are never read
+    for line in mmap.split(|&b| b == b'\n') {
+        if found.len() == ouis.len() {
+            break;
+        }
+        let line = String::from_utf8_lossy(line);
+        let Some(caps) = oui_regex.captures(line.trim_end_matches('\r')) else { continue };
+        let (prefix, vendor) = oui_entry(&caps);
+        if ouis.contains(&prefix) {
+            found.insert(prefix, vendor);
+        }
+    }
+    Ok(found)
+}
+
+/// Vendors for just `prefixes` (MACs or OUIs) from an OUI file
+///
+/// Stops reading once every prefix is found, so a few lookups against a
+/// cold file don't wait on the full parse. Returns {oui: vendor} with keys
+/// as in `parse_oui_file`; prefixes not in the file are left out. No parse
+/// quality check is made.
+#[pyfunction]
+pub fn parse_oui_file_for(py: Python, filepath: &str, prefixes: Vec<String>) -> PyResult<HashMap<String, String>> {
+    let ouis: HashSet<String> = prefixes.iter().map(|p| oui_key(p)).collect();
+    py.allow_threads(|| read_oui_file_for(filepath, &ouis))
+}
+
+/// Parse IEEE `oui.txt` content ("XX-XX-XX   (hex)   Vendor" lines) or
+/// nmap-mac-prefixes content ("XXXXXX Vendor" lines)
+pub fn parse_oui_content(content: &str) -> HashMap<String, String> {
+    parse_oui_content_with_stats(content).entries
+}
+
+fn parse_oui_content_with_stats(content: &str) -> OuiParse {
+    let content = &*clean_text(content);
     // Parallel parsing with regex
-    let oui_regex = Regex::new(r"(?m)^([0-9A-Fa-f]{2}[:\-]?[0-9A-Fa-f]{2}[:\-]?[0-9A-Fa-f]{2})\s+\(hex\)\s+(.+)$").unwrap();
+    let oui_regex = Regex::new(OUI_LINE_PATTERN).unwrap();
     
     let results: DashMap<String, String> = DashMap::new();
     
     content.par_lines().for_each(|line| {
         if let Some(caps) = oui_regex.captures(line) {
-            let prefix = caps.get(1).unwrap().as_str().to_uppercase().replace("-", ":");
-            let vendor = caps.get(2).unwrap().as_str().trim().to_string();
+            let (prefix, vendor) = oui_entry(&caps);
             results.insert(prefix, vendor);
         }
     });
     
-    Ok(results.into_iter().collect())
+    // Lines marked "(hex)" are the ones that should have parsed; failing
+    // those, lines that open with a hex pair and aren't "(base 16)" lines
+    let unmatched = |wanted: &dyn Fn(&str) -> bool| -> Vec<String> {
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| wanted(line) && !oui_regex.is_match(line))
+            .take(3)
+            .map(str::to_string)
+            .collect()
+    };
+    let mut unmatched_samples = unmatched(&|line| line.to_lowercase().contains("(hex)"));
+    if unmatched_samples.is_empty() {
+        unmatched_samples = unmatched(&|line| {
+            line.len() >= 2 && line[..2].chars().all(|c| c.is_ascii_hexdigit()) && !line.contains("(base 16)")
+        });
+    }
+    OuiParse {
+        entries: results.into_iter().collect(),
+        nonempty_lines: content.par_lines().filter(|line| !line.trim().is_empty()).count(),
+        unmatched_samples,
+        replaced_bytes: 0,
+    }
 }
 
 /// Fast OUI lookup from pre-parsed database
@@ -125,6 +517,136 @@ fn lookup_ouis(oui_db: HashMap<String, String>, macs: Vec<String>) -> HashMap<St
         .collect()
 }
 
+/// OUI map key as `parse_oui_content` writes it ("00:1A:2B"); keys without
+/// six hex digits are kept, uppercased
+fn oui_key(key: &str) -> String {
+    let hex: String = key.chars().filter(|c| c.is_ascii_hexdigit()).take(6).collect::<String>().to_uppercase();
+    if hex.len() == 6 {
+        format!("{}:{}:{}", &hex[0..2], &hex[2..4], &hex[4..6])
+    } else {
+        key.trim().to_uppercase()
+    }
+}
+
+/// Merge two OUI maps; `override_policy` is "primary_wins" (the overlay
+/// only fills gaps) or "secondary_wins" (the overlay replaces entries)
+fn merge_oui_maps(
+    base: HashMap<String, String>,
+    overlay: HashMap<String, String>,
+    override_policy: &str,
+) -> PyResult<HashMap<String, String>> {
+    let overlay_wins = match override_policy {
+        "primary_wins" => false,
+        "secondary_wins" => true,
+        _ => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid override_policy '{}': expected 'primary_wins' or 'secondary_wins'",
+                override_policy
+            )));
+        }
+    };
+    let mut merged: HashMap<String, String> = base.into_iter().map(|(k, v)| (oui_key(&k), v)).collect();
+    for (key, vendor) in overlay {
+        let key = oui_key(&key);
+        if overlay_wins || !merged.contains_key(&key) {
+            merged.insert(key, vendor);
+        }
+    }
+    Ok(merged)
+}
+
+/// Merge an IEEE OUI file with a second one, e.g. in-house assignments
+///
+/// Both files are parsed in parallel; see `merge_oui_databases_from_map`
+/// for `override_policy`. The primary file gets `parse_oui_file`'s quality
+/// check (ParseQualityError); the secondary may be as small as it likes.
+#[pyfunction]
+#[pyo3(signature = (primary_path, secondary_path, override_policy="primary_wins"))]
+fn merge_oui_databases(
+    py: Python,
+    primary_path: &str,
+    secondary_path: &str,
+    override_policy: &str,
+) -> PyResult<HashMap<String, String>> {
+    let (primary, secondary) = py.allow_threads(|| {
+        rayon::join(|| read_oui_file(primary_path), || read_oui_file(secondary_path))
+    });
+    let (primary, secondary) = (primary?, secondary?);
+    check_parse_quality(py, primary.quality_problem(primary_path, OUI_MIN_ENTRIES, OUI_MIN_LINE_RATIO), true)?;
+    merge_oui_maps(primary.entries, secondary.entries, override_policy)
+}
+
+/// Merge two in-memory OUI maps
+///
+/// With "primary_wins" `overlay` only adds prefixes `base` lacks; with
+/// "secondary_wins" it also replaces `base`'s vendors. Prefixes are matched
+/// whatever their separators ("00-1a-2b", "001A2B" and "00:1A:2B" are one).
+#[pyfunction]
+#[pyo3(signature = (base, overlay, override_policy="primary_wins"))]
+fn merge_oui_databases_from_map(
+    base: HashMap<String, String>,
+    overlay: HashMap<String, String>,
+    override_policy: &str,
+) -> PyResult<HashMap<String, String>> {
+    merge_oui_maps(base, overlay, override_policy)
+}
+
+/// Six hex digits of an OUI ("00:50:56", "00-50-56", "005056") as a u32
+/// with the OUI in the top 24 bits
+fn oui_u32(oui: &str) -> Option<u32> {
+    let hex: String = oui.chars().filter(|c| !matches!(c, ':' | '-' | '.')).collect();
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(&hex, 16).ok().map(|n| n << 8)
+}
+
+/// OUI prefix as an integer: "00:50:56" -> 0x00505600 (top 24 bits)
+#[pyfunction]
+fn oui_prefix_to_u32(oui: &str) -> PyResult<u32> {
+    oui_u32(oui.trim()).ok_or_else(|| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid OUI prefix '{}': expected XX:XX:XX",
+            oui
+        ))
+    })
+}
+
+/// Reverse of `oui_prefix_to_u32`; the low 8 bits are ignored
+#[pyfunction]
+fn u32_to_oui_prefix(n: u32) -> String {
+    format!("{:02X}:{:02X}:{:02X}", n >> 24, (n >> 16) & 0xFF, (n >> 8) & 0xFF)
+}
+
+/// OUI map as (prefix as u32, vendor) sorted by prefix, for `lookup_oui_fast`
+///
+/// Prefixes written differently ("00-50-56", "00:50:56") are one entry; if
+/// their vendors differ the alphabetically first is kept. Raises ValueError
+/// on a key that isn't a 24-bit OUI.
+#[pyfunction]
+fn build_oui_index(oui_db: HashMap<String, String>) -> PyResult<Vec<(u32, String)>> {
+    let mut index = oui_db
+        .into_iter()
+        .map(|(oui, vendor)| Ok((oui_prefix_to_u32(&oui)?, vendor)))
+        .collect::<PyResult<Vec<(u32, String)>>>()?;
+    index.sort_unstable();
+    index.dedup_by_key(|(prefix, _)| *prefix);
+    Ok(index)
+}
+
+/// Vendor of `mac` by binary search of a `build_oui_index` index
+///
+/// The index is converted from Python on every call, so keep it for one-off
+/// lookups; for many MACs use `lookup_ouis` or an OuiDatabase.
+#[pyfunction]
+fn lookup_oui_fast(index: Vec<(u32, String)>, mac: &str) -> Option<String> {
+    let prefix = oui_u32(&extract_oui(mac))?;
+    index
+        .binary_search_by_key(&prefix, |(oui, _)| *oui)
+        .ok()
+        .map(|i| index[i].1.clone())
+}
+
 // =============================================================================
 // IP Address Utilities (5-20x faster than Python)
 // =============================================================================
@@ -159,9 +681,103 @@ fn expand_cidr_hosts(cidr: &str) -> PyResult<Vec<String>> {
         .collect())
 }
 
-/// Expand IP range to list
+/// First host of an IPv4 CIDR and the number of hosts, leaving out the
+/// network and broadcast addresses as `expand_cidr_hosts` does
+fn cidr_host_span(cidr: &str) -> PyResult<(u64, u64)> {
+    let network: Ipv4Network = cidr.parse().map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid CIDR: {}", e))
+    })?;
+    let base = u32::from(network.network()) as u64;
+    let size = 1u64 << (32 - network.prefix());
+    Ok(if size <= 2 { (base, size) } else { (base + 1, size - 2) })
+}
+
+/// Generator for the sampling functions: seeded when `seed` is given, so
+/// the same seed always gives the same picks
+fn sampling_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// `n` distinct offsets below `count` in O(n) (Floyd's algorithm), unordered
+fn distinct_offsets(count: u64, n: u64, rng: &mut StdRng) -> HashSet<u64> {
+    let mut picked = HashSet::with_capacity(n as usize);
+    for j in (count - n)..count {
+        let offset = rng.gen_range(0..=j);
+        if !picked.insert(offset) {
+            picked.insert(j);
+        }
+    }
+    picked
+}
+
+/// `n` distinct random hosts of an IPv4 CIDR, in address order, without
+/// expanding it
+///
+/// Hosts exclude the network and broadcast addresses as in
+/// `expand_cidr_hosts`; when `n` covers them all, all are returned. The same
+/// seed always gives the same sample, so sampling a /8 a /24's worth at a
+/// time is reproducible.
 #[pyfunction]
-fn expand_ip_range(start: &str, end: &str) -> PyResult<Vec<String>> {
+fn expand_cidr_sample(cidr: &str, n: usize, seed: u64) -> PyResult<Vec<String>> {
+    let (first, count) = cidr_host_span(cidr)?;
+    if n as u64 >= count {
+        return Ok((first..first + count).map(|ip| Ipv4Addr::from(ip as u32).to_string()).collect());
+    }
+
+    let mut offsets: Vec<u64> = distinct_offsets(count, n as u64, &mut sampling_rng(Some(seed))).into_iter().collect();
+    offsets.sort_unstable();
+    Ok(offsets.into_iter().map(|offset| Ipv4Addr::from((first + offset) as u32).to_string()).collect())
+}
+
+/// One random host of an IPv4 CIDR (network and broadcast excluded as in
+/// `expand_cidr_hosts`)
+///
+/// The same `seed` always picks the same host; without one the pick differs
+/// per call.
+#[pyfunction]
+#[pyo3(signature = (cidr, seed=None))]
+fn cidr_random_host(cidr: &str, seed: Option<u64>) -> PyResult<String> {
+    Ok(cidr_random_hosts(cidr, 1, seed, true)?.remove(0))
+}
+
+/// `n` random hosts of an IPv4 CIDR, in random order
+///
+/// With `with_replacement=False` the hosts are distinct, picked without
+/// expanding the CIDR, and asking for more than it holds raises
+/// ValueError; with `with_replacement=True` each host is an independent
+/// pick. Unlike `expand_cidr_sample`, the result is not sorted. The same
+/// `seed` always gives the same hosts in the same order.
+#[pyfunction]
+#[pyo3(signature = (cidr, n, seed=None, with_replacement=false))]
+fn cidr_random_hosts(cidr: &str, n: usize, seed: Option<u64>, with_replacement: bool) -> PyResult<Vec<String>> {
+    let (first, count) = cidr_host_span(cidr)?;
+    let mut rng = sampling_rng(seed);
+    let host = |offset: u64| Ipv4Addr::from((first + offset) as u32).to_string();
+
+    if with_replacement {
+        return Ok((0..n).map(|_| host(rng.gen_range(0..count))).collect());
+    }
+    if n as u64 > count {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Cannot pick {} distinct hosts from {}, which has {}",
+            n, cidr, count
+        )));
+    }
+    let mut offsets: Vec<u64> = distinct_offsets(count, n as u64, &mut rng).into_iter().collect();
+    // HashSet order is not seeded; sort, then shuffle from the same stream
+    offsets.sort_unstable();
+    offsets.shuffle(&mut rng);
+    Ok(offsets.into_iter().map(host).collect())
+}
+
+/// Addresses `expand_ip_range` will materialise before pointing callers at
+/// `iter_ip_range`; a million strings is already ~50 MB of Python objects
+pub(crate) const DEFAULT_MAX_IP_RANGE: usize = 1_000_000;
+
+fn parse_ip_range(start: &str, end: &str) -> PyResult<(u32, u32)> {
     let start_ip: Ipv4Addr = start.parse().map_err(|e| {
         PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid start IP: {}", e))
     })?;
@@ -177,45 +793,494 @@ fn expand_ip_range(start: &str, end: &str) -> PyResult<Vec<String>> {
             "End IP must be >= start IP"
         ));
     }
+    Ok((start_u32, end_u32))
+}
+
+/// Expand IP range to list
+///
+/// Ranges over `max_range` addresses (default 1,000,000) raise ValueError;
+/// use `iter_ip_range` for those. `max_range=None` keeps the old unbounded
+/// expansion with a DeprecationWarning and will be removed.
+#[pyfunction]
+#[pyo3(signature = (start, end, max_range=Some(DEFAULT_MAX_IP_RANGE)))]
+fn expand_ip_range(py: Python, start: &str, end: &str, max_range: Option<usize>) -> PyResult<Vec<String>> {
+    match max_range {
+        Some(max) => expand_ip_range_with_limit(start, end, max),
+        None => {
+            PyErr::warn(
+                py,
+                py.get_type::<pyo3::exceptions::PyDeprecationWarning>(),
+                "expand_ip_range(max_range=None) expands without a limit and will be removed in the next release; \
+                 use iter_ip_range() for lazy iteration",
+                1,
+            )?;
+            expand_ip_range_with_limit(start, end, usize::MAX)
+        }
+    }
+}
+
+/// Expand IP range to list, raising ValueError past `max` addresses
+#[pyfunction]
+pub fn expand_ip_range_with_limit(start: &str, end: &str, max: usize) -> PyResult<Vec<String>> {
+    let (start_u32, end_u32) = parse_ip_range(start, end)?;
+    let count = (end_u32 - start_u32) as u64 + 1;
+    if count > max as u64 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "IP range contains {} addresses, exceeding the maximum of {}. Use iter_ip_range() for lazy iteration.",
+            count, max
+        )));
+    }
     
     Ok((start_u32..=end_u32)
         .map(|n| Ipv4Addr::from(n).to_string())
         .collect())
 }
 
-/// Check if IP is private
+/// Lazy iterator over an IP range, one address per step
+#[pyclass]
+pub struct IpRangeIterator {
+    next: u64,
+    end: u64,
+}
+
+#[pymethods]
+impl IpRangeIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<String> {
+        if self.next > self.end {
+            return None;
+        }
+        let ip = Ipv4Addr::from(self.next as u32);
+        self.next += 1;
+        Some(ip.to_string())
+    }
+
+    /// Addresses not yet yielded
+    fn __len__(&self) -> usize {
+        (self.end + 1).saturating_sub(self.next) as usize
+    }
+
+    fn __repr__(&self) -> String {
+        format!("IpRangeIterator(remaining={})", self.__len__())
+    }
+}
+
+/// Iterate over an IP range without materialising it; no size limit
 #[pyfunction]
-fn is_private_ip(ip: &str) -> bool {
-    if let Ok(addr) = ip.parse::<Ipv4Addr>() {
-        addr.is_private() || addr.is_loopback() || addr.is_link_local()
+fn iter_ip_range(start: &str, end: &str) -> PyResult<IpRangeIterator> {
+    let (start_u32, end_u32) = parse_ip_range(start, end)?;
+    Ok(IpRangeIterator { next: start_u32 as u64, end: end_u32 as u64 })
+}
+
+// =============================================================================
+// IP / Hostname Values
+// =============================================================================
+//
+// Device lists often carry hostnames in the `ip` column. Every function below
+// handles them explicitly rather than dropping them: a value is an address, a
+// syntactically valid hostname, or invalid, and each class is kept.
+
+/// What a value from an `ip` column turned out to be
+enum HostValue {
+    Ip(IpAddr),
+    Hostname(String),
+    Invalid,
+}
+
+/// RFC 1123 hostname: dot-separated labels of letters, digits, '-' (not at
+/// either end) and '_'; an all-numeric last label is a malformed IP instead
+fn is_hostname(value: &str) -> bool {
+    let name = value.strip_suffix('.').unwrap_or(value);
+    if name.is_empty() || name.len() > 253 {
+        return false;
+    }
+    let valid_labels = name.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    });
+    valid_labels && !name.rsplit('.').next().is_some_and(|tld| tld.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn classify_host_value(value: &str) -> HostValue {
+    let value = value.trim();
+    if let Ok(ip) = value.parse::<IpAddr>() {
+        HostValue::Ip(ip)
+    } else if is_hostname(value) {
+        HostValue::Hostname(value.trim_end_matches('.').to_lowercase())
     } else {
-        false
+        HostValue::Invalid
     }
 }
 
-/// Sort IP addresses numerically
+/// Address class: "public", "private", "loopback", "link_local", "cgnat",
+/// "multicast", "broadcast", "documentation", "benchmarking", "unspecified"
+/// or "reserved". IPv4-mapped IPv6 addresses are classified as IPv4; IPv6
+/// unique local (fc00::/7) counts as private.
+pub(crate) fn classify_ip_addr(ip: IpAddr) -> &'static str {
+    match ip {
+        IpAddr::V4(addr) => {
+            let [a, b, c, _] = addr.octets();
+            if addr.is_unspecified() || a == 0 {
+                "unspecified"
+            } else if addr.is_loopback() {
+                "loopback"
+            } else if addr.is_private() {
+                "private"
+            } else if addr.is_link_local() {
+                "link_local"
+            } else if a == 100 && b & 0xc0 == 64 {
+                "cgnat"
+            } else if addr.is_multicast() {
+                "multicast"
+            } else if addr.is_broadcast() {
+                "broadcast"
+            } else if matches!((a, b, c), (192, 0, 2) | (198, 51, 100) | (203, 0, 113)) {
+                "documentation"
+            } else if a == 198 && b & 0xfe == 18 {
+                "benchmarking"
+            } else if a >= 240 || (a, b, c) == (192, 0, 0) {
+                "reserved"
+            } else {
+                "public"
+            }
+        }
+        IpAddr::V6(addr) => {
+            if let Some(v4) = addr.to_ipv4_mapped() {
+                return classify_ip_addr(IpAddr::V4(v4));
+            }
+            let [first, second, ..] = addr.segments();
+            if addr.is_unspecified() {
+                "unspecified"
+            } else if addr.is_loopback() {
+                "loopback"
+            } else if first & 0xfe00 == 0xfc00 {
+                "private"
+            } else if first & 0xffc0 == 0xfe80 {
+                "link_local"
+            } else if addr.is_multicast() {
+                "multicast"
+            } else if (first, second) == (0x2001, 0x0db8) {
+                "documentation"
+            } else if first & 0xe000 == 0x2000 {
+                "public"
+            } else {
+                "reserved"
+            }
+        }
+    }
+}
+
+fn ip_is_private(ip: IpAddr) -> bool {
+    matches!(classify_ip_addr(ip), "private" | "loopback" | "link_local")
+}
+
+/// Classify an address (see the classes above); raises ValueError for
+/// anything that isn't an IPv4/IPv6 address
+#[pyfunction]
+fn classify_ip(ip: &str) -> PyResult<String> {
+    let addr: IpAddr = ip.trim().parse().map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IP '{}': {}", ip, e))
+    })?;
+    Ok(classify_ip_addr(addr).to_string())
+}
+
+/// Split a mixed list into {"ips": [...], "hostnames": [...], "invalid": [...]}
+///
+/// Values keep their original spelling and order within each list.
 #[pyfunction]
-fn sort_ips(ips: Vec<String>) -> Vec<String> {
-    let mut parsed: Vec<(Ipv4Addr, String)> = ips
+fn partition_ips_hostnames(values: Vec<String>) -> HashMap<String, Vec<String>> {
+    let mut parts: HashMap<String, Vec<String>> = ["ips", "hostnames", "invalid"]
+        .iter()
+        .map(|k| (k.to_string(), Vec::new()))
+        .collect();
+    for value in values {
+        let key = match classify_host_value(&value) {
+            HostValue::Ip(_) => "ips",
+            HostValue::Hostname(_) => "hostnames",
+            HostValue::Invalid => "invalid",
+        };
+        parts.get_mut(key).expect("all keys present").push(value);
+    }
+    parts
+}
+
+/// Check if IP is private (RFC 1918, loopback, link-local; IPv6 ULA too)
+///
+/// Hostnames are False unless `resolve=True`, in which case the name is
+/// looked up through the shared DnsCache and its address checked;
+/// "localhost" is always private.
+#[pyfunction]
+#[pyo3(signature = (ip, resolve=false, timeout_ms=2000))]
+fn is_private_ip(py: Python, ip: &str, resolve: bool, timeout_ms: u64) -> bool {
+    match classify_host_value(ip) {
+        HostValue::Ip(addr) => ip_is_private(addr),
+        HostValue::Hostname(name) if name == "localhost" => true,
+        HostValue::Hostname(name) if resolve => py
+            .allow_threads(|| {
+                scanner::runtime().block_on(resolve::shared_cache().resolve_host(&name, timeout_ms))
+            })
+            .is_some_and(ip_is_private),
+        _ => false,
+    }
+}
+
+/// `is_private_ip` (without resolution) over a list, in parallel
+#[pyfunction]
+fn are_private_ips(py: Python, ips: Vec<String>) -> Vec<bool> {
+    py.allow_threads(|| {
+        ips.par_iter()
+            .map(|ip| match classify_host_value(ip) {
+                HostValue::Ip(addr) => ip_is_private(addr),
+                HostValue::Hostname(name) => name == "localhost",
+                HostValue::Invalid => false,
+            })
+            .collect()
+    })
+}
+
+/// Split device dicts by the scope of their `ip`: (private, public, invalid)
+///
+/// Private means `is_private_ip` (private, loopback, link-local); every
+/// other address class, including CGNAT, multicast and reserved space,
+/// counts as public so that exporting the private list never leaks an
+/// outside address. Rows whose `ip` is missing, malformed or a hostname
+/// (other than "localhost") can't be scoped without resolving and go to
+/// invalid. The dicts themselves are returned, untouched and in order.
+#[pyfunction]
+fn partition_devices_by_scope(
+    py: Python,
+    devices: Vec<&PyDict>,
+) -> PyResult<(Vec<PyObject>, Vec<PyObject>, Vec<PyObject>)> {
+    let ips: Vec<Option<String>> = devices
+        .iter()
+        .map(|device| match device.get_item("ip")? {
+            Some(ip) if !ip.is_none() => Ok(ip.extract::<String>().ok()),
+            _ => Ok(None),
+        })
+        .collect::<PyResult<_>>()?;
+    let scopes: Vec<Option<bool>> = py.allow_threads(|| {
+        ips.par_iter()
+            .map(|ip| match classify_host_value(ip.as_deref()?) {
+                HostValue::Ip(addr) => Some(ip_is_private(addr)),
+                HostValue::Hostname(name) if name == "localhost" => Some(true),
+                _ => None,
+            })
+            .collect()
+    });
+
+    let (mut private, mut public, mut invalid) = (Vec::new(), Vec::new(), Vec::new());
+    for (device, scope) in devices.into_iter().zip(scopes) {
+        match scope {
+            Some(true) => private.push(device.into_py(py)),
+            Some(false) => public.push(device.into_py(py)),
+            None => invalid.push(device.into_py(py)),
+        }
+    }
+    Ok((private, public, invalid))
+}
+
+/// Convert an IPv4 address to its IPv4-mapped IPv6 form (::ffff:a.b.c.d)
+#[pyfunction]
+fn ipv4_to_ipv6_mapped(ip: &str) -> PyResult<String> {
+    let addr: Ipv4Addr = ip.trim().parse().map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IPv4 address: {}", e))
+    })?;
+    Ok(addr.to_ipv6_mapped().to_string())
+}
+
+/// Extract the IPv4 address from an IPv4-mapped IPv6 address
+#[pyfunction]
+fn ipv6_mapped_to_ipv4(ip: &str) -> PyResult<String> {
+    let addr: Ipv6Addr = ip.trim().parse().map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IPv6 address: {}", e))
+    })?;
+    addr.to_ipv4_mapped()
+        .map(|v4| v4.to_string())
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Not an IPv4-mapped address: {}", ip)
+        ))
+}
+
+/// Check if an address is IPv4-mapped IPv6 (::ffff:0:0/96)
+#[pyfunction]
+fn is_ipv4_mapped(ip: &str) -> bool {
+    ip.trim()
+        .parse::<Ipv6Addr>()
+        .map(|addr| addr.to_ipv4_mapped().is_some())
+        .unwrap_or(false)
+}
+
+/// Reverse-lookup (PTR) name of an address: "192.168.1.5" gives
+/// "5.1.168.192.in-addr.arpa", IPv6 addresses their nibbles under ip6.arpa
+#[pyfunction]
+fn ip_to_ptr_name(ip: &str) -> PyResult<String> {
+    let addr: IpAddr = ip.trim().parse().map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IP '{}': {}", ip, e))
+    })?;
+    Ok(dns::reverse_name(addr))
+}
+
+/// Address of an in-addr.arpa or ip6.arpa name (the inverse of
+/// `ip_to_ptr_name`); partial names such as zone apexes raise ValueError
+#[pyfunction]
+fn ptr_name_to_ip(ptr: &str) -> PyResult<String> {
+    dns::parse_reverse_name(ptr)
+        .map(|addr| addr.to_string())
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+/// `ip_to_ptr_name` over a list, in parallel; invalid addresses give ""
+#[pyfunction]
+fn batch_ip_to_ptr_names(py: Python, ips: Vec<String>) -> Vec<String> {
+    py.allow_threads(|| {
+        ips.par_iter()
+            .map(|ip| ip.trim().parse::<IpAddr>().map(dns::reverse_name).unwrap_or_default())
+            .collect()
+    })
+}
+
+/// Sort IP addresses numerically (IPv4 before IPv6)
+///
+/// With `hostnames_last` (the default) hostnames follow the addresses in
+/// case-insensitive alphabetical order, and values that are neither come
+/// last in their original order. `hostnames_last=False` keeps only addresses.
+#[pyfunction]
+#[pyo3(signature = (ips, hostnames_last=true))]
+fn sort_ips(ips: Vec<String>, hostnames_last: bool) -> Vec<String> {
+    let mut parsed: Vec<((u8, u128, String), String)> = ips
         .into_iter()
-        .filter_map(|s| s.parse::<Ipv4Addr>().ok().map(|ip| (ip, s)))
+        .filter_map(|s| {
+            let key = match classify_host_value(&s) {
+                HostValue::Ip(IpAddr::V4(ip)) => (0, u32::from(ip) as u128, String::new()),
+                HostValue::Ip(IpAddr::V6(ip)) => (1, u128::from(ip), String::new()),
+                HostValue::Hostname(name) if hostnames_last => (2, 0, name),
+                HostValue::Invalid if hostnames_last => (3, 0, String::new()),
+                _ => return None,
+            };
+            Some((key, s))
+        })
         .collect();
     
-    parsed.par_sort_by_key(|(ip, _)| u32::from(*ip));
+    parsed.par_sort_by(|(a, _), (b, _)| a.cmp(b));
     parsed.into_iter().map(|(_, s)| s).collect()
 }
 
+/// Subnet containment hierarchy: {cidr: [direct child cidrs]}
+///
+/// Every CIDR is a key (leaves map to []); roots are the keys that appear in
+/// no child list. Host bits are masked off ("10.1.2.3/8" -> "10.0.0.0/8")
+/// and duplicates merged. IPv4 and IPv6 form separate trees.
+#[pyfunction]
+fn ip_subnet_tree(cidrs: Vec<String>) -> PyResult<HashMap<String, Vec<String>>> {
+    let mut networks: Vec<IpNetwork> = cidrs
+        .iter()
+        .map(|cidr| {
+            let net: IpNetwork = cidr.trim().parse().map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid CIDR '{}': {}", cidr, e))
+            })?;
+            IpNetwork::new(net.network(), net.prefix()).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid CIDR '{}': {}", cidr, e))
+            })
+        })
+        .collect::<PyResult<_>>()?;
+    
+    // Widest first, so every possible parent is placed before its children
+    networks.sort_by_key(|net| (net.is_ipv6(), net.prefix(), net.network()));
+    networks.dedup();
+    
+    let mut tree: HashMap<String, Vec<String>> = HashMap::with_capacity(networks.len());
+    for (i, net) in networks.iter().enumerate() {
+        tree.entry(net.to_string()).or_default();
+        // Nearest enclosing network: the longest earlier prefix that contains it
+        let parent = networks[..i]
+            .iter()
+            .rev()
+            .find(|candidate| candidate.prefix() < net.prefix() && candidate.contains(net.network()));
+        if let Some(parent) = parent {
+            tree.entry(parent.to_string()).or_default().push(net.to_string());
+        }
+    }
+    for children in tree.values_mut() {
+        children.sort_by_key(|cidr| cidr.parse::<IpNetwork>().map(|n| n.network()).ok());
+    }
+    Ok(tree)
+}
+
+/// CIDRs from `cidrs` that contain `ip`, longest prefix first
+///
+/// CIDRs are returned as given; equal prefixes keep their input order.
+#[pyfunction]
+fn rank_cidrs_by_specificity(ip: &str, cidrs: Vec<String>) -> PyResult<Vec<(String, u8)>> {
+    let addr: IpAddr = ip.trim().parse().map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IP '{}': {}", ip, e))
+    })?;
+    let mut matches = Vec::new();
+    for cidr in &cidrs {
+        let net: IpNetwork = cidr.trim().parse().map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid CIDR '{}': {}", cidr, e))
+        })?;
+        if net.contains(addr) {
+            matches.push((cidr.trim().to_string(), net.prefix()));
+        }
+    }
+    matches.sort_by_key(|(_, prefix)| std::cmp::Reverse(*prefix));
+    Ok(matches)
+}
+
+/// The CIDR with the longest prefix containing `ip` (first listed on a
+/// tie), or None when none do
+#[pyfunction]
+fn find_most_specific_cidr(ip: &str, cidrs: Vec<String>) -> PyResult<Option<String>> {
+    Ok(rank_cidrs_by_specificity(ip, cidrs)?.into_iter().next().map(|(cidr, _)| cidr))
+}
+
 // =============================================================================
 // Text Parsing (for ARP tables, nmap output, etc.)
 // =============================================================================
+//
+// Captures arrive with CRLF line endings, carriage returns left mid-line by
+// progress output, non-breaking spaces from copy-pasted terminals and the
+// odd binary banner in a log. Parsers take their input through `clean_text`,
+// and files through `decode_lossy`, so none of these fail a whole file.
 
-/// Parse ARP table output (arp -a format)
-#[pyfunction]
-fn parse_arp_output(output: &str) -> Vec<(String, String, String)> {
+/// Text of a file, with invalid UTF-8 replaced by U+FFFD instead of failing;
+/// returns the text and the number of bytes replaced
+pub fn decode_lossy(bytes: &[u8]) -> (std::borrow::Cow<'_, str>, usize) {
+    let replaced = bytes.utf8_chunks().map(|chunk| chunk.invalid().len()).sum();
+    (String::from_utf8_lossy(bytes), replaced)
+}
+
+/// Warn (RuntimeWarning) that `path` held bytes `decode_lossy` replaced
+pub fn warn_replaced_bytes(py: Python, path: &str, replaced: usize) -> PyResult<()> {
+    if replaced == 0 {
+        return Ok(());
+    }
+    let message = format!("{}: replaced {} invalid UTF-8 byte(s) while parsing", path, replaced);
+    PyErr::warn(py, py.get_type::<pyo3::exceptions::PyRuntimeWarning>(), &message, 1)
+}
+
+/// Text as the parsers expect it: CRLF and lone CR become "\n", a leading
+/// BOM is dropped and non-breaking spaces become plain spaces
+pub fn clean_text(text: &str) -> std::borrow::Cow<'_, str> {
+    let text = text.trim_start_matches('\u{feff}');
+    if !text.contains(['\r', '\u{a0}', '\u{202f}']) {
+        return std::borrow::Cow::Borrowed(text);
+    }
+    std::borrow::Cow::Owned(text.replace("\r\n", "\n").replace('\r', "\n").replace(['\u{a0}', '\u{202f}'], " "))
+}
+
+/// Parse ARP table output (arp -a format) into (ip, mac, hostname) entries
+pub fn parse_arp_output(output: &str) -> Vec<(String, String, String)> {
     // Pattern: hostname (IP) at MAC on interface
     let re = Regex::new(r"(?m)^(\S+)\s+\((\d+\.\d+\.\d+\.\d+)\)\s+at\s+([0-9a-fA-F:]+)").unwrap();
     
-    output.lines()
+    clean_text(output).lines()
         .par_bridge()
         .filter_map(|line| {
             re.captures(line).map(|caps| {
@@ -229,64 +1294,293 @@ fn parse_arp_output(output: &str) -> Vec<(String, String, String)> {
         .collect()
 }
 
-/// Parse pipe-delimited file (common scan output format)
+/// Parse ARP table output (arp -a format)
+///
+/// Returns a ScanResult (source "arp_table") per entry; hosts `arp` could
+/// not name ("?") get an empty hostname. `legacy=True` returns the old
+/// (ip, mac, hostname) tuples.
 #[pyfunction]
-fn parse_pipe_file(filepath: &str) -> PyResult<Vec<HashMap<String, String>>> {
-    let file = File::open(filepath).map_err(|e| {
+#[pyo3(name = "parse_arp_output", signature = (output, legacy=false))]
+fn parse_arp_output_py(py: Python, output: &str, legacy: bool) -> PyResult<PyObject> {
+    let entries = parse_arp_output(output);
+    if legacy {
+        schema::warn_legacy(py, "parse_arp_output")?;
+        return Ok(entries.into_py(py));
+    }
+    let results: Vec<scanner::ScanResult> = entries
+        .into_iter()
+        .map(|(ip, mac, hostname)| scanner::ScanResult {
+            ip,
+            mac,
+            hostname: if hostname == "?" { String::new() } else { hostname },
+            status: "up".to_string(),
+            discovery_method: "arp".to_string(),
+            sources: vec!["arp_table".to_string()],
+            ..Default::default()
+        })
+        .collect();
+    Ok(results.into_py(py))
+}
+
+/// Records of a pipe-delimited file, keyed by its header row, and the
+/// number of invalid UTF-8 bytes replaced
+fn read_pipe_records(filepath: &str) -> PyResult<(Vec<HashMap<String, String>>, usize)> {
+    let bytes = std::fs::read(filepath).map_err(|e| {
         PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot open file: {}", e))
     })?;
-    
-    let reader = BufReader::new(file);
-    let lines: Vec<String> = reader.lines().filter_map(Result::ok).collect();
+    let (text, replaced) = decode_lossy(&bytes);
+    let text = clean_text(&text);
+    let lines: Vec<&str> = text.lines().collect();
     
     if lines.is_empty() {
-        return Ok(vec![]);
+        return Ok((vec![], replaced));
     }
     
     // First line is header
-    let headers: Vec<&str> = lines[0].split('|').collect();
+    let headers: Vec<&str> = lines[0].split('|').map(str::trim).collect();
     
-    Ok(lines[1..]
+    let records = lines[1..]
         .par_iter()
         .map(|line| {
             let values: Vec<&str> = line.split('|').collect();
             headers.iter()
                 .zip(values.iter())
-                .map(|(h, v)| (h.to_string(), v.to_string()))
+                .map(|(h, v)| (h.to_string(), v.trim().to_string()))
                 .collect()
         })
-        .collect())
+        .collect();
+    Ok((records, replaced))
+}
+
+/// Parse pipe-delimited file (common scan output format)
+///
+/// Returns a ScanResult (source "pipe_file") per row; header names are
+/// mapped as `to_canonical` maps dict keys, so files written by
+/// `write_pipe_file` read back field for field. `legacy=True` returns the
+/// rows as plain {header: value} dicts. Invalid UTF-8 is replaced with
+/// U+FFFD, with a RuntimeWarning giving the number of bytes replaced.
+#[pyfunction]
+#[pyo3(signature = (filepath, legacy=false))]
+fn parse_pipe_file(py: Python, filepath: &str, legacy: bool) -> PyResult<PyObject> {
+    let (records, replaced) = read_pipe_records(filepath)?;
+    warn_replaced_bytes(py, filepath, replaced)?;
+    schema::emit_records(py, "parse_pipe_file", records, "pipe_file", legacy)
+}
+
+/// Resolve the column list: explicit fields, else the first record's keys (sorted)
+fn resolve_fields(records: &[HashMap<String, String>], fields: Vec<String>) -> Vec<String> {
+    if !fields.is_empty() {
+        return fields;
+    }
+    let mut keys: Vec<String> = records
+        .first()
+        .map(|r| r.keys().cloned().collect())
+        .unwrap_or_default();
+    keys.sort();
+    keys
+}
+
+/// Write header + rows; returns number of data rows written
+pub fn write_delimited<W: Write>(
+    out: &mut W,
+    records: &[HashMap<String, String>],
+    delimiter: &str,
+    fields: &[String],
+) -> std::io::Result<usize> {
+    // The reader has no quoting, so keep values from breaking the row layout
+    let clean = |value: &str| -> String {
+        value.replace(delimiter, " ").replace(['\r', '\n'], " ")
+    };
+
+    writeln!(out, "{}", fields.iter().map(|f| clean(f)).collect::<Vec<_>>().join(delimiter))?;
+    for record in records {
+        let row: Vec<String> = fields
+            .iter()
+            .map(|f| record.get(f).map(|v| clean(v)).unwrap_or_default())
+            .collect();
+        writeln!(out, "{}", row.join(delimiter))?;
+    }
+    Ok(records.len())
+}
+
+fn check_delimiter(delimiter: &str) -> PyResult<()> {
+    if delimiter.is_empty() || delimiter.contains(['\r', '\n']) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid delimiter: {:?}", delimiter)
+        ));
+    }
+    Ok(())
+}
+
+/// Write records to a delimited file with a header row
+#[pyfunction]
+#[pyo3(signature = (records, filepath, delimiter, fields=Vec::new()))]
+fn write_delimited_file(
+    records: Vec<HashMap<String, String>>,
+    filepath: &str,
+    delimiter: &str,
+    fields: Vec<String>,
+) -> PyResult<usize> {
+    check_delimiter(delimiter)?;
+    let fields = resolve_fields(&records, fields);
+    let file = File::create(filepath).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot create file: {}", e))
+    })?;
+
+    let mut writer = BufWriter::new(file);
+    write_delimited(&mut writer, &records, delimiter, &fields)
+        .and_then(|rows| writer.flush().map(|_| rows))
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot write file: {}", e)))
+}
+
+/// Write records to a pipe-delimited file (complement to parse_pipe_file)
+#[pyfunction]
+#[pyo3(signature = (records, filepath, fields=Vec::new()))]
+fn write_pipe_file(records: Vec<HashMap<String, String>>, filepath: &str, fields: Vec<String>) -> PyResult<usize> {
+    write_delimited_file(records, filepath, "|", fields)
+}
+
+/// Render records as delimited text with a header row
+#[pyfunction]
+#[pyo3(signature = (records, delimiter, fields=Vec::new()))]
+fn write_delimited_string(
+    records: Vec<HashMap<String, String>>,
+    delimiter: &str,
+    fields: Vec<String>,
+) -> PyResult<String> {
+    check_delimiter(delimiter)?;
+    let fields = resolve_fields(&records, fields);
+    let mut buffer: Vec<u8> = Vec::new();
+    write_delimited(&mut buffer, &records, delimiter, &fields)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))?;
+    Ok(String::from_utf8(buffer).unwrap_or_default())
+}
+
+/// Render records as pipe-delimited text
+#[pyfunction]
+#[pyo3(signature = (records, fields=Vec::new()))]
+fn write_pipe_string(records: Vec<HashMap<String, String>>, fields: Vec<String>) -> PyResult<String> {
+    write_delimited_string(records, "|", fields)
+}
+
+/// Render scan results as pipe-delimited text using the standard
+/// ScanResult columns (same layout as `netscan-cli --format pipe`)
+#[pyfunction]
+fn write_results_pipe_string(results: Vec<scanner::ScanResult>) -> PyResult<String> {
+    let records: Vec<HashMap<String, String>> = results.iter().map(|r| r.to_record()).collect();
+    let fields: Vec<String> = scanner::RECORD_FIELDS.iter().map(|f| f.to_string()).collect();
+    write_delimited_string(records, "|", fields)
 }
 
 // =============================================================================
 // Device Deduplication
 // =============================================================================
 
+/// Flatten a ScanResult to the string dict layout `dedupe_devices` and the
+/// pipe writers use: RECORD_FIELDS, with ports as "80,443" and numbers as
+/// text, plus each `attributes` entry under its own key
+#[pyfunction]
+fn scan_result_to_device_record(result: scanner::ScanResult) -> HashMap<String, String> {
+    let mut record = result.to_record();
+    for (key, value) in result.attributes {
+        record.entry(key).or_insert(value);
+    }
+    record
+}
+
+/// Inverse of `scan_result_to_device_record`; keys outside RECORD_FIELDS
+/// go to `attributes`
+///
+/// Missing fields take their defaults, but a response time, timestamp or
+/// port that is present and not a number raises ValueError.
+#[pyfunction]
+fn device_record_to_scan_result(record: HashMap<String, String>) -> PyResult<scanner::ScanResult> {
+    let invalid = |key: &str, value: &str| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid {} in device record: '{}'", key, value))
+    };
+    for key in ["response_time_ms", "scan_timestamp"] {
+        if let Some(value) = record.get(key).map(|v| v.trim()).filter(|v| !v.is_empty()) {
+            value.parse::<f64>().map_err(|_| invalid(key, value))?;
+        }
+    }
+    if let Some(ports) = record.get("open_ports") {
+        for port in ports.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            port.parse::<u16>().map_err(|_| invalid("open_ports", port))?;
+        }
+    }
+
+    let mut result = scanner::ScanResult::from_record(&record);
+    result.attributes = record
+        .into_iter()
+        .filter(|(key, _)| !scanner::RECORD_FIELDS.contains(&key.as_str()))
+        .collect();
+    Ok(result)
+}
+
 /// Deduplicate devices by IP, keeping the one with most info
+///
+/// Hostnames in the `ip` field are keyed by lowercase name, or, with
+/// `resolve_hostnames=True`, by the address they resolve to through `cache`
+/// (default: the shared DnsCache) so they merge with rows for that address.
+/// Rows without a usable `ip` are kept as they are, never merged.
 #[pyfunction]
-fn dedupe_devices(devices: Vec<HashMap<String, String>>) -> Vec<HashMap<String, String>> {
+#[pyo3(signature = (devices, resolve_hostnames=false, cache=None, timeout_ms=2000))]
+fn dedupe_devices(
+    py: Python,
+    devices: Vec<HashMap<String, String>>,
+    resolve_hostnames: bool,
+    cache: Option<resolve::DnsCache>,
+    timeout_ms: u64,
+) -> Vec<HashMap<String, String>> {
+    let values: Vec<HostValue> = devices
+        .iter()
+        .map(|device| device.get("ip").map(|ip| classify_host_value(ip)).unwrap_or(HostValue::Invalid))
+        .collect();
+
+    let resolved: HashMap<String, Option<IpAddr>> = if resolve_hostnames {
+        let mut names: Vec<String> = values
+            .iter()
+            .filter_map(|v| match v {
+                HostValue::Hostname(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        let cache = cache.unwrap_or_else(|| resolve::shared_cache().clone());
+        py.allow_threads(|| scanner::runtime().block_on(resolve::resolve_hosts(&cache, names, timeout_ms, 64)))
+    } else {
+        HashMap::new()
+    };
+
     let deduped: DashMap<String, HashMap<String, String>> = DashMap::new();
     
-    devices.into_par_iter().for_each(|device| {
-        if let Some(ip) = device.get("ip") {
-            deduped.entry(ip.clone())
-                .and_modify(|existing| {
-                    // Keep entry with more non-empty fields
-                    let existing_score: usize = existing.values().filter(|v| !v.is_empty()).count();
-                    let new_score: usize = device.values().filter(|v| !v.is_empty()).count();
-                    if new_score > existing_score {
-                        *existing = device.clone();
-                    } else {
-                        // Merge non-empty fields
-                        for (k, v) in &device {
-                            if !v.is_empty() && existing.get(k).map(|e| e.is_empty()).unwrap_or(true) {
-                                existing.insert(k.clone(), v.clone());
-                            }
+    devices.into_par_iter().zip(values).enumerate().for_each(|(index, (device, value))| {
+        let key = match value {
+            HostValue::Ip(ip) => ip.to_string(),
+            HostValue::Hostname(name) => match resolved.get(&name) {
+                Some(Some(ip)) => ip.to_string(),
+                _ => format!("host:{}", name),
+            },
+            HostValue::Invalid => format!("row:{}", index),
+        };
+        deduped.entry(key)
+            .and_modify(|existing| {
+                // Keep entry with more non-empty fields
+                let existing_score: usize = existing.values().filter(|v| !v.is_empty()).count();
+                let new_score: usize = device.values().filter(|v| !v.is_empty()).count();
+                if new_score > existing_score {
+                    *existing = device.clone();
+                } else {
+                    // Merge non-empty fields
+                    for (k, v) in &device {
+                        if !v.is_empty() && existing.get(k).map(|e| e.is_empty()).unwrap_or(true) {
+                            existing.insert(k.clone(), v.clone());
                         }
                     }
-                })
-                .or_insert(device);
-        }
+                }
+            })
+            .or_insert(device);
     });
     
     deduped.into_iter().map(|(_, v)| v).collect()
@@ -300,30 +1594,394 @@ fn dedupe_devices(devices: Vec<HashMap<String, String>>) -> Vec<HashMap<String,
 fn netscan_core(_py: Python, m: &PyModule) -> PyResult<()> {
     // MAC functions
     m.add_function(wrap_pyfunction!(normalize_mac, m)?)?;
+    m.add_function(wrap_pyfunction!(normalize_mac_strict, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_mac, m)?)?;
     m.add_function(wrap_pyfunction!(normalize_macs, m)?)?;
+    m.add_function(wrap_pyfunction!(normalize_macs_with_stats, m)?)?;
     m.add_function(wrap_pyfunction!(extract_oui, m)?)?;
     
     // OUI database functions
     m.add_function(wrap_pyfunction!(parse_oui_file, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_oui_file_with_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_oui_file_for, m)?)?;
+    m.add("ParseQualityError", m.py().get_type::<ParseQualityError>())?;
     m.add_function(wrap_pyfunction!(lookup_oui, m)?)?;
     m.add_function(wrap_pyfunction!(lookup_ouis, m)?)?;
+    m.add_function(wrap_pyfunction!(merge_oui_databases, m)?)?;
+    m.add_function(wrap_pyfunction!(merge_oui_databases_from_map, m)?)?;
+    m.add_function(wrap_pyfunction!(oui_prefix_to_u32, m)?)?;
+    m.add_function(wrap_pyfunction!(u32_to_oui_prefix, m)?)?;
+    m.add_function(wrap_pyfunction!(build_oui_index, m)?)?;
+    m.add_function(wrap_pyfunction!(lookup_oui_fast, m)?)?;
     
     // IP functions
     m.add_function(wrap_pyfunction!(expand_cidr, m)?)?;
     m.add_function(wrap_pyfunction!(expand_cidr_hosts, m)?)?;
+    m.add_function(wrap_pyfunction!(expand_cidr_sample, m)?)?;
+    m.add_function(wrap_pyfunction!(cidr_random_host, m)?)?;
+    m.add_function(wrap_pyfunction!(cidr_random_hosts, m)?)?;
     m.add_function(wrap_pyfunction!(expand_ip_range, m)?)?;
+    m.add_function(wrap_pyfunction!(expand_ip_range_with_limit, m)?)?;
+    m.add_function(wrap_pyfunction!(iter_ip_range, m)?)?;
     m.add_function(wrap_pyfunction!(is_private_ip, m)?)?;
+    m.add_function(wrap_pyfunction!(are_private_ips, m)?)?;
+    m.add_function(wrap_pyfunction!(partition_devices_by_scope, m)?)?;
+    m.add_function(wrap_pyfunction!(classify_ip, m)?)?;
     m.add_function(wrap_pyfunction!(sort_ips, m)?)?;
+    m.add_function(wrap_pyfunction!(partition_ips_hostnames, m)?)?;
+    m.add_function(wrap_pyfunction!(ip_subnet_tree, m)?)?;
+    m.add_function(wrap_pyfunction!(find_most_specific_cidr, m)?)?;
+    m.add_function(wrap_pyfunction!(rank_cidrs_by_specificity, m)?)?;
+    m.add_function(wrap_pyfunction!(ipv4_to_ipv6_mapped, m)?)?;
+    m.add_function(wrap_pyfunction!(ipv6_mapped_to_ipv4, m)?)?;
+    m.add_function(wrap_pyfunction!(is_ipv4_mapped, m)?)?;
+    m.add_function(wrap_pyfunction!(ip_to_ptr_name, m)?)?;
+    m.add_function(wrap_pyfunction!(ptr_name_to_ip, m)?)?;
+    m.add_function(wrap_pyfunction!(batch_ip_to_ptr_names, m)?)?;
+    m.add_function(wrap_pyfunction!(ipv6::canonicalize_ipv6, m)?)?;
+    m.add_function(wrap_pyfunction!(ipv6::ipv6_subnet_info, m)?)?;
+    m.add_function(wrap_pyfunction!(ipv6::split_ipv6, m)?)?;
+    m.add_function(wrap_pyfunction!(ipv6::is_eui64_address, m)?)?;
+    m.add_function(wrap_pyfunction!(ipv6::is_privacy_address, m)?)?;
+    m.add_function(wrap_pyfunction!(ipv6::correlate_dual_stack, m)?)?;
+    
+    // Target specification functions
+    m.add_function(wrap_pyfunction!(targets::expand_wildcard, m)?)?;
+    m.add_function(wrap_pyfunction!(targets::parse_targets, m)?)?;
+    m.add_function(wrap_pyfunction!(targets::count_targets, m)?)?;
+    m.add_function(wrap_pyfunction!(targets::interleave_targets_py, m)?)?;
+    
+    // Scope functions
+    m.add_function(wrap_pyfunction!(scope::filter_scan_results_by_cidr_list, m)?)?;
+    m.add_function(wrap_pyfunction!(scope::assert_results_in_scope, m)?)?;
+    m.add_function(wrap_pyfunction!(scope::set_authorized_scopes, m)?)?;
+    m.add_function(wrap_pyfunction!(scope::get_authorized_scopes, m)?)?;
+    m.add("UnauthorizedTargetError", m.py().get_type::<scope::UnauthorizedTargetError>())?;
+    m.add_function(wrap_pyfunction!(scope::generate_exclude_list_from_scan, m)?)?;
+    m.add_function(wrap_pyfunction!(scope::generate_exclude_cidr_list, m)?)?;
+    m.add_function(wrap_pyfunction!(scope::filter_ips_by_exclusion_list, m)?)?;
+    
+    // Result query functions
+    m.add_function(wrap_pyfunction!(query::filter_results, m)?)?;
+    m.add_function(wrap_pyfunction!(query::sort_results, m)?)?;
+    m.add_function(wrap_pyfunction!(query::paginate, m)?)?;
+    m.add_function(wrap_pyfunction!(query::query_results, m)?)?;
+    m.add_function(wrap_pyfunction!(query::sort_scan_results_by_ip, m)?)?;
+    m.add_function(wrap_pyfunction!(query::find_scan_result_by_ip, m)?)?;
+    m.add_function(wrap_pyfunction!(query::find_scan_result_by_mac, m)?)?;
+    m.add_function(wrap_pyfunction!(query::get_ports_by_state, m)?)?;
+    m.add_function(wrap_pyfunction!(query::filter_by_port_state, m)?)?;
+    m.add_function(wrap_pyfunction!(reconcile::scan_result_merge_by_mac_and_ip, m)?)?;
+    m.add_function(wrap_pyfunction!(reconcile::scan_result_intersect, m)?)?;
+    m.add_function(wrap_pyfunction!(reconcile::scan_result_union, m)?)?;
+    m.add_function(wrap_pyfunction!(compliance::check_compliance, m)?)?;
+    m.add_function(wrap_pyfunction!(honeypot::detect_port_scan_from_results, m)?)?;
+    m.add_function(wrap_pyfunction!(honeypot::compute_port_scan_suspicion_score_py, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::scan_metrics_prometheus, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::scan_metrics_json, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::results_to_prometheus, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::compute_network_entropy, m)?)?;
     
     // Parsing functions
-    m.add_function(wrap_pyfunction!(parse_arp_output, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_arp_output_py, m)?)?;
+    m.add_function(wrap_pyfunction!(parsers::parse_cisco_mac_table, m)?)?;
+    m.add_function(wrap_pyfunction!(parsers::parse_cisco_ip_arp, m)?)?;
+    m.add_function(wrap_pyfunction!(parsers::parse_powershell_json, m)?)?;
+    m.add_function(wrap_pyfunction!(parsers::parse_iw_station_dump, m)?)?;
+    m.add_function(wrap_pyfunction!(parsers::parse_hostapd_all_sta, m)?)?;
     m.add_function(wrap_pyfunction!(parse_pipe_file, m)?)?;
+    m.add_function(wrap_pyfunction!(schema::to_canonical, m)?)?;
+    m.add_function(wrap_pyfunction!(write_pipe_file, m)?)?;
+    m.add_function(wrap_pyfunction!(write_delimited_file, m)?)?;
+    m.add_function(wrap_pyfunction!(write_pipe_string, m)?)?;
+    m.add_function(wrap_pyfunction!(write_delimited_string, m)?)?;
+    m.add_function(wrap_pyfunction!(write_results_pipe_string, m)?)?;
+    m.add_function(wrap_pyfunction!(jsonl::write_scan_results_jsonl, m)?)?;
+    m.add_function(wrap_pyfunction!(jsonl::read_scan_results_jsonl, m)?)?;
+    m.add_function(wrap_pyfunction!(jsonl::stream_scan_results_jsonl, m)?)?;
     m.add_function(wrap_pyfunction!(dedupe_devices, m)?)?;
+    m.add_function(wrap_pyfunction!(inventory::apply_scan_to_inventory, m)?)?;
+    m.add_function(wrap_pyfunction!(inventory::flapping_ports, m)?)?;
+    m.add_function(wrap_pyfunction!(scan_result_to_device_record, m)?)?;
+    m.add_function(wrap_pyfunction!(device_record_to_scan_result, m)?)?;
+    
+    // Enrichment functions
+    m.add_function(wrap_pyfunction!(enrich::enrich_hostname_priority, m)?)?;
+    m.add_function(wrap_pyfunction!(enrich::enrich_hostnames_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(enrich::parse_dhcp_lease_file, m)?)?;
+    m.add_function(wrap_pyfunction!(enrich::enrich_scan_results_from_dhcp, m)?)?;
+    m.add_function(wrap_pyfunction!(dns_services::dns_service_lookup, m)?)?;
+    m.add_function(wrap_pyfunction!(dns_services::mark_dns_roles, m)?)?;
+    m.add_function(wrap_pyfunction!(dns_services::resolve_names, m)?)?;
+    #[cfg(feature = "rdap")]
+    m.add_function(wrap_pyfunction!(rdap::rdap_lookup, m)?)?;
+    m.add_function(wrap_pyfunction!(fingerprint::fingerprint_device, m)?)?;
+    m.add_function(wrap_pyfunction!(fingerprint::fingerprint_devices, m)?)?;
+    m.add_function(wrap_pyfunction!(resolve::reverse_dns_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(resolve::async_batch_dns_resolve, m)?)?;
+    m.add_function(wrap_pyfunction!(hostnames::analyze_hostnames, m)?)?;
+    m.add_function(wrap_pyfunction!(hostnames::expand_hostname_pattern, m)?)?;
+    
+    // Import functions
+    m.add_function(wrap_pyfunction!(importers::parse_nessus_xml, m)?)?;
+    m.add_function(wrap_pyfunction!(importers::parse_nmap_xml, m)?)?;
+    m.add_function(wrap_pyfunction!(importers::results_to_nmap_xml, m)?)?;
+    m.add_function(wrap_pyfunction!(slack::scan_summary_to_slack_message, m)?)?;
+    m.add_function(wrap_pyfunction!(slack::scan_diff_to_slack_message, m)?)?;
+    m.add_function(wrap_pyfunction!(importers::parse_scan_output_auto, m)?)?;
+    m.add_function(wrap_pyfunction!(service_probes::parse_nmap_service_probe_file, m)?)?;
+    m.add_function(wrap_pyfunction!(service_probes::parse_nmap_service_probe_ports, m)?)?;
+    m.add_function(wrap_pyfunction!(service_probes::apply_nmap_probes, m)?)?;
+    m.add_function(wrap_pyfunction!(banners::normalize_banner, m)?)?;
+    m.add_function(wrap_pyfunction!(banners::dedupe_banners, m)?)?;
+    m.add_function(wrap_pyfunction!(banners::match_fingerprints, m)?)?;
+    m.add_function(wrap_pyfunction!(banners::detect_services_from_banners, m)?)?;
+    m.add_function(wrap_pyfunction!(reconcile::load_cmdb_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(reconcile::reconcile, m)?)?;
+    
+    // Routing functions
+    m.add_function(wrap_pyfunction!(routes::get_routes, m)?)?;
+    m.add_function(wrap_pyfunction!(routes::route_for, m)?)?;
+    m.add_function(wrap_pyfunction!(routes::find_default_gateway, m)?)?;
+    m.add_function(wrap_pyfunction!(routes::find_all_gateways, m)?)?;
+    m.add_function(wrap_pyfunction!(routes::identify_gateways, m)?)?;
+    m.add_function(wrap_pyfunction!(routes::count_devices, m)?)?;
+    m.add_function(wrap_pyfunction!(routes::plan_discovery, m)?)?;
+    m.add_function(wrap_pyfunction!(routes::require_local_targets, m)?)?;
     
     // Scanner functions
     m.add_function(wrap_pyfunction!(scanner::tcp_scan_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(icmp::icmp_discovery, m)?)?;
+    m.add_function(wrap_pyfunction!(ndp::scan_ipv6_multicast, m)?)?;
+    m.add_function(wrap_pyfunction!(ndp::scan_ipv6_from_nics, m)?)?;
+    m.add_function(wrap_pyfunction!(liveness::host_is_up, m)?)?;
+    m.add_function(wrap_pyfunction!(liveness::wait_for_host_up, m)?)?;
+    m.add_function(wrap_pyfunction!(syn::tcp_half_open_detection, m)?)?;
+    m.add_function(wrap_pyfunction!(syn::tcp_half_open_scan, m)?)?;
     m.add_function(wrap_pyfunction!(scanner::ping_sweep_fast, m)?)?;
     m.add_function(wrap_pyfunction!(scanner::get_common_ports, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::parse_ports, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::port_range_complement, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::port_range_intersection, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::port_range_union, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::port_range_difference, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::check_host, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::check_port, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::tcp_connect_via_socks5, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::estimate_scan_duration_py, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::estimate_scan_duration_str, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::preview_scan_targets, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::scan_all_interfaces, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::scan_cidr_with_exclusions, m)?)?;
+    m.add_function(wrap_pyfunction!(scanner::scan_ip_range_with_exclusions, m)?)?;
+    m.add_function(wrap_pyfunction!(schedule::run_windowed_scan, m)?)?;
+    m.add_function(wrap_pyfunction!(schedule::in_scan_window, m)?)?;
+    m.add_function(wrap_pyfunction!(schedule::cron_next_runs, m)?)?;
+    m.add_function(wrap_pyfunction!(schedule::scan_schedule_cron, m)?)?;
+    m.add_function(wrap_pyfunction!(schedule::scan_schedule_once_at, m)?)?;
+    
+    // Capability functions
+    m.add_function(wrap_pyfunction!(capabilities::capability_report, m)?)?;
+    m.add_function(wrap_pyfunction!(capabilities::require, m)?)?;
+    m.add_function(wrap_pyfunction!(capabilities::self_test, m)?)?;
+    
+    // Scanner classes
+    m.add_class::<scanner::ScanResultDataclass>()?;
+    m.add_class::<scanner::ScanConfig>()?;
+    m.add_class::<scanner::Scanner>()?;
+    m.add_class::<cache::ScanCache>()?;
+    m.add_class::<monitor::ScanRateMonitor>()?;
+    m.add_class::<monitor::ScanProgress>()?;
+    m.add_class::<resolve::DnsCache>()?;
+    m.add_class::<oui::OuiDatabase>()?;
+    m.add_class::<inventory::Inventory>()?;
+    m.add_class::<secret::Secret>()?;
+    m.add_class::<schedule::WindowedScanState>()?;
+    m.add_class::<schedule::ScheduleCancel>()?;
+    m.add_class::<jsonl::ScanResultJsonlIterator>()?;
+    m.add_class::<IpRangeIterator>()?;
+    
+    // Service probe functions
+    m.add_function(wrap_pyfunction!(probes::scan_host_services, m)?)?;
+    m.add_function(wrap_pyfunction!(probes::check_open_redirect, m)?)?;
+    m.add_function(wrap_pyfunction!(probes::check_open_redirect_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(probes::batch_http_title_grab, m)?)?;
+    m.add_function(wrap_pyfunction!(probes::tcp_probe_with_send, m)?)?;
+    m.add_function(wrap_pyfunction!(probes::tcp_probe_with_send_str, m)?)?;
+    m.add_function(wrap_pyfunction!(probes::tcp_probe_with_send_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(secret::set_redaction_patterns, m)?)?;
+    m.add_function(wrap_pyfunction!(udp::udp_service_sweep, m)?)?;
+    m.add_function(wrap_pyfunction!(multicast::multicast_discovery, m)?)?;
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_cidrs_rank_most_specific_first() {
+        let cidrs = |list: &[&str]| list.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        let nested = cidrs(&["10.0.0.0/8", "10.1.0.0/16", "10.1.1.0/24", "10.2.0.0/16"]);
+        assert_eq!(find_most_specific_cidr("10.1.1.5", nested.clone()).unwrap().as_deref(), Some("10.1.1.0/24"));
+        assert_eq!(
+            rank_cidrs_by_specificity("10.1.1.5", nested.clone()).unwrap(),
+            [("10.1.1.0/24".to_string(), 24), ("10.1.0.0/16".to_string(), 16), ("10.0.0.0/8".to_string(), 8)]
+        );
+        assert_eq!(find_most_specific_cidr("10.1.2.5", nested.clone()).unwrap().as_deref(), Some("10.1.0.0/16"));
+        assert_eq!(find_most_specific_cidr("192.168.0.1", nested).unwrap(), None);
+        // Equal prefixes keep their input order
+        let tied = cidrs(&["10.1.1.9/24", "10.1.1.0/24"]);
+        assert_eq!(find_most_specific_cidr("10.1.1.5", tied).unwrap().as_deref(), Some("10.1.1.9/24"));
+        assert!(rank_cidrs_by_specificity("10.1.1.5", cidrs(&["10.1.1.0/33"])).is_err());
+    }
+
+    #[test]
+    fn short_and_long_hex_runs_never_leave_half_groups() {
+        pyo3::prepare_freethreaded_python();
+        let cases = [
+            ("aabbccddee", "AABBCCDDEE"),
+            ("aabbccddeef", "AA:BB:CC:DD:EE:0F"),
+            ("aabbccddeeff", "AA:BB:CC:DD:EE:FF"),
+            ("aabbccddeeff1", "AA:BB:CC:DD:EE:FF"),
+        ];
+        for (input, expected) in cases {
+            let normalized = normalize_mac(input);
+            assert_eq!(normalized, expected, "{}", input);
+            assert!(!normalized.ends_with(':'));
+            Python::with_gil(|py| match normalize_mac_strict(input) {
+                Ok(strict) => assert_eq!((input.len(), strict.as_str()), (12, expected)),
+                Err(e) => {
+                    assert_ne!(input.len(), 12);
+                    assert!(e.is_instance_of::<pyo3::exceptions::PyValueError>(py));
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn dotted_macs_normalize_and_validate() {
+        assert_eq!(normalize_mac("aabb.ccdd.eeff"), "AA:BB:CC:DD:EE:FF");
+        assert_eq!(normalize_mac("1.2.3"), "00:01:00:02:00:03");
+        for valid in ["aabb.ccdd.eeff", "AA:BB:CC:DD:EE:FF", "aa-bb-cc-dd-ee-ff", "aabbccddeeff"] {
+            assert!(validate_mac(valid), "{}", valid);
+        }
+        for invalid in ["aabb.ccdd", "aabb.ccdd.eefg", "aab.bccdd.eeff0", "AA:BB:CC:DD:EE", "AA:BB-CC:DD:EE:FF", ""] {
+            assert!(!validate_mac(invalid), "{}", invalid);
+        }
+    }
+
+    /// Run `f` with every Python warning recorded; its result and the
+    /// messages of the warnings it raised
+    pub(crate) fn recorded_warnings<T>(py: Python, f: impl FnOnce() -> T) -> (T, Vec<String>) {
+        let warnings = py.import("warnings").unwrap();
+        let kwargs = pyo3::types::PyDict::new(py);
+        kwargs.set_item("record", true).unwrap();
+        let catcher = warnings.call_method("catch_warnings", (), Some(kwargs)).unwrap();
+        let log: &pyo3::types::PyList = catcher.call_method0("__enter__").unwrap().downcast().unwrap();
+        warnings.call_method1("simplefilter", ("always",)).unwrap();
+        let result = f();
+        catcher.call_method1("__exit__", (py.None(), py.None(), py.None())).unwrap();
+        let messages = log
+            .iter()
+            .map(|warning| warning.getattr("message").unwrap().str().unwrap().to_string())
+            .collect();
+        (result, messages)
+    }
+
+    /// Path of a file under tests/fixtures
+    pub(crate) fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[test]
+    fn arp_output_survives_crlf_stray_cr_and_nbsp() {
+        let bytes = std::fs::read(fixture("arp_crlf_nbsp.txt")).unwrap();
+        let entries = parse_arp_output(std::str::from_utf8(&bytes).unwrap());
+        let ips: Vec<&str> = entries.iter().map(|(ip, _, _)| ip.as_str()).collect();
+        assert_eq!(ips, ["192.168.1.1", "192.168.1.20", "192.168.1.30"]);
+        assert_eq!(entries[2].1, "AA:BB:CC:DD:EE:1E");
+        assert_eq!(entries[2].2, "printer");
+    }
+
+    #[test]
+    fn pipe_file_replaces_invalid_utf8_and_counts_it() {
+        let (records, replaced) = read_pipe_records(&fixture("pipe_invalid_utf8.txt")).unwrap();
+        assert_eq!(replaced, 2);
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1]["hostname"], "cam\u{fffd}\u{fffd}");
+        assert_eq!(records[2]["ip"], "10.0.0.3");
+        assert_eq!(records[2]["hostname"], "nas");
+    }
+
+    #[test]
+    fn oui_file_stats_count_replaced_bytes() {
+        let parse = read_oui_file(&fixture("oui_mixed.txt")).unwrap();
+        assert_eq!(parse.replaced_bytes, 1);
+        assert_eq!(parse.entries.len(), 3);
+        assert_eq!(parse.entries["00:00:0C"], "Cisco Systems, Inc");
+        assert_eq!(parse.entries["00:00:5E"], "ICANN, IANA Department");
+        assert_eq!(parse.entries["00:00:1B"], "Novell\u{fffd} Inc");
+        assert_eq!(parse.nonempty_lines, 5);
+    }
+
+    #[test]
+    fn nmap_mac_prefixes_parse_like_oui_txt() {
+        let nmap = "# comment\n00000C Cisco Systems\n0000 bogus\n00005E ICANN, IANA Department\n";
+        let parse = parse_oui_content_with_stats(nmap);
+        assert_eq!(parse.entries.get("00:00:0C").map(String::as_str), Some("Cisco Systems"));
+        assert_eq!(parse.entries.len(), 2);
+        assert_eq!(parse.unmatched_samples, vec!["0000 bogus".to_string()]);
+
+        let oui = "00-00-0C   (hex)\t\tCisco Systems, Inc\n00000C     (base 16)\t\tCisco Systems, Inc\n";
+        let entries = parse_oui_content(oui);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries["00:00:0C"], "Cisco Systems, Inc");
+    }
+
+    #[test]
+    fn random_hosts_are_uniform() {
+        // 6 hosts in a /29, 60,000 draws: each should get close to 10,000
+        let picks = cidr_random_hosts("10.0.0.0/29", 60_000, Some(5), true).unwrap();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for ip in picks {
+            *counts.entry(ip).or_default() += 1;
+        }
+        assert_eq!(counts.len(), 6);
+        let chi_square: f64 = counts.values().map(|&c| (c as f64 - 10_000.0).powi(2) / 10_000.0).sum();
+        // 5 degrees of freedom; 20.5 is the 0.1% critical value
+        assert!(chi_square < 20.5, "chi-square {} for {:?}", chi_square, counts);
+    }
+
+    #[test]
+    fn random_host_does_not_step_with_the_seed() {
+        let last_octet = |seed| -> i32 {
+            cidr_random_host("10.0.0.0/24", Some(seed)).unwrap().rsplit('.').next().unwrap().parse().unwrap()
+        };
+        let picks: Vec<i32> = (0..20).map(last_octet).collect();
+        let steps: HashSet<i32> = picks.windows(2).map(|w| (w[1] - w[0]).rem_euclid(254)).collect();
+        assert!(steps.len() > 10, "picks follow the seed: {:?}", picks);
+        assert_eq!(picks, (0..20).map(last_octet).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn cidr_sample_is_not_a_stride() {
+        let sample = expand_cidr_sample("10.0.0.0/16", 64, 42).unwrap();
+        let offsets: Vec<u32> = sample.iter().map(|ip| u32::from(ip.parse::<Ipv4Addr>().unwrap())).collect();
+        let gaps: HashSet<u32> = offsets.windows(2).map(|w| w[1] - w[0]).collect();
+        assert_eq!(offsets.len(), 64);
+        assert!(gaps.len() > 32, "sample is a near-constant stride: {:?}", gaps);
+        assert_eq!(sample, expand_cidr_sample("10.0.0.0/16", 64, 42).unwrap());
+        assert_ne!(sample, expand_cidr_sample("10.0.0.0/16", 64, 43).unwrap());
+    }
+
+    #[test]
+    fn random_hosts_without_replacement_are_distinct() {
+        let picks = cidr_random_hosts("10.0.0.0/29", 6, Some(1), false).unwrap();
+        let mut sorted = picks.clone();
+        sorted.sort_by_key(|ip| ip.parse::<Ipv4Addr>().unwrap());
+        assert_eq!(sorted, expand_cidr_hosts("10.0.0.0/29").unwrap());
+        assert!(cidr_random_hosts("10.0.0.0/30", 3, None, false).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use pyo3::prelude::*;
+
+use crate::icmp::{icmp_sweep, IcmpProbe};
+use crate::scanner::{runtime, tcp_probe_state, PortState};
+use crate::scope::authorize_targets;
+
+// =============================================================================
+// Single-host Liveness
+// =============================================================================
+
+/// Ports tried by the "tcp" method
+pub const LIVENESS_PORTS: &[u16] = &[80, 443, 22, 445];
+
+/// Probe the liveness ports concurrently; any answer (SYN-ACK or RST) means up
+async fn tcp_alive(ip: IpAddr, timeout_ms: u64) -> bool {
+    let mut probes = tokio::task::JoinSet::new();
+    for &port in LIVENESS_PORTS {
+        let addr = ip.to_string();
+        probes.spawn(async move { tcp_probe_state(&addr, port, timeout_ms).await.0 });
+    }
+    while let Some(state) = probes.join_next().await {
+        if matches!(state, Ok(PortState::Open | PortState::Closed)) {
+            probes.abort_all();
+            return true;
+        }
+    }
+    false
+}
+
+/// Why a method couldn't be used
+enum ProbeError {
+    /// Not applicable to this target (IPv6, not on a local subnet)
+    Unsupported(String),
+    /// Applicable, but the process lacks the privileges
+    Permission(String),
+}
+
+fn icmp_alive(ip: IpAddr, timeout_ms: u64) -> Result<bool, ProbeError> {
+    let IpAddr::V4(addr) = ip else {
+        return Err(ProbeError::Unsupported("ICMP liveness supports IPv4 only".to_string()));
+    };
+    icmp_sweep(&[addr], &[IcmpProbe::Echo], timeout_ms)
+        .map(|replies| !replies.is_empty())
+        .map_err(ProbeError::Permission)
+}
+
+fn arp_alive(ip: IpAddr, timeout_ms: u64) -> Result<bool, ProbeError> {
+    let IpAddr::V4(addr) = ip else {
+        return Err(ProbeError::Unsupported("ARP applies to IPv4 only".to_string()));
+    };
+    if crate::arp::local_interface_for(addr).is_none() {
+        return Err(ProbeError::Unsupported(format!("{} is not on a directly attached subnet", addr)));
+    }
+    crate::arp::arp_ping(addr, timeout_ms)
+        .map(|reply| reply.is_some())
+        .map_err(ProbeError::Permission)
+}
+
+/// One liveness check; Err means the method can't be used for this host
+fn probe(ip: IpAddr, method: &str, timeout_ms: u64) -> Result<bool, ProbeError> {
+    match method {
+        "tcp" => Ok(runtime().block_on(tcp_alive(ip, timeout_ms))),
+        "icmp" => icmp_alive(ip, timeout_ms),
+        "arp" => arp_alive(ip, timeout_ms),
+        // Methods that can't run here (no privileges, not local) are skipped
+        "any" => Ok(runtime().block_on(tcp_alive(ip, timeout_ms))
+            || icmp_alive(ip, timeout_ms).unwrap_or(false)
+            || arp_alive(ip, timeout_ms).unwrap_or(false)),
+        _ => unreachable!("method validated by caller"),
+    }
+}
+
+fn validate(ip: &str, method: &str) -> PyResult<(IpAddr, String)> {
+    let addr = ip.trim().parse::<IpAddr>().map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IP: {}", e))
+    })?;
+    let method = method.trim().to_lowercase();
+    if !["tcp", "icmp", "arp", "any"].contains(&method.as_str()) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown liveness method '{}' (expected tcp, icmp, arp or any)", method
+        )));
+    }
+    Ok((addr, method))
+}
+
+fn probe_error(e: ProbeError) -> PyErr {
+    match e {
+        ProbeError::Unsupported(msg) => PyErr::new::<pyo3::exceptions::PyValueError, _>(msg),
+        ProbeError::Permission(msg) => PyErr::new::<pyo3::exceptions::PyPermissionError, _>(msg),
+    }
+}
+
+/// Is a single host reachable?
+///
+/// method: "tcp" (ports 80, 443, 22, 445; a refused connection also counts),
+/// "icmp" (echo), "arp" (local subnet only) or "any" (each in that order,
+/// skipping methods unavailable for this host or process). `force=True`
+/// probes a host outside `set_authorized_scopes`.
+#[pyfunction]
+#[pyo3(signature = (ip, method="tcp", timeout_ms=1000, force=false))]
+pub fn host_is_up(py: Python, ip: &str, method: &str, timeout_ms: u64, force: bool) -> PyResult<bool> {
+    let (addr, method) = validate(ip, method)?;
+    authorize_targets(py, "host_is_up", &[addr.to_string()], force)?;
+    py.allow_threads(|| probe(addr, &method, timeout_ms)).map_err(probe_error)
+}
+
+/// Poll `host_is_up` until the host answers or `timeout_total_ms` elapses
+#[pyfunction]
+#[pyo3(signature = (ip, method="tcp", timeout_total_ms=60000, poll_interval_ms=1000, force=false))]
+pub fn wait_for_host_up(
+    py: Python,
+    ip: &str,
+    method: &str,
+    timeout_total_ms: u64,
+    poll_interval_ms: u64,
+    force: bool,
+) -> PyResult<bool> {
+    let (addr, method) = validate(ip, method)?;
+    authorize_targets(py, "wait_for_host_up", &[addr.to_string()], force)?;
+    let deadline = Instant::now() + Duration::from_millis(timeout_total_ms);
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(false);
+        }
+        let attempt_ms = (remaining.as_millis() as u64).clamp(1, poll_interval_ms.max(200));
+        let started = Instant::now();
+        if py.allow_threads(|| probe(addr, &method, attempt_ms)).map_err(probe_error)? {
+            return Ok(true);
+        }
+        // Let Ctrl-C interrupt long waits
+        py.check_signals()?;
+
+        let pause = Duration::from_millis(poll_interval_ms)
+            .saturating_sub(started.elapsed())
+            .min(deadline.saturating_duration_since(Instant::now()));
+        py.allow_threads(|| std::thread::sleep(pause));
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use pyo3::prelude::*;
+
+use crate::scanner::ScanResult;
+
+// =============================================================================
+// Scan Metrics Export
+// =============================================================================
+
+/// Aggregates shared by the Prometheus and JSON exporters
+struct ScanMetrics {
+    hosts_by_status: BTreeMap<String, usize>,
+    open_ports_total: usize,
+    vendors: BTreeMap<String, usize>,
+    scan_duration_seconds: f64,
+    last_scan_time: f64,
+}
+
+impl ScanMetrics {
+    /// Duration defaults to the spread of the results' scan timestamps
+    fn collect(results: &[ScanResult], duration_seconds: Option<f64>) -> Self {
+        let mut hosts_by_status = BTreeMap::new();
+        let mut vendors = BTreeMap::new();
+        for r in results {
+            let status = if r.status.is_empty() { "unknown" } else { r.status.as_str() };
+            *hosts_by_status.entry(status.to_string()).or_insert(0) += 1;
+            let vendor = if r.vendor.trim().is_empty() { "Unknown" } else { r.vendor.trim() };
+            *vendors.entry(vendor.to_string()).or_insert(0) += 1;
+        }
+
+        let timestamps: Vec<f64> = results.iter().map(|r| r.scan_timestamp).filter(|t| *t > 0.0).collect();
+        let first = timestamps.iter().cloned().fold(f64::INFINITY, f64::min);
+        let last = timestamps.iter().cloned().fold(0.0, f64::max);
+
+        ScanMetrics {
+            hosts_by_status,
+            open_ports_total: results.iter().map(|r| r.open_ports.len()).sum(),
+            vendors,
+            scan_duration_seconds: duration_seconds
+                .unwrap_or(if timestamps.is_empty() { 0.0 } else { last - first }),
+            last_scan_time: last,
+        }
+    }
+}
+
+/// Escape a label value per the text exposition format
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn valid_metric_prefix(prefix: &str) -> bool {
+    let mut chars = prefix.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+}
+
+fn metric_header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+}
+
+/// Render scan results as Prometheus text exposition format
+#[pyfunction]
+#[pyo3(signature = (results, prefix="netscan", duration_seconds=None))]
+pub fn scan_metrics_prometheus(
+    results: Vec<ScanResult>,
+    prefix: &str,
+    duration_seconds: Option<f64>,
+) -> PyResult<String> {
+    if !valid_metric_prefix(prefix) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid metric prefix: {:?}", prefix)
+        ));
+    }
+    let metrics = ScanMetrics::collect(&results, duration_seconds);
+    let mut out = String::new();
+
+    let name = format!("{}_hosts_discovered", prefix);
+    metric_header(&mut out, &name, "Hosts in the scan results by status");
+    for (status, count) in &metrics.hosts_by_status {
+        let _ = writeln!(out, "{}{{status=\"{}\"}} {}", name, escape_label(status), count);
+    }
+
+    let name = format!("{}_open_ports_total", prefix);
+    metric_header(&mut out, &name, "Open ports across all hosts");
+    let _ = writeln!(out, "{} {}", name, metrics.open_ports_total);
+
+    let name = format!("{}_vendor", prefix);
+    metric_header(&mut out, &name, "Hosts per hardware vendor");
+    for (vendor, count) in &metrics.vendors {
+        let _ = writeln!(out, "{}{{name=\"{}\"}} {}", name, escape_label(vendor), count);
+    }
+
+    let name = format!("{}_scan_duration_seconds", prefix);
+    metric_header(&mut out, &name, "Duration of the scan in seconds");
+    let _ = writeln!(out, "{} {}", name, metrics.scan_duration_seconds);
+
+    let name = format!("{}_last_scan_time", prefix);
+    metric_header(&mut out, &name, "Unix time of the most recent host scan");
+    let _ = writeln!(out, "{} {}", name, metrics.last_scan_time);
+
+    Ok(out)
+}
+
+/// Default cap on the series `results_to_prometheus` will emit
+const DEFAULT_MAX_SERIES: usize = 10_000;
+
+/// Render per-host series plus scan-level gauges in Prometheus text format
+///
+/// Each host gets `<prefix>_host_up{ip,hostname}` (1 or 0), one
+/// `<prefix>_open_port{ip,port}` per open port and, when measured,
+/// `<prefix>_response_time_ms{ip}`. The scan as a whole adds `hosts_up`,
+/// `scan_duration_seconds` and `probes_sent`. Raises ValueError rather than
+/// render more than `max_series` series, so scraping a /16 by accident
+/// doesn't flood the TSDB.
+#[pyfunction]
+#[pyo3(signature = (results, prefix="netscan", duration_seconds=None, max_series=DEFAULT_MAX_SERIES))]
+pub fn results_to_prometheus(
+    results: Vec<ScanResult>,
+    prefix: &str,
+    duration_seconds: Option<f64>,
+    max_series: usize,
+) -> PyResult<String> {
+    if !valid_metric_prefix(prefix) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            format!("Invalid metric prefix: {:?}", prefix)
+        ));
+    }
+    let timed = results.iter().filter(|r| r.response_time_ms > 0.0).count();
+    let open_ports: usize = results.iter().map(|r| r.open_ports.len()).sum();
+    let series = results.len() + open_ports + timed + 3;
+    if series > max_series {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Results would produce {} series, more than max_series={}", series, max_series
+        )));
+    }
+    let metrics = ScanMetrics::collect(&results, duration_seconds);
+    let mut out = String::new();
+
+    let name = format!("{}_host_up", prefix);
+    metric_header(&mut out, &name, "Whether the host answered the scan");
+    for r in &results {
+        let up = if r.status == "up" { 1 } else { 0 };
+        let _ = writeln!(out, "{}{{ip=\"{}\",hostname=\"{}\"}} {}", name, escape_label(&r.ip), escape_label(&r.hostname), up);
+    }
+
+    let name = format!("{}_open_port", prefix);
+    metric_header(&mut out, &name, "Open TCP ports per host");
+    for r in &results {
+        for port in &r.open_ports {
+            let _ = writeln!(out, "{}{{ip=\"{}\",port=\"{}\"}} 1", name, escape_label(&r.ip), port);
+        }
+    }
+
+    let name = format!("{}_response_time_ms", prefix);
+    metric_header(&mut out, &name, "Host response time in milliseconds");
+    for r in results.iter().filter(|r| r.response_time_ms > 0.0) {
+        let _ = writeln!(out, "{}{{ip=\"{}\"}} {}", name, escape_label(&r.ip), r.response_time_ms);
+    }
+
+    let name = format!("{}_hosts_up", prefix);
+    metric_header(&mut out, &name, "Hosts that answered the scan");
+    let _ = writeln!(out, "{} {}", name, metrics.hosts_by_status.get("up").copied().unwrap_or(0));
+
+    let name = format!("{}_scan_duration_seconds", prefix);
+    metric_header(&mut out, &name, "Duration of the scan in seconds");
+    let _ = writeln!(out, "{} {}", name, metrics.scan_duration_seconds);
+
+    let name = format!("{}_probes_sent", prefix);
+    metric_header(&mut out, &name, "Probes sent across all hosts");
+    let _ = writeln!(out, "{} {}", name, results.iter().map(|r| r.probes_sent).sum::<u64>());
+
+    Ok(out)
+}
+
+/// Same aggregates as `scan_metrics_prometheus`, as a JSON object
+#[pyfunction]
+#[pyo3(signature = (results, duration_seconds=None))]
+pub fn scan_metrics_json(results: Vec<ScanResult>, duration_seconds: Option<f64>) -> String {
+    let metrics = ScanMetrics::collect(&results, duration_seconds);
+    serde_json::json!({
+        "hosts_total": results.len(),
+        "hosts_by_status": metrics.hosts_by_status,
+        "open_ports_total": metrics.open_ports_total,
+        "vendors": metrics.vendors,
+        "scan_duration_seconds": metrics.scan_duration_seconds,
+        "last_scan_time": metrics.last_scan_time,
+    })
+    .to_string()
+}
+
+// =============================================================================
+// Distribution Entropy
+// =============================================================================
+
+/// Shannon entropy in bits, H = -sum(p * log2(p)), of a set of counts
+fn shannon_entropy<'a>(counts: impl IntoIterator<Item = &'a usize>) -> f64 {
+    let counts: Vec<f64> = counts.into_iter().filter(|c| **c > 0).map(|c| *c as f64).collect();
+    let total: f64 = counts.iter().sum();
+    if total == 0.0 {
+        return 0.0;
+    }
+    let entropy: f64 = counts.iter().map(|c| c / total).map(|p| -p * p.log2()).sum();
+    // A single category sums to -0.0
+    entropy.abs()
+}
+
+/// Subnet an address is counted under: its /24 (IPv4) or /64 (IPv6)
+fn entropy_subnet(ip: &str) -> String {
+    match ip.trim().parse::<std::net::IpAddr>() {
+        Ok(std::net::IpAddr::V4(v4)) => {
+            let [a, b, c, _] = v4.octets();
+            format!("{}.{}.{}.0/24", a, b, c)
+        }
+        Ok(std::net::IpAddr::V6(v6)) => {
+            let s = v6.segments();
+            format!("{:x}:{:x}:{:x}:{:x}::/64", s[0], s[1], s[2], s[3])
+        }
+        Err(_) => ip.trim().to_string(),
+    }
+}
+
+/// Shannon entropy (bits) of how the results are spread out
+///
+/// - "ip_distribution_entropy": hosts across /24 subnets (/64 for IPv6)
+/// - "port_entropy": open ports across all hosts; high when many different
+///   ports are open, as on enterprise networks
+/// - "vendor_entropy": hosts across vendors, unknown vendors counted as one;
+///   near 0 means one vendor dominates, or OUI data is missing
+///
+/// Each is 0.0 for an empty distribution or a single category, and
+/// log2(n) for n equally common ones.
+#[pyfunction]
+pub fn compute_network_entropy(results: Vec<ScanResult>) -> HashMap<String, f64> {
+    let mut subnets: HashMap<String, usize> = HashMap::new();
+    let mut ports: HashMap<u16, usize> = HashMap::new();
+    for r in &results {
+        *subnets.entry(entropy_subnet(&r.ip)).or_insert(0) += 1;
+        for port in &r.open_ports {
+            *ports.entry(*port).or_insert(0) += 1;
+        }
+    }
+    let vendors = ScanMetrics::collect(&results, Some(0.0)).vendors;
+
+    HashMap::from([
+        ("ip_distribution_entropy".to_string(), shannon_entropy(subnets.values())),
+        ("port_entropy".to_string(), shannon_entropy(ports.values())),
+        ("vendor_entropy".to_string(), shannon_entropy(vendors.values())),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn entropy_of_uniform_and_skewed_distributions() {
+        assert!(close(shannon_entropy(&[5, 5, 5, 5]), 2.0));
+        assert!(close(shannon_entropy(&[7]), 0.0));
+        assert!(close(shannon_entropy(&[]), 0.0));
+        // 3:1 is well under the 1 bit of an even split
+        let skewed = -(0.75f64 * 0.75f64.log2() + 0.25 * 0.25f64.log2());
+        assert!(close(shannon_entropy(&[3, 1, 0]), skewed));
+
+        let host = |ip: &str, vendor: &str, ports: &[u16]| ScanResult {
+            ip: ip.to_string(),
+            vendor: vendor.to_string(),
+            open_ports: ports.to_vec(),
+            ..Default::default()
+        };
+        let results = vec![
+            host("10.0.1.1", "Cisco", &[22]),
+            host("10.0.2.1", "Cisco", &[80]),
+            host("10.0.3.1", "Cisco", &[443]),
+            host("10.0.4.1", "Dell", &[8080]),
+        ];
+        let entropy = compute_network_entropy(results);
+        assert!(close(entropy["ip_distribution_entropy"], 2.0));
+        assert!(close(entropy["port_entropy"], 2.0));
+        assert!(close(entropy["vendor_entropy"], skewed));
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use parking_lot::Mutex;
+use pyo3::prelude::*;
+
+use crate::scanner::unix_now;
+
+// =============================================================================
+// Scan Rate Monitoring
+// =============================================================================
+
+/// Running counters for a scan: probes sent and cache effectiveness
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct ScanRateMonitor {
+    started: Instant,
+    #[pyo3(get)]
+    pub probes_sent: u64,
+    #[pyo3(get)]
+    pub cache_hits: u64,
+    #[pyo3(get)]
+    pub cache_misses: u64,
+}
+
+impl Default for ScanRateMonitor {
+    fn default() -> Self {
+        ScanRateMonitor {
+            started: Instant::now(),
+            probes_sent: 0,
+            cache_hits: 0,
+            cache_misses: 0,
+        }
+    }
+}
+
+impl ScanRateMonitor {
+    pub fn record_probes(&mut self, count: u64) {
+        self.probes_sent += count;
+    }
+
+    pub fn record_cache(&mut self, hits: u64, misses: u64) {
+        self.cache_hits += hits;
+        self.cache_misses += misses;
+    }
+}
+
+#[pymethods]
+impl ScanRateMonitor {
+    #[new]
+    fn new() -> Self {
+        ScanRateMonitor::default()
+    }
+
+    /// Fraction of cache lookups answered from the cache (0.0 when none made)
+    fn cache_hit_rate(&self) -> f64 {
+        let lookups = self.cache_hits + self.cache_misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.cache_hits as f64 / lookups as f64
+        }
+    }
+
+    /// Probes sent per second since the monitor was created or reset
+    fn probes_per_second(&self) -> f64 {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        if elapsed > 0.0 {
+            self.probes_sent as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = ScanRateMonitor::default();
+    }
+
+    /// All counters and derived rates as a dict
+    fn snapshot(&self, py: Python) -> HashMap<String, PyObject> {
+        let mut map = HashMap::new();
+        map.insert("elapsed_s".to_string(), self.started.elapsed().as_secs_f64().into_py(py));
+        map.insert("probes_sent".to_string(), self.probes_sent.into_py(py));
+        map.insert("probes_per_second".to_string(), self.probes_per_second().into_py(py));
+        map.insert("cache_hits".to_string(), self.cache_hits.into_py(py));
+        map.insert("cache_misses".to_string(), self.cache_misses.into_py(py));
+        map.insert("cache_hit_rate".to_string(), self.cache_hit_rate().into_py(py));
+        map
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ScanRateMonitor(probes_sent={}, cache_hit_rate={:.2})",
+            self.probes_sent,
+            self.cache_hit_rate()
+        )
+    }
+}
+
+// =============================================================================
+// Traffic Accounting
+// =============================================================================
+
+/// Typical IPv4 TCP segment sizes: SYN / SYN-ACK carry ~20 bytes of options
+const SYN_BYTES: u64 = 60;
+const ACK_BYTES: u64 = 52;
+
+/// Packets and bytes attributed to one probe type
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProbeTraffic {
+    pub probes: u64,
+    pub packets_sent: u64,
+    pub packets_received: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+/// Per-probe-type traffic for one scan
+///
+/// Connect scans go through the kernel, so their figures are estimates from
+/// the handshake each outcome implies (`estimated` is set).
+#[derive(Debug, Clone, Default)]
+pub struct TrafficStats {
+    pub by_probe: HashMap<String, ProbeTraffic>,
+    pub estimated: bool,
+}
+
+impl TrafficStats {
+    /// Connect probes: an open port costs SYN, ACK, FIN, ACK out and
+    /// SYN-ACK, ACK, FIN back, or when closed with RST (`reset` of the
+    /// `open` ones) SYN, ACK, RST out and only the SYN-ACK back; anything
+    /// else is counted as a lone SYN (kernel retransmits to filtered ports
+    /// are not seen)
+    pub fn record_connect(&mut self, open: u64, not_open: u64, reset: u64) {
+        let entry = self.by_probe.entry("tcp_connect".to_string()).or_default();
+        let fin = open - reset;
+        entry.probes += open + not_open;
+        entry.packets_sent += reset * 3 + fin * 4 + not_open;
+        entry.packets_received += reset + fin * 3;
+        entry.bytes_sent += reset * (SYN_BYTES + 2 * ACK_BYTES) + fin * (SYN_BYTES + 3 * ACK_BYTES) + not_open * SYN_BYTES;
+        entry.bytes_received += reset * SYN_BYTES + fin * (SYN_BYTES + 2 * ACK_BYTES);
+        self.estimated = true;
+    }
+
+    /// SYN probes: a SYN out per probe, answered by SYN-ACK from an open
+    /// port (which the kernel, knowing no such connection, resets) or RST
+    /// from a closed one
+    pub fn record_syn(&mut self, open: u64, closed: u64, silent: u64) {
+        let entry = self.by_probe.entry("tcp_syn".to_string()).or_default();
+        let probes = open + closed + silent;
+        entry.probes += probes;
+        entry.packets_sent += probes + open;
+        entry.packets_received += open + closed;
+        entry.bytes_sent += probes * SYN_BYTES + open * ACK_BYTES;
+        entry.bytes_received += open * SYN_BYTES + closed * ACK_BYTES;
+        self.estimated = true;
+    }
+
+    pub fn total(&self) -> ProbeTraffic {
+        self.by_probe.values().fold(ProbeTraffic::default(), |acc, t| ProbeTraffic {
+            probes: acc.probes + t.probes,
+            packets_sent: acc.packets_sent + t.packets_sent,
+            packets_received: acc.packets_received + t.packets_received,
+            bytes_sent: acc.bytes_sent + t.bytes_sent,
+            bytes_received: acc.bytes_received + t.bytes_received,
+        })
+    }
+
+    /// {"estimated": bool, "total": {...}, "by_probe": {probe: {...}}}
+    pub fn to_py_dict(&self, py: Python) -> HashMap<String, PyObject> {
+        fn counts(t: &ProbeTraffic) -> HashMap<&'static str, u64> {
+            HashMap::from([
+                ("probes", t.probes),
+                ("packets_sent", t.packets_sent),
+                ("packets_received", t.packets_received),
+                ("bytes_sent", t.bytes_sent),
+                ("bytes_received", t.bytes_received),
+            ])
+        }
+        let by_probe: HashMap<&String, HashMap<&str, u64>> =
+            self.by_probe.iter().map(|(probe, t)| (probe, counts(t))).collect();
+        let mut map = HashMap::new();
+        map.insert("estimated".to_string(), self.estimated.into_py(py));
+        map.insert("total".to_string(), counts(&self.total()).into_py(py));
+        map.insert("by_probe".to_string(), by_probe.into_py(py));
+        map
+    }
+}
+
+// =============================================================================
+// Scan Progress and ETA
+// =============================================================================
+//
+// A host's scan time is mostly decided by whether it answers: a silent host
+// costs a full timeout per port, one that answers costs a round trip. The
+// estimate keeps a per-port time for each kind and the share of silent hosts,
+// all as moving averages, so it follows the scan from a responsive subnet
+// into a firewalled one instead of extrapolating the average so far.
+
+/// Weight of the newest host in the moving averages
+const ETA_SMOOTHING: f64 = 0.1;
+
+fn smooth(average: &mut Option<f64>, sample: f64) {
+    *average = Some(match *average {
+        Some(avg) => avg + ETA_SMOOTHING * (sample - avg),
+        None => sample,
+    });
+}
+
+#[derive(Debug, Default)]
+struct ProgressState {
+    started: Option<Instant>,
+    finished: Option<Instant>,
+    done: usize,
+    total: Option<usize>,
+    concurrency: usize,
+    timeout_s: f64,
+    /// Moving averages: seconds per probed port for hosts that answered and
+    /// for silent ones, share of silent hosts, ports probed per host
+    answered_port_s: Option<f64>,
+    silent_port_s: Option<f64>,
+    silent_share: Option<f64>,
+    ports_per_host: Option<f64>,
+    /// Target group -> (done, total), when the scan groups its targets
+    groups: HashMap<String, (usize, usize)>,
+}
+
+impl ProgressState {
+    fn elapsed_s(&self) -> f64 {
+        match (self.started, self.finished) {
+            (Some(start), Some(end)) => end.duration_since(start).as_secs_f64(),
+            (Some(start), None) => start.elapsed().as_secs_f64(),
+            _ => 0.0,
+        }
+    }
+
+    fn rate_hosts_per_s(&self) -> f64 {
+        let elapsed = self.elapsed_s();
+        if elapsed > 0.0 { self.done as f64 / elapsed } else { 0.0 }
+    }
+
+    /// Seconds until the last host is done; None while the total is unknown
+    fn eta_s(&self) -> Option<f64> {
+        if self.finished.is_some() {
+            return Some(0.0);
+        }
+        let remaining = self.total?.saturating_sub(self.done);
+        if remaining == 0 {
+            return Some(0.0);
+        }
+        // Until hosts of a kind finish, assume the worst: every port times out
+        let silent = self.silent_port_s.unwrap_or(self.timeout_s);
+        let answered = self.answered_port_s.unwrap_or(silent);
+        let share = self.silent_share.unwrap_or(1.0);
+        let per_host = self.ports_per_host.unwrap_or(1.0) * (share * silent + (1.0 - share) * answered);
+        // Hosts run `concurrency` at a time, each scanning its ports in turn
+        let parallel = self.concurrency.clamp(1, remaining) as f64;
+        Some(remaining as f64 * per_host / parallel)
+    }
+}
+
+fn group_progress(state: &ProgressState) -> HashMap<String, (usize, usize, f64)> {
+    state
+        .groups
+        .iter()
+        .map(|(group, &(done, total))| {
+            let percent = if total > 0 { done as f64 * 100.0 / total as f64 } else { 100.0 };
+            (group.clone(), (done, total, percent))
+        })
+        .collect()
+}
+
+/// Live progress of a Scanner's current or last scan
+///
+/// `scanner.progress` can be read while the scan runs, from the progress
+/// callback or from another thread.
+#[pyclass]
+#[derive(Debug, Clone, Default)]
+pub struct ScanProgress {
+    state: Arc<Mutex<ProgressState>>,
+}
+
+impl ScanProgress {
+    /// Reset for a scan of `total` hosts (None if not known up front)
+    pub fn begin(&self, total: Option<usize>, concurrency: usize, ports_per_host: usize, timeout_ms: u64) {
+        *self.state.lock() = ProgressState {
+            started: Some(Instant::now()),
+            total,
+            concurrency,
+            timeout_s: timeout_ms as f64 / 1000.0,
+            ports_per_host: Some(ports_per_host as f64),
+            ..Default::default()
+        };
+    }
+
+    /// A host finished after probing `ports` ports; `answered` if any port
+    /// was open or refused. Returns (done, total, eta_seconds, rate).
+    pub fn record(&self, answered: bool, ports: usize, elapsed: Duration) -> (usize, Option<usize>, Option<f64>, f64) {
+        let mut state = self.state.lock();
+        state.done += 1;
+        if let Some(total) = state.total {
+            // A host the feed produced beyond the known count
+            state.total = Some(total.max(state.done));
+        }
+        smooth(&mut state.ports_per_host, ports as f64);
+        smooth(&mut state.silent_share, if answered { 0.0 } else { 1.0 });
+        if ports > 0 {
+            let per_port = elapsed.as_secs_f64() / ports as f64;
+            smooth(if answered { &mut state.answered_port_s } else { &mut state.silent_port_s }, per_port);
+        }
+        (state.done, state.total, state.eta_s(), state.rate_hosts_per_s())
+    }
+
+    /// Track completion per target group, given each group's size
+    pub fn set_groups(&self, totals: HashMap<String, usize>) {
+        self.state.lock().groups = totals.into_iter().map(|(group, total)| (group, (0, total))).collect();
+    }
+
+    /// A host of `group` finished (call alongside `record`)
+    pub fn record_group(&self, group: &str) {
+        if let Some((done, _)) = self.state.lock().groups.get_mut(group) {
+            *done += 1;
+        }
+    }
+
+    pub fn finish(&self) {
+        let mut state = self.state.lock();
+        state.finished = Some(Instant::now());
+        state.total = Some(state.done);
+    }
+}
+
+#[pymethods]
+impl ScanProgress {
+    #[getter]
+    fn done(&self) -> usize {
+        self.state.lock().done
+    }
+
+    /// Hosts in the scan; None while targets come from an iterator of unknown length
+    #[getter]
+    fn total(&self) -> Option<usize> {
+        self.state.lock().total
+    }
+
+    /// Estimated seconds until the scan completes; None without a total
+    #[getter]
+    fn eta_seconds(&self) -> Option<f64> {
+        self.state.lock().eta_s()
+    }
+
+    /// Estimated Unix time of completion
+    #[getter]
+    fn eta_timestamp(&self) -> Option<f64> {
+        self.eta_seconds().map(|eta| unix_now() + eta)
+    }
+
+    /// Hosts completed per second so far
+    #[getter]
+    fn rate_hosts_per_s(&self) -> f64 {
+        self.state.lock().rate_hosts_per_s()
+    }
+
+    #[getter]
+    fn elapsed_s(&self) -> f64 {
+        self.state.lock().elapsed_s()
+    }
+
+    #[getter]
+    fn running(&self) -> bool {
+        let state = self.state.lock();
+        state.started.is_some() && state.finished.is_none()
+    }
+
+    /// Per target group (done, total, percent complete), for scans with
+    /// `ScanConfig.interleave` or `target_groups` set; empty otherwise
+    #[getter]
+    fn groups(&self) -> HashMap<String, (usize, usize, f64)> {
+        group_progress(&self.state.lock())
+    }
+
+    /// All of the above as a dict
+    fn snapshot(&self, py: Python) -> HashMap<String, PyObject> {
+        let state = self.state.lock();
+        let eta = state.eta_s();
+        let mut map = HashMap::new();
+        map.insert("done".to_string(), state.done.into_py(py));
+        map.insert("total".to_string(), state.total.into_py(py));
+        map.insert("eta_seconds".to_string(), eta.into_py(py));
+        map.insert("eta_timestamp".to_string(), eta.map(|eta| unix_now() + eta).into_py(py));
+        map.insert("rate_hosts_per_s".to_string(), state.rate_hosts_per_s().into_py(py));
+        map.insert("elapsed_s".to_string(), state.elapsed_s().into_py(py));
+        map.insert("running".to_string(), (state.started.is_some() && state.finished.is_none()).into_py(py));
+        map.insert("groups".to_string(), group_progress(&state).into_py(py));
+        map
+    }
+
+    fn __repr__(&self) -> String {
+        let state = self.state.lock();
+        format!(
+            "ScanProgress(done={}, total={}, eta_seconds={})",
+            state.done,
+            state.total.map(|t| t.to_string()).unwrap_or_else(|| "None".to_string()),
+            state.eta_s().map(|e| format!("{:.1}", e)).unwrap_or_else(|| "None".to_string())
+        )
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::time::{Duration, Instant};
+use pnet::datalink;
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use socket2::{Domain, Protocol, Socket, Type};
+
+use crate::dns;
+
+// =============================================================================
+// Multicast Service Discovery (mDNS / SSDP)
+// =============================================================================
+//
+// Multicast queries leave through whichever interface the routing table picks
+// for the group, which on hosts with Docker bridges, VPN tunnels or Wi-Fi plus
+// Ethernet is often the wrong one. Queries are therefore sent once per
+// interface from a socket bound to that interface, so every answer is known
+// to have arrived on it.
+
+struct MulticastProbe {
+    name: &'static str,
+    group: Ipv4Addr,
+    port: u16,
+    ttl: u32,
+    build: fn(u64) -> Vec<u8>,
+    /// (service, detail) pairs announced in one reply
+    parse: fn(&[u8]) -> Vec<(String, String)>,
+}
+
+const MULTICAST_PROBES: &[MulticastProbe] = &[
+    MulticastProbe {
+        name: "mdns",
+        group: Ipv4Addr::new(224, 0, 0, 251),
+        port: 5353,
+        ttl: 255,
+        build: build_mdns_browse,
+        parse: parse_mdns_browse,
+    },
+    MulticastProbe {
+        name: "ssdp",
+        group: Ipv4Addr::new(239, 255, 255, 250),
+        port: 1900,
+        ttl: 2,
+        build: build_ssdp_search,
+        parse: parse_ssdp_reply,
+    },
+];
+
+/// DNS-SD service type enumeration; sent from an ephemeral port, so
+/// responders answer by unicast ("legacy unicast", RFC 6762 6.7)
+fn build_mdns_browse(_timeout_ms: u64) -> Vec<u8> {
+    dns::build_query(0, "_services._dns-sd._udp.local", dns::TYPE_PTR, dns::CLASS_IN, false)
+}
+
+fn parse_mdns_browse(packet: &[u8]) -> Vec<(String, String)> {
+    let Some(response) = dns::parse_response(packet) else {
+        return Vec::new();
+    };
+    response
+        .answers
+        .iter()
+        .filter(|a| a.rtype == dns::TYPE_PTR)
+        .filter_map(|a| dns::read_name(packet, a.rdata_offset))
+        .map(|(name, _)| (name.trim_end_matches('.').to_string(), String::new()))
+        .collect()
+}
+
+fn build_ssdp_search(timeout_ms: u64) -> Vec<u8> {
+    // Responders spread replies over MX seconds; keep that inside the timeout
+    let mx = (timeout_ms / 1000).clamp(1, 5);
+    format!(
+        "M-SEARCH * HTTP/1.1\r\nHOST: 239.255.255.250:1900\r\nMAN: \"ssdp:discover\"\r\nMX: {}\r\nST: ssdp:all\r\n\r\n",
+        mx
+    )
+    .into_bytes()
+}
+
+/// Service is the ST header, detail the device description LOCATION
+fn parse_ssdp_reply(packet: &[u8]) -> Vec<(String, String)> {
+    let text = String::from_utf8_lossy(packet);
+    let mut lines = text.lines();
+    if !lines.next().is_some_and(|status| status.starts_with("HTTP/1.1 200")) {
+        return Vec::new();
+    }
+    let mut headers: HashMap<String, String> = HashMap::new();
+    for line in lines {
+        if let Some((key, value)) = line.split_once(':') {
+            headers.insert(key.trim().to_uppercase(), value.trim().to_string());
+        }
+    }
+    match headers.remove("ST") {
+        Some(st) if !st.is_empty() => vec![(st, headers.remove("LOCATION").unwrap_or_default())],
+        _ => Vec::new(),
+    }
+}
+
+/// Up, multicast-capable, non-loopback interfaces with an IPv4 address, or
+/// exactly the named ones
+fn multicast_interfaces(requested: Option<Vec<String>>) -> Result<Vec<(String, Ipv4Addr)>, String> {
+    let interfaces = datalink::interfaces();
+    let ipv4 = |iface: &datalink::NetworkInterface| {
+        iface.ips.iter().find_map(|net| match net.ip() {
+            IpAddr::V4(addr) => Some(addr),
+            _ => None,
+        })
+    };
+
+    match requested {
+        Some(names) => names
+            .iter()
+            .map(|name| {
+                let iface = interfaces
+                    .iter()
+                    .find(|iface| iface.name == *name)
+                    .ok_or_else(|| format!("Unknown interface: {}", name))?;
+                let addr = ipv4(iface).ok_or_else(|| format!("{} has no IPv4 address", name))?;
+                Ok((name.clone(), addr))
+            })
+            .collect(),
+        None => Ok(interfaces
+            .iter()
+            .filter(|iface| iface.is_up() && iface.is_multicast() && !iface.is_loopback())
+            .filter_map(|iface| Some((iface.name.clone(), ipv4(iface)?)))
+            .collect()),
+    }
+}
+
+fn interface_socket(name: &str, addr: Ipv4Addr, ttl: u32) -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    // Best effort: binding to the device also pins replies to it, but needs
+    // privileges on older kernels; the address bind below is enough otherwise
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    let _ = socket.bind_device(Some(name.as_bytes()));
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    let _ = name;
+    socket.bind(&SocketAddr::from((addr, 0)).into())?;
+    socket.set_multicast_if_v4(&addr)?;
+    socket.set_multicast_ttl_v4(ttl)?;
+    Ok(socket.into())
+}
+
+/// Send one probe on one interface and collect (responder, service, detail)
+fn query_interface(
+    name: &str,
+    addr: Ipv4Addr,
+    probe: &MulticastProbe,
+    timeout_ms: u64,
+) -> Result<Vec<(Ipv4Addr, String, String)>, String> {
+    let socket = interface_socket(name, addr, probe.ttl).map_err(|e| e.to_string())?;
+    socket
+        .send_to(&(probe.build)(timeout_ms), SocketAddrV4::new(probe.group, probe.port))
+        .map_err(|e| format!("{} query failed: {}", probe.name, e))?;
+
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    let mut buf = vec![0u8; 9000];
+    let mut found = Vec::new();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() || socket.set_read_timeout(Some(remaining)).is_err() {
+            break;
+        }
+        let Ok((n, from)) = socket.recv_from(&mut buf) else {
+            break;
+        };
+        let IpAddr::V4(responder) = from.ip() else {
+            continue;
+        };
+        found.extend(
+            (probe.parse)(&buf[..n])
+                .into_iter()
+                .map(|(service, detail)| (responder, service, detail)),
+        );
+    }
+    Ok(found)
+}
+
+struct MulticastAnswer {
+    ip: Ipv4Addr,
+    protocol: &'static str,
+    service: String,
+    detail: String,
+    /// Every interface the answer arrived on, in query order
+    interfaces: Vec<String>,
+}
+
+/// Discover mDNS (DNS-SD) and SSDP services by multicast, per interface
+///
+/// With `interface=None` the queries go out on every up, multicast-capable,
+/// non-loopback interface with an IPv4 address; a name or list of names uses
+/// exactly those interfaces. Returns {"responses": [...], "summary": {...}}.
+/// Each response is {ip, protocol, service, detail, interface, interfaces}:
+/// answers seen on several interfaces appear once per (ip, service), tagged
+/// with the first interface that received them and listing all of them.
+/// The summary holds `interfaces_queried`, `interfaces_answered`,
+/// `responses_by_interface` and `errors` (interface -> reason the query could
+/// not be sent), so an interface that stays silent is easy to spot.
+#[pyfunction]
+#[pyo3(signature = (interface=None, protocols=None, timeout_ms=2000))]
+pub fn multicast_discovery(
+    py: Python,
+    interface: Option<&PyAny>,
+    protocols: Option<Vec<String>>,
+    timeout_ms: u64,
+) -> PyResult<HashMap<String, PyObject>> {
+    let requested = match interface {
+        None => None,
+        Some(name) if name.is_instance_of::<PyString>() => Some(vec![name.extract::<String>()?]),
+        Some(names) => Some(names.extract::<Vec<String>>()?),
+    };
+    let probes: Vec<&MulticastProbe> = match protocols {
+        None => MULTICAST_PROBES.iter().collect(),
+        Some(names) => names
+            .iter()
+            .map(|name| {
+                let name = name.trim().to_lowercase();
+                MULTICAST_PROBES.iter().find(|p| p.name == name).ok_or_else(|| {
+                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "Unknown multicast protocol: {} (expected mdns or ssdp)",
+                        name
+                    ))
+                })
+            })
+            .collect::<PyResult<_>>()?,
+    };
+    let interfaces = multicast_interfaces(requested).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+
+    let outcomes = py.allow_threads(|| {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = interfaces
+                .iter()
+                .flat_map(|(name, addr)| {
+                    probes.iter().map(move |probe| {
+                        scope.spawn(move || (name, *probe, query_interface(name, *addr, probe, timeout_ms)))
+                    })
+                })
+                .collect();
+            handles.into_iter().filter_map(|h| h.join().ok()).collect::<Vec<_>>()
+        })
+    });
+
+    let mut answers: Vec<MulticastAnswer> = Vec::new();
+    let mut index: HashMap<(Ipv4Addr, String), usize> = HashMap::new();
+    let mut by_interface: HashMap<String, usize> = interfaces.iter().map(|(name, _)| (name.clone(), 0)).collect();
+    let mut errors: HashMap<String, String> = HashMap::new();
+
+    for (name, probe, outcome) in outcomes {
+        let found = match outcome {
+            Ok(found) => found,
+            Err(e) => {
+                let reason = errors.entry(name.clone()).or_default();
+                if !reason.is_empty() {
+                    reason.push_str("; ");
+                }
+                reason.push_str(&format!("{}: {}", probe.name, e));
+                continue;
+            }
+        };
+        for (ip, service, detail) in found {
+            match index.get(&(ip, service.clone())) {
+                Some(&i) => {
+                    if !answers[i].interfaces.contains(name) {
+                        answers[i].interfaces.push(name.clone());
+                        *by_interface.entry(name.clone()).or_default() += 1;
+                    }
+                }
+                None => {
+                    index.insert((ip, service.clone()), answers.len());
+                    *by_interface.entry(name.clone()).or_default() += 1;
+                    answers.push(MulticastAnswer {
+                        ip,
+                        protocol: probe.name,
+                        service,
+                        detail,
+                        interfaces: vec![name.clone()],
+                    });
+                }
+            }
+        }
+    }
+
+    let interfaces_queried: Vec<String> = interfaces.into_iter().map(|(name, _)| name).collect();
+    let interfaces_answered: Vec<String> = interfaces_queried
+        .iter()
+        .filter(|name| by_interface.get(*name).is_some_and(|n| *n > 0))
+        .cloned()
+        .collect();
+
+    let responses: Vec<HashMap<String, PyObject>> = answers
+        .into_iter()
+        .map(|answer| {
+            let mut map = HashMap::new();
+            map.insert("ip".to_string(), answer.ip.to_string().into_py(py));
+            map.insert("protocol".to_string(), answer.protocol.into_py(py));
+            map.insert("service".to_string(), answer.service.into_py(py));
+            map.insert("detail".to_string(), answer.detail.into_py(py));
+            map.insert("interface".to_string(), answer.interfaces[0].clone().into_py(py));
+            map.insert("interfaces".to_string(), answer.interfaces.into_py(py));
+            map
+        })
+        .collect();
+
+    let mut summary = HashMap::new();
+    summary.insert("interfaces_queried".to_string(), interfaces_queried.into_py(py));
+    summary.insert("interfaces_answered".to_string(), interfaces_answered.into_py(py));
+    summary.insert("responses_by_interface".to_string(), by_interface.into_py(py));
+    summary.insert("errors".to_string(), errors.into_py(py));
+
+    let mut result = HashMap::new();
+    result.insert("responses".to_string(), responses.into_py(py));
+    result.insert("summary".to_string(), summary.into_py(py));
+    Ok(result)
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV6};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use pnet::datalink::{self, Channel, NetworkInterface};
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket, MutableEthernetPacket};
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::Packet;
+use pnet::util::MacAddr;
+use pyo3::prelude::*;
+use tokio::net::TcpStream as AsyncTcpStream;
+use tokio::sync::Semaphore;
+use tokio::time::timeout;
+
+use crate::icmp::checksum;
+use crate::scanner::{checked_ports, runtime, unix_now, ScanConfig, ScanResult};
+
+// =============================================================================
+// IPv6 Link-Local Discovery (all-nodes multicast)
+// =============================================================================
+//
+// fe80::/10 can't be enumerated, so discovery pings ff02::1 and records every
+// host that answers. Hosts resolving our MAC before replying send neighbor
+// solicitations, and some volunteer advertisements; both are collected too.
+
+const ICMPV6_ECHO_REQUEST: u8 = 128;
+const ICMPV6_ECHO_REPLY: u8 = 129;
+const ICMPV6_NEIGHBOR_SOLICIT: u8 = 135;
+const ICMPV6_NEIGHBOR_ADVERT: u8 = 136;
+
+fn find_interface(name: &str) -> PyResult<(NetworkInterface, Ipv6Addr)> {
+    let iface = datalink::interfaces()
+        .into_iter()
+        .find(|iface| iface.name == name)
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Unknown interface: {}", name)))?;
+    let link_local = iface
+        .ips
+        .iter()
+        .find_map(|net| match net.ip() {
+            IpAddr::V6(addr) if addr.segments()[0] & 0xffc0 == 0xfe80 => Some(addr),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{} has no IPv6 link-local address", name))
+        })?;
+    Ok((iface, link_local))
+}
+
+/// Ethernet + IPv6 + ICMPv6 echo request from `source` to ff02::1
+fn all_nodes_echo(source_mac: MacAddr, source: Ipv6Addr) -> Vec<u8> {
+    let destination: Ipv6Addr = "ff02::1".parse().expect("valid multicast address");
+    let mut icmp = vec![ICMPV6_ECHO_REQUEST, 0, 0, 0];
+    icmp.extend_from_slice(&((std::process::id() & 0xFFFF) as u16).to_be_bytes());
+    icmp.extend_from_slice(&1u16.to_be_bytes());
+    icmp.extend_from_slice(b"netscan-ndp-prb!");
+
+    // Checksum covers the IPv6 pseudo-header
+    let mut pseudo = Vec::with_capacity(40 + icmp.len());
+    pseudo.extend_from_slice(&source.octets());
+    pseudo.extend_from_slice(&destination.octets());
+    pseudo.extend_from_slice(&(icmp.len() as u32).to_be_bytes());
+    pseudo.extend_from_slice(&[0, 0, 0, 58]);
+    pseudo.extend_from_slice(&icmp);
+    let sum = checksum(&pseudo);
+    icmp[2..4].copy_from_slice(&sum.to_be_bytes());
+
+    let mut frame = vec![0u8; 14];
+    {
+        let mut ethernet = MutableEthernetPacket::new(&mut frame).expect("buffer fits ethernet header");
+        ethernet.set_destination(MacAddr::new(0x33, 0x33, 0, 0, 0, 1));
+        ethernet.set_source(source_mac);
+        ethernet.set_ethertype(EtherTypes::Ipv6);
+    }
+    frame.extend_from_slice(&[0x60, 0, 0, 0]);
+    frame.extend_from_slice(&(icmp.len() as u16).to_be_bytes());
+    frame.extend_from_slice(&[58, 255]);
+    frame.extend_from_slice(&source.octets());
+    frame.extend_from_slice(&destination.octets());
+    frame.extend_from_slice(&icmp);
+    frame
+}
+
+/// Ping ff02::1 on `iface` and collect (address, mac) of every responder
+fn multicast_discover(iface: &NetworkInterface, source: Ipv6Addr, timeout_ms: u64) -> Result<Vec<(Ipv6Addr, String)>, String> {
+    let source_mac = iface.mac.unwrap_or_else(MacAddr::zero);
+    let config = datalink::Config {
+        read_timeout: Some(Duration::from_millis(50)),
+        ..Default::default()
+    };
+    let (mut tx, mut rx) = match datalink::channel(iface, config) {
+        Ok(Channel::Ethernet(tx, rx)) => (tx, rx),
+        Ok(_) => return Err(format!("Unsupported channel type on {}", iface.name)),
+        Err(e) => {
+            return Err(format!(
+                "Cannot open {} for NDP ({}); {}",
+                iface.name, e, crate::capabilities::grant_hint("raw_packet")
+            ))
+        }
+    };
+
+    match tx.send_to(&all_nodes_echo(source_mac, source), None) {
+        Some(Ok(())) => {}
+        Some(Err(e)) => return Err(format!("Cannot send ICMPv6 probe: {}", e)),
+        None => return Err("Cannot send ICMPv6 probe".to_string()),
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    let mut found: Vec<(Ipv6Addr, String)> = Vec::new();
+    while Instant::now() < deadline {
+        let Ok(frame) = rx.next() else {
+            continue;
+        };
+        let Some(ethernet) = EthernetPacket::new(frame) else {
+            continue;
+        };
+        if ethernet.get_ethertype() != EtherTypes::Ipv6 || ethernet.get_source() == source_mac {
+            continue;
+        }
+        let Some(ipv6) = Ipv6Packet::new(ethernet.payload()) else {
+            continue;
+        };
+        if ipv6.get_next_header() != IpNextHeaderProtocols::Icmpv6 {
+            continue;
+        }
+        let responder = ipv6.get_source();
+        let icmp_type = ipv6.payload().first().copied().unwrap_or(0);
+        if !matches!(icmp_type, ICMPV6_ECHO_REPLY | ICMPV6_NEIGHBOR_SOLICIT | ICMPV6_NEIGHBOR_ADVERT)
+            || responder.is_unspecified()
+            || responder.is_multicast()
+        {
+            continue;
+        }
+        if !found.iter().any(|(ip, _)| *ip == responder) {
+            found.push((responder, crate::normalize_mac(&ethernet.get_source().to_string())));
+        }
+    }
+    Ok(found)
+}
+
+/// Discover IPv6 neighbors on `interface` via the ff02::1 all-nodes group
+///
+/// Returns [(ipv6, mac)] for every host that answered within `timeout_ms`.
+/// Needs raw packet access (root or CAP_NET_RAW).
+#[pyfunction]
+#[pyo3(signature = (interface, timeout_ms=2000))]
+pub fn scan_ipv6_multicast(py: Python, interface: &str, timeout_ms: u64) -> PyResult<Vec<(String, String)>> {
+    let (iface, source) = find_interface(interface)?;
+    let found = py
+        .allow_threads(|| multicast_discover(&iface, source, timeout_ms))
+        .map_err(PyErr::new::<pyo3::exceptions::PyPermissionError, _>)?;
+    Ok(found.into_iter().map(|(ip, mac)| (ip.to_string(), mac)).collect())
+}
+
+/// Connect scan of scoped IPv6 addresses (link-local needs the interface index)
+async fn scan_scoped(
+    targets: Vec<Ipv6Addr>,
+    scope_id: u32,
+    ports: Vec<u16>,
+    timeout_ms: u64,
+    max_concurrent: usize,
+) -> HashMap<Ipv6Addr, (Vec<u16>, f64)> {
+    let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+    let mut handles = Vec::new();
+    for ip in targets {
+        for &port in &ports {
+            let sem = semaphore.clone();
+            handles.push(tokio::spawn(async move {
+                let _permit = sem.acquire_owned().await.ok();
+                let addr = SocketAddr::V6(SocketAddrV6::new(ip, port, 0, scope_id));
+                let start = Instant::now();
+                let open = matches!(
+                    timeout(Duration::from_millis(timeout_ms), AsyncTcpStream::connect(addr)).await,
+                    Ok(Ok(_))
+                );
+                (ip, port, open.then(|| start.elapsed().as_secs_f64() * 1000.0))
+            }));
+        }
+    }
+
+    let mut scanned: HashMap<Ipv6Addr, (Vec<u16>, f64)> = HashMap::new();
+    for handle in handles {
+        if let Ok((ip, port, Some(rtt))) = handle.await {
+            let entry = scanned.entry(ip).or_insert((Vec::new(), f64::MAX));
+            entry.0.push(port);
+            entry.1 = entry.1.min(rtt);
+        }
+    }
+    scanned
+}
+
+/// Multicast discovery on `interface`, then a TCP connect scan of every
+/// responder using the config's ports, timeout and concurrency
+///
+/// Every discovered host is returned (status "up", discovery_method "ndp")
+/// with its MAC, whether or not any port was open.
+#[pyfunction]
+#[pyo3(signature = (interface, port_scan_config=None))]
+pub fn scan_ipv6_from_nics(
+    py: Python,
+    interface: &str,
+    port_scan_config: Option<ScanConfig>,
+) -> PyResult<Vec<ScanResult>> {
+    let mut config = port_scan_config.unwrap_or_default();
+    config.ports = checked_ports(py, config.ports)?;
+    let (iface, source) = find_interface(interface)?;
+    let discovery_timeout = config.timeout_ms.max(1000);
+
+    let (found, scanned) = py
+        .allow_threads(|| -> Result<_, String> {
+            let found = multicast_discover(&iface, source, discovery_timeout)?;
+            let targets = found.iter().map(|(ip, _)| *ip).collect();
+            let scanned = runtime().block_on(scan_scoped(
+                targets,
+                iface.index,
+                config.ports.clone(),
+                config.timeout_ms,
+                config.max_concurrent,
+            ));
+            Ok((found, scanned))
+        })
+        .map_err(PyErr::new::<pyo3::exceptions::PyPermissionError, _>)?;
+
+    let scan_timestamp = unix_now();
+    Ok(found
+        .into_iter()
+        .map(|(ip, mac)| {
+            let (mut open_ports, rtt) = scanned.get(&ip).cloned().unwrap_or((Vec::new(), 0.0));
+            open_ports.sort_unstable();
+            ScanResult {
+                ip: ip.to_string(),
+                mac,
+                status: "up".to_string(),
+                response_time_ms: if rtt == f64::MAX { 0.0 } else { rtt },
+                open_ports,
+                discovery_method: "ndp".to_string(),
+                scan_timestamp,
+                sources: vec!["ndp".to_string()],
+                ..Default::default()
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,286 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::SystemTime;
+use parking_lot::{Mutex, RwLock};
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::scanner::unix_now;
+
+// =============================================================================
+// Reloadable OUI Database
+// =============================================================================
+//
+// A long-running monitor holds one database for days while the registry file
+// is refreshed underneath it. Each load builds a complete map off to the side
+// and swaps it in whole; a lookup takes the map it started with, so a batch
+// running during a reload sees the old database or the new one, never a mix.
+//
+// A lazy database starts with a placeholder snapshot and parses the file on
+// a background thread, swapping the result in the same way. Until then,
+// lookups read only as far into the file as their prefixes need
+// (`parse_oui_file_for`) and keep what they found.
+
+/// One complete load of the database
+#[derive(Debug, Default)]
+struct Snapshot {
+    entries: HashMap<String, String>,
+    path: Option<String>,
+    /// Modification time of `path` when it was read
+    mtime: Option<SystemTime>,
+    loaded_at: f64,
+    /// Placeholder while a lazy load parses `path` in the background
+    pending: bool,
+}
+
+impl Snapshot {
+    /// The loaded file, and what looks wrong with the parse if anything (see
+    /// `parse_oui_file`)
+    fn load(path: &str) -> PyResult<(Self, Option<String>)> {
+        let mtime = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let parse = crate::read_oui_file(path)?;
+        let problem = parse.quality_problem(path, crate::OUI_MIN_ENTRIES, crate::OUI_MIN_LINE_RATIO);
+        let snapshot = Snapshot {
+            entries: parse.entries,
+            path: Some(path.to_string()),
+            mtime,
+            loaded_at: unix_now(),
+            pending: false,
+        };
+        Ok((snapshot, problem))
+    }
+}
+
+/// Vendor lookups from an IEEE `oui.txt` file that can be reloaded in place
+///
+/// With `auto_reload_if_changed`, `lookup_many` first checks the file's
+/// modification time and reloads it when it changed. A file that parses
+/// into suspiciously few entries (see `parse_oui_file`) draws a
+/// RuntimeWarning when loaded explicitly and is skipped by auto-reload. Whoever refreshes the
+/// file should write a new one and rename it over the old, so a reload never
+/// reads it half-written.
+///
+/// With `lazy=True` the constructor returns at once and the file is parsed
+/// in the background; lookups made before it finishes scan the file only
+/// for their own prefixes. A background parse that fails, or fails the
+/// quality check, leaves the database on that slower path until `reload()`.
+#[pyclass]
+#[derive(Debug)]
+pub struct OuiDatabase {
+    current: Arc<RwLock<Arc<Snapshot>>>,
+    /// Prefixes looked up while a lazy load is pending, found or not
+    partial: Mutex<HashMap<String, Option<String>>>,
+    #[pyo3(get, set)]
+    pub auto_reload_if_changed: bool,
+}
+
+impl OuiDatabase {
+    fn snapshot(&self) -> Arc<Snapshot> {
+        self.current.read().clone()
+    }
+
+    /// Vendors for `ouis` while the load is pending, scanning the file for
+    /// those not looked up before; a file that can't be read finds nothing
+    fn pending_lookup(&self, py: Python, snapshot: &Snapshot, ouis: &[String]) -> HashMap<String, Option<String>> {
+        let missing: HashSet<String> = {
+            let partial = self.partial.lock();
+            ouis.iter().filter(|oui| !partial.contains_key(*oui)).cloned().collect()
+        };
+        if let (false, Some(path)) = (missing.is_empty(), snapshot.path.as_deref()) {
+            let found = py.allow_threads(|| crate::read_oui_file_for(path, &missing)).unwrap_or_default();
+            let mut partial = self.partial.lock();
+            for oui in missing {
+                let vendor = found.get(&oui).cloned();
+                partial.insert(oui, vendor);
+            }
+        }
+        let partial = self.partial.lock();
+        ouis.iter().map(|oui| (oui.clone(), partial.get(oui).cloned().flatten())).collect()
+    }
+
+    /// Reload when the file on disk is newer than the loaded copy; a failed
+    /// reload, or one that fails the parse quality check, keeps the loaded
+    /// copy and is retried on the next check
+    fn reload_if_changed(&self, py: Python) {
+        let snapshot = self.snapshot();
+        let Some(path) = snapshot.path.as_deref().filter(|_| !snapshot.pending) else {
+            return;
+        };
+        let mtime = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if mtime.is_some() && mtime != snapshot.mtime {
+            if let Ok((fresh, None)) = py.allow_threads(|| Snapshot::load(path)) {
+                *self.current.write() = Arc::new(fresh);
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl OuiDatabase {
+    /// Load `path`, or start empty when it is None
+    #[new]
+    #[pyo3(signature = (path=None, auto_reload_if_changed=false, lazy=false))]
+    pub fn new(py: Python, path: Option<&str>, auto_reload_if_changed: bool, lazy: bool) -> PyResult<Self> {
+        let snapshot = match path {
+            Some(path) if lazy => {
+                // Fail now on a missing file rather than on the background thread
+                std::fs::metadata(path).map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot open file: {}", e))
+                })?;
+                Snapshot { path: Some(path.to_string()), pending: true, ..Snapshot::default() }
+            }
+            Some(path) => {
+                let (snapshot, problem) = py.allow_threads(|| Snapshot::load(path))?;
+                crate::check_parse_quality(py, problem, false)?;
+                snapshot
+            }
+            None => Snapshot::default(),
+        };
+        let pending = Arc::new(snapshot);
+        let current = Arc::new(RwLock::new(pending.clone()));
+        if pending.pending {
+            let current = current.clone();
+            std::thread::spawn(move || {
+                let Some(path) = pending.path.as_deref() else { return };
+                if let Ok((fresh, None)) = Snapshot::load(path) {
+                    let mut current = current.write();
+                    // An explicit reload in the meantime wins
+                    if Arc::ptr_eq(&current, &pending) {
+                        *current = Arc::new(fresh);
+                    }
+                }
+            });
+        }
+        Ok(OuiDatabase { current, partial: Mutex::new(HashMap::new()), auto_reload_if_changed })
+    }
+
+    /// Re-read the database from `path`, or from the file it was last
+    /// loaded from, and swap it in; returns the number of entries
+    ///
+    /// On error (unreadable file, no path) the loaded database is kept.
+    #[pyo3(signature = (path=None))]
+    fn reload(&self, py: Python, path: Option<String>) -> PyResult<usize> {
+        let path = path.or_else(|| self.snapshot().path.clone()).ok_or_else(|| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>("No path given and the database was not loaded from a file")
+        })?;
+        let (fresh, problem) = py.allow_threads(|| Snapshot::load(&path))?;
+        crate::check_parse_quality(py, problem, false)?;
+        let count = fresh.entries.len();
+        *self.current.write() = Arc::new(fresh);
+        Ok(count)
+    }
+
+    /// Unix time of the last successful load (0.0 if never loaded from a file)
+    fn last_loaded(&self) -> f64 {
+        self.snapshot().loaded_at
+    }
+
+    /// False while a lazy load is still parsing in the background
+    fn is_loaded(&self) -> bool {
+        !self.snapshot().pending
+    }
+
+    /// File the database was last loaded from
+    fn source_path(&self) -> Option<String> {
+        self.snapshot().path.clone()
+    }
+
+    /// Vendor for `mac`
+    fn lookup(&self, py: Python, mac: &str) -> Option<String> {
+        let snapshot = self.snapshot();
+        let oui = crate::extract_oui(mac);
+        if snapshot.pending {
+            return self.pending_lookup(py, &snapshot, &[oui]).into_values().next().flatten();
+        }
+        snapshot.entries.get(&oui).cloned()
+    }
+
+    /// {mac: vendor} for the MACs with a known vendor (parallel)
+    fn lookup_many(&self, py: Python, macs: Vec<String>) -> HashMap<String, String> {
+        if self.auto_reload_if_changed {
+            self.reload_if_changed(py);
+        }
+        let snapshot = self.snapshot();
+        if snapshot.pending {
+            let ouis: Vec<String> = macs.iter().map(|mac| crate::extract_oui(mac)).collect();
+            let vendors = self.pending_lookup(py, &snapshot, &ouis);
+            return macs
+                .into_iter()
+                .zip(ouis)
+                .filter_map(|(mac, oui)| vendors.get(&oui).cloned().flatten().map(|v| (mac, v)))
+                .collect();
+        }
+        py.allow_threads(|| {
+            macs.par_iter()
+                .filter_map(|mac| snapshot.entries.get(&crate::extract_oui(mac)).map(|v| (mac.clone(), v.clone())))
+                .collect()
+        })
+    }
+
+    fn __len__(&self) -> usize {
+        self.snapshot().entries.len()
+    }
+
+    fn __repr__(&self) -> String {
+        let snapshot = self.snapshot();
+        let entries = if snapshot.pending {
+            "loading".to_string()
+        } else {
+            format!("{} entries", snapshot.entries.len())
+        };
+        format!(
+            "OuiDatabase(<{}>, path={})",
+            entries,
+            snapshot.path.as_ref().map(|p| format!("'{}'", p)).unwrap_or_else(|| "None".to_string())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An oui.txt with 1,200 prefixes, every one naming `vendor`
+    fn write_oui_file(path: &std::path::Path, vendor: &str) {
+        let content: String = (0..1200u32)
+            .map(|i| format!("00-{:02X}-{:02X}   (hex)\t\t{}\n", i >> 8, i & 0xff, vendor))
+            .collect();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn reload_during_parallel_lookup_never_mixes_databases() {
+        let dir = std::env::temp_dir().join(format!("oui-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (old, new) = (dir.join("old.txt"), dir.join("new.txt"));
+        write_oui_file(&old, "Old Vendor");
+        write_oui_file(&new, "New Vendor");
+        let macs: Vec<String> = (0..20_000u32)
+            .map(|i| format!("00:{:02X}:{:02X}:00:{:02X}:{:02X}", (i % 1200) >> 8, (i % 1200) & 0xff, i >> 8, i & 0xff))
+            .collect();
+
+        pyo3::prepare_freethreaded_python();
+        let db = Python::with_gil(|py| OuiDatabase::new(py, old.to_str(), false, false)).unwrap();
+        let seen = Mutex::new(HashSet::new());
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..10 {
+                        let found = Python::with_gil(|py| db.lookup_many(py, macs.clone()));
+                        assert_eq!(found.len(), macs.len());
+                        let vendors: HashSet<&String> = found.values().collect();
+                        assert_eq!(vendors.len(), 1, "one lookup saw both databases");
+                        seen.lock().extend(vendors.into_iter().cloned());
+                    }
+                });
+            }
+            for round in 0..10 {
+                let path = if round % 2 == 0 { &new } else { &old };
+                let count = Python::with_gil(|py| db.reload(py, path.to_str().map(String::from))).unwrap();
+                assert_eq!(count, 1200);
+            }
+        });
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(!seen.into_inner().is_empty());
+    }
+}
@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use regex::Regex;
+
+use crate::scanner::ScanResult;
+use crate::schema::emit_records;
+
+// =============================================================================
+// Network Device Output Parsers
+// =============================================================================
+
+/// MAC in Cisco dotted (0011.2233.4455), colon or dash notation
+fn mac_token(token: &str) -> Option<String> {
+    static MAC: OnceLock<Regex> = OnceLock::new();
+    let re = MAC.get_or_init(|| {
+        Regex::new(r"^(?:[0-9A-Fa-f]{4}\.[0-9A-Fa-f]{4}\.[0-9A-Fa-f]{4}|[0-9A-Fa-f]{2}(?:[:-][0-9A-Fa-f]{2}){5})$").unwrap()
+    });
+    re.is_match(token).then(|| crate::normalize_mac(token))
+}
+
+/// Strip terminal paging artifacts: "--More--" prompts and the backspaces
+/// used to erase them
+fn clean_cli_line(line: &str) -> String {
+    line.replace("--More--", "")
+        .replace(['\u{8}', '\r'], "")
+        .trim()
+        .to_string()
+}
+
+/// `show mac address-table` entries as {vlan, mac, type, port}
+fn mac_table_entries(output: &str) -> Vec<HashMap<String, String>> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = clean_cli_line(line);
+            // NX-OS prefixes entries with flags such as "*", "+", "G", "C"
+            let tokens: Vec<&str> = line
+                .split_whitespace()
+                .skip_while(|t| matches!(*t, "*" | "+" | "G" | "C" | "O" | "R"))
+                .collect();
+            // Entries are "vlan mac type [age secure ntfy] port"
+            if tokens.len() < 4 {
+                return None;
+            }
+            let vlan = tokens[0];
+            if !(vlan.chars().all(|c| c.is_ascii_digit()) || vlan.eq_ignore_ascii_case("all")) {
+                return None;
+            }
+            let mac = mac_token(tokens[1])?;
+            let entry_type = tokens[2];
+            let port = tokens[tokens.len() - 1];
+
+            Some(HashMap::from([
+                ("vlan".to_string(), vlan.to_string()),
+                ("mac".to_string(), mac),
+                ("type".to_string(), entry_type.to_lowercase()),
+                ("port".to_string(), port.to_string()),
+            ]))
+        })
+        .collect()
+}
+
+/// `show ip arp` entries as {ip, age, mac, interface}
+fn ip_arp_entries(output: &str) -> Vec<HashMap<String, String>> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = clean_cli_line(line);
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            // Internet  10.0.0.5  12  0011.2233.4466  ARPA  Vlan10
+            if tokens.len() < 4 || !tokens[0].eq_ignore_ascii_case("internet") {
+                return None;
+            }
+            let ip = tokens[1].parse::<IpAddr>().ok()?;
+            let age = match tokens[2] {
+                "-" => String::new(),
+                age if age.chars().all(|c| c.is_ascii_digit()) => age.to_string(),
+                _ => return None,
+            };
+            let mac = mac_token(tokens[3])?;
+            let interface = tokens.get(5).copied().unwrap_or_default();
+
+            Some(HashMap::from([
+                ("ip".to_string(), ip.to_string()),
+                ("age".to_string(), age),
+                ("mac".to_string(), mac),
+                ("interface".to_string(), interface.to_string()),
+            ]))
+        })
+        .collect()
+}
+
+/// Parse `show mac address-table` (IOS, IOS-XE and NX-OS layouts)
+///
+/// Returns a ScanResult (source "cisco_mac_table") per entry with the MAC as
+/// XX:XX:XX:XX:XX:XX and `vlan`, `type` and `port` in `attributes`;
+/// `legacy=True` returns the old [{vlan, mac, type, port}] dicts. Header,
+/// footer and prompt lines are skipped wherever they appear, so several
+/// captures concatenated together parse as one table.
+#[pyfunction]
+#[pyo3(signature = (output, legacy=false))]
+pub fn parse_cisco_mac_table(py: Python, output: &str, legacy: bool) -> PyResult<PyObject> {
+    emit_records(py, "parse_cisco_mac_table", mac_table_entries(output), "cisco_mac_table", legacy)
+}
+
+/// Parse `show ip arp`
+///
+/// Returns a ScanResult (source "cisco_arp") per entry with `age` (minutes,
+/// or "" for the router's own addresses) and `interface` in `attributes`;
+/// `legacy=True` returns the old [{ip, age, mac, interface}] dicts.
+/// Incomplete entries carry no hardware address and are skipped.
+#[pyfunction]
+#[pyo3(signature = (output, legacy=false))]
+pub fn parse_cisco_ip_arp(py: Python, output: &str, legacy: bool) -> PyResult<PyObject> {
+    emit_records(py, "parse_cisco_ip_arp", ip_arp_entries(output), "cisco_arp", legacy)
+}
+
+// =============================================================================
+// PowerShell JSON Output
+// =============================================================================
+//
+// Windows endpoints report neighbors and adapters as `ConvertTo-Json` output
+// of Get-NetNeighbor, Get-NetAdapter and Get-NetIPAddress. PowerShell emits
+// a bare object instead of an array when there is one result, writes enums
+// as their numeric values unless asked not to, and redirected output is
+// often UTF-16 with a BOM.
+
+/// Get-NetNeighbor `State` values, by enum number
+const NEIGHBOR_STATES: &[&str] = &["unreachable", "incomplete", "probe", "delay", "stale", "reachable", "permanent"];
+
+/// Text of a PowerShell payload given as str or bytes (UTF-8 or UTF-16,
+/// with or without a BOM)
+fn powershell_text(payload: &PyAny) -> PyResult<String> {
+    let invalid = |e: String| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid PowerShell output: {}", e));
+    let text = if let Ok(bytes) = payload.downcast::<PyBytes>() {
+        let bytes = bytes.as_bytes();
+        let utf16 = |bytes: &[u8], little_endian: bool| {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| if little_endian { u16::from_le_bytes([pair[0], pair[1]]) } else { u16::from_be_bytes([pair[0], pair[1]]) })
+                .collect();
+            String::from_utf16(&units).map_err(|e| invalid(e.to_string()))
+        };
+        match bytes {
+            [0xFF, 0xFE, rest @ ..] => utf16(rest, true)?,
+            [0xFE, 0xFF, rest @ ..] => utf16(rest, false)?,
+            // No BOM, but ASCII JSON in UTF-16LE has a NUL after every character
+            [first, 0, ..] if *first != 0 => utf16(bytes, true)?,
+            _ => String::from_utf8(bytes.to_vec()).map_err(|e| invalid(e.to_string()))?,
+        }
+    } else {
+        payload.extract::<String>()?
+    };
+    Ok(text.trim_start_matches('\u{feff}').to_string())
+}
+
+/// A property as text: strings as they are, numbers in decimal, null and
+/// missing as ""; names match case-insensitively, as in PowerShell
+fn ps_field(object: &serde_json::Value, name: &str) -> String {
+    let value = object
+        .as_object()
+        .and_then(|o| o.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, v)| v));
+    match value {
+        Some(serde_json::Value::String(s)) => s.trim().to_string(),
+        Some(serde_json::Value::Number(n)) => n.to_string(),
+        Some(serde_json::Value::Bool(b)) => b.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Get-NetNeighbor entries that hold a hardware address
+fn neighbor_results(objects: &[serde_json::Value]) -> Vec<ScanResult> {
+    objects
+        .iter()
+        .filter_map(|object| {
+            let ip: IpAddr = ps_field(object, "IPAddress").split('%').next()?.parse().ok()?;
+            let state = ps_field(object, "State");
+            let state = match state.parse::<usize>() {
+                Ok(n) => NEIGHBOR_STATES.get(n).map_or(state, |s| s.to_string()),
+                Err(_) => state.to_lowercase(),
+            };
+            let mac = mac_token(&ps_field(object, "LinkLayerAddress"))?;
+            if matches!(state.as_str(), "unreachable" | "incomplete") || mac == "00:00:00:00:00:00" {
+                return None;
+            }
+            let method = if ip.is_ipv4() { "arp" } else { "ndp" };
+            let mut result = ScanResult {
+                ip: ip.to_string(),
+                mac,
+                status: "up".to_string(),
+                discovery_method: method.to_string(),
+                sources: vec!["powershell_neighbor".to_string()],
+                ..Default::default()
+            };
+            result.attributes.insert("state".to_string(), state);
+            result.attributes.insert("interface".to_string(), ps_field(object, "InterfaceAlias"));
+            Some(result)
+        })
+        .collect()
+}
+
+/// Get-NetAdapter entries as {interface, mac, status, description}
+fn adapter_records(objects: &[serde_json::Value]) -> Vec<HashMap<String, String>> {
+    objects
+        .iter()
+        .filter_map(|object| {
+            let name = ps_field(object, "Name");
+            if name.is_empty() {
+                return None;
+            }
+            let mac = ps_field(object, "MacAddress");
+            Some(HashMap::from([
+                ("interface".to_string(), name),
+                ("mac".to_string(), mac_token(&mac).unwrap_or_default()),
+                ("status".to_string(), ps_field(object, "Status").to_lowercase()),
+                ("description".to_string(), ps_field(object, "InterfaceDescription")),
+            ]))
+        })
+        .collect()
+}
+
+/// Get-NetIPAddress entries as {interface, ip, prefix_length}
+fn ip_address_records(objects: &[serde_json::Value]) -> Vec<HashMap<String, String>> {
+    objects
+        .iter()
+        .filter_map(|object| {
+            let ip: IpAddr = ps_field(object, "IPAddress").split('%').next()?.parse().ok()?;
+            Some(HashMap::from([
+                ("interface".to_string(), ps_field(object, "InterfaceAlias")),
+                ("ip".to_string(), ip.to_string()),
+                ("prefix_length".to_string(), ps_field(object, "PrefixLength")),
+            ]))
+        })
+        .collect()
+}
+
+/// Parse PowerShell `ConvertTo-Json` output of a networking cmdlet
+///
+/// `kind` names the cmdlet: "neighbor" (Get-NetNeighbor) returns a
+/// ScanResult per neighbor with a hardware address (source
+/// "powershell_neighbor", `state` and `interface` in `attributes`), like
+/// ARP table imports; "adapter" (Get-NetAdapter) and "ipaddress"
+/// (Get-NetIPAddress) return interface dicts, {interface, mac, status,
+/// description} and {interface, ip, prefix_length}. The cmdlet names are
+/// accepted as kinds too. `payload` is the JSON as str, or bytes in UTF-8
+/// or UTF-16. Raises ValueError for an unknown kind or invalid JSON.
+#[pyfunction]
+pub fn parse_powershell_json(py: Python, payload: &PyAny, kind: &str) -> PyResult<PyObject> {
+    let text = powershell_text(payload)?;
+    let objects = if text.trim().is_empty() {
+        Vec::new()
+    } else {
+        match serde_json::from_str(&text) {
+            Ok(serde_json::Value::Array(items)) => items,
+            Ok(single) => vec![single],
+            Err(e) => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid PowerShell JSON: {}", e)));
+            }
+        }
+    };
+    match kind.trim().to_lowercase().as_str() {
+        "neighbor" | "get-netneighbor" => Ok(neighbor_results(&objects).into_py(py)),
+        "adapter" | "get-netadapter" => Ok(adapter_records(&objects).into_py(py)),
+        "ipaddress" | "get-netipaddress" => Ok(ip_address_records(&objects).into_py(py)),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unknown kind '{}': expected neighbor, adapter or ipaddress", kind
+        ))),
+    }
+}
+
+// =============================================================================
+// Wireless Station Lists
+// =============================================================================
+//
+// Wireless clients may never send traffic a scan sees, but the access point
+// lists every associated station. Both parsers return device dicts keyed by
+// MAC, {mac, interface, source, wireless: {...}}, ready to merge by MAC with
+// DHCP leases. Captures from several interfaces may be concatenated; a
+// station listed twice keeps its last entry.
+
+/// One associated station as an AP reports it
+#[derive(Debug, Default)]
+struct Station {
+    mac: String,
+    interface: String,
+    signal_dbm: Option<i64>,
+    rx_bitrate_mbps: Option<f64>,
+    tx_bitrate_mbps: Option<f64>,
+    connected_time_s: Option<u64>,
+    inactive_ms: Option<u64>,
+    rx_bytes: Option<u64>,
+    tx_bytes: Option<u64>,
+    /// hostapd's [AUTH][ASSOC]... flags, lowercased
+    flags: Vec<String>,
+}
+
+impl Station {
+    fn into_device(self, py: Python, source: &str) -> HashMap<String, PyObject> {
+        let mut wireless: HashMap<&str, PyObject> = HashMap::new();
+        let mut put = |key, value: Option<PyObject>| {
+            if let Some(value) = value {
+                wireless.insert(key, value);
+            }
+        };
+        put("signal_dbm", self.signal_dbm.map(|v| v.into_py(py)));
+        put("rx_bitrate_mbps", self.rx_bitrate_mbps.map(|v| v.into_py(py)));
+        put("tx_bitrate_mbps", self.tx_bitrate_mbps.map(|v| v.into_py(py)));
+        put("connected_time_s", self.connected_time_s.map(|v| v.into_py(py)));
+        put("inactive_ms", self.inactive_ms.map(|v| v.into_py(py)));
+        put("rx_bytes", self.rx_bytes.map(|v| v.into_py(py)));
+        put("tx_bytes", self.tx_bytes.map(|v| v.into_py(py)));
+        put("flags", (!self.flags.is_empty()).then(|| self.flags.into_py(py)));
+
+        HashMap::from([
+            ("mac".to_string(), self.mac.into_py(py)),
+            ("interface".to_string(), self.interface.into_py(py)),
+            ("source".to_string(), source.into_py(py)),
+            ("wireless".to_string(), wireless.into_py(py)),
+        ])
+    }
+}
+
+fn first_number<T: std::str::FromStr>(value: &str) -> Option<T> {
+    value.split_whitespace().next()?.parse().ok()
+}
+
+fn iw_stations(output: &str) -> Vec<Station> {
+    let mut stations = Vec::new();
+    let mut current: Option<Station> = None;
+    for line in output.lines() {
+        // Station aa:bb:cc:dd:ee:ff (on wlan0)
+        if let Some(rest) = line.trim().strip_prefix("Station ") {
+            stations.extend(current.take());
+            let mut parts = rest.split_whitespace();
+            current = parts.next().and_then(mac_token).map(|mac| Station {
+                mac,
+                interface: parts
+                    .next()
+                    .filter(|p| *p == "(on")
+                    .and_then(|_| parts.next())
+                    .map(|i| i.trim_end_matches(')').to_string())
+                    .unwrap_or_default(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let (Some(station), Some((key, value))) = (current.as_mut(), line.split_once(':')) else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "signal" => station.signal_dbm = first_number(value),
+            "rx bitrate" => station.rx_bitrate_mbps = first_number(value),
+            "tx bitrate" => station.tx_bitrate_mbps = first_number(value),
+            "connected time" => station.connected_time_s = first_number(value),
+            "inactive time" => station.inactive_ms = first_number(value),
+            "rx bytes" => station.rx_bytes = first_number(value),
+            "tx bytes" => station.tx_bytes = first_number(value),
+            _ => {}
+        }
+    }
+    stations.extend(current);
+    stations
+}
+
+fn hostapd_stations(output: &str) -> Vec<Station> {
+    let mut stations = Vec::new();
+    let mut current: Option<Station> = None;
+    let mut interface = String::new();
+    for line in output.lines().map(str::trim) {
+        // hostapd_cli names the interface it picked when none was given
+        if let Some(rest) = line.strip_prefix("Selected interface") {
+            interface = rest.trim().trim_matches('\'').to_string();
+            continue;
+        }
+        if let Some(mac) = mac_token(line) {
+            stations.extend(current.take());
+            current = Some(Station { mac, interface: interface.clone(), ..Default::default() });
+            continue;
+        }
+        let (Some(station), Some((key, value))) = (current.as_mut(), line.split_once('=')) else {
+            continue;
+        };
+        match key {
+            "signal" => station.signal_dbm = first_number(value),
+            // Rates are in units of 100 kbit/s
+            "rx_rate_info" => station.rx_bitrate_mbps = first_number::<f64>(value).map(|r| r / 10.0),
+            "tx_rate_info" => station.tx_bitrate_mbps = first_number::<f64>(value).map(|r| r / 10.0),
+            "connected_time" => station.connected_time_s = first_number(value),
+            "inactive_msec" => station.inactive_ms = first_number(value),
+            "rx_bytes" => station.rx_bytes = first_number(value),
+            "tx_bytes" => station.tx_bytes = first_number(value),
+            "flags" => {
+                station.flags = value
+                    .split(['[', ']'])
+                    .filter(|f| !f.is_empty())
+                    .map(str::to_lowercase)
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+    stations.extend(current);
+    stations
+}
+
+fn devices_by_mac(py: Python, stations: Vec<Station>, source: &str) -> HashMap<String, HashMap<String, PyObject>> {
+    stations
+        .into_iter()
+        .map(|station| (station.mac.clone(), station.into_device(py, source)))
+        .collect()
+}
+
+/// Parse `iw dev <iface> station dump` into device dicts keyed by MAC
+///
+/// `wireless` holds signal_dbm, rx/tx_bitrate_mbps, connected_time_s,
+/// inactive_ms and rx/tx_bytes, as far as the capture has them; the
+/// interface comes from each "Station ... (on wlan0)" line.
+#[pyfunction]
+pub fn parse_iw_station_dump(py: Python, output: &str) -> HashMap<String, HashMap<String, PyObject>> {
+    devices_by_mac(py, iw_stations(output), "iw")
+}
+
+/// Parse `hostapd_cli all_sta` into device dicts keyed by MAC
+///
+/// `wireless` has the same keys as `parse_iw_station_dump` (bitrates
+/// converted from hostapd's 100 kbit/s units) plus `flags` such as
+/// ["auth", "assoc", "authorized"]. The interface is taken from
+/// hostapd_cli's "Selected interface" line, so it is empty for captures
+/// made with `-i`.
+#[pyfunction]
+pub fn parse_hostapd_all_sta(py: Python, output: &str) -> HashMap<String, HashMap<String, PyObject>> {
+    devices_by_mac(py, hostapd_stations(output), "hostapd")
+}
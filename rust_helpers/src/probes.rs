@@ -0,0 +1,957 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, SignatureScheme};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+use tokio::time::timeout;
+use tokio_rustls::TlsConnector;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use regex::Regex;
+
+use crate::banners::detect_service;
+use crate::scanner::{checked_ports, runtime, tcp_probe_state, PortState};
+use crate::scope::authorize_targets;
+use crate::secret::redact;
+
+// =============================================================================
+// Low-level TCP helpers
+// =============================================================================
+
+/// Connect with a timeout
+pub async fn connect(ip: &str, port: u16, timeout_ms: u64) -> Option<TcpStream> {
+    let addr = SocketAddr::new(ip.parse::<IpAddr>().ok()?, port);
+    match timeout(Duration::from_millis(timeout_ms), TcpStream::connect(addr)).await {
+        Ok(Ok(stream)) => Some(stream),
+        _ => None,
+    }
+}
+
+/// Read whatever the peer sends within the timeout, up to `max_bytes`
+pub async fn read_some<S: AsyncRead + Unpin>(stream: &mut S, timeout_ms: u64, max_bytes: usize) -> Vec<u8> {
+    let mut buf = vec![0u8; max_bytes];
+    let mut filled = 0;
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(timeout_ms);
+
+    while filled < max_bytes {
+        match tokio::time::timeout_at(deadline, stream.read(&mut buf[filled..])).await {
+            Ok(Ok(0)) | Ok(Err(_)) | Err(_) => break,
+            Ok(Ok(n)) => filled += n,
+        }
+    }
+    buf.truncate(filled);
+    buf
+}
+
+/// Connect, optionally send a payload, and read the response
+pub async fn send_and_read(
+    ip: &str,
+    port: u16,
+    payload: &[u8],
+    timeout_ms: u64,
+    max_bytes: usize,
+) -> Option<Vec<u8>> {
+    let mut stream = connect(ip, port, timeout_ms).await?;
+    if !payload.is_empty() {
+        timeout(Duration::from_millis(timeout_ms), stream.write_all(payload))
+            .await
+            .ok()?
+            .ok()?;
+    }
+    Some(read_some(&mut stream, timeout_ms, max_bytes).await)
+}
+
+/// Accepts any server certificate: scanned services are reached by IP and
+/// are often self-signed, and the probes only read what they serve
+#[derive(Debug)]
+struct AcceptAnyCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Client config that skips certificate checks (see `AcceptAnyCert`)
+pub(crate) fn unverified_tls_config() -> Arc<ClientConfig> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .expect("ring supports the default TLS versions")
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCert(provider)))
+        .with_no_client_auth();
+    Arc::new(config)
+}
+
+fn tls_connector() -> &'static TlsConnector {
+    static CONNECTOR: OnceLock<TlsConnector> = OnceLock::new();
+    CONNECTOR.get_or_init(|| TlsConnector::from(unverified_tls_config()))
+}
+
+/// `send_and_read`, over TLS when `tls` is set (certificates unchecked)
+pub async fn send_and_read_over(
+    ip: &str,
+    port: u16,
+    tls: bool,
+    payload: &[u8],
+    timeout_ms: u64,
+    max_bytes: usize,
+) -> Option<Vec<u8>> {
+    if !tls {
+        return send_and_read(ip, port, payload, timeout_ms, max_bytes).await;
+    }
+    let stream = connect(ip, port, timeout_ms).await?;
+    let name = ServerName::from(ip.parse::<IpAddr>().ok()?);
+    let mut stream = timeout(Duration::from_millis(timeout_ms), tls_connector().connect(name, stream))
+        .await
+        .ok()?
+        .ok()?;
+    timeout(Duration::from_millis(timeout_ms), stream.write_all(payload))
+        .await
+        .ok()?
+        .ok()?;
+    Some(read_some(&mut stream, timeout_ms, max_bytes).await)
+}
+
+/// Printable, single-line rendering of a raw banner
+pub fn clean_banner(raw: &[u8]) -> String {
+    String::from_utf8_lossy(raw)
+        .chars()
+        .map(|c| if c == '\r' || c == '\n' || c == '\t' { ' ' } else { c })
+        .filter(|c| !c.is_control() && *c != '\u{FFFD}')
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// =============================================================================
+// Protocol probes
+// =============================================================================
+
+/// HTTP HEAD probe: status code and Server header
+pub async fn http_probe(ip: &str, port: u16, timeout_ms: u64) -> HashMap<String, String> {
+    let request = format!(
+        "HEAD / HTTP/1.0\r\nHost: {}\r\nUser-Agent: netscan\r\nConnection: close\r\n\r\n",
+        ip
+    );
+    let mut info = HashMap::new();
+    let Some(raw) = send_and_read(ip, port, request.as_bytes(), timeout_ms, 4096).await else {
+        return info;
+    };
+    let text = String::from_utf8_lossy(&raw);
+    let mut lines = text.lines();
+
+    if let Some(status_line) = lines.next() {
+        if status_line.starts_with("HTTP/") {
+            if let Some(code) = status_line.split_whitespace().nth(1) {
+                info.insert("http_status".to_string(), code.to_string());
+            }
+        }
+    }
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("server") {
+                info.insert("server".to_string(), value.trim().to_string());
+            }
+        }
+    }
+    info
+}
+
+fn tls_extension(ext_type: u16, data: &[u8]) -> Vec<u8> {
+    let mut out = ext_type.to_be_bytes().to_vec();
+    out.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    out.extend_from_slice(data);
+    out
+}
+
+fn u16_list(values: &[u16]) -> Vec<u8> {
+    let mut out = ((values.len() * 2) as u16).to_be_bytes().to_vec();
+    for v in values {
+        out.extend_from_slice(&v.to_be_bytes());
+    }
+    out
+}
+
+/// Minimal TLS 1.2 ClientHello offering common ECDHE/RSA suites
+fn tls_client_hello() -> Vec<u8> {
+    const CIPHERS: &[u16] = &[
+        0xC02F, 0xC030, 0xC02B, 0xC02C, 0xCCA8, 0xCCA9,
+        0xC013, 0xC014, 0x009C, 0x009D, 0x002F, 0x0035,
+    ];
+
+    let mut random = [0u8; 32];
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0x9E37_79B9_7F4A_7C15);
+    let mut x = seed | 1;
+    for byte in random.iter_mut() {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        *byte = x as u8;
+    }
+
+    let mut extensions = Vec::new();
+    extensions.extend(tls_extension(0x000a, &u16_list(&[0x001d, 0x0017, 0x0018])));
+    extensions.extend(tls_extension(0x000b, &[1, 0]));
+    extensions.extend(tls_extension(0x000d, &u16_list(&[
+        0x0403, 0x0503, 0x0603, 0x0804, 0x0805, 0x0806, 0x0401, 0x0501, 0x0601, 0x0201,
+    ])));
+    extensions.extend(tls_extension(0xff01, &[0]));
+
+    let mut body = vec![0x03, 0x03];
+    body.extend_from_slice(&random);
+    body.push(0); // session id
+    body.extend(u16_list(CIPHERS));
+    body.extend_from_slice(&[1, 0]); // null compression
+    body.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+    body.extend(extensions);
+
+    let mut handshake = vec![0x01];
+    handshake.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+    handshake.extend(body);
+
+    let mut record = vec![0x16, 0x03, 0x01];
+    record.extend_from_slice(&(handshake.len() as u16).to_be_bytes());
+    record.extend(handshake);
+    record
+}
+
+fn tls_version_name(version: u16) -> String {
+    match version {
+        0x0300 => "SSLv3".to_string(),
+        0x0301 => "TLSv1.0".to_string(),
+        0x0302 => "TLSv1.1".to_string(),
+        0x0303 => "TLSv1.2".to_string(),
+        0x0304 => "TLSv1.3".to_string(),
+        other => format!("0x{:04X}", other),
+    }
+}
+
+/// TLS handshake probe: negotiated protocol version and cipher suite
+pub async fn tls_probe(ip: &str, port: u16, timeout_ms: u64) -> HashMap<String, String> {
+    let mut info = HashMap::new();
+    let Some(raw) = send_and_read(ip, port, &tls_client_hello(), timeout_ms, 512).await else {
+        return info;
+    };
+
+    match raw.first() {
+        Some(0x16) if raw.len() >= 44 && raw[5] == 0x02 => {
+            let version = u16::from_be_bytes([raw[9], raw[10]]);
+            let sid_len = raw[43] as usize;
+            info.insert("tls".to_string(), "true".to_string());
+            info.insert("tls_version".to_string(), tls_version_name(version));
+            if let Some(cipher) = raw.get(44 + sid_len..46 + sid_len) {
+                info.insert(
+                    "tls_cipher".to_string(),
+                    format!("0x{:04X}", u16::from_be_bytes([cipher[0], cipher[1]])),
+                );
+            }
+        }
+        Some(0x15) => {
+            info.insert("tls".to_string(), "true".to_string());
+            info.insert("tls_alert".to_string(), raw.get(6).map(|d| d.to_string()).unwrap_or_default());
+        }
+        _ => {
+            info.insert("tls".to_string(), "false".to_string());
+        }
+    }
+    info
+}
+
+/// SMB negotiate probe offering SMB1 and SMB2 dialects
+pub async fn smb_probe(ip: &str, port: u16, timeout_ms: u64) -> HashMap<String, String> {
+    const DIALECTS: &[&str] = &["NT LM 0.12", "SMB 2.002", "SMB 2.???"];
+
+    let mut smb = vec![
+        0xFF, b'S', b'M', b'B', 0x72, // negotiate
+        0x00, 0x00, 0x00, 0x00,       // status
+        0x18, 0x01, 0x48,             // flags, flags2
+        0x00, 0x00,                   // pid high
+        0, 0, 0, 0, 0, 0, 0, 0,       // signature
+        0x00, 0x00,                   // reserved
+        0x00, 0x00, 0xFF, 0xFE,       // tid, pid
+        0x00, 0x00, 0x00, 0x00,       // uid, mid
+        0x00,                         // word count
+    ];
+    let mut dialects = Vec::new();
+    for d in DIALECTS {
+        dialects.push(0x02);
+        dialects.extend_from_slice(d.as_bytes());
+        dialects.push(0);
+    }
+    smb.extend_from_slice(&(dialects.len() as u16).to_le_bytes());
+    smb.extend(dialects);
+
+    let mut packet = vec![0x00];
+    packet.extend_from_slice(&(smb.len() as u32).to_be_bytes()[1..]);
+    packet.extend(smb);
+
+    let mut info = HashMap::new();
+    let Some(raw) = send_and_read(ip, port, &packet, timeout_ms, 512).await else {
+        return info;
+    };
+    match raw.get(4..8) {
+        Some([0xFF, b'S', b'M', b'B']) => {
+            info.insert("smb_dialect".to_string(), "SMB1".to_string());
+        }
+        Some([0xFE, b'S', b'M', b'B']) => {
+            let dialect = raw
+                .get(72..74)
+                .map(|d| u16::from_le_bytes([d[0], d[1]]))
+                .unwrap_or(0);
+            let name = match dialect {
+                0x0202 => "SMB 2.0.2".to_string(),
+                0x02FF => "SMB2+".to_string(),
+                other => format!("SMB 0x{:04X}", other),
+            };
+            info.insert("smb_dialect".to_string(), name);
+        }
+        _ => {}
+    }
+    info
+}
+
+/// Passive banner grab (read what the server sends on connect)
+pub async fn banner_probe(ip: &str, port: u16, timeout_ms: u64) -> HashMap<String, String> {
+    let mut info = HashMap::new();
+    if let Some(raw) = send_and_read(ip, port, &[], timeout_ms, 1024).await {
+        let banner = clean_banner(&raw);
+        if !banner.is_empty() {
+            info.insert("banner".to_string(), banner);
+        }
+    }
+    info
+}
+
+/// Well-known service name for a port
+pub fn service_name(port: u16) -> &'static str {
+    match port {
+        21 => "ftp",
+        22 => "ssh",
+        23 => "telnet",
+        25 => "smtp",
+        53 => "domain",
+        80 => "http",
+        110 => "pop3",
+        111 => "rpcbind",
+        135 => "msrpc",
+        139 => "netbios-ssn",
+        143 => "imap",
+        389 => "ldap",
+        443 => "https",
+        445 => "microsoft-ds",
+        465 => "submissions",
+        587 => "submission",
+        636 => "ldaps",
+        873 => "rsync",
+        993 => "imaps",
+        995 => "pop3s",
+        1433 => "ms-sql-s",
+        1723 => "pptp",
+        2049 => "nfs",
+        3306 => "mysql",
+        3389 => "ms-wbt-server",
+        5060 => "sip",
+        5432 => "postgresql",
+        5672 => "amqp",
+        5900 => "vnc",
+        6379 => "redis",
+        8080 => "http-proxy",
+        8443 => "https-alt",
+        11211 => "memcache",
+        27017 => "mongodb",
+        _ => "unknown",
+    }
+}
+
+/// Run the probes appropriate for an open port
+async fn probe_service(ip: &str, port: u16, timeout_ms: u64, grab_ssl: bool) -> HashMap<String, String> {
+    let mut info = HashMap::new();
+    info.insert("service".to_string(), service_name(port).to_string());
+
+    match port {
+        80 | 8080 => info.extend(http_probe(ip, port, timeout_ms).await),
+        443 | 8443 => {
+            // Plain HTTP to a TLS port usually still yields a Server header
+            info.extend(http_probe(ip, port, timeout_ms).await);
+            if grab_ssl {
+                info.extend(tls_probe(ip, port, timeout_ms).await);
+            }
+        }
+        445 => info.extend(smb_probe(ip, port, timeout_ms).await),
+        _ => info.extend(banner_probe(ip, port, timeout_ms).await),
+    }
+
+    // HTTP probes keep only the status and Server header; rebuild enough of
+    // the response head for the banner patterns
+    let response = match (info.get("banner"), info.get("http_status")) {
+        (Some(banner), _) => banner.clone(),
+        (None, Some(status)) => format!(
+            "HTTP/1.0 {}\nServer: {}",
+            status,
+            info.get("server").map(String::as_str).unwrap_or_default()
+        ),
+        (None, None) => String::new(),
+    };
+    let tls = info.get("tls").is_some_and(|tls| tls == "true");
+    info.extend(detect_service(port, &response, tls));
+    info
+}
+
+/// Enrich a single host: find open ports, then run the matching probe on
+/// each (HTTP, banner, TLS, SMB, or generic banner)
+///
+/// Each port's `service`, `product`, `version` and `confidence` come from
+/// `detect_services_from_banners` applied to what the probes read.
+///
+/// Values are passed through `set_redaction_patterns` before they are
+/// returned. `force=True` scans a host outside `set_authorized_scopes`.
+#[pyfunction]
+#[pyo3(signature = (ip, timeout_ms, ports, max_concurrent=32, grab_ssl=true, force=false))]
+pub fn scan_host_services(
+    py: Python,
+    ip: &str,
+    timeout_ms: u64,
+    ports: Vec<u16>,
+    max_concurrent: usize,
+    grab_ssl: bool,
+    force: bool,
+) -> PyResult<HashMap<u16, HashMap<String, String>>> {
+    let addr = ip.trim().parse::<IpAddr>().map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IP: {}", e))
+    })?.to_string();
+    authorize_targets(py, "scan_host_services", &[&addr], force)?;
+    let ports = checked_ports(py, ports)?;
+
+    let services = py.allow_threads(|| {
+        runtime().block_on(async {
+            let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+            let mut handles = Vec::new();
+
+            for port in ports {
+                let addr = addr.clone();
+                let sem = semaphore.clone();
+                handles.push(tokio::spawn(async move {
+                    let _permit = sem.acquire_owned().await.ok()?;
+                    let (state, rtt) = tcp_probe_state(&addr, port, timeout_ms).await;
+                    if state != PortState::Open {
+                        return None;
+                    }
+                    let mut info = probe_service(&addr, port, timeout_ms, grab_ssl).await;
+                    for value in info.values_mut() {
+                        *value = redact(value);
+                    }
+                    info.insert("state".to_string(), state.as_str().to_string());
+                    if let Some(rtt) = rtt {
+                        info.insert("response_time_ms".to_string(), format!("{:.2}", rtt));
+                    }
+                    Some((port, info))
+                }));
+            }
+
+            let mut services = HashMap::new();
+            for handle in handles {
+                if let Ok(Some((port, info))) = handle.await {
+                    services.insert(port, info);
+                }
+            }
+            services
+        })
+    });
+
+    Ok(services)
+}
+
+// =============================================================================
+// Open Redirect Check
+// =============================================================================
+
+/// Query parameters web apps commonly take a post-login / post-action
+/// destination from
+const REDIRECT_PARAMS: &[&str] = &[
+    "url", "next", "redirect", "redirect_uri", "redirect_url", "redirectUrl", "return",
+    "return_to", "returnTo", "return_url", "returnUrl", "goto", "dest", "destination",
+    "continue", "target", "rurl", "forward", "to", "out",
+];
+
+/// Destination planted in the redirect parameter
+const REDIRECT_TARGET_HOST: &str = "example.com";
+
+/// Host part of a Location header value; None for relative locations
+fn location_host(location: &str) -> Option<String> {
+    let location = location.trim();
+    let rest = match location.split_once("://") {
+        Some((scheme, rest)) if scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c)) => rest,
+        _ => location.strip_prefix("//")?,
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    let host = host.split(':').next().unwrap_or(host);
+    (!host.is_empty()).then(|| host.trim_end_matches('.').to_lowercase())
+}
+
+/// "ip:port", with IPv6 addresses bracketed, as a Host header wants it
+fn host_port(ip: &str, port: u16) -> String {
+    match ip.parse::<IpAddr>() {
+        Ok(IpAddr::V6(_)) => format!("[{}]:{}", ip, port),
+        _ => format!("{}:{}", ip, port),
+    }
+}
+
+/// Whether a GET with `param` set to the planted URL is answered with a
+/// 3xx whose Location points at the planted host
+async fn redirects_offsite(ip: &str, port: u16, tls: bool, param: &str, timeout_ms: u64) -> bool {
+    let host = host_port(ip, port);
+    let request = format!(
+        "GET /?{}=https://{}/ HTTP/1.0\r\nHost: {}\r\nUser-Agent: netscan\r\nConnection: close\r\n\r\n",
+        param, REDIRECT_TARGET_HOST, host
+    );
+    let Some(raw) = send_and_read_over(ip, port, tls, request.as_bytes(), timeout_ms, 4096).await else {
+        return false;
+    };
+    let text = String::from_utf8_lossy(&raw);
+    let mut lines = text.lines();
+    let redirected = lines
+        .next()
+        .filter(|status| status.starts_with("HTTP/"))
+        .and_then(|status| status.split_whitespace().nth(1))
+        .is_some_and(|code| code.starts_with('3'));
+    redirected
+        && lines
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.split_once(':'))
+            .filter(|(name, _)| name.trim().eq_ignore_ascii_case("location"))
+            .filter_map(|(_, value)| location_host(value))
+            .any(|h| h == REDIRECT_TARGET_HOST || h.ends_with(&format!(".{}", REDIRECT_TARGET_HOST)))
+}
+
+/// First parameter in REDIRECT_PARAMS the server redirects off-site on
+async fn find_open_redirect(ip: &str, port: u16, tls: bool, timeout_ms: u64) -> Option<String> {
+    for param in REDIRECT_PARAMS {
+        if redirects_offsite(ip, port, tls, param, timeout_ms).await {
+            return Some(param.to_string());
+        }
+    }
+    None
+}
+
+/// Check an HTTP service for an open redirect
+///
+/// Requests "/?<param>=https://example.com/" for each common redirect
+/// parameter (url, next, redirect, return_to, goto, ...) and returns the
+/// first one answered with a 3xx whose Location points to example.com, or
+/// None. Relative redirects don't count. `use_tls=True` speaks HTTPS,
+/// accepting any certificate. `force=True` checks a host outside
+/// `set_authorized_scopes`.
+#[pyfunction]
+#[pyo3(signature = (ip, port, use_tls=false, timeout_ms=3000, force=false))]
+pub fn check_open_redirect(
+    py: Python,
+    ip: &str,
+    port: u16,
+    use_tls: bool,
+    timeout_ms: u64,
+    force: bool,
+) -> PyResult<Option<String>> {
+    let addr = ip.trim().parse::<IpAddr>().map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IP: {}", e))
+    })?.to_string();
+    authorize_targets(py, "check_open_redirect", &[&addr], force)?;
+    Ok(py.allow_threads(|| runtime().block_on(find_open_redirect(&addr, port, use_tls, timeout_ms))))
+}
+
+/// `check_open_redirect` over many (ip, port, use_tls) services at once;
+/// returns (ip, parameter) for each vulnerable one, in input order
+#[pyfunction]
+#[pyo3(signature = (hosts, timeout_ms=3000, max_concurrent=32, force=false))]
+pub fn check_open_redirect_batch(
+    py: Python,
+    hosts: Vec<(String, u16, bool)>,
+    timeout_ms: u64,
+    max_concurrent: usize,
+    force: bool,
+) -> PyResult<Vec<(String, String)>> {
+    let mut targets = Vec::with_capacity(hosts.len());
+    for (ip, port, use_tls) in hosts {
+        let addr = ip.trim().parse::<IpAddr>().map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IP '{}': {}", ip, e))
+        })?;
+        targets.push((addr.to_string(), port, use_tls));
+    }
+    let ips: Vec<&str> = targets.iter().map(|(ip, _, _)| ip.as_str()).collect();
+    authorize_targets(py, "check_open_redirect_batch", &ips, force)?;
+
+    let found = py.allow_threads(|| {
+        runtime().block_on(async {
+            let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+            let handles: Vec<_> = targets
+                .into_iter()
+                .map(|(ip, port, tls)| {
+                    let sem = semaphore.clone();
+                    tokio::spawn(async move {
+                        let _permit = sem.acquire_owned().await.ok()?;
+                        let param = find_open_redirect(&ip, port, tls, timeout_ms).await?;
+                        Some((ip, param))
+                    })
+                })
+                .collect();
+            let mut found = Vec::new();
+            for handle in handles {
+                if let Ok(Some(hit)) = handle.await {
+                    found.push(hit);
+                }
+            }
+            found
+        })
+    });
+    Ok(found)
+}
+
+// =============================================================================
+// HTTP Page Titles
+// =============================================================================
+
+/// Body bytes searched for the title
+const TITLE_BODY_BYTES: usize = 4096;
+/// Room for the status line and headers ahead of the body
+const TITLE_HEADER_BYTES: usize = 8192;
+
+/// Text of the first <title> element in `body`, whitespace collapsed
+fn html_title(body: &[u8]) -> String {
+    static TITLE: OnceLock<Regex> = OnceLock::new();
+    let title = TITLE.get_or_init(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title\s*>").unwrap());
+    let body = String::from_utf8_lossy(body);
+    title
+        .captures(&body)
+        .map(|caps| caps[1].split_whitespace().collect::<Vec<_>>().join(" "))
+        .unwrap_or_default()
+}
+
+/// GET / and the page title; empty when the fetch fails or the page has none
+async fn http_title(ip: &str, port: u16, tls: bool, timeout_ms: u64) -> String {
+    let request = format!(
+        "GET / HTTP/1.0\r\nHost: {}\r\nUser-Agent: netscan\r\nAccept: text/html\r\nConnection: close\r\n\r\n",
+        host_port(ip, port)
+    );
+    let max_bytes = TITLE_HEADER_BYTES + TITLE_BODY_BYTES;
+    let Some(raw) = send_and_read_over(ip, port, tls, request.as_bytes(), timeout_ms, max_bytes).await else {
+        return String::new();
+    };
+    if !raw.starts_with(b"HTTP/") {
+        return String::new();
+    }
+    let Some(body_start) = raw.windows(4).position(|w| w == b"\r\n\r\n").map(|i| i + 4) else {
+        return String::new();
+    };
+    let body = &raw[body_start..];
+    html_title(&body[..body.len().min(TITLE_BODY_BYTES)])
+}
+
+/// HTML page titles of web services: {"ip:port": title}
+///
+/// Fetches "/" from each (ip, port) over HTTP/1.0 (HTTPS with
+/// `use_tls=True`, accepting any certificate), at most
+/// `max_concurrent` at a time, and takes the first <title> in the first
+/// 4096 bytes of the body (a regex, not an HTML parser). Every service gets
+/// an entry; failed fetches and pages without a title map to "". IPv6 keys
+/// are bracketed ("[::1]:80"). Redirects are not followed. Hosts outside
+/// `set_authorized_scopes` are refused unless `force=True`.
+#[pyfunction]
+#[pyo3(signature = (hosts, use_tls=false, timeout_ms=3000, max_concurrent=32, force=false))]
+pub fn batch_http_title_grab(
+    py: Python,
+    hosts: Vec<(String, u16)>,
+    use_tls: bool,
+    timeout_ms: u64,
+    max_concurrent: usize,
+    force: bool,
+) -> PyResult<HashMap<String, String>> {
+    let mut targets = Vec::with_capacity(hosts.len());
+    for (ip, port) in hosts {
+        let addr = ip.trim().parse::<IpAddr>().map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IP '{}': {}", ip, e))
+        })?;
+        targets.push((addr.to_string(), port));
+    }
+    let ips: Vec<&str> = targets.iter().map(|(ip, _)| ip.as_str()).collect();
+    authorize_targets(py, "batch_http_title_grab", &ips, force)?;
+
+    let titles = py.allow_threads(|| {
+        runtime().block_on(async {
+            let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+            let handles: Vec<_> = targets
+                .into_iter()
+                .map(|(ip, port)| {
+                    let sem = semaphore.clone();
+                    tokio::spawn(async move {
+                        let key = host_port(&ip, port);
+                        let Ok(_permit) = sem.acquire_owned().await else {
+                            return (key, String::new());
+                        };
+                        (key, http_title(&ip, port, use_tls, timeout_ms).await)
+                    })
+                })
+                .collect();
+            let mut titles = HashMap::new();
+            for handle in handles {
+                if let Ok((key, title)) = handle.await {
+                    titles.insert(key, title);
+                }
+            }
+            titles
+        })
+    });
+    Ok(titles)
+}
+
+// =============================================================================
+// Custom Send Probes
+// =============================================================================
+//
+// Services that wait for a client greeting (MySQL, PostgreSQL, and most
+// ICS/SCADA protocols such as Modbus/TCP and BACnet) stay silent to a
+// passive banner grab; these send caller-supplied bytes first.
+
+/// Largest response buffer a caller can ask for
+const MAX_SEND_PROBE_RESPONSE: usize = 1 << 20;
+
+fn probe_addr(ip: &str) -> PyResult<String> {
+    ip.trim().parse::<IpAddr>().map(|addr| addr.to_string()).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IP '{}': {}", ip, e))
+    })
+}
+
+/// Connect, send `probe_bytes` and return up to `max_response_bytes` of the
+/// response as bytes
+///
+/// None when the connection or the send fails; b"" when the service
+/// accepted the probe but answered nothing within the timeout. An empty
+/// probe sends nothing and just reads. Responses are capped at 1 MiB.
+/// A Modbus/TCP "read holding register 0" is
+/// b"\x00\x00\x00\x00\x00\x06\x01\x03\x00\x00\x00\x01". `force=True`
+/// probes a host outside `set_authorized_scopes`.
+#[pyfunction]
+#[pyo3(signature = (ip, port, probe_bytes, timeout_ms=3000, max_response_bytes=4096, force=false))]
+pub fn tcp_probe_with_send(
+    py: Python,
+    ip: &str,
+    port: u16,
+    probe_bytes: &[u8],
+    timeout_ms: u64,
+    max_response_bytes: usize,
+    force: bool,
+) -> PyResult<Option<PyObject>> {
+    let addr = probe_addr(ip)?;
+    authorize_targets(py, "tcp_probe_with_send", &[&addr], force)?;
+    let max_bytes = max_response_bytes.min(MAX_SEND_PROBE_RESPONSE);
+    let response = py.allow_threads(|| {
+        runtime().block_on(send_and_read(&addr, port, probe_bytes, timeout_ms, max_bytes))
+    });
+    Ok(response.map(|raw| PyBytes::new(py, &raw).to_object(py)))
+}
+
+/// `tcp_probe_with_send` for text protocols: the probe is sent as UTF-8
+/// and the response decoded with invalid bytes replaced
+#[pyfunction]
+#[pyo3(signature = (ip, port, probe_str, timeout_ms=3000, max_response_bytes=4096, force=false))]
+pub fn tcp_probe_with_send_str(
+    py: Python,
+    ip: &str,
+    port: u16,
+    probe_str: &str,
+    timeout_ms: u64,
+    max_response_bytes: usize,
+    force: bool,
+) -> PyResult<Option<String>> {
+    let addr = probe_addr(ip)?;
+    authorize_targets(py, "tcp_probe_with_send_str", &[&addr], force)?;
+    let max_bytes = max_response_bytes.min(MAX_SEND_PROBE_RESPONSE);
+    let response = py.allow_threads(|| {
+        runtime().block_on(send_and_read(&addr, port, probe_str.as_bytes(), timeout_ms, max_bytes))
+    });
+    Ok(response.map(|raw| String::from_utf8_lossy(&raw).into_owned()))
+}
+
+/// `tcp_probe_with_send` against many (ip, port) services at once, at most
+/// `max_concurrent` at a time: {"ip:port": bytes or None}
+///
+/// Every service gets an entry; IPv6 keys are bracketed ("[::1]:502").
+#[pyfunction]
+#[pyo3(signature = (hosts, probe_bytes, timeout_ms=3000, max_response_bytes=4096, max_concurrent=32, force=false))]
+pub fn tcp_probe_with_send_batch(
+    py: Python,
+    hosts: Vec<(String, u16)>,
+    probe_bytes: &[u8],
+    timeout_ms: u64,
+    max_response_bytes: usize,
+    max_concurrent: usize,
+    force: bool,
+) -> PyResult<HashMap<String, Option<PyObject>>> {
+    let mut targets = Vec::with_capacity(hosts.len());
+    for (ip, port) in hosts {
+        targets.push((probe_addr(&ip)?, port));
+    }
+    let ips: Vec<&str> = targets.iter().map(|(ip, _)| ip.as_str()).collect();
+    authorize_targets(py, "tcp_probe_with_send_batch", &ips, force)?;
+    let max_bytes = max_response_bytes.min(MAX_SEND_PROBE_RESPONSE);
+    let payload: Arc<[u8]> = Arc::from(probe_bytes);
+
+    let responses = py.allow_threads(|| {
+        runtime().block_on(async {
+            let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+            let handles: Vec<_> = targets
+                .into_iter()
+                .map(|(ip, port)| {
+                    let sem = semaphore.clone();
+                    let payload = payload.clone();
+                    tokio::spawn(async move {
+                        let key = host_port(&ip, port);
+                        let Ok(_permit) = sem.acquire_owned().await else {
+                            return (key, None);
+                        };
+                        (key, send_and_read(&ip, port, &payload, timeout_ms, max_bytes).await)
+                    })
+                })
+                .collect();
+            let mut responses = HashMap::new();
+            for handle in handles {
+                if let Ok((key, response)) = handle.await {
+                    responses.insert(key, response);
+                }
+            }
+            responses
+        })
+    });
+    Ok(responses
+        .into_iter()
+        .map(|(key, raw)| (key, raw.map(|raw| PyBytes::new(py, &raw).to_object(py))))
+        .collect())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Serve `response` over TLS to each connection on an ephemeral port,
+    /// with the self-signed certificate in tests/fixtures; each request's
+    /// headers and body are sent down the returned channel
+    pub(crate) fn tls_server(response: &'static str) -> (u16, std::sync::mpsc::Receiver<Vec<u8>>) {
+        use rustls::pki_types::pem::PemObject;
+        use rustls::pki_types::PrivateKeyDer;
+
+        let certs = CertificateDer::pem_file_iter(crate::tests::fixture("tls_test_cert.pem"))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let key = PrivateKeyDer::from_pem_file(crate::tests::fixture("tls_test_key.pem")).unwrap();
+        let config = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .unwrap();
+        let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+        let listener = runtime().block_on(tokio::net::TcpListener::bind("127.0.0.1:0")).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (requests, received) = std::sync::mpsc::channel();
+        runtime().spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                let requests = requests.clone();
+                tokio::spawn(async move {
+                    let Ok(mut tls) = acceptor.accept(stream).await else { return };
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    let complete = |request: &[u8]| {
+                        let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") else {
+                            return false;
+                        };
+                        let headers = String::from_utf8_lossy(&request[..end]).to_lowercase();
+                        let length = headers
+                            .lines()
+                            .find_map(|line| line.strip_prefix("content-length:"))
+                            .and_then(|n| n.trim().parse::<usize>().ok())
+                            .unwrap_or(0);
+                        request.len() >= end + 4 + length
+                    };
+                    while !complete(&request) {
+                        match tls.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buf[..n]),
+                        }
+                    }
+                    let _ = requests.send(request);
+                    let _ = tls.write_all(response.as_bytes()).await;
+                    let _ = tls.shutdown().await;
+                });
+            }
+        });
+        (port, received)
+    }
+
+    #[test]
+    fn title_grab_speaks_tls() {
+        let (port, _) = tls_server("HTTP/1.0 200 OK\r\nContent-Type: text/html\r\n\r\n<html><title>\n Admin  Console </title></html>");
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let hosts = vec![("127.0.0.1".to_string(), port)];
+            let titles = batch_http_title_grab(py, hosts.clone(), true, 2000, 4, true).unwrap();
+            assert_eq!(titles[&format!("127.0.0.1:{}", port)], "Admin Console");
+            // Plain HTTP to the same port gets no page
+            let titles = batch_http_title_grab(py, hosts, false, 500, 4, true).unwrap();
+            assert_eq!(titles[&format!("127.0.0.1:{}", port)], "");
+        });
+    }
+
+    #[test]
+    fn open_redirect_check_speaks_tls() {
+        let (port, _) = tls_server("HTTP/1.0 302 Found\r\nLocation: https://example.com/\r\n\r\n");
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            assert_eq!(check_open_redirect(py, "127.0.0.1", port, true, 2000, true).unwrap(), Some("url".to_string()));
+            let hosts = vec![("127.0.0.1".to_string(), port, true), ("127.0.0.1".to_string(), port, false)];
+            let found = check_open_redirect_batch(py, hosts, 500, 4, true).unwrap();
+            assert_eq!(found, vec![("127.0.0.1".to_string(), "url".to_string())]);
+        });
+    }
+}
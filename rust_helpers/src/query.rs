@@ -0,0 +1,528 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use ipnetwork::IpNetwork;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::scanner::ScanResult;
+
+// =============================================================================
+// Filter Expressions
+// =============================================================================
+//
+// Grammar:
+//   expr       := and_expr ("or" and_expr)*
+//   and_expr   := not_expr ("and" not_expr)*
+//   not_expr   := "not" not_expr | "(" expr ")" | comparison
+//   comparison := field op value
+//   op         := == | != | > | >= | < | <= | ~ (contains) | in
+//
+// Fields: ip, mac, hostname, vendor, status, discovery_method, os,
+// response_time_ms, port_count, last_seen (scan_timestamp), port.
+// `ip in 10.0.0.0/8` tests CIDR membership; `port == 22` / `port in 22,80`
+// test open ports. String comparisons are case-insensitive.
+//
+// Example: status == up and (port in 22,3389 or vendor ~ cisco)
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+    In,
+}
+
+#[derive(Debug, Clone)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Compare { field: String, op: Op, value: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Op(Op),
+    LParen,
+    RParen,
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => { tokens.push(Token::LParen); i += 1; }
+            ')' => { tokens.push(Token::RParen); i += 1; }
+            '~' => { tokens.push(Token::Op(Op::Contains)); i += 1; }
+            '=' | '!' | '<' | '>' => {
+                let next = chars.get(i + 1).copied();
+                let (op, width) = match (c, next) {
+                    ('=', Some('=')) => (Op::Eq, 2),
+                    ('=', _) => (Op::Eq, 1),
+                    ('!', Some('=')) => (Op::Ne, 2),
+                    ('>', Some('=')) => (Op::Ge, 2),
+                    ('>', _) => (Op::Gt, 1),
+                    ('<', Some('=')) => (Op::Le, 2),
+                    ('<', _) => (Op::Lt, 1),
+                    _ => return Err(format!("Unexpected '{}' at position {}", c, i)),
+                };
+                tokens.push(Token::Op(op));
+                i += width;
+            }
+            '"' | '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&ch| ch == c)
+                    .ok_or_else(|| format!("Unterminated string at position {}", i))?;
+                tokens.push(Token::Word(chars[i + 1..i + 1 + end].iter().collect()));
+                i += end + 2;
+            }
+            _ => {
+                let start = i;
+                while i < chars.len()
+                    && !chars[i].is_whitespace()
+                    && !matches!(chars[i], '(' | ')' | '=' | '!' | '<' | '>' | '~' | '"' | '\'')
+                {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                if word.eq_ignore_ascii_case("in") {
+                    tokens.push(Token::Op(Op::In));
+                } else {
+                    tokens.push(Token::Word(word));
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, String> {
+        let mut left = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            let right = self.parse_and()?;
+            left = Filter::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, String> {
+        let mut left = self.parse_not()?;
+        while self.peek_keyword("and") {
+            self.pos += 1;
+            let right = self.parse_not()?;
+            left = Filter::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Filter, String> {
+        if self.peek_keyword("not") {
+            self.pos += 1;
+            return Ok(Filter::Not(Box::new(self.parse_not()?)));
+        }
+        if self.tokens.get(self.pos) == Some(&Token::LParen) {
+            self.pos += 1;
+            let inner = self.parse_or()?;
+            if self.tokens.get(self.pos) != Some(&Token::RParen) {
+                return Err("Expected ')'".to_string());
+            }
+            self.pos += 1;
+            return Ok(inner);
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Filter, String> {
+        let field = match self.tokens.get(self.pos) {
+            Some(Token::Word(w)) => w.to_lowercase(),
+            other => return Err(format!("Expected field name, found {:?}", other)),
+        };
+        if !FIELDS.contains(&field.as_str()) {
+            return Err(format!("Unknown field '{}'", field));
+        }
+        let op = match self.tokens.get(self.pos + 1) {
+            Some(Token::Op(op)) => *op,
+            other => return Err(format!("Expected operator after '{}', found {:?}", field, other)),
+        };
+        let value = match self.tokens.get(self.pos + 2) {
+            Some(Token::Word(w)) => w.clone(),
+            other => return Err(format!("Expected value after '{}', found {:?}", field, other)),
+        };
+        self.pos += 3;
+        Ok(Filter::Compare { field, op, value })
+    }
+}
+
+const FIELDS: &[&str] = &[
+    "ip", "mac", "hostname", "vendor", "status", "discovery_method", "os",
+    "response_time_ms", "port_count", "last_seen", "scan_timestamp", "port",
+];
+
+/// Compile a filter expression
+pub fn compile_filter(expr: &str) -> Result<Filter, String> {
+    let tokens = tokenize(expr)?;
+    if tokens.is_empty() {
+        return Err("Empty filter expression".to_string());
+    }
+    let mut parser = Parser { tokens, pos: 0 };
+    let filter = parser.parse_or()?;
+    if parser.pos != parser.tokens.len() {
+        return Err(format!("Unexpected trailing input in filter: {:?}", &parser.tokens[parser.pos..]));
+    }
+    Ok(filter)
+}
+
+fn compare_f64(actual: f64, op: Op, value: &str) -> bool {
+    let Ok(expected) = value.parse::<f64>() else {
+        return false;
+    };
+    match op {
+        Op::Eq => actual == expected,
+        Op::Ne => actual != expected,
+        Op::Gt => actual > expected,
+        Op::Ge => actual >= expected,
+        Op::Lt => actual < expected,
+        Op::Le => actual <= expected,
+        Op::Contains | Op::In => false,
+    }
+}
+
+fn compare_str(actual: &str, op: Op, value: &str) -> bool {
+    let actual = actual.to_lowercase();
+    let value = value.to_lowercase();
+    match op {
+        Op::Eq => actual == value,
+        Op::Ne => actual != value,
+        Op::Contains => actual.contains(&value),
+        Op::In => value.split(',').any(|v| v.trim() == actual),
+        Op::Gt => actual > value,
+        Op::Ge => actual >= value,
+        Op::Lt => actual < value,
+        Op::Le => actual <= value,
+    }
+}
+
+fn compare_ip(actual: &str, op: Op, value: &str) -> bool {
+    match op {
+        Op::In => {
+            let Ok(addr) = actual.parse::<IpAddr>() else {
+                return false;
+            };
+            value.split(',').any(|cidr| {
+                cidr.trim().parse::<IpNetwork>().map(|n| n.contains(addr)).unwrap_or(false)
+            })
+        }
+        Op::Gt | Op::Ge | Op::Lt | Op::Le => {
+            match (actual.parse::<IpAddr>(), value.parse::<IpAddr>()) {
+                (Ok(a), Ok(b)) => {
+                    let ord = a.cmp(&b);
+                    match op {
+                        Op::Gt => ord == Ordering::Greater,
+                        Op::Ge => ord != Ordering::Less,
+                        Op::Lt => ord == Ordering::Less,
+                        _ => ord != Ordering::Greater,
+                    }
+                }
+                _ => false,
+            }
+        }
+        _ => compare_str(actual, op, value),
+    }
+}
+
+fn compare_ports(ports: &[u16], op: Op, value: &str) -> bool {
+    let wanted: Vec<u16> = value.split(',').filter_map(|p| p.trim().parse().ok()).collect();
+    match op {
+        Op::Eq | Op::In | Op::Contains => wanted.iter().any(|p| ports.contains(p)),
+        Op::Ne => wanted.iter().all(|p| !ports.contains(p)),
+        _ => ports.iter().any(|&p| compare_f64(p as f64, op, value)),
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, r: &ScanResult) -> bool {
+        match self {
+            Filter::And(a, b) => a.matches(r) && b.matches(r),
+            Filter::Or(a, b) => a.matches(r) || b.matches(r),
+            Filter::Not(inner) => !inner.matches(r),
+            Filter::Compare { field, op, value } => match field.as_str() {
+                "ip" => compare_ip(&r.ip, *op, value),
+                "mac" => compare_str(&r.mac, *op, &crate::normalize_mac(value)),
+                "hostname" => compare_str(&r.hostname, *op, value),
+                "vendor" => compare_str(&r.vendor, *op, value),
+                "status" => compare_str(&r.status, *op, value),
+                "discovery_method" => compare_str(&r.discovery_method, *op, value),
+                "os" => compare_str(&r.os, *op, value),
+                "response_time_ms" => compare_f64(r.response_time_ms, *op, value),
+                "port_count" => compare_f64(r.open_ports.len() as f64, *op, value),
+                "last_seen" | "scan_timestamp" => compare_f64(r.scan_timestamp, *op, value),
+                "port" => compare_ports(&r.open_ports, *op, value),
+                _ => false,
+            },
+        }
+    }
+}
+
+pub fn compile_filter_py(expr: &str) -> PyResult<Filter> {
+    compile_filter(expr).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid filter: {}", e))
+    })
+}
+
+/// Keep results matching a filter expression
+#[pyfunction]
+pub fn filter_results(results: Vec<ScanResult>, filter_expr: &str) -> PyResult<Vec<ScanResult>> {
+    let filter = compile_filter_py(filter_expr)?;
+    Ok(results.into_par_iter().filter(|r| filter.matches(r)).collect())
+}
+
+// =============================================================================
+// Sorting and Pagination
+// =============================================================================
+
+/// Numeric IP ordering; unparseable values sort after all addresses
+fn ip_key(ip: &str) -> (u8, Option<IpAddr>, String) {
+    match ip.trim().parse::<IpAddr>() {
+        Ok(addr) => (0, Some(addr), String::new()),
+        Err(_) => (1, None, ip.to_string()),
+    }
+}
+
+fn sort_in_place(results: &mut [ScanResult], by: &str, descending: bool) -> Result<(), String> {
+    let cmp: fn(&ScanResult, &ScanResult) -> Ordering = match by {
+        "ip" => |a, b| ip_key(&a.ip).cmp(&ip_key(&b.ip)),
+        "mac" => |a, b| a.mac.cmp(&b.mac),
+        "hostname" => |a, b| a.hostname.to_lowercase().cmp(&b.hostname.to_lowercase()),
+        "vendor" => |a, b| a.vendor.to_lowercase().cmp(&b.vendor.to_lowercase()),
+        "response_time_ms" => |a, b| a.response_time_ms.total_cmp(&b.response_time_ms),
+        "port_count" | "open_ports" => |a, b| a.open_ports.len().cmp(&b.open_ports.len()),
+        "last_seen" | "scan_timestamp" => |a, b| a.scan_timestamp.total_cmp(&b.scan_timestamp),
+        other => return Err(format!("Unknown sort key '{}'", other)),
+    };
+
+    if descending {
+        results.par_sort_by(|a, b| cmp(b, a));
+    } else {
+        results.par_sort_by(cmp);
+    }
+    Ok(())
+}
+
+/// Sort results by ip (numeric), mac, hostname, vendor, response_time_ms,
+/// port_count, or last_seen
+#[pyfunction]
+#[pyo3(signature = (results, by, descending=false))]
+pub fn sort_results(mut results: Vec<ScanResult>, by: &str, descending: bool) -> PyResult<Vec<ScanResult>> {
+    sort_in_place(&mut results, by, descending).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(e)
+    })?;
+    Ok(results)
+}
+
+/// Return `limit` results starting at `offset`
+#[pyfunction]
+pub fn paginate(results: Vec<ScanResult>, offset: usize, limit: usize) -> Vec<ScanResult> {
+    results.into_iter().skip(offset).take(limit).collect()
+}
+
+/// Filter, sort and paginate in one call; returns
+/// {"total": matched count, "offset", "limit", "results": page}
+#[pyfunction]
+#[pyo3(signature = (results, filter_expr=None, sort_by=None, offset=0, limit=100, descending=false))]
+pub fn query_results(
+    py: Python,
+    results: Vec<ScanResult>,
+    filter_expr: Option<&str>,
+    sort_by: Option<&str>,
+    offset: usize,
+    limit: usize,
+    descending: bool,
+) -> PyResult<HashMap<String, PyObject>> {
+    let mut matched = match filter_expr.map(str::trim).filter(|e| !e.is_empty()) {
+        Some(expr) => filter_results(results, expr)?,
+        None => results,
+    };
+    if let Some(key) = sort_by {
+        sort_in_place(&mut matched, key, descending).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(e)
+        })?;
+    }
+
+    let total = matched.len();
+    let page = paginate(matched, offset, limit);
+
+    let mut map = HashMap::new();
+    map.insert("total".to_string(), total.into_py(py));
+    map.insert("offset".to_string(), offset.into_py(py));
+    map.insert("limit".to_string(), limit.into_py(py));
+    map.insert("results".to_string(), page.into_py(py));
+    Ok(map)
+}
+
+// =============================================================================
+// Lookup
+// =============================================================================
+
+/// Results sorted by numeric IP (stable; the order `find_scan_result_by_ip`
+/// expects)
+#[pyfunction]
+pub fn sort_scan_results_by_ip(mut results: Vec<ScanResult>) -> Vec<ScanResult> {
+    results.par_sort_by_cached_key(|r| ip_key(&r.ip));
+    results
+}
+
+/// Index of the first result for `ip` in a list sorted by IP, by binary search
+///
+/// Raises ValueError if `ip` is not an address or the list is not in
+/// `sort_scan_results_by_ip` order.
+#[pyfunction]
+pub fn find_scan_result_by_ip(results: Vec<ScanResult>, ip: &str) -> PyResult<Option<usize>> {
+    let target = ip_key(ip);
+    if target.1.is_none() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IP: {}", ip)));
+    }
+    let keys: Vec<_> = results.par_iter().map(|r| ip_key(&r.ip)).collect();
+    if let Some(n) = keys.windows(2).position(|w| w[0] > w[1]) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Results are not sorted by IP ({} comes after {}); use sort_scan_results_by_ip first",
+            results[n + 1].ip, results[n].ip
+        )));
+    }
+
+    let index = keys.partition_point(|key| *key < target);
+    Ok((keys.get(index) == Some(&target)).then_some(index))
+}
+
+/// States a port can have in `port_state_detail`
+const PORT_STATES: &[&str] = &["open", "closed", "filtered", "open|filtered"];
+
+fn checked_port_state(state: &str) -> PyResult<String> {
+    let state = state.trim().to_lowercase();
+    if PORT_STATES.contains(&state.as_str()) {
+        Ok(state)
+    } else {
+        Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid port state '{}': expected one of {}",
+            state,
+            PORT_STATES.join(", ")
+        )))
+    }
+}
+
+/// Ports of `result` recorded in `state`, ascending; "open" also takes in
+/// `open_ports`, for results whose scan kept no per-port detail
+fn ports_in_state(result: &ScanResult, state: &str) -> Vec<u16> {
+    let mut ports: Vec<u16> = result
+        .port_state_detail
+        .iter()
+        .filter(|(_, s)| s.eq_ignore_ascii_case(state))
+        .map(|(port, _)| *port)
+        .collect();
+    if state == "open" {
+        ports.extend(&result.open_ports);
+    }
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+/// Ports of `result` in `state` ("open", "closed", "filtered" or
+/// "open|filtered"), ascending; raises ValueError for any other state
+#[pyfunction]
+pub fn get_ports_by_state(result: ScanResult, state: &str) -> PyResult<Vec<u16>> {
+    Ok(ports_in_state(&result, &checked_port_state(state)?))
+}
+
+/// Results with at least one port in `state` (see `get_ports_by_state`)
+///
+/// "open|filtered" is its own state, the one UDP and SYN probes report when
+/// silence could mean either; it does not match "open" or "filtered" ports.
+#[pyfunction]
+pub fn filter_by_port_state(results: Vec<ScanResult>, state: &str) -> PyResult<Vec<ScanResult>> {
+    let state = checked_port_state(state)?;
+    Ok(results.into_par_iter().filter(|r| !ports_in_state(r, &state).is_empty()).collect())
+}
+
+/// Index of the first result whose MAC matches `mac` in any notation
+#[pyfunction]
+pub fn find_scan_result_by_mac(results: Vec<ScanResult>, mac: &str) -> Option<usize> {
+    let target = crate::normalize_mac(mac.trim());
+    if target.is_empty() {
+        return None;
+    }
+    results
+        .iter()
+        .position(|r| !r.mac.trim().is_empty() && crate::normalize_mac(r.mac.trim()) == target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(ip: &str, open_ports: Vec<u16>, detail: &[(u16, &str)]) -> ScanResult {
+        ScanResult {
+            ip: ip.to_string(),
+            open_ports,
+            port_state_detail: detail.iter().map(|(port, state)| (*port, state.to_string())).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ports_are_picked_by_state() {
+        pyo3::prepare_freethreaded_python();
+        let r = result(
+            "10.0.0.1",
+            vec![8080],
+            &[(443, "open"), (22, "OPEN"), (23, "closed"), (161, "open|filtered"), (3389, "filtered")],
+        );
+        assert_eq!(get_ports_by_state(r.clone(), "open").unwrap(), vec![22, 443, 8080]);
+        assert_eq!(get_ports_by_state(r.clone(), " Closed ").unwrap(), vec![23]);
+        assert_eq!(get_ports_by_state(r.clone(), "filtered").unwrap(), vec![3389]);
+        assert_eq!(get_ports_by_state(r.clone(), "open|filtered").unwrap(), vec![161]);
+        let err = get_ports_by_state(r, "half-open").unwrap_err();
+        Python::with_gil(|py| assert!(err.is_instance_of::<pyo3::exceptions::PyValueError>(py)));
+    }
+
+    #[test]
+    fn results_are_filtered_by_port_state() {
+        pyo3::prepare_freethreaded_python();
+        let results = vec![
+            result("10.0.0.1", vec![80], &[]),
+            result("10.0.0.2", vec![], &[(23, "closed")]),
+            result("10.0.0.3", vec![], &[(161, "open|filtered")]),
+            result("10.0.0.4", vec![22], &[(22, "open"), (25, "filtered")]),
+        ];
+        let ips = |state: &str| -> Vec<String> {
+            filter_by_port_state(results.clone(), state).unwrap().into_iter().map(|r| r.ip).collect()
+        };
+        assert_eq!(ips("open"), ["10.0.0.1", "10.0.0.4"]);
+        assert_eq!(ips("closed"), ["10.0.0.2"]);
+        assert_eq!(ips("filtered"), ["10.0.0.4"]);
+        assert_eq!(ips("open|filtered"), ["10.0.0.3"]);
+        assert!(filter_by_port_state(results, "shut").is_err());
+    }
+}
@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use ipnetwork::IpNetwork;
+use parking_lot::Mutex;
+use pyo3::prelude::*;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::classify_ip_addr;
+use crate::scanner::unix_now;
+
+// =============================================================================
+// RDAP Network Ownership (feature "rdap")
+// =============================================================================
+//
+// IANA's bootstrap files map address blocks to the registry (RIR) serving
+// them; the registry's `ip/<addr>` answer describes the covering network.
+// Answers are cached per network, so every other address inside a network
+// already looked up is served from the cache without a request.
+
+const BOOTSTRAP_URLS: [&str; 2] = [
+    "https://data.iana.org/rdap/ipv4.json",
+    "https://data.iana.org/rdap/ipv6.json",
+];
+
+/// One registry network as returned and cached
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct RdapNetwork {
+    start: String,
+    end: String,
+    name: String,
+    handle: String,
+    country: String,
+    abuse_email: String,
+    fetched_at: f64,
+}
+
+impl RdapNetwork {
+    fn contains(&self, ip: IpAddr) -> bool {
+        match (self.start.parse::<IpAddr>(), self.end.parse::<IpAddr>()) {
+            (Ok(start), Ok(end)) => address_key(start) <= address_key(ip) && address_key(ip) <= address_key(end),
+            _ => false,
+        }
+    }
+}
+
+/// Totally ordered form of an address; IPv4 sorts before IPv6
+fn address_key(ip: IpAddr) -> (u8, u128) {
+    match ip {
+        IpAddr::V4(addr) => (4, u32::from(addr) as u128),
+        IpAddr::V6(addr) => (6, u128::from(addr)),
+    }
+}
+
+/// Cache key: the network's CIDR, or "start-end" when the range isn't one
+fn prefix_key(network: &RdapNetwork, cidr: Option<&str>) -> String {
+    cidr.map(str::to_string)
+        .unwrap_or_else(|| format!("{}-{}", network.start, network.end))
+}
+
+fn load_cache(path: &str) -> HashMap<String, RdapNetwork> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(path: &str, cache: &HashMap<String, RdapNetwork>) -> Result<(), String> {
+    // Write then rename so an interrupted save never leaves a torn cache
+    let tmp = format!("{}.tmp", path);
+    let content = serde_json::to_string(cache).map_err(|e| e.to_string())?;
+    std::fs::write(&tmp, content).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+fn get_json(agent: &ureq::Agent, url: &str) -> Result<Value, String> {
+    let body = agent
+        .get(url)
+        .set("Accept", "application/rdap+json, application/json")
+        .call()
+        .map_err(|e| match e {
+            ureq::Error::Status(code, _) => format!("{} returned HTTP {}", url, code),
+            ureq::Error::Transport(t) => format!("{} unreachable: {}", url, t),
+        })?
+        .into_string()
+        .map_err(|e| format!("{} read failed: {}", url, e))?;
+    serde_json::from_str(&body).map_err(|e| format!("{} returned invalid JSON: {}", url, e))
+}
+
+/// (block, registry base URL) pairs from the IANA bootstrap files
+fn load_bootstrap(agent: &ureq::Agent) -> Result<Vec<(IpNetwork, String)>, String> {
+    let mut services = Vec::new();
+    for url in BOOTSTRAP_URLS {
+        let bootstrap = get_json(agent, url)?;
+        for service in bootstrap["services"].as_array().into_iter().flatten() {
+            let (Some(blocks), Some(urls)) = (service[0].as_array(), service[1].as_array()) else {
+                continue;
+            };
+            let urls: Vec<&str> = urls.iter().filter_map(Value::as_str).collect();
+            let Some(base) = urls.iter().find(|u| u.starts_with("https://")).or(urls.first()) else {
+                continue;
+            };
+            for block in blocks.iter().filter_map(Value::as_str) {
+                if let Ok(net) = block.parse::<IpNetwork>() {
+                    services.push((net, base.trim_end_matches('/').to_string()));
+                }
+            }
+        }
+    }
+    Ok(services)
+}
+
+/// Registry for `ip`: the most specific bootstrap block containing it
+fn registry_for(bootstrap: &[(IpNetwork, String)], ip: IpAddr) -> Option<&str> {
+    bootstrap
+        .iter()
+        .filter(|(net, _)| net.contains(ip))
+        .max_by_key(|(net, _)| net.prefix())
+        .map(|(_, base)| base.as_str())
+}
+
+/// First email in an entity's jCard, if any
+fn vcard_email(entity: &Value) -> Option<String> {
+    entity["vcardArray"][1]
+        .as_array()?
+        .iter()
+        .find(|prop| prop[0].as_str() == Some("email"))
+        .and_then(|prop| prop[3].as_str())
+        .map(str::to_string)
+}
+
+/// Email of the first entity with the "abuse" role, searching nested
+/// entities too (ARIN lists abuse contacts under the registrant)
+fn abuse_email(entities: &Value) -> Option<String> {
+    for entity in entities.as_array().into_iter().flatten() {
+        let is_abuse = entity["roles"]
+            .as_array()
+            .is_some_and(|roles| roles.iter().any(|r| r.as_str() == Some("abuse")));
+        if is_abuse {
+            if let Some(email) = vcard_email(entity) {
+                return Some(email);
+            }
+        }
+        if let Some(email) = abuse_email(&entity["entities"]) {
+            return Some(email);
+        }
+    }
+    None
+}
+
+/// Network fields and covering CIDR (from the cidr0 extension) of an answer
+fn parse_network(answer: &Value) -> (RdapNetwork, Option<String>) {
+    let text = |key: &str| answer[key].as_str().unwrap_or_default().to_string();
+    let network = RdapNetwork {
+        start: text("startAddress"),
+        end: text("endAddress"),
+        name: text("name"),
+        handle: text("handle"),
+        country: text("country"),
+        abuse_email: abuse_email(&answer["entities"]).unwrap_or_default(),
+        fetched_at: unix_now(),
+    };
+    let cidr = answer["cidr0_cidrs"].as_array().and_then(|cidrs| {
+        // Only a single CIDR describes the whole range
+        let [cidr] = cidrs.as_slice() else {
+            return None;
+        };
+        let prefix = cidr["v4prefix"].as_str().or(cidr["v6prefix"].as_str())?;
+        Some(format!("{}/{}", prefix, cidr["length"].as_u64()?))
+    });
+    (network, cidr)
+}
+
+type NetworkInfo = HashMap<String, String>;
+
+fn network_info(network: &RdapNetwork, prefix: &str, source: &str) -> NetworkInfo {
+    let mut info = HashMap::new();
+    info.insert("status".to_string(), "ok".to_string());
+    info.insert("classification".to_string(), "public".to_string());
+    info.insert("prefix".to_string(), prefix.to_string());
+    info.insert("name".to_string(), network.name.clone());
+    info.insert("handle".to_string(), network.handle.clone());
+    info.insert("country".to_string(), network.country.clone());
+    info.insert("abuse_email".to_string(), network.abuse_email.clone());
+    info.insert("source".to_string(), source.to_string());
+    info
+}
+
+fn status_info(status: &str, classification: &str, error: Option<String>) -> NetworkInfo {
+    let mut info = HashMap::new();
+    info.insert("status".to_string(), status.to_string());
+    info.insert("classification".to_string(), classification.to_string());
+    if let Some(error) = error {
+        info.insert("error".to_string(), error);
+    }
+    info
+}
+
+fn cached_network(cache: &HashMap<String, RdapNetwork>, ip: IpAddr) -> Option<(String, RdapNetwork)> {
+    cache
+        .iter()
+        .filter(|(_, network)| network.contains(ip))
+        // Narrowest range wins when registries delegate sub-blocks
+        .min_by_key(|(_, network)| {
+            let start = network.start.parse().map(address_key).unwrap_or_default();
+            let end = network.end.parse().map(address_key).unwrap_or_default();
+            end.1.saturating_sub(start.1)
+        })
+        .map(|(prefix, network)| (prefix.clone(), network.clone()))
+}
+
+/// Look up the owning network of public addresses over RDAP
+///
+/// Returns {ip: info}. Public addresses get status "ok" with prefix, name,
+/// handle, country, abuse_email and source ("rdap" or "cache"), or status
+/// "error" with an `error` message. Private, loopback, documentation and
+/// other non-public addresses are never sent anywhere: they get status
+/// "skipped" and their `classify_ip` class. With `cache_path`, answers
+/// are kept in that JSON file keyed by network prefix and reused for every
+/// address the network covers.
+#[pyfunction]
+#[pyo3(signature = (ips, max_concurrent=4, cache_path=None, timeout_ms=10000))]
+pub fn rdap_lookup(
+    py: Python,
+    ips: Vec<String>,
+    max_concurrent: usize,
+    cache_path: Option<&str>,
+    timeout_ms: u64,
+) -> PyResult<HashMap<String, NetworkInfo>> {
+    let mut results: HashMap<String, NetworkInfo> = HashMap::new();
+    let mut public: Vec<(String, IpAddr)> = Vec::new();
+    for ip in ips {
+        let addr: IpAddr = ip.trim().parse().map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IP '{}': {}", ip, e))
+        })?;
+        match classify_ip_addr(addr) {
+            "public" => public.push((ip, addr)),
+            class => {
+                results.insert(ip, status_info("skipped", class, None));
+            }
+        }
+    }
+
+    let cache = Mutex::new(cache_path.map(load_cache).unwrap_or_default());
+    let looked_up = py.allow_threads(|| -> Result<Vec<(String, NetworkInfo)>, String> {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_millis(timeout_ms.max(1)))
+            .build();
+        let uncached = public.iter().any(|(_, addr)| cached_network(&cache.lock(), *addr).is_none());
+        let bootstrap = if uncached { load_bootstrap(&agent)? } else { Vec::new() };
+
+        let next = AtomicUsize::new(0);
+        let found = Mutex::new(Vec::with_capacity(public.len()));
+        std::thread::scope(|scope| {
+            for _ in 0..max_concurrent.clamp(1, public.len().max(1)) {
+                scope.spawn(|| {
+                    while let Some((ip, addr)) = public.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        // Checked per address: a worker may just have cached the covering network
+                        if let Some((prefix, network)) = cached_network(&cache.lock(), *addr) {
+                            found.lock().push((ip.clone(), network_info(&network, &prefix, "cache")));
+                            continue;
+                        }
+                        let info = match registry_for(&bootstrap, *addr) {
+                            None => status_info("error", "public", Some("No RDAP registry for this address".to_string())),
+                            Some(base) => match get_json(&agent, &format!("{}/ip/{}", base, addr)) {
+                                Ok(answer) => {
+                                    let (network, cidr) = parse_network(&answer);
+                                    let prefix = prefix_key(&network, cidr.as_deref());
+                                    if network.contains(*addr) {
+                                        cache.lock().insert(prefix.clone(), network.clone());
+                                    }
+                                    network_info(&network, &prefix, "rdap")
+                                }
+                                Err(e) => status_info("error", "public", Some(e)),
+                            },
+                        };
+                        found.lock().push((ip.clone(), info));
+                    }
+                });
+            }
+        });
+        Ok(found.into_inner())
+    })
+    .map_err(PyErr::new::<pyo3::exceptions::PyIOError, _>)?;
+
+    if let Some(path) = cache_path {
+        save_cache(path, &cache.into_inner()).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot write RDAP cache: {}", e))
+        })?;
+    }
+    results.extend(looked_up);
+    Ok(results)
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::fs;
+use std::net::IpAddr;
+use pyo3::prelude::*;
+
+use crate::scanner::ScanResult;
+
+// =============================================================================
+// Inventory Reconciliation (CMDB join)
+// =============================================================================
+
+type Record = HashMap<String, String>;
+
+/// Canonical form of a join key so formatting differences don't block a match
+fn normalize_key(field: &str, value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    match field {
+        "mac" => {
+            let mac = crate::normalize_mac(value);
+            (mac.len() == 17 && mac != "00:00:00:00:00:00").then_some(mac)
+        }
+        "ip" => value.parse::<IpAddr>().ok().map(|ip| ip.to_string()),
+        // Compare short names: "web01.corp.example." and "WEB01" are the same asset
+        "hostname" => value
+            .trim_end_matches('.')
+            .split('.')
+            .next()
+            .filter(|h| !h.is_empty())
+            .map(str::to_lowercase),
+        _ => Some(value.to_lowercase()),
+    }
+}
+
+/// Split CSV text into rows of fields (double-quoted fields, "" escapes)
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|r| r.iter().any(|f| !f.trim().is_empty()));
+    rows
+}
+
+/// Load a CMDB CSV export; header names are trimmed and lowercased so
+/// "Hostname"/"IP"/"MAC" line up with scan result keys
+#[pyfunction]
+pub fn load_cmdb_csv(filepath: &str) -> PyResult<Vec<Record>> {
+    let text = fs::read_to_string(filepath).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot open file: {}", e))
+    })?;
+    let mut rows = parse_csv(text.trim_start_matches('\u{feff}')).into_iter();
+    let Some(header) = rows.next() else {
+        return Ok(vec![]);
+    };
+    let header: Vec<String> = header.iter().map(|h| h.trim().to_lowercase()).collect();
+
+    Ok(rows
+        .map(|row| {
+            header
+                .iter()
+                .zip(row.iter().chain(std::iter::repeat(&String::new())))
+                .map(|(h, v)| (h.clone(), v.trim().to_string()))
+                .collect()
+        })
+        .collect())
+}
+
+/// Join scan devices against CMDB rows, one key at a time in priority order
+///
+/// Each device and each CMDB row is matched at most once. Returns
+/// (matched, unmatched_devices, unmatched_cmdb); matched devices carry the CMDB
+/// columns under a `cmdb_` prefix and `match_key` naming the key that joined them.
+#[pyfunction]
+#[pyo3(signature = (devices, cmdb_rows, match_on=vec!["mac".to_string(), "ip".to_string(), "hostname".to_string()]))]
+pub fn reconcile(
+    devices: Vec<Record>,
+    cmdb_rows: Vec<Record>,
+    match_on: Vec<String>,
+) -> (Vec<Record>, Vec<Record>, Vec<Record>) {
+    let mut device_match: Vec<Option<(usize, String)>> = vec![None; devices.len()];
+    let mut cmdb_taken = vec![false; cmdb_rows.len()];
+
+    for field in &match_on {
+        let field = field.trim().to_lowercase();
+
+        // Unclaimed CMDB rows by normalized key, in file order
+        let mut index: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, row) in cmdb_rows.iter().enumerate() {
+            if cmdb_taken[i] {
+                continue;
+            }
+            if let Some(key) = row.get(&field).and_then(|v| normalize_key(&field, v)) {
+                index.entry(key).or_default().push(i);
+            }
+        }
+
+        for (d, device) in devices.iter().enumerate() {
+            if device_match[d].is_some() {
+                continue;
+            }
+            let Some(key) = device.get(&field).and_then(|v| normalize_key(&field, v)) else {
+                continue;
+            };
+            let Some(candidates) = index.get(&key) else {
+                continue;
+            };
+            if let Some(&row) = candidates.iter().find(|&&i| !cmdb_taken[i]) {
+                cmdb_taken[row] = true;
+                device_match[d] = Some((row, field.clone()));
+            }
+        }
+    }
+
+    let mut matched = Vec::new();
+    let mut unmatched_devices = Vec::new();
+    for (device, found) in devices.into_iter().zip(device_match) {
+        match found {
+            Some((row, field)) => {
+                let mut merged = device;
+                for (column, value) in &cmdb_rows[row] {
+                    merged.insert(format!("cmdb_{}", column), value.clone());
+                }
+                merged.insert("match_key".to_string(), field);
+                matched.push(merged);
+            }
+            None => unmatched_devices.push(device),
+        }
+    }
+
+    let unmatched_cmdb = cmdb_rows
+        .into_iter()
+        .zip(cmdb_taken)
+        .filter(|(_, taken)| !taken)
+        .map(|(row, _)| row)
+        .collect();
+
+    (matched, unmatched_devices, unmatched_cmdb)
+}
+
+// =============================================================================
+// Scan Result Joins
+// =============================================================================
+//
+// Results are combined with `ScanResult::merge`; the first list's values win
+// where both have one. Output keeps the order of `a`, then unmatched `b`.
+
+/// Join `b` into `a` by IP; records with the same IP are merged, including
+/// duplicates within one list. With `match_mac`, a `b` record whose IP found
+/// nothing joins the `a` record with the same MAC, if that one is still
+/// unmatched. `keep_unmatched` keeps records found in only one list.
+fn join_results(a: Vec<ScanResult>, b: Vec<ScanResult>, match_mac: bool, keep_unmatched: bool) -> Vec<ScanResult> {
+    let ip_key = |r: &ScanResult| normalize_key("ip", &r.ip).unwrap_or_else(|| r.ip.trim().to_string());
+    let mut joined: Vec<ScanResult> = Vec::with_capacity(a.len() + b.len());
+    let mut by_ip: HashMap<String, usize> = HashMap::new();
+    let mut by_mac: HashMap<String, usize> = HashMap::new();
+    let mut from_a = 0;
+
+    for result in a {
+        let key = ip_key(&result);
+        match by_ip.get(&key).filter(|_| !key.is_empty()) {
+            Some(&i) => joined[i].merge(&result),
+            None => {
+                if let Some(mac) = normalize_key("mac", &result.mac) {
+                    by_mac.entry(mac).or_insert(joined.len());
+                }
+                by_ip.insert(key, joined.len());
+                joined.push(result);
+                from_a += 1;
+            }
+        }
+    }
+
+    let mut matched = vec![false; joined.len()];
+    for result in b {
+        let key = ip_key(&result);
+        let by_address = by_ip.get(&key).filter(|_| !key.is_empty()).copied();
+        let by_hardware = || {
+            let mac = normalize_key("mac", &result.mac)?;
+            by_mac.get(&mac).copied().filter(|&i| !matched[i])
+        };
+        match by_address.or_else(|| if match_mac { by_hardware() } else { None }) {
+            Some(i) => {
+                joined[i].merge(&result);
+                if i < from_a {
+                    matched[i] = true;
+                }
+            }
+            None => {
+                by_ip.insert(key, joined.len());
+                joined.push(result);
+                matched.push(false);
+            }
+        }
+    }
+
+    if keep_unmatched {
+        joined
+    } else {
+        joined.into_iter().zip(matched).take(from_a).filter(|(_, m)| *m).map(|(r, _)| r).collect()
+    }
+}
+
+/// Merge an ARP-style scan with a port scan: records pair up by IP first,
+/// then by MAC for records whose IP had no partner. Records without a
+/// partner are kept as they are.
+#[pyfunction]
+pub fn scan_result_merge_by_mac_and_ip(a: Vec<ScanResult>, b: Vec<ScanResult>) -> Vec<ScanResult> {
+    join_results(a, b, true, true)
+}
+
+/// Hosts present in both lists (inner join by IP), merged
+#[pyfunction]
+pub fn scan_result_intersect(a: Vec<ScanResult>, b: Vec<ScanResult>) -> Vec<ScanResult> {
+    join_results(a, b, false, false)
+}
+
+/// Hosts present in either list (outer join by IP), merged where both have one
+#[pyfunction]
+pub fn scan_result_union(a: Vec<ScanResult>, b: Vec<ScanResult>) -> Vec<ScanResult> {
+    join_results(a, b, false, true)
+}
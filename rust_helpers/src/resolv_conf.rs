@@ -0,0 +1,145 @@
+use std::net::IpAddr;
+
+// =============================================================================
+// System Resolver Configuration
+// =============================================================================
+//
+// Where forward lookups go and how short names are qualified, read the way
+// the OS stub resolver reads it: /etc/resolv.conf on Unix, `ipconfig /all`
+// on Windows. Only the parts that change which queries are sent are kept:
+// nameservers, the search list and ndots.
+
+/// glibc's default when resolv.conf sets no ndots
+const DEFAULT_NDOTS: usize = 1;
+/// glibc caps ndots at 15
+const MAX_NDOTS: usize = 15;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvConf {
+    pub nameservers: Vec<IpAddr>,
+    pub search: Vec<String>,
+    pub ndots: usize,
+}
+
+impl Default for ResolvConf {
+    fn default() -> Self {
+        ResolvConf { nameservers: Vec::new(), search: Vec::new(), ndots: DEFAULT_NDOTS }
+    }
+}
+
+fn domain(value: &str) -> Option<String> {
+    let value = value.trim().trim_matches('.').to_lowercase();
+    (!value.is_empty()).then_some(value)
+}
+
+impl ResolvConf {
+    /// Parse resolv.conf content
+    ///
+    /// As in glibc, the last `search` or `domain` line wins and `domain`
+    /// is a one-entry search list; zone ids ("fe80::1%eth0") are dropped.
+    pub fn parse(content: &str) -> Self {
+        let mut conf = ResolvConf::default();
+        for line in content.lines() {
+            let line = line.split(['#', ';']).next().unwrap_or("");
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("nameserver") => {
+                    if let Some(ip) = parts.next().and_then(|a| a.split('%').next()?.parse().ok()) {
+                        conf.nameservers.push(ip);
+                    }
+                }
+                Some("search") => conf.search = parts.filter_map(domain).collect(),
+                Some("domain") => conf.search = parts.next().and_then(domain).into_iter().collect(),
+                Some("options") => {
+                    for option in parts {
+                        if let Some(n) = option.strip_prefix("ndots:").and_then(|n| n.parse::<usize>().ok()) {
+                            conf.ndots = n.min(MAX_NDOTS);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        conf
+    }
+
+    /// Parse `ipconfig /all` output (Windows): "DNS Servers" and "DNS Suffix
+    /// Search List" across all adapters, including their continuation lines
+    pub fn parse_ipconfig(output: &str) -> Self {
+        let mut conf = ResolvConf::default();
+        let mut section = "";
+        for line in output.lines() {
+            let value = match line.split_once(" : ") {
+                Some((label, value)) => {
+                    let label = label.trim_end_matches([' ', '.']).trim();
+                    section = if label.eq_ignore_ascii_case("DNS Servers") {
+                        "nameservers"
+                    } else if label.eq_ignore_ascii_case("DNS Suffix Search List") {
+                        "search"
+                    } else {
+                        ""
+                    };
+                    value
+                }
+                // Continuation lines are indented values under the last label
+                None if line.starts_with(' ') && !line.trim().is_empty() => line,
+                None => {
+                    section = "";
+                    continue;
+                }
+            };
+            match section {
+                "nameservers" => {
+                    if let Some(ip) = value.trim().split('%').next().and_then(|a| a.parse::<IpAddr>().ok()) {
+                        if !conf.nameservers.contains(&ip) {
+                            conf.nameservers.push(ip);
+                        }
+                    }
+                }
+                "search" => {
+                    if let Some(d) = domain(value) {
+                        if !conf.search.contains(&d) {
+                            conf.search.push(d);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        conf
+    }
+
+    /// The running system's configuration; empty (no nameservers) when it
+    /// can't be read
+    pub fn system() -> Self {
+        if cfg!(target_os = "windows") {
+            std::process::Command::new("ipconfig")
+                .arg("/all")
+                .output()
+                .map(|out| ResolvConf::parse_ipconfig(&String::from_utf8_lossy(&out.stdout)))
+                .unwrap_or_default()
+        } else {
+            std::fs::read_to_string("/etc/resolv.conf")
+                .map(|content| ResolvConf::parse(&content))
+                .unwrap_or_default()
+        }
+    }
+
+    /// Fully qualified names to try for `name`, in order
+    ///
+    /// A trailing dot means the name is already absolute. Otherwise a name
+    /// with at least `ndots` dots is tried as given before the search
+    /// domains, and a shorter one after them.
+    pub fn candidates(&self, name: &str) -> Vec<String> {
+        let name = name.trim();
+        if let Some(absolute) = name.strip_suffix('.') {
+            return vec![absolute.to_string()];
+        }
+        let searched = self.search.iter().map(|d| format!("{}.{}", name, d));
+        if name.matches('.').count() >= self.ndots {
+            std::iter::once(name.to_string()).chain(searched).collect()
+        } else {
+            searched.chain(std::iter::once(name.to_string())).collect()
+        }
+    }
+}
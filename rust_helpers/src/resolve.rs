@@ -0,0 +1,426 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use dashmap::DashMap;
+use dns_lookup::LookupErrorKind;
+use pyo3::prelude::*;
+use tokio::sync::Semaphore;
+use tokio::time::timeout;
+
+use crate::scanner::runtime;
+
+// =============================================================================
+// Reverse DNS Cache
+// =============================================================================
+
+#[cfg(unix)]
+const NI_NAMEREQD: i32 = libc::NI_NAMEREQD;
+#[cfg(windows)]
+const NI_NAMEREQD: i32 = 0x04;
+
+/// Resolver calls (getnameinfo / getaddrinfo) allowed on the blocking pool
+/// at once, across every batch
+const MAX_BLOCKING_LOOKUPS: usize = 64;
+
+#[derive(Debug, Clone)]
+struct DnsEntry {
+    /// None records a negative result (NXDOMAIN or repeated timeouts)
+    hostname: Option<String>,
+    expires: Instant,
+}
+
+#[derive(Debug, Clone)]
+struct HostEntry {
+    /// None records a name that did not resolve
+    address: Option<IpAddr>,
+    expires: Instant,
+}
+
+#[derive(Debug, Default)]
+struct DnsCacheInner {
+    entries: DashMap<IpAddr, DnsEntry>,
+    /// Forward (hostname -> address) answers, keyed by lowercase name
+    hosts: DashMap<String, HostEntry>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    negative_hits: AtomicU64,
+}
+
+/// Reverse DNS results with per-entry TTL and negative caching
+///
+/// Clones share the same entries, so one cache can back several Scanners;
+/// the map is sharded, so concurrent lookups don't contend on a single lock.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct DnsCache {
+    inner: Arc<DnsCacheInner>,
+    #[pyo3(get, set)]
+    pub ttl_seconds: u64,
+    #[pyo3(get, set)]
+    pub negative_ttl_seconds: u64,
+}
+
+/// Outcome of one reverse lookup attempt
+enum Lookup {
+    Found(String),
+    NotFound,
+    Transient,
+}
+
+impl DnsCache {
+    /// Cached answer if present and fresh: Some(Some(name)) or Some(None) for
+    /// a negative entry; None means the resolver must be asked
+    fn cached(&self, ip: &IpAddr) -> Option<Option<String>> {
+        let entry = self.inner.entries.get(ip)?;
+        if entry.expires <= Instant::now() {
+            return None;
+        }
+        match &entry.hostname {
+            Some(_) => self.inner.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.inner.negative_hits.fetch_add(1, Ordering::Relaxed),
+        };
+        Some(entry.hostname.clone())
+    }
+
+    fn store(&self, ip: IpAddr, hostname: Option<String>) {
+        let ttl = if hostname.is_some() { self.ttl_seconds } else { self.negative_ttl_seconds };
+        self.inner.entries.insert(ip, DnsEntry {
+            hostname,
+            expires: Instant::now() + Duration::from_secs(ttl),
+        });
+    }
+
+    /// Resolve through the cache; timeouts and SERVFAIL are retried
+    /// `retries` times before being cached as negative
+    pub async fn resolve(&self, ip: IpAddr, timeout_ms: u64, retries: u32) -> Option<String> {
+        if let Some(answer) = self.cached(&ip) {
+            return answer;
+        }
+        self.inner.misses.fetch_add(1, Ordering::Relaxed);
+
+        let mut hostname = None;
+        for _ in 0..=retries {
+            match lookup_once(ip, timeout_ms).await {
+                Lookup::Found(name) => {
+                    hostname = Some(name);
+                    break;
+                }
+                Lookup::NotFound => break,
+                Lookup::Transient => continue,
+            }
+        }
+        self.store(ip, hostname.clone());
+        hostname
+    }
+
+    /// Forward-resolve `name` through the cache, preferring an IPv4 address
+    pub async fn resolve_host(&self, name: &str, timeout_ms: u64) -> Option<IpAddr> {
+        let key = name.trim().trim_end_matches('.').to_lowercase();
+        if let Some(entry) = self.inner.hosts.get(&key) {
+            if entry.expires > Instant::now() {
+                match entry.address {
+                    Some(_) => self.inner.hits.fetch_add(1, Ordering::Relaxed),
+                    None => self.inner.negative_hits.fetch_add(1, Ordering::Relaxed),
+                };
+                return entry.address;
+            }
+        }
+        self.inner.misses.fetch_add(1, Ordering::Relaxed);
+
+        let host = key.clone();
+        let address = match blocking_lookup(resolver_slots(), timeout_ms, move || dns_lookup::lookup_host(&host)).await {
+            Some(Ok(addrs)) => addrs.iter().find(|a| a.is_ipv4()).or(addrs.first()).copied(),
+            _ => None,
+        };
+        let ttl = if address.is_some() { self.ttl_seconds } else { self.negative_ttl_seconds };
+        self.inner.hosts.insert(key, HostEntry {
+            address,
+            expires: Instant::now() + Duration::from_secs(ttl),
+        });
+        address
+    }
+}
+
+/// Process-wide cache used when callers don't supply their own
+pub fn shared_cache() -> &'static DnsCache {
+    static CACHE: OnceLock<DnsCache> = OnceLock::new();
+    CACHE.get_or_init(|| DnsCache::new(3600, 300))
+}
+
+fn resolver_slots() -> &'static Arc<Semaphore> {
+    static SLOTS: OnceLock<Arc<Semaphore>> = OnceLock::new();
+    SLOTS.get_or_init(|| Arc::new(Semaphore::new(MAX_BLOCKING_LOOKUPS)))
+}
+
+/// Run a blocking resolver call on the blocking pool, waiting at most
+/// `timeout_ms` (including the wait for a slot); None on timeout
+///
+/// The libc resolver can't be cancelled, so a call that times out keeps its
+/// thread until the resolver gives up. It also keeps its slot until then,
+/// so a dead resolver ties up at most `slots` threads instead of one more
+/// per lookup.
+async fn blocking_lookup<T, F>(slots: &Arc<Semaphore>, timeout_ms: u64, lookup: F) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let run = async {
+        let permit = slots.clone().acquire_owned().await.ok()?;
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            lookup()
+        })
+        .await
+        .ok()
+    };
+    timeout(Duration::from_millis(timeout_ms), run).await.ok().flatten()
+}
+
+async fn lookup_once(ip: IpAddr, timeout_ms: u64) -> Lookup {
+    let lookup = move || dns_lookup::getnameinfo(&SocketAddr::new(ip, 0), NI_NAMEREQD);
+    match blocking_lookup(resolver_slots(), timeout_ms, lookup).await {
+        Some(Ok((name, _))) if !name.is_empty() => Lookup::Found(name.trim_end_matches('.').to_string()),
+        Some(Err(e)) if matches!(e.kind(), LookupErrorKind::Again) => Lookup::Transient,
+        Some(_) => Lookup::NotFound,
+        None => Lookup::Transient,
+    }
+}
+
+#[pymethods]
+impl DnsCache {
+    #[new]
+    #[pyo3(signature = (ttl_seconds=3600, negative_ttl_seconds=300))]
+    pub fn new(ttl_seconds: u64, negative_ttl_seconds: u64) -> Self {
+        DnsCache {
+            inner: Arc::new(DnsCacheInner::default()),
+            ttl_seconds,
+            negative_ttl_seconds,
+        }
+    }
+
+    /// Lookup counters: hits, misses, negative_hits, entries (reverse),
+    /// host_entries (forward)
+    fn stats(&self) -> HashMap<String, u64> {
+        let mut stats = HashMap::new();
+        stats.insert("hits".to_string(), self.inner.hits.load(Ordering::Relaxed));
+        stats.insert("misses".to_string(), self.inner.misses.load(Ordering::Relaxed));
+        stats.insert("negative_hits".to_string(), self.inner.negative_hits.load(Ordering::Relaxed));
+        stats.insert("entries".to_string(), self.inner.entries.len() as u64);
+        stats.insert("host_entries".to_string(), self.inner.hosts.len() as u64);
+        stats
+    }
+
+    /// Drop all entries and reset the counters
+    fn flush(&self) {
+        self.inner.entries.clear();
+        self.inner.hosts.clear();
+        self.inner.hits.store(0, Ordering::Relaxed);
+        self.inner.misses.store(0, Ordering::Relaxed);
+        self.inner.negative_hits.store(0, Ordering::Relaxed);
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.entries.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "DnsCache(<{} entries>, ttl_seconds={}, negative_ttl_seconds={})",
+            self.inner.entries.len(), self.ttl_seconds, self.negative_ttl_seconds
+        )
+    }
+}
+
+/// Resolve many IPs concurrently through a cache
+pub async fn resolve_many(
+    cache: &DnsCache,
+    ips: Vec<IpAddr>,
+    timeout_ms: u64,
+    retries: u32,
+    max_concurrent: usize,
+) -> HashMap<IpAddr, Option<String>> {
+    let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+    let mut handles = Vec::with_capacity(ips.len());
+
+    for ip in ips {
+        let cache = cache.clone();
+        let sem = semaphore.clone();
+        handles.push(tokio::spawn(async move {
+            let _permit = sem.acquire_owned().await.ok();
+            (ip, cache.resolve(ip, timeout_ms, retries).await)
+        }));
+    }
+
+    let mut names = HashMap::new();
+    for handle in handles {
+        if let Ok((ip, hostname)) = handle.await {
+            names.insert(ip, hostname);
+        }
+    }
+    names
+}
+
+/// Forward-resolve many hostnames concurrently through a cache
+pub async fn resolve_hosts(
+    cache: &DnsCache,
+    names: Vec<String>,
+    timeout_ms: u64,
+    max_concurrent: usize,
+) -> HashMap<String, Option<IpAddr>> {
+    let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+    let mut handles = Vec::with_capacity(names.len());
+
+    for name in names {
+        let cache = cache.clone();
+        let sem = semaphore.clone();
+        handles.push(tokio::spawn(async move {
+            let _permit = sem.acquire_owned().await.ok();
+            let address = cache.resolve_host(&name, timeout_ms).await;
+            (name, address)
+        }));
+    }
+
+    let mut addresses = HashMap::new();
+    for handle in handles {
+        if let Ok((name, address)) = handle.await {
+            addresses.insert(name, address);
+        }
+    }
+    addresses
+}
+
+/// Reverse-resolve IPs in parallel: {ip: hostname or None}
+///
+/// Uses `cache` if given, otherwise a process-wide shared DnsCache. Entries
+/// that aren't IP addresses (a hostname in a device list, say) map to None
+/// without a lookup. At most 64 resolver calls run at once process-wide; a
+/// call that times out holds its slot until the system resolver returns.
+#[pyfunction]
+#[pyo3(signature = (ips, cache=None, timeout_ms=2000, retries=1, max_concurrent=64))]
+pub fn reverse_dns_batch(
+    py: Python,
+    ips: Vec<String>,
+    cache: Option<DnsCache>,
+    timeout_ms: u64,
+    retries: u32,
+    max_concurrent: usize,
+) -> PyResult<HashMap<String, Option<String>>> {
+    let addrs: Vec<Option<IpAddr>> = ips.iter().map(|ip| ip.trim().parse::<IpAddr>().ok()).collect();
+    let cache = cache.unwrap_or_else(|| shared_cache().clone());
+
+    let lookups: Vec<IpAddr> = addrs.iter().flatten().copied().collect();
+    let names = py.allow_threads(|| {
+        runtime().block_on(resolve_many(&cache, lookups, timeout_ms, retries, max_concurrent))
+    });
+
+    Ok(ips
+        .into_iter()
+        .zip(addrs)
+        .map(|(ip, addr)| (ip, addr.and_then(|addr| names.get(&addr).cloned().flatten())))
+        .collect())
+}
+
+/// Forward-resolve `names` concurrently: {name: address}
+///
+/// Each lookup runs through `blocking_lookup` with one of `max_concurrent`
+/// slots, held until the resolver returns even after `timeout_ms` has
+/// given up on it.
+pub fn resolve_names(names: Vec<String>, timeout_ms: u64, max_concurrent: usize) -> HashMap<String, String> {
+    runtime().block_on(async {
+        let slots = Arc::new(Semaphore::new(max_concurrent.max(1)));
+        let mut handles = Vec::with_capacity(names.len());
+        for name in names {
+            let slots = slots.clone();
+            handles.push(tokio::spawn(async move {
+                let host = name.trim().trim_end_matches('.').to_string();
+                if let Ok(ip) = host.parse::<IpAddr>() {
+                    return (name, Some(ip));
+                }
+                let address = match blocking_lookup(&slots, timeout_ms, move || dns_lookup::lookup_host(&host)).await {
+                    Some(Ok(addrs)) => addrs.iter().find(|a| a.is_ipv4()).or(addrs.first()).copied(),
+                    _ => None,
+                };
+                (name, address)
+            }));
+        }
+
+        let mut resolved = HashMap::new();
+        for handle in handles {
+            if let Ok((name, Some(address))) = handle.await {
+                resolved.insert(name, address.to_string());
+            }
+        }
+        resolved
+    })
+}
+
+/// Forward-resolve many names on the Tokio runtime: {name: address}
+///
+/// Lookups run as Tokio tasks, at most `max_concurrent` resolver calls at a
+/// time, each limited to `timeout_ms`. The resolver call is getaddrinfo on
+/// the blocking pool and can't be cancelled, so one that times out keeps
+/// its thread and its slot until it returns: a batch never holds more than
+/// `max_concurrent` threads, and once that many lookups hang the rest time
+/// out waiting for a slot. IPv4 answers are preferred; IP literals map to
+/// themselves and names that fail or time out are left out. Bypasses the
+/// DnsCache.
+#[pyfunction]
+#[pyo3(signature = (ips, timeout_ms=2000, max_concurrent=256))]
+pub fn async_batch_dns_resolve(
+    py: Python,
+    ips: Vec<String>,
+    timeout_ms: u64,
+    max_concurrent: usize,
+) -> PyResult<HashMap<String, String>> {
+    Ok(py.allow_threads(|| resolve_names(ips, timeout_ms, max_concurrent)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hostname_input_maps_to_none() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let ips = vec!["printer.local".to_string(), "not an ip".to_string()];
+            let names = reverse_dns_batch(py, ips, Some(DnsCache::new(60, 60)), 200, 0, 4).unwrap();
+            assert_eq!(names.len(), 2);
+            assert_eq!(names["printer.local"], None);
+            assert_eq!(names["not an ip"], None);
+        });
+    }
+
+    #[test]
+    fn timed_out_lookup_keeps_its_slot_until_it_returns() {
+        let slots = Arc::new(Semaphore::new(2));
+        runtime().block_on(async {
+            let slow = || std::thread::sleep(Duration::from_millis(300));
+            assert_eq!(blocking_lookup(&slots, 20, slow).await, None);
+            assert_eq!(slots.available_permits(), 1);
+            assert_eq!(blocking_lookup(&slots, 20, slow).await, None);
+            // Both slots are held by running lookups; a third can't start
+            assert_eq!(blocking_lookup(&slots, 20, || 1).await, None);
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            assert_eq!(slots.available_permits(), 2);
+            assert_eq!(blocking_lookup(&slots, 200, || 1).await, Some(1));
+        });
+    }
+
+    #[test]
+    fn batch_resolves_names_and_literals() {
+        let names = vec![
+            "localhost".to_string(),
+            " 10.0.0.7 ".to_string(),
+            "::1".to_string(),
+            "nonexistent.invalid".to_string(),
+        ];
+        let resolved = resolve_names(names, 2000, 2);
+        assert_eq!(resolved.get("localhost").map(String::as_str), Some("127.0.0.1"));
+        assert_eq!(resolved[" 10.0.0.7 "], "10.0.0.7");
+        assert_eq!(resolved["::1"], "::1");
+        assert!(!resolved.contains_key("nonexistent.invalid"));
+    }
+}
@@ -0,0 +1,412 @@
+use std::collections::{HashMap, HashSet};
+use std::net::Ipv4Addr;
+use ipnetwork::Ipv4Network;
+use pyo3::prelude::*;
+
+use crate::ipv6::record_mac;
+use crate::scanner::ScanResult;
+
+// =============================================================================
+// Routing Table
+// =============================================================================
+//
+// Linux reads the kernel table from /proc/net/route (same data netlink
+// returns for the main table), macOS/BSD parses `netstat -rn -f inet`, and
+// Windows parses `route print -4`.
+
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub destination: Ipv4Network,
+    /// None for directly connected (on-link) routes
+    pub gateway: Option<Ipv4Addr>,
+    pub interface: String,
+    pub metric: u32,
+}
+
+impl Route {
+    pub fn is_on_link(&self) -> bool {
+        self.gateway.is_none()
+    }
+
+    fn into_py_dict(self, py: Python) -> HashMap<String, PyObject> {
+        let mut map = HashMap::new();
+        map.insert("destination".to_string(), self.destination.to_string().into_py(py));
+        map.insert(
+            "gateway".to_string(),
+            self.gateway.map(|g| g.to_string()).into_py(py),
+        );
+        map.insert("interface".to_string(), self.interface.into_py(py));
+        map.insert("metric".to_string(), self.metric.into_py(py));
+        map
+    }
+}
+
+/// Parse /proc/net/route (hex fields in native byte order)
+pub fn parse_proc_net_route(content: &str) -> Vec<Route> {
+    const RTF_UP: u32 = 0x0001;
+    const RTF_GATEWAY: u32 = 0x0002;
+
+    let hex_addr = |s: &str| u32::from_str_radix(s, 16).ok().map(|n| Ipv4Addr::from(n.to_ne_bytes()));
+
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if cols.len() < 8 {
+                return None;
+            }
+            let flags = u32::from_str_radix(cols[3], 16).ok()?;
+            if flags & RTF_UP == 0 {
+                return None;
+            }
+            let dest = hex_addr(cols[1])?;
+            let gateway = hex_addr(cols[2])?;
+            let mask = hex_addr(cols[7])?;
+            let prefix = u32::from(mask).count_ones() as u8;
+            Some(Route {
+                destination: Ipv4Network::new(dest, prefix).ok()?,
+                gateway: if flags & RTF_GATEWAY != 0 && !gateway.is_unspecified() {
+                    Some(gateway)
+                } else {
+                    None
+                },
+                interface: cols[0].to_string(),
+                metric: cols[6].parse().unwrap_or(0),
+            })
+        })
+        .collect()
+}
+
+/// Parse a BSD-style destination ("default", "192.168.1", "10/8", "10.0.0.5")
+fn parse_bsd_destination(dest: &str) -> Option<Ipv4Network> {
+    if dest == "default" {
+        return Ipv4Network::new(Ipv4Addr::UNSPECIFIED, 0).ok();
+    }
+    let (addr, prefix) = match dest.split_once('/') {
+        Some((a, p)) => (a, Some(p.parse::<u8>().ok()?)),
+        None => (dest, None),
+    };
+    let mut octets: Vec<u8> = addr
+        .split('.')
+        .map(|o| o.parse::<u8>())
+        .collect::<Result<_, _>>()
+        .ok()?;
+    if octets.is_empty() || octets.len() > 4 {
+        return None;
+    }
+    let implied = (octets.len() * 8) as u8;
+    octets.resize(4, 0);
+    let ip = Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3]);
+    Ipv4Network::new(ip, prefix.unwrap_or(implied)).ok()
+}
+
+/// Parse `netstat -rn -f inet` output (macOS / BSD)
+pub fn parse_netstat_routes(output: &str) -> Vec<Route> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if cols.len() < 4 {
+                return None;
+            }
+            let destination = parse_bsd_destination(cols[0])?;
+            // link#N and MAC gateways are on-link entries
+            let gateway = cols[1].parse::<Ipv4Addr>().ok();
+            Some(Route {
+                destination,
+                gateway,
+                interface: cols[3].to_string(),
+                metric: 0,
+            })
+        })
+        .collect()
+}
+
+/// Parse `route print -4` output (Windows)
+pub fn parse_route_print(output: &str) -> Vec<Route> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if cols.len() != 5 {
+                return None;
+            }
+            let dest: Ipv4Addr = cols[0].parse().ok()?;
+            let mask: Ipv4Addr = cols[1].parse().ok()?;
+            let interface: Ipv4Addr = cols[3].parse().ok()?;
+            Some(Route {
+                destination: Ipv4Network::with_netmask(dest, mask).ok()?,
+                gateway: cols[2].parse::<Ipv4Addr>().ok(),
+                interface: interface.to_string(),
+                metric: cols[4].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+fn command_output(cmd: &str, args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new(cmd)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Cannot run {}: {}", cmd, e))?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Read the system IPv4 routing table
+pub fn read_routes() -> Result<Vec<Route>, String> {
+    if cfg!(target_os = "linux") {
+        std::fs::read_to_string("/proc/net/route")
+            .map(|content| parse_proc_net_route(&content))
+            .map_err(|e| format!("Cannot read /proc/net/route: {}", e))
+    } else if cfg!(target_os = "windows") {
+        command_output("route", &["print", "-4"]).map(|out| parse_route_print(&out))
+    } else {
+        command_output("netstat", &["-rn", "-f", "inet"]).map(|out| parse_netstat_routes(&out))
+    }
+}
+
+/// Longest-prefix match (lowest metric breaks ties)
+pub fn lookup_route(routes: &[Route], ip: Ipv4Addr) -> Option<&Route> {
+    routes
+        .iter()
+        .filter(|r| r.destination.contains(ip))
+        .max_by(|a, b| {
+            a.destination
+                .prefix()
+                .cmp(&b.destination.prefix())
+                .then(b.metric.cmp(&a.metric))
+        })
+}
+
+fn routes_or_err() -> PyResult<Vec<Route>> {
+    read_routes().map_err(PyErr::new::<pyo3::exceptions::PyOSError, _>)
+}
+
+/// `ip` as an IPv4 address, or ValueError naming it
+pub(crate) fn parse_ipv4(ip: &str) -> PyResult<Ipv4Addr> {
+    ip.trim().parse().map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IPv4 address '{}': {}", ip, e))
+    })
+}
+
+/// Return the IPv4 routing table as a list of dicts
+/// (destination, gateway, interface, metric)
+#[pyfunction]
+pub fn get_routes(py: Python) -> PyResult<Vec<HashMap<String, PyObject>>> {
+    Ok(routes_or_err()?
+        .into_iter()
+        .map(|r| r.into_py_dict(py))
+        .collect())
+}
+
+/// Longest-prefix match of an IP against the routing table
+#[pyfunction]
+pub fn route_for(py: Python, ip: &str) -> PyResult<Option<HashMap<String, PyObject>>> {
+    let addr = parse_ipv4(ip)?;
+    let routes = routes_or_err()?;
+    Ok(lookup_route(&routes, addr).cloned().map(|r| r.into_py_dict(py)))
+}
+
+/// Routes through a gateway as (gateway, interface, metric), default
+/// routes first, then by metric
+fn gateways(routes: Vec<Route>) -> Vec<(String, String, u32)> {
+    let mut routes: Vec<Route> = routes.into_iter().filter(|r| !r.is_on_link()).collect();
+    routes.sort_by_key(|r| (r.destination.prefix() != 0, r.metric));
+    let mut gateways: Vec<(String, String, u32)> = Vec::new();
+    for route in routes {
+        let entry = (route.gateway.map(|g| g.to_string()).unwrap_or_default(), route.interface, route.metric);
+        if !gateways.contains(&entry) {
+            gateways.push(entry);
+        }
+    }
+    gateways
+}
+
+/// The default route's (gateway_ip, interface); the lowest metric wins
+/// when there are several. Raises OSError when there is no default route.
+///
+/// Reads the same table as `get_routes`: /proc/net/route on Linux, so no
+/// process is spawned there.
+#[pyfunction]
+pub fn find_default_gateway() -> PyResult<(String, String)> {
+    let mut routes = routes_or_err()?;
+    routes.retain(|r| r.destination.prefix() == 0);
+    gateways(routes)
+        .into_iter()
+        .next()
+        .map(|(gateway, interface, _)| (gateway, interface))
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyOSError, _>("No default route"))
+}
+
+/// Every gateway in the routing table as (gateway, interface, metric):
+/// default routes first, then by metric, one entry per distinct triple
+#[pyfunction]
+pub fn find_all_gateways() -> PyResult<Vec<(String, String, u32)>> {
+    Ok(gateways(routes_or_err()?))
+}
+
+/// Choose a discovery method per target: "arp" for directly connected
+/// targets, "tcp" for routed ones
+#[pyfunction]
+pub fn plan_discovery(ips: Vec<String>) -> PyResult<HashMap<String, String>> {
+    let routes = routes_or_err()?;
+    let mut plan = HashMap::with_capacity(ips.len());
+    for ip in ips {
+        let addr = parse_ipv4(&ip)?;
+        let method = match lookup_route(&routes, addr) {
+            Some(route) if route.is_on_link() => "arp",
+            _ => "tcp",
+        };
+        plan.insert(ip, method.to_string());
+    }
+    Ok(plan)
+}
+
+/// Raise ValueError naming the next hop for any target that is not on a
+/// directly connected segment (ARP cannot reach routed targets)
+#[pyfunction]
+pub fn require_local_targets(ips: Vec<String>) -> PyResult<()> {
+    let routes = routes_or_err()?;
+    let mut problems = Vec::new();
+    for ip in &ips {
+        let addr = parse_ipv4(ip)?;
+        match lookup_route(&routes, addr) {
+            Some(route) if route.is_on_link() => {}
+            Some(route) => problems.push(format!(
+                "{} is routed via {} on {}",
+                ip,
+                route.gateway.map(|g| g.to_string()).unwrap_or_default(),
+                route.interface
+            )),
+            None => problems.push(format!("{} has no route", ip)),
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "ARP scanning requires directly connected targets: {}",
+            problems.join("; ")
+        )))
+    }
+}
+
+// =============================================================================
+// Gateway Identification
+// =============================================================================
+//
+// Routers skew device counts: one box answers on every VLAN it routes, and a
+// redundant pair shares a virtual MAC (VRRP, HSRP, GLBP) that follows
+// whichever unit is active. Subnets are taken as /24s, since a scan record
+// carries no netmask.
+
+/// Virtual-router MAC prefixes: (prefix, protocol)
+const VIRTUAL_ROUTER_MACS: &[(&str, &str)] = &[
+    ("00:00:5E:00:01:", "VRRP"),
+    ("00:00:5E:00:02:", "VRRPv6"),
+    ("00:00:0C:07:AC:", "HSRP"),
+    ("00:00:0C:9F:F", "HSRPv2"),
+    ("00:07:B4:00:", "GLBP"),
+];
+
+/// Protocol whose virtual MAC `mac` (normalized) is
+fn virtual_router_protocol(mac: &str) -> Option<&'static str> {
+    VIRTUAL_ROUTER_MACS
+        .iter()
+        .find(|(prefix, _)| mac.starts_with(prefix))
+        .map(|(_, protocol)| *protocol)
+}
+
+/// Default gateways of a `get_routes` style list of dicts
+fn default_gateways(routes: &[&pyo3::types::PyDict]) -> PyResult<Vec<Ipv4Addr>> {
+    let mut gateways = Vec::new();
+    for route in routes {
+        let destination: Option<String> = route.get_item("destination")?.map(|d| d.extract()).transpose()?;
+        let gateway: Option<String> = match route.get_item("gateway")? {
+            Some(gateway) => gateway.extract()?,
+            None => None,
+        };
+        let is_default = destination.is_some_and(|d| d == "0.0.0.0/0" || d == "default");
+        if let (true, Some(gateway)) = (is_default, gateway.and_then(|g| g.trim().parse().ok())) {
+            gateways.push(gateway);
+        }
+    }
+    Ok(gateways)
+}
+
+/// Flag the routers among `devices`
+///
+/// A record is a gateway when its MAC is a virtual-router MAC (VRRP, HSRP,
+/// GLBP), when its MAC answers for IPs in at least `min_subnets` different
+/// /24s (0 turns this check off), or when its IP is a default gateway in `routes` (`get_routes`
+/// output; None reads this host's routing table). Gateways get
+/// `attributes["role"] = "gateway"` and the reasons, comma-separated, in
+/// `attributes["gateway_evidence"]`: "virtual_mac:<protocol>",
+/// "multi_subnet_mac:<n>" and "default_gateway". Devices come back in input
+/// order; use `count_devices` for totals with and without them.
+#[pyfunction]
+#[pyo3(signature = (devices, routes=None, min_subnets=2))]
+pub fn identify_gateways(
+    devices: Vec<ScanResult>,
+    routes: Option<Vec<&pyo3::types::PyDict>>,
+    min_subnets: usize,
+) -> PyResult<Vec<ScanResult>> {
+    let default_gateways = match routes {
+        Some(routes) => default_gateways(&routes)?,
+        None => {
+            let mut routes = routes_or_err()?;
+            routes.retain(|r| r.destination.prefix() == 0);
+            routes.into_iter().filter_map(|r| r.gateway).collect()
+        }
+    };
+
+    let mut subnets: HashMap<String, HashSet<[u8; 3]>> = HashMap::new();
+    for device in &devices {
+        if let (Some(mac), Ok(ip)) = (record_mac(&device.mac), device.ip.trim().parse::<Ipv4Addr>()) {
+            let [a, b, c, _] = ip.octets();
+            subnets.entry(mac).or_default().insert([a, b, c]);
+        }
+    }
+
+    Ok(devices
+        .into_iter()
+        .map(|mut device| {
+            let mac = record_mac(&device.mac);
+            let mut evidence = Vec::new();
+            if let Some(protocol) = mac.as_deref().and_then(virtual_router_protocol) {
+                evidence.push(format!("virtual_mac:{}", protocol));
+            }
+            let spread = mac.and_then(|mac| subnets.get(&mac)).map_or(0, HashSet::len);
+            if min_subnets > 0 && spread >= min_subnets.max(2) {
+                evidence.push(format!("multi_subnet_mac:{}", spread));
+            }
+            if device.ip.trim().parse().is_ok_and(|ip: Ipv4Addr| default_gateways.contains(&ip)) {
+                evidence.push("default_gateway".to_string());
+            }
+            if !evidence.is_empty() {
+                device.attributes.insert("role".to_string(), "gateway".to_string());
+                device.attributes.insert("gateway_evidence".to_string(), evidence.join(","));
+            }
+            device
+        })
+        .collect())
+}
+
+/// Whether `identify_gateways` marked `device` as network infrastructure
+pub fn is_infrastructure(device: &ScanResult) -> bool {
+    device.attributes.get("role").is_some_and(|role| role == "gateway")
+}
+
+/// Device totals: {"total", "infrastructure", "endpoints"}, infrastructure
+/// being the records `identify_gateways` flagged
+#[pyfunction]
+pub fn count_devices(devices: Vec<ScanResult>) -> HashMap<String, usize> {
+    let infrastructure = devices.iter().filter(|d| is_infrastructure(d)).count();
+    HashMap::from([
+        ("total".to_string(), devices.len()),
+        ("infrastructure".to_string(), infrastructure),
+        ("endpoints".to_string(), devices.len() - infrastructure),
+    ])
+}
@@ -1,14 +1,26 @@
 use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream};
 use std::time::{Duration, Instant};
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::net::TcpStream as AsyncTcpStream;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpSocket, TcpStream as AsyncTcpStream};
 use tokio::time::timeout;
 use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 use pyo3::prelude::*;
+use pyo3::types::PyIterator;
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::cache::ScanCache;
+use crate::custom_probes::{run_custom_probes, CustomProbe};
+use crate::monitor::{ScanProgress, ScanRateMonitor, TrafficStats};
+use crate::resolve::{resolve_many, shared_cache, DnsCache};
+use crate::scope::{authorize_iterable, authorize_targets, CidrSet};
+use crate::targets::{check_interleave, expand_targets, interleave_targets, shuffle_targets, TargetGroups};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ScanResult {
     pub ip: String,
     pub mac: String,
@@ -18,38 +30,902 @@ pub struct ScanResult {
     pub response_time_ms: f64,
     pub open_ports: Vec<u16>,
     pub discovery_method: String,
+    pub os: String,
+    /// Unix timestamp (seconds) when the host was scanned; 0.0 if unknown
+    pub scan_timestamp: f64,
+    /// Hostname reported by each resolution source (dns, netbios, mdns, arp, dhcp)
+    pub hostname_sources: HashMap<String, String>,
+    /// Per-port probe outcome ("open", "closed", "filtered", "open|filtered")
+    /// where the scan method can tell them apart
+    pub port_state_detail: HashMap<u16, String>,
+    /// Probes sent to this host by the scan that produced the result
+    /// (cached answers are not counted)
+    pub probes_sent: u64,
+    /// Producers that contributed to this result ("tcp_connect", "arp_table",
+    /// "dhcp_lease", ...), in the order they were merged in
+    pub sources: Vec<String>,
+    /// Why the host could not be scanned; empty on success
+    pub error: String,
+    /// Producer-specific facts without a field of their own (switch port,
+    /// VLAN, ARP age, ...)
+    pub attributes: HashMap<String, String>,
+    /// Interface the probes were bound to; empty when the OS picked the route
+    pub scanned_via: String,
+    /// Whether the host answered from each interface it was scanned from
+    pub reachability: HashMap<String, bool>,
+    /// IPv6 addresses of the same device (see `correlate_dual_stack`)
+    pub ipv6_addresses: Vec<String>,
+}
+
+// =============================================================================
+// Python ScanResult class
+// =============================================================================
+//
+// Results used to cross into Python as plain dicts; they are now
+// `netscan_core.ScanResult` objects. Migrating Python callers:
+//   r["ip"], r.get("mac", "")   ->  r.ip, r.mac (every field is always set)
+//   r["open_ports"].append(22)  ->  r.open_ports = r.open_ports + [22]
+//                                   (getters return copies)
+//   80 in r["open_ports"]       ->  80 in r, r.has_port(80)
+//   dict(r), json.dumps(r)      ->  r.to_dict(), r.to_json()
+//   {"ip": "10.0.0.1", ...}     ->  ScanResult(ip="10.0.0.1", ...)
+// Functions that take results still accept dicts, so both forms can be mixed
+// while code is being moved over. Instances compare field by field, hash by
+// IP, and pickle through __getstate__ / __setstate__.
+
+/// Field order for the Python class (constructor, repr, to_dict)
+pub(crate) const PY_FIELDS: &[&str] = &[
+    "ip", "mac", "hostname", "vendor", "status", "response_time_ms", "open_ports",
+    "discovery_method", "os", "scan_timestamp", "hostname_sources", "port_state_detail",
+    "probes_sent", "sources", "error", "attributes", "scanned_via", "reachability",
+    "ipv6_addresses",
+];
+
+#[pyclass(name = "ScanResult", module = "netscan_core")]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScanResultDataclass {
+    #[pyo3(get, set)]
+    pub ip: String,
+    #[pyo3(get, set)]
+    pub mac: String,
+    #[pyo3(get, set)]
+    pub hostname: String,
+    #[pyo3(get, set)]
+    pub vendor: String,
+    #[pyo3(get, set)]
+    pub status: String,
+    #[pyo3(get, set)]
+    pub response_time_ms: f64,
+    /// Kept sorted and de-duplicated, so port checks can binary search
+    #[pyo3(get)]
+    pub open_ports: Vec<u16>,
+    #[pyo3(get, set)]
+    pub discovery_method: String,
+    #[pyo3(get, set)]
+    pub os: String,
+    #[pyo3(get, set)]
+    pub scan_timestamp: f64,
+    #[pyo3(get, set)]
+    pub hostname_sources: HashMap<String, String>,
+    #[pyo3(get, set)]
+    pub port_state_detail: HashMap<u16, String>,
+    #[pyo3(get, set)]
+    pub probes_sent: u64,
+    #[pyo3(get, set)]
+    pub sources: Vec<String>,
+    #[pyo3(get, set)]
+    pub error: String,
+    #[pyo3(get, set)]
+    pub attributes: HashMap<String, String>,
+    #[pyo3(get, set)]
+    pub scanned_via: String,
+    #[pyo3(get, set)]
+    pub reachability: HashMap<String, bool>,
+    #[pyo3(get, set)]
+    pub ipv6_addresses: Vec<String>,
+}
+
+impl From<ScanResult> for ScanResultDataclass {
+    fn from(mut r: ScanResult) -> Self {
+        r.sort_ports();
+        ScanResultDataclass {
+            ip: r.ip,
+            mac: r.mac,
+            hostname: r.hostname,
+            vendor: r.vendor,
+            status: r.status,
+            response_time_ms: r.response_time_ms,
+            open_ports: r.open_ports,
+            discovery_method: r.discovery_method,
+            os: r.os,
+            scan_timestamp: r.scan_timestamp,
+            hostname_sources: r.hostname_sources,
+            port_state_detail: r.port_state_detail,
+            probes_sent: r.probes_sent,
+            sources: r.sources,
+            error: r.error,
+            attributes: r.attributes,
+            scanned_via: r.scanned_via,
+            reachability: r.reachability,
+            ipv6_addresses: r.ipv6_addresses,
+        }
+    }
+}
+
+impl From<ScanResultDataclass> for ScanResult {
+    fn from(r: ScanResultDataclass) -> Self {
+        ScanResult {
+            ip: r.ip,
+            mac: r.mac,
+            hostname: r.hostname,
+            vendor: r.vendor,
+            status: r.status,
+            response_time_ms: r.response_time_ms,
+            open_ports: r.open_ports,
+            discovery_method: r.discovery_method,
+            os: r.os,
+            scan_timestamp: r.scan_timestamp,
+            hostname_sources: r.hostname_sources,
+            port_state_detail: r.port_state_detail,
+            probes_sent: r.probes_sent,
+            sources: r.sources,
+            error: r.error,
+            attributes: r.attributes,
+            scanned_via: r.scanned_via,
+            reachability: r.reachability,
+            ipv6_addresses: r.ipv6_addresses,
+        }
+    }
+}
+
+#[pymethods]
+impl ScanResultDataclass {
+    #[new]
+    #[pyo3(signature = (
+        ip=String::new(), mac=String::new(), hostname=String::new(), vendor=String::new(),
+        status=String::new(), response_time_ms=0.0, open_ports=Vec::new(),
+        discovery_method=String::new(), os=String::new(), scan_timestamp=0.0,
+        hostname_sources=HashMap::new(), port_state_detail=HashMap::new(), probes_sent=0,
+        sources=Vec::new(), error=String::new(), attributes=HashMap::new(),
+        scanned_via=String::new(), reachability=HashMap::new(), ipv6_addresses=Vec::new()
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        ip: String,
+        mac: String,
+        hostname: String,
+        vendor: String,
+        status: String,
+        response_time_ms: f64,
+        open_ports: Vec<u16>,
+        discovery_method: String,
+        os: String,
+        scan_timestamp: f64,
+        hostname_sources: HashMap<String, String>,
+        port_state_detail: HashMap<u16, String>,
+        probes_sent: u64,
+        sources: Vec<String>,
+        error: String,
+        attributes: HashMap<String, String>,
+        scanned_via: String,
+        reachability: HashMap<String, bool>,
+        ipv6_addresses: Vec<String>,
+    ) -> Self {
+        let mut result = ScanResultDataclass {
+            ip,
+            mac,
+            hostname,
+            vendor,
+            status,
+            response_time_ms,
+            open_ports: Vec::new(),
+            discovery_method,
+            os,
+            scan_timestamp,
+            hostname_sources,
+            port_state_detail,
+            probes_sent,
+            sources,
+            error,
+            attributes,
+            scanned_via,
+            reachability,
+            ipv6_addresses,
+        };
+        result.set_open_ports(open_ports);
+        result
+    }
+
+    /// Stored sorted and de-duplicated
+    #[setter]
+    fn set_open_ports(&mut self, mut ports: Vec<u16>) {
+        ports.sort_unstable();
+        ports.dedup();
+        self.open_ports = ports;
+    }
+
+    /// Whether `port` is open
+    fn has_port(&self, port: u16) -> bool {
+        self.open_ports.binary_search(&port).is_ok()
+    }
+
+    /// Whether any of `ports` is open
+    fn has_any_port(&self, ports: Vec<u16>) -> bool {
+        ports.iter().any(|port| self.has_port(*port))
+    }
+
+    /// Whether every one of `ports` is open (True for an empty list)
+    fn has_all_ports(&self, ports: Vec<u16>) -> bool {
+        ports.iter().all(|port| self.has_port(*port))
+    }
+
+    /// Number of open ports
+    fn port_count(&self) -> usize {
+        self.open_ports.len()
+    }
+
+    /// `port in result`; anything that isn't a port number is not in it
+    fn __contains__(&self, port: &PyAny) -> bool {
+        port.extract::<u16>().is_ok_and(|port| self.has_port(port))
+    }
+
+    /// All fields as a plain dict (the pre-class result layout)
+    pub fn to_dict(&self, py: Python) -> PyResult<Py<pyo3::types::PyDict>> {
+        let dict = pyo3::types::PyDict::new(py);
+        dict.set_item("ip", &self.ip)?;
+        dict.set_item("mac", &self.mac)?;
+        dict.set_item("hostname", &self.hostname)?;
+        dict.set_item("vendor", &self.vendor)?;
+        dict.set_item("status", &self.status)?;
+        dict.set_item("response_time_ms", self.response_time_ms)?;
+        dict.set_item("open_ports", self.open_ports.clone())?;
+        dict.set_item("discovery_method", &self.discovery_method)?;
+        dict.set_item("os", &self.os)?;
+        dict.set_item("scan_timestamp", self.scan_timestamp)?;
+        dict.set_item("hostname_sources", self.hostname_sources.clone())?;
+        dict.set_item("port_state_detail", self.port_state_detail.clone())?;
+        dict.set_item("probes_sent", self.probes_sent)?;
+        dict.set_item("sources", self.sources.clone())?;
+        dict.set_item("error", &self.error)?;
+        dict.set_item("attributes", self.attributes.clone())?;
+        dict.set_item("scanned_via", &self.scanned_via)?;
+        dict.set_item("reachability", self.reachability.clone())?;
+        dict.set_item("ipv6_addresses", self.ipv6_addresses.clone())?;
+        Ok(dict.into())
+    }
+
+    /// Build from the `to_dict` layout; missing or None fields take their
+    /// defaults (use `to_canonical` for other producers' layouts)
+    #[staticmethod]
+    fn from_dict(dict: &pyo3::types::PyDict) -> PyResult<Self> {
+        Ok(ScanResult::from_dict(dict)?.into())
+    }
+
+    /// This result as a JSON object string (the `to_dict` layout)
+    pub fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&ScanResult::from(self.clone())).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Cannot serialize ScanResult: {}", e))
+        })
+    }
+
+    /// Parse a JSON object written by `to_json`; missing fields take their
+    /// defaults
+    #[staticmethod]
+    fn from_json(s: &str) -> PyResult<Self> {
+        serde_json::from_str::<ScanResult>(s).map(Into::into).map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid ScanResult JSON: {}", e))
+        })
+    }
+
+    fn __getstate__(&self, py: Python) -> PyResult<Py<pyo3::types::PyDict>> {
+        self.to_dict(py)
+    }
+
+    fn __setstate__(&mut self, state: &pyo3::types::PyDict) -> PyResult<()> {
+        *self = ScanResult::from_dict(state)?.into();
+        Ok(())
+    }
+
+    fn __repr__(&self, py: Python) -> PyResult<String> {
+        let dict = self.to_dict(py)?;
+        let dict = dict.as_ref(py);
+        let fields = PY_FIELDS
+            .iter()
+            .map(|name| {
+                let value = dict.get_item(name)?.map(|v| v.repr()).transpose()?;
+                Ok(format!("{}={}", name, value.map(|v| v.to_string()).unwrap_or_default()))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(format!("ScanResult({})", fields.join(", ")))
+    }
+
+    fn __eq__(&self, other: &PyAny) -> PyObject {
+        let py = other.py();
+        match other.extract::<PyRef<ScanResultDataclass>>() {
+            Ok(other) => (*self == *other).into_py(py),
+            Err(_) => py.NotImplemented(),
+        }
+    }
+
+    fn __hash__(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.ip.hash(&mut hasher);
+        hasher.finish()
+    }
 }
 
 impl IntoPy<PyObject> for ScanResult {
     fn into_py(self, py: Python) -> PyObject {
-        let dict = pyo3::types::PyDict::new(py);
-        dict.set_item("ip", self.ip).unwrap();
-        dict.set_item("mac", self.mac).unwrap();
-        dict.set_item("hostname", self.hostname).unwrap();
-        dict.set_item("vendor", self.vendor).unwrap();
-        dict.set_item("status", self.status).unwrap();
-        dict.set_item("response_time_ms", self.response_time_ms).unwrap();
-        dict.set_item("open_ports", self.open_ports).unwrap();
-        dict.set_item("discovery_method", self.discovery_method).unwrap();
-        dict.into()
+        ScanResultDataclass::from(self).into_py(py)
+    }
+}
+
+impl ScanResult {
+    /// Build from the dict layout; missing or None fields take their defaults
+    fn from_dict(dict: &pyo3::types::PyDict) -> PyResult<Self> {
+        fn field<'a, T: FromPyObject<'a> + Default>(
+            dict: &'a pyo3::types::PyDict,
+            key: &str,
+        ) -> PyResult<T> {
+            match dict.get_item(key)? {
+                Some(value) if !value.is_none() => value.extract(),
+                _ => Ok(T::default()),
+            }
+        }
+        
+        Ok(ScanResult {
+            ip: field(dict, "ip")?,
+            mac: field(dict, "mac")?,
+            hostname: field(dict, "hostname")?,
+            vendor: field(dict, "vendor")?,
+            status: field(dict, "status")?,
+            response_time_ms: field(dict, "response_time_ms")?,
+            open_ports: field(dict, "open_ports")?,
+            discovery_method: field(dict, "discovery_method")?,
+            os: field(dict, "os")?,
+            scan_timestamp: field(dict, "scan_timestamp")?,
+            hostname_sources: field(dict, "hostname_sources")?,
+            port_state_detail: field(dict, "port_state_detail")?,
+            probes_sent: field(dict, "probes_sent")?,
+            sources: field(dict, "sources")?,
+            error: field(dict, "error")?,
+            attributes: field(dict, "attributes")?,
+            scanned_via: field(dict, "scanned_via")?,
+            reachability: field(dict, "reachability")?,
+            ipv6_addresses: field(dict, "ipv6_addresses")?,
+        })
+    }
+}
+
+/// Accepts a ScanResult instance or a dict in the legacy layout
+impl<'source> FromPyObject<'source> for ScanResult {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        if let Ok(result) = ob.extract::<PyRef<ScanResultDataclass>>() {
+            return Ok(result.clone().into());
+        }
+        ScanResult::from_dict(ob.downcast()?)
+    }
+}
+
+/// Column order for flat (pipe/CSV) renderings of a ScanResult
+pub const RECORD_FIELDS: &[&str] = &[
+    "ip", "mac", "hostname", "vendor", "status", "response_time_ms",
+    "open_ports", "discovery_method", "os", "scan_timestamp", "sources", "error",
+];
+
+impl ScanResult {
+    /// Flat string record keyed by RECORD_FIELDS; ports are comma-joined
+    pub fn to_record(&self) -> HashMap<String, String> {
+        let ports: Vec<String> = self.open_ports.iter().map(u16::to_string).collect();
+        [
+            ("ip", self.ip.clone()),
+            ("mac", self.mac.clone()),
+            ("hostname", self.hostname.clone()),
+            ("vendor", self.vendor.clone()),
+            ("status", self.status.clone()),
+            ("response_time_ms", format!("{:.2}", self.response_time_ms)),
+            ("open_ports", ports.join(",")),
+            ("discovery_method", self.discovery_method.clone()),
+            ("os", self.os.clone()),
+            ("scan_timestamp", format!("{:.3}", self.scan_timestamp)),
+            ("sources", self.sources.join(",")),
+            ("error", self.error.clone()),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect()
+    }
+    
+    /// Inverse of `to_record`; missing or unparseable fields take their defaults
+    pub fn from_record(record: &HashMap<String, String>) -> ScanResult {
+        let text = |key: &str| record.get(key).map(|v| v.trim().to_string()).unwrap_or_default();
+        let number = |key: &str| record.get(key).and_then(|v| v.trim().parse::<f64>().ok()).unwrap_or(0.0);
+        ScanResult {
+            ip: text("ip"),
+            mac: text("mac"),
+            hostname: text("hostname"),
+            vendor: text("vendor"),
+            status: text("status"),
+            response_time_ms: number("response_time_ms"),
+            open_ports: text("open_ports")
+                .split(',')
+                .filter_map(|p| p.trim().parse().ok())
+                .collect(),
+            discovery_method: text("discovery_method"),
+            os: text("os"),
+            scan_timestamp: number("scan_timestamp"),
+            sources: text("sources")
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect(),
+            error: text("error"),
+            ..Default::default()
+        }
+    }
+
+    /// Sort and de-duplicate `open_ports`, as the Python class keeps them
+    pub fn sort_ports(&mut self) {
+        self.open_ports.sort_unstable();
+        self.open_ports.dedup();
+    }
+
+    /// Fold another observation of the same host into this one
+    ///
+    /// Empty fields are filled from `other` (existing values win), open
+    /// ports, sources, IPv6 addresses, attributes and per-source maps are unioned (a host
+    /// reachable from an interface in either stays reachable), "up" beats any other status,
+    /// the newer timestamp and the probe total of both are kept.
+    pub fn merge(&mut self, other: &ScanResult) {
+        for (mine, theirs) in [
+            (&mut self.ip, &other.ip),
+            (&mut self.mac, &other.mac),
+            (&mut self.hostname, &other.hostname),
+            (&mut self.vendor, &other.vendor),
+            (&mut self.discovery_method, &other.discovery_method),
+            (&mut self.os, &other.os),
+            (&mut self.error, &other.error),
+            (&mut self.scanned_via, &other.scanned_via),
+        ] {
+            if mine.trim().is_empty() {
+                mine.clone_from(theirs);
+            }
+        }
+        if self.status.trim().is_empty() || (other.status == "up" && self.status != "up") {
+            self.status.clone_from(&other.status);
+        }
+        if self.response_time_ms <= 0.0 {
+            self.response_time_ms = other.response_time_ms;
+        }
+        self.open_ports.extend_from_slice(&other.open_ports);
+        self.sort_ports();
+        for (source, name) in &other.hostname_sources {
+            self.hostname_sources.entry(source.clone()).or_insert_with(|| name.clone());
+        }
+        for (port, state) in &other.port_state_detail {
+            self.port_state_detail.entry(*port).or_insert_with(|| state.clone());
+        }
+        for (key, value) in &other.attributes {
+            self.attributes.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        // Reachable from an interface in either observation means reachable
+        for (interface, reachable) in &other.reachability {
+            *self.reachability.entry(interface.clone()).or_insert(false) |= reachable;
+        }
+        for source in &other.sources {
+            if !self.sources.contains(source) {
+                self.sources.push(source.clone());
+            }
+        }
+        for address in &other.ipv6_addresses {
+            if !self.ipv6_addresses.contains(address) {
+                self.ipv6_addresses.push(address.clone());
+            }
+        }
+        self.scan_timestamp = self.scan_timestamp.max(other.scan_timestamp);
+        self.probes_sent += other.probes_sent;
+    }
+}
+
+/// Current time as Unix seconds
+pub fn unix_now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+/// Shared Tokio runtime for single-host calls (avoids per-call runtime setup)
+pub fn runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build Tokio runtime")
+    })
+}
+
+/// TCP port state as observed by a connect probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortState {
+    Open,
+    Closed,
+    Filtered,
+}
+
+impl PortState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PortState::Open => "open",
+            PortState::Closed => "closed",
+            PortState::Filtered => "filtered",
+        }
+    }
+}
+
+/// TCP connect probe distinguishing open (connected), closed (RST) and filtered (timeout)
+pub async fn tcp_probe_state(
+    ip: &str,
+    port: u16,
+    timeout_ms: u64,
+) -> (PortState, Option<f64>) {
+    tcp_probe_state_via(ip, port, timeout_ms, &ProbeRoute::default()).await
+}
+
+/// `tcp_probe_state` sent along `route`
+///
+/// Through a proxy, a target that refused the proxy's connection is closed
+/// and every other failure, including the proxy's own, is filtered.
+pub async fn tcp_probe_state_via(
+    ip: &str,
+    port: u16,
+    timeout_ms: u64,
+    route: &ProbeRoute,
+) -> (PortState, Option<f64>) {
+    tcp_probe_outcome(ip, port, timeout_ms, route).await.unwrap_or((PortState::Filtered, None))
+}
+
+/// Whether a connect error happened before anything was sent: the address
+/// can't be used from here (no route, wrong family, interface without that
+/// address) or the socket couldn't be made (permissions, out of descriptors)
+fn is_setup_error(error: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    if matches!(
+        error.kind(),
+        ErrorKind::AddrNotAvailable
+            | ErrorKind::NetworkUnreachable
+            | ErrorKind::NetworkDown
+            | ErrorKind::PermissionDenied
+            | ErrorKind::InvalidInput
+            | ErrorKind::Unsupported
+    ) {
+        return true;
+    }
+    #[cfg(unix)]
+    if let Some(code) = error.raw_os_error() {
+        return matches!(code, libc::EAFNOSUPPORT | libc::EMFILE | libc::ENFILE | libc::ENOBUFS);
     }
+    false
 }
 
-/// Fast TCP connect scan
-pub async fn tcp_connect_scan(
+/// `tcp_probe_state_via`, with an Err describing why the probe couldn't be
+/// sent at all (unparsable address, see `is_setup_error`) instead of
+/// counting that as filtered
+pub async fn tcp_probe_outcome(
     ip: &str,
     port: u16,
     timeout_ms: u64,
-) -> Option<f64> {
-    let addr: SocketAddr = format!("{}:{}", ip, port).parse().ok()?;
+    route: &ProbeRoute,
+) -> Result<(PortState, Option<f64>), String> {
+    let addr = match ip.parse::<IpAddr>() {
+        Ok(addr) => SocketAddr::new(addr, port),
+        Err(_) => return Err(format!("Invalid IP address '{}'", ip)),
+    };
+    if let Some(pacer) = &route.pacer {
+        pacer.wait().await;
+    }
     let start = Instant::now();
+    let limit = Duration::from_millis(timeout_ms);
+    let interface = route.interface.as_deref();
     
-    match timeout(
-        Duration::from_millis(timeout_ms),
-        AsyncTcpStream::connect(addr)
-    ).await {
-        Ok(Ok(_)) => Some(start.elapsed().as_secs_f64() * 1000.0),
-        _ => None
+    let state = match &route.proxy {
+        None => match timeout(limit, connect_via(addr, interface)).await {
+            Ok(Ok(stream)) => {
+                route.close(&stream);
+                PortState::Open
+            }
+            Ok(Err(e)) if e.kind() == std::io::ErrorKind::ConnectionRefused => PortState::Closed,
+            Ok(Err(e)) if is_setup_error(&e) => return Err(format!("Cannot connect to {}: {}", addr, e)),
+            _ => return Ok((PortState::Filtered, None)),
+        },
+        Some(proxy) => match timeout(limit, socks5_connect(proxy, addr, interface)).await {
+            Ok(Ok(stream)) => {
+                route.close(&stream);
+                PortState::Open
+            }
+            Ok(Err(Socks5Error::Reply(SOCKS5_CONNECTION_REFUSED))) => PortState::Closed,
+            _ => return Ok((PortState::Filtered, None)),
+        },
+    };
+    Ok((state, Some(start.elapsed().as_secs_f64() * 1000.0)))
+}
+
+/// Spaces new connections out to at most `rate` per second across every
+/// probe sharing it
+#[derive(Debug)]
+pub struct ConnectPacer {
+    interval: Duration,
+    next: parking_lot::Mutex<Instant>,
+}
+
+impl ConnectPacer {
+    /// None for a rate of 0 (unlimited)
+    pub fn new(rate: f64) -> Option<Arc<Self>> {
+        (rate > 0.0 && rate.is_finite()).then(|| {
+            Arc::new(ConnectPacer {
+                interval: Duration::from_secs_f64(1.0 / rate),
+                next: parking_lot::Mutex::new(Instant::now()),
+            })
+        })
+    }
+
+    /// Claim the next free slot
+    fn claim(&self) -> Instant {
+        let mut next = self.next.lock();
+        let slot = (*next).max(Instant::now());
+        *next = slot + self.interval;
+        slot
+    }
+
+    /// Wait for this probe's slot
+    async fn wait(&self) {
+        tokio::time::sleep_until(self.claim().into()).await;
+    }
+
+    /// `wait` for probes sent from a blocking thread
+    pub fn wait_blocking(&self) {
+        std::thread::sleep(self.claim().saturating_duration_since(Instant::now()));
+    }
+}
+
+/// Connections probes opened, and how many of them were closed with RST
+#[derive(Debug, Default)]
+pub struct CloseCounts {
+    pub opened: AtomicU64,
+    pub reset: AtomicU64,
+}
+
+/// How probes reach their targets
+#[derive(Debug, Clone, Default)]
+pub struct ProbeRoute {
+    /// Interface probes leave through (see `connect_via`)
+    pub interface: Option<String>,
+    /// SOCKS5 proxy (host, port) that makes the connections; the interface
+    /// then applies to the connection to the proxy
+    pub proxy: Option<(String, u16)>,
+    /// Close connected probes with RST (SO_LINGER 0) rather than FIN
+    pub abort_close: bool,
+    /// Probe IPv4 hosts with raw SYNs (`syn_probe_ports_via`) instead of
+    /// connecting; other hosts, or a host the raw socket fails for, are
+    /// connect-probed (see `HostScan::syn_fallback`)
+    pub syn: bool,
+    /// Paces connection attempts (`ScanConfig.max_connection_rate`)
+    pub pacer: Option<Arc<ConnectPacer>>,
+    /// Shared by clones, so a scan's routes add up to its totals
+    pub closes: Arc<CloseCounts>,
+}
+
+impl ProbeRoute {
+    pub fn from_config(config: &ScanConfig) -> Self {
+        ProbeRoute {
+            interface: config.interface.clone(),
+            proxy: config.proxy.clone(),
+            abort_close: config.abort_close,
+            syn: false,
+            pacer: ConnectPacer::new(config.max_connection_rate),
+            closes: Arc::default(),
+        }
+    }
+
+    /// Arrange how a connected probe's socket closes when it is dropped:
+    /// with `abort_close` a zero linger makes the kernel send RST and skip
+    /// TIME_WAIT, so neither the scanner nor a stateful middlebox keeps an
+    /// entry for it. Only connections whose zero linger was accepted count
+    /// as reset.
+    fn close(&self, stream: &AsyncTcpStream) {
+        self.closes.opened.fetch_add(1, Ordering::Relaxed);
+        if self.abort_close && socket2::SockRef::from(stream).set_linger(Some(Duration::ZERO)).is_ok() {
+            self.closes.reset.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+// =============================================================================
+// Source Interface Binding
+// =============================================================================
+//
+// Scanning "from" an interface means the probes leave through it. On Linux
+// the socket is bound to the device (SO_BINDTODEVICE, which needs
+// CAP_NET_RAW), so routing can't pick another interface; elsewhere the
+// closest equivalent is binding to the interface's address.
+
+/// Address of `interface` in the family of `target`
+pub(crate) fn interface_address(interface: &str, target: &IpAddr) -> Result<IpAddr, String> {
+    let iface = pnet::datalink::interfaces()
+        .into_iter()
+        .find(|i| i.name == interface)
+        .ok_or_else(|| format!("No interface named '{}'", interface))?;
+    iface
+        .ips
+        .iter()
+        .map(|net| net.ip())
+        .find(|ip| ip.is_ipv4() == target.is_ipv4())
+        .ok_or_else(|| format!("Interface '{}' has no {} address", interface, if target.is_ipv4() { "IPv4" } else { "IPv6" }))
+}
+
+/// Connect to `addr`, leaving through `interface` when one is given
+async fn connect_via(addr: SocketAddr, interface: Option<&str>) -> std::io::Result<AsyncTcpStream> {
+    let Some(interface) = interface else {
+        return AsyncTcpStream::connect(addr).await;
+    };
+    let socket = if addr.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+    #[cfg(target_os = "linux")]
+    socket.bind_device(Some(interface.as_bytes()))?;
+    #[cfg(not(target_os = "linux"))]
+    {
+        let source = interface_address(interface, &addr.ip())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::AddrNotAvailable, e))?;
+        socket.bind(SocketAddr::new(source, 0))?;
+    }
+    socket.connect(addr).await
+}
+
+/// Fail early if probes can't be bound to `interface`, rather than have
+/// every probe fail and every host look filtered
+pub fn check_interface(interface: &str) -> PyResult<()> {
+    interface_address(interface, &IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+        .or_else(|_| interface_address(interface, &IpAddr::V6(std::net::Ipv6Addr::UNSPECIFIED)))
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+    #[cfg(target_os = "linux")]
+    TcpSocket::new_v4()
+        .and_then(|socket| socket.bind_device(Some(interface.as_bytes())))
+        .map_err(|e| {
+            PyErr::new::<pyo3::exceptions::PyPermissionError, _>(format!(
+                "Cannot bind probes to {}: {} (binding to a device needs root or CAP_NET_RAW)",
+                interface, e
+            ))
+        })?;
+    Ok(())
+}
+
+// =============================================================================
+// SOCKS5 Proxy
+// =============================================================================
+//
+// Connect probes relayed by a SOCKS5 proxy (RFC 1928, no authentication),
+// for scanning from a pivot host. The proxy's CONNECT reply stands in for
+// the handshake we can't see: success is open, "connection refused" is
+// closed, anything else (unreachable, TTL expired, timeout) is filtered.
+
+const SOCKS5_VERSION: u8 = 0x05;
+const SOCKS5_NO_AUTH: u8 = 0x00;
+const SOCKS5_CONNECT: u8 = 0x01;
+const SOCKS5_ATYP_IPV4: u8 = 0x01;
+const SOCKS5_ATYP_DOMAIN: u8 = 0x03;
+const SOCKS5_ATYP_IPV6: u8 = 0x04;
+const SOCKS5_SUCCEEDED: u8 = 0x00;
+const SOCKS5_CONNECTION_REFUSED: u8 = 0x05;
+
+#[derive(Debug)]
+enum Socks5Error {
+    /// The proxy couldn't be reached or didn't speak unauthenticated SOCKS5
+    Proxy(std::io::Error),
+    /// The proxy answered CONNECT with this failure code
+    Reply(u8),
+}
+
+impl From<std::io::Error> for Socks5Error {
+    fn from(e: std::io::Error) -> Self {
+        Socks5Error::Proxy(e)
+    }
+}
+
+fn socks5_protocol_error(message: &str) -> Socks5Error {
+    Socks5Error::Proxy(std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string()))
+}
+
+/// Have the SOCKS5 proxy at `proxy` connect to `target`; the returned
+/// stream is relayed to the target
+async fn socks5_connect(
+    proxy: &(String, u16),
+    target: SocketAddr,
+    interface: Option<&str>,
+) -> Result<AsyncTcpStream, Socks5Error> {
+    let proxy_addr = tokio::net::lookup_host((proxy.0.as_str(), proxy.1))
+        .await?
+        .next()
+        .ok_or_else(|| socks5_protocol_error("proxy host did not resolve"))?;
+    let mut stream = connect_via(proxy_addr, interface).await?;
+
+    stream.write_all(&[SOCKS5_VERSION, 1, SOCKS5_NO_AUTH]).await?;
+    let mut choice = [0u8; 2];
+    stream.read_exact(&mut choice).await?;
+    if choice != [SOCKS5_VERSION, SOCKS5_NO_AUTH] {
+        return Err(socks5_protocol_error("proxy requires authentication or is not SOCKS5"));
+    }
+
+    let mut request = vec![SOCKS5_VERSION, SOCKS5_CONNECT, 0x00];
+    match target.ip() {
+        IpAddr::V4(ip) => {
+            request.push(SOCKS5_ATYP_IPV4);
+            request.extend_from_slice(&ip.octets());
+        }
+        IpAddr::V6(ip) => {
+            request.push(SOCKS5_ATYP_IPV6);
+            request.extend_from_slice(&ip.octets());
+        }
+    }
+    request.extend_from_slice(&target.port().to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[0] != SOCKS5_VERSION {
+        return Err(socks5_protocol_error("malformed CONNECT reply"));
+    }
+    if reply[1] != SOCKS5_SUCCEEDED {
+        return Err(Socks5Error::Reply(reply[1]));
+    }
+    // Skip the bound address and port the proxy reports
+    let bound_len = match reply[3] {
+        SOCKS5_ATYP_IPV4 => 4,
+        SOCKS5_ATYP_IPV6 => 16,
+        SOCKS5_ATYP_DOMAIN => stream.read_u8().await? as usize,
+        _ => return Err(socks5_protocol_error("malformed CONNECT reply")),
+    };
+    let mut bound = vec![0u8; bound_len + 2];
+    stream.read_exact(&mut bound).await?;
+    Ok(stream)
+}
+
+/// Round-trip time in ms of a TCP connect to (ip, port) made through the
+/// SOCKS5 proxy at (proxy_host, proxy_port), or None if the target didn't
+/// accept it
+///
+/// The time covers the whole exchange with the proxy, so it includes the
+/// hop to the proxy. Raises ConnectionError when the proxy itself can't be
+/// reached or refuses to relay without authentication, so a dead proxy
+/// isn't mistaken for a dead target. The target is subject to
+/// `set_authorized_scopes` (`force=True` overrides).
+#[pyfunction]
+#[pyo3(signature = (ip, port, proxy_host, proxy_port, timeout_ms=1000, force=false))]
+pub fn tcp_connect_via_socks5(
+    py: Python,
+    ip: &str,
+    port: u16,
+    proxy_host: &str,
+    proxy_port: u16,
+    timeout_ms: u64,
+    force: bool,
+) -> PyResult<Option<f64>> {
+    let target = SocketAddr::new(parse_target_ip(ip)?, port);
+    authorize_targets(py, "tcp_connect_via_socks5", &[ip], force)?;
+    let proxy = (proxy_host.to_string(), proxy_port);
+    let start = Instant::now();
+    let outcome = py.allow_threads(|| {
+        runtime().block_on(async {
+            timeout(Duration::from_millis(timeout_ms), socks5_connect(&proxy, target, None)).await
+        })
+    });
+    match outcome {
+        Ok(Ok(_)) => Ok(Some(start.elapsed().as_secs_f64() * 1000.0)),
+        Ok(Err(Socks5Error::Proxy(e))) => Err(PyErr::new::<pyo3::exceptions::PyConnectionError, _>(
+            format!("SOCKS5 proxy {}:{}: {}", proxy_host, proxy_port, e)
+        )),
+        Ok(Err(Socks5Error::Reply(_))) | Err(_) => Ok(None),
     }
 }
 
@@ -59,99 +935,742 @@ pub async fn scan_host_ports(
     ports: &[u16],
     timeout_ms: u64,
     semaphore: Arc<Semaphore>,
-) -> (String, Vec<u16>, f64) {
+    route: &ProbeRoute,
+) -> HostScan {
+    let mut syn_fallback = None;
+    if let (true, Ok(addr)) = (route.syn, ip.parse::<Ipv4Addr>()) {
+        match syn_scan_host(addr, ports, timeout_ms, &semaphore, route).await {
+            Ok(host) => return host,
+            Err(e) => syn_fallback = Some(e),
+        }
+    }
     let mut open_ports = Vec::new();
-    let mut min_response_time = f64::MAX;
+    let mut port_states = HashMap::with_capacity(ports.len());
+    let mut rst_count = 0;
+    let mut fastest_open = f64::MAX;
+    let mut fastest_rst = f64::MAX;
+    let mut sent = 0usize;
+    let mut setup_error = None;
     
     for &port in ports {
         let _permit = semaphore.acquire().await.unwrap();
         
-        if let Some(response_time) = tcp_connect_scan(ip, port, timeout_ms).await {
-            open_ports.push(port);
-            if response_time < min_response_time {
-                min_response_time = response_time;
+        let outcome = match tcp_probe_outcome(ip, port, timeout_ms, route).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                // An unusable address fails the same way on every port
+                let unparsable = ip.parse::<IpAddr>().is_err();
+                setup_error.get_or_insert(e);
+                if unparsable {
+                    break;
+                }
+                continue;
+            }
+        };
+        sent += 1;
+        port_states.insert(port, outcome.0);
+        match outcome {
+            (PortState::Open, rtt) => {
+                open_ports.push(port);
+                fastest_open = fastest_open.min(rtt.unwrap_or(f64::MAX));
+            }
+            (PortState::Closed, rtt) => {
+                rst_count += 1;
+                fastest_rst = fastest_rst.min(rtt.unwrap_or(f64::MAX));
             }
+            (PortState::Filtered, _) => {}
         }
     }
     
-    let response = if min_response_time == f64::MAX { 0.0 } else { min_response_time };
-    (ip.to_string(), open_ports, response)
+    // A host up only by RST is timed by its RSTs
+    let fastest = if open_ports.is_empty() { fastest_rst } else { fastest_open };
+    HostScan {
+        ip: ip.to_string(),
+        open_ports,
+        port_states,
+        response_time_ms: if fastest == f64::MAX { 0.0 } else { fastest },
+        rst_count,
+        // A host with any probe out was scanned, however the rest went
+        setup_error: setup_error.filter(|_| sent == 0),
+        syn_fallback,
+    }
 }
 
-/// Batch TCP connect scan
-#[pyfunction]
-pub fn tcp_scan_batch(
-    py: Python,
+/// SYN-probe one host's ports on the blocking pool under one permit, paced
+/// and bound as `route` says; the error when the raw socket can't be used,
+/// so the caller falls back to connects
+async fn syn_scan_host(
+    addr: Ipv4Addr,
+    ports: &[u16],
+    timeout_ms: u64,
+    semaphore: &Semaphore,
+    route: &ProbeRoute,
+) -> Result<HostScan, String> {
+    let _permit = semaphore.acquire().await.map_err(|e| e.to_string())?;
+    let start = Instant::now();
+    let owned = ports.to_vec();
+    let interface = route.interface.clone();
+    let pacer = route.pacer.clone();
+    let states = tokio::task::spawn_blocking(move || {
+        crate::syn::syn_probe_ports_via(addr, &owned, timeout_ms, interface.as_deref(), pacer.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+    let open_ports: Vec<u16> = ports.iter().copied().filter(|p| states.get(p) == Some(&PortState::Open)).collect();
+    let rst_count = states.values().filter(|state| **state == PortState::Closed).count() as u32;
+    let answered = !open_ports.is_empty() || rst_count > 0;
+    Ok(HostScan {
+        ip: addr.to_string(),
+        open_ports,
+        port_states: states,
+        response_time_ms: if answered { start.elapsed().as_secs_f64() * 1000.0 } else { 0.0 },
+        rst_count,
+        setup_error: None,
+        syn_fallback: None,
+    })
+}
+
+/// Raw per-host scan output
+#[derive(Debug, Clone, Default)]
+pub struct HostScan {
+    pub ip: String,
+    pub open_ports: Vec<u16>,
+    /// State of every port probed
+    pub port_states: HashMap<u16, PortState>,
+    /// Fastest answer in ms: from an open port, or from an RST when no port
+    /// was open; 0.0 if nothing answered
+    pub response_time_ms: f64,
+    /// Ports that answered with RST (closed)
+    pub rst_count: u32,
+    /// Why no probe to this host could be sent (see `tcp_probe_outcome`);
+    /// None once any probe went out
+    pub setup_error: Option<String>,
+    /// Why SYN probes couldn't be sent, when the host was connect-probed
+    /// instead
+    pub syn_fallback: Option<String>,
+}
+
+/// A host scan task that panicked or was cancelled instead of returning
+#[derive(Debug, Clone)]
+pub struct TaskError {
+    pub ip: String,
+    pub message: String,
+}
+
+impl TaskError {
+    pub fn to_py_dict(&self, py: Python) -> HashMap<String, PyObject> {
+        let mut map = HashMap::new();
+        map.insert("ip".to_string(), self.ip.clone().into_py(py));
+        map.insert("error".to_string(), self.message.clone().into_py(py));
+        map
+    }
+}
+
+impl std::fmt::Display for TaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "scan of {} failed: {}", if self.ip.is_empty() { "<unknown>" } else { &self.ip }, self.message)
+    }
+}
+
+fn describe_join_error(error: tokio::task::JoinError) -> String {
+    if error.is_cancelled() {
+        return "task cancelled".to_string();
+    }
+    let payload = error.into_panic();
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic payload".to_string());
+    format!("panicked: {}", message)
+}
+
+/// Aborts the task when dropped, so cancelling the wrapper cancels the host scan
+struct AbortOnDrop<T>(tokio::task::JoinHandle<T>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Scan every host concurrently, bounded by `max_concurrent` probes in flight
+// The extension module scans through `scan_target_feed`; these list forms serve the CLI
+#[cfg_attr(not(feature = "cli"), allow(dead_code))]
+pub async fn scan_hosts(
     ips: Vec<String>,
     ports: Vec<u16>,
     timeout_ms: u64,
     max_concurrent: usize,
-) -> PyResult<Vec<HashMap<String, PyObject>>> {
-    py.allow_threads(|| {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        
-        rt.block_on(async {
-            let semaphore = Arc::new(Semaphore::new(max_concurrent));
-            let mut handles = Vec::new();
-            
-            for ip in ips {
-                let ports = ports.clone();
-                let sem = semaphore.clone();
-                
-                handles.push(tokio::spawn(async move {
-                    scan_host_ports(&ip, &ports, timeout_ms, sem).await
-                }));
-            }
-            
-            let mut results = Vec::new();
-            for handle in handles {
-                if let Ok((ip, open_ports, response_time)) = handle.await {
-                    if !open_ports.is_empty() {
-                        let mut map = HashMap::new();
-                        Python::with_gil(|py| {
-                            map.insert("ip".to_string(), ip.into_py(py));
-                            map.insert("open_ports".to_string(), open_ports.into_py(py));
-                            map.insert("response_time_ms".to_string(), response_time.into_py(py));
-                            map.insert("status".to_string(), "up".into_py(py));
-                        });
-                        results.push(map);
-                    }
-                }
-            }
-            
-            Ok(results)
-        })
-    })
+) -> (Vec<HostScan>, Vec<TaskError>) {
+    let targets = ips.into_iter().map(|ip| (ip, ports.clone())).collect();
+    scan_targets(targets, timeout_ms, max_concurrent, ProbeRoute::default()).await
 }
 
-/// Fast ping sweep using raw sockets (requires root on Linux)
-#[pyfunction]
-pub fn ping_sweep_fast(
-    py: Python,
-    ips: Vec<String>,
+/// Like `scan_hosts`, but with a separate port list per host and probes
+/// sent along `route`
+pub async fn scan_targets(
+    targets: Vec<(String, Vec<u16>)>,
     timeout_ms: u64,
     max_concurrent: usize,
-) -> PyResult<Vec<HashMap<String, PyObject>>> {
-    // Fall back to TCP ping on common ports
-    let common_ports = vec![80, 443, 22, 445, 139, 21, 23, 25, 3389];
-    tcp_scan_batch(py, ips, common_ports, timeout_ms, max_concurrent)
+    route: ProbeRoute,
+) -> (Vec<HostScan>, Vec<TaskError>) {
+    let mut targets = targets.into_iter();
+    let feed = |want: usize| Ok::<_, std::convert::Infallible>(targets.by_ref().take(want).collect());
+    match scan_target_feed(feed, timeout_ms, max_concurrent, &route).await {
+        Ok(scanned) => scanned,
+        Err(never) => match never {},
+    }
 }
 
-// Common port list for quick scans
-pub const COMMON_PORTS: &[u16] = &[
-    21,    // FTP
-    22,    // SSH
-    23,    // Telnet
-    25,    // SMTP
-    53,    // DNS
-    80,    // HTTP
-    110,   // POP3
-    111,   // RPC
-    135,   // MSRPC
-    139,   // NetBIOS
-    143,   // IMAP
-    443,   // HTTPS
-    445,   // SMB
+/// Scan targets pulled from `feed` as hosts complete
+///
+/// `feed(n)` returns up to `n` more (ip, ports) targets; an empty batch means
+/// the source is exhausted. At most `max_concurrent` hosts are in flight, so
+/// the source is only drained as fast as the scan progresses. A feed error
+/// aborts the hosts in flight and is returned. Results keep feed order.
+/// Hosts whose task panicked or was cancelled are returned as `TaskError`s
+/// rather than results, also in feed order. Every probe is sent along
+/// `route`.
+pub async fn scan_target_feed<F, E>(
+    feed: F,
+    timeout_ms: u64,
+    max_concurrent: usize,
+    route: &ProbeRoute,
+) -> Result<(Vec<HostScan>, Vec<TaskError>), E>
+where
+    F: FnMut(usize) -> Result<Vec<(String, Vec<u16>)>, E>,
+{
+    scan_target_feed_observed(feed, |_, _, _| Ok(()), timeout_ms, max_concurrent, route).await
+}
+
+/// `scan_target_feed`, calling `on_done(host, ports_probed, elapsed)` as
+/// each host completes; an error from it aborts the scan like a feed error
+pub async fn scan_target_feed_observed<F, D, E>(
+    mut feed: F,
+    mut on_done: D,
+    timeout_ms: u64,
+    max_concurrent: usize,
+    route: &ProbeRoute,
+) -> Result<(Vec<HostScan>, Vec<TaskError>), E>
+where
+    F: FnMut(usize) -> Result<Vec<(String, Vec<u16>)>, E>,
+    D: FnMut(&HostScan, usize, Duration) -> Result<(), E>,
+{
+    let max_concurrent = max_concurrent.max(1);
+    let semaphore = Arc::new(Semaphore::new(max_concurrent));
+    let mut in_flight = JoinSet::new();
+    let mut results = Vec::new();
+    let mut errors = Vec::new();
+    let mut next_index = 0usize;
+    let mut exhausted = false;
+    let route = Arc::new(route.clone());
+    
+    loop {
+        let room = max_concurrent - in_flight.len();
+        if !exhausted && room > 0 {
+            // Dropping the JoinSet on error aborts the hosts in flight
+            let batch = feed(room)?;
+            exhausted = batch.is_empty();
+            for (ip, ports) in batch {
+                let sem = semaphore.clone();
+                let route = route.clone();
+                let index = next_index;
+                next_index += 1;
+                in_flight.spawn(async move {
+                    // The scan runs as its own task so a panic is caught here,
+                    // where the target it belongs to is still known
+                    let target = ip.clone();
+                    let probed = ports.len();
+                    let started = Instant::now();
+                    let mut host = AbortOnDrop(tokio::spawn(async move {
+                        scan_host_ports(&ip, &ports, timeout_ms, sem, &route).await
+                    }));
+                    let outcome = (&mut host.0).await.map_err(|e| TaskError {
+                        ip: target,
+                        message: describe_join_error(e),
+                    });
+                    (index, outcome, probed, started.elapsed())
+                });
+            }
+        }
+        match in_flight.join_next().await {
+            Some(Ok((index, Ok(result), probed, elapsed))) => {
+                on_done(&result, probed, elapsed)?;
+                results.push((index, result));
+            }
+            Some(Ok((index, Err(error), _, _))) => errors.push((index, error)),
+            Some(Err(e)) => errors.push((usize::MAX, TaskError { ip: String::new(), message: describe_join_error(e) })),
+            None if exhausted => break,
+            None => {}
+        }
+    }
+    
+    results.sort_unstable_by_key(|(index, _)| *index);
+    errors.sort_by_key(|(index, _)| *index);
+    Ok((
+        results.into_iter().map(|(_, result)| result).collect(),
+        errors.into_iter().map(|(_, error)| error).collect(),
+    ))
+}
+
+/// Failed host tasks become a RuntimeError with `strict`, otherwise a
+/// RuntimeWarning each
+fn surface_task_errors(py: Python, errors: &[TaskError], strict: bool) -> PyResult<()> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    if strict {
+        let details: Vec<String> = errors.iter().map(TaskError::to_string).collect();
+        return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!(
+            "{} host scan task(s) failed: {}",
+            errors.len(),
+            details.join("; ")
+        )));
+    }
+    for error in errors {
+        PyErr::warn(py, py.get_type::<pyo3::exceptions::PyRuntimeWarning>(), &error.to_string(), 1)?;
+    }
+    Ok(())
+}
+
+/// Pull up to `want` target strings from a Python iterator (takes the GIL)
+pub fn pull_targets(targets: &Py<PyIterator>, want: usize) -> PyResult<Vec<String>> {
+    Python::with_gil(|py| {
+        let mut iter = targets.as_ref(py);
+        let mut batch = Vec::with_capacity(want);
+        while batch.len() < want {
+            match iter.next() {
+                Some(item) => batch.push(item?.extract::<String>()?.trim().to_string()),
+                None => break,
+            }
+        }
+        Ok(batch)
+    })
+}
+
+/// TCP connect scan shared by `tcp_scan_batch` and `ping_sweep_fast`;
+/// results are tagged with `method`
+#[allow(clippy::too_many_arguments)]
+fn tcp_scan_hosts(
+    py: Python,
+    producer: &str,
+    method: &str,
+    ips: &PyAny,
+    ports: Vec<u16>,
+    timeout_ms: u64,
+    max_concurrent: usize,
+    strict: bool,
+    legacy: bool,
+    liveness_threshold: u32,
+    force: bool,
+    abort_close: bool,
+) -> PyResult<PyObject> {
+    let ports = checked_ports(py, ports)?;
+    let route = ProbeRoute { abort_close, ..ProbeRoute::default() };
+    let (ips, _) = authorize_iterable(py, producer, ips, force)?;
+    let targets: Py<PyIterator> = ips.iter()?.into();
+    let scan_timestamp = unix_now();
+    let (scanned, errors) = py.allow_threads(|| {
+        runtime().block_on(async {
+            let feed = |want| {
+                pull_targets(&targets, want)
+                    .map(|ips| ips.into_iter().map(|ip| (ip, ports.clone())).collect())
+            };
+            scan_target_feed(feed, timeout_ms, max_concurrent, &route).await
+        })
+    })?;
+    surface_task_errors(py, &errors, strict)?;
+    
+    if !legacy {
+        return Ok(results_from_scan(scanned, method, scan_timestamp, liveness_threshold).into_py(py));
+    }
+    crate::schema::warn_legacy(py, producer)?;
+    let mut results = Vec::new();
+    for host in scanned {
+        if !host.open_ports.is_empty() {
+            let mut map = HashMap::new();
+            map.insert("ip".to_string(), host.ip.into_py(py));
+            map.insert("open_ports".to_string(), host.open_ports.into_py(py));
+            map.insert("response_time_ms".to_string(), host.response_time_ms.into_py(py));
+            map.insert("status".to_string(), "up".into_py(py));
+            results.push(map);
+        }
+    }
+    Ok(results.into_py(py))
+}
+
+/// Batch TCP connect scan
+///
+/// `ips` may be any iterable of IP strings, including a generator that is
+/// still producing targets; it is consumed as the scan makes progress.
+/// Returns a ScanResult (source "tcp_connect") per host with open ports, plus
+/// hosts with none open that answered at least `liveness_threshold` probes
+/// with RST (see `ScanConfig.liveness_threshold`; 0 turns this off);
+/// `legacy=True` returns the old {ip, open_ports, response_time_ms, status}
+/// dicts, for hosts with open ports only, instead. A host whose scan task fails (a panic in the scan path) is
+/// reported as a RuntimeWarning, or with `strict=True` fails the call with
+/// RuntimeError.
+///
+/// A target no probe could be sent to (an unparsable address, no route to
+/// its network, out of file descriptors) comes back as status "error" with
+/// the reason in `error`, and the rest of the batch is scanned as usual.
+///
+/// With `set_authorized_scopes` in effect, `ips` is read in full and checked
+/// before the first probe; targets outside the scopes raise
+/// UnauthorizedTargetError unless `force=True`. `abort_close=True` closes
+/// connections to open ports with RST (see `ScanConfig.abort_close`).
+#[pyfunction]
+#[pyo3(signature = (ips, ports, timeout_ms, max_concurrent, strict=false, legacy=false, liveness_threshold=1, force=false, abort_close=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn tcp_scan_batch(
+    py: Python,
+    ips: &PyAny,
+    ports: Vec<u16>,
+    timeout_ms: u64,
+    max_concurrent: usize,
+    strict: bool,
+    legacy: bool,
+    liveness_threshold: u32,
+    force: bool,
+    abort_close: bool,
+) -> PyResult<PyObject> {
+    tcp_scan_hosts(
+        py, "tcp_scan_batch", "tcp_connect", ips, ports, timeout_ms, max_concurrent, strict, legacy, liveness_threshold,
+        force, abort_close,
+    )
+}
+
+/// Discovery method of hosts that showed themselves only by RSTs
+pub const TCP_RST_METHOD: &str = "tcp_rst";
+
+/// Turn raw per-host scan output into results for the hosts found up:
+/// those with open ports, and those with none open but at least
+/// `liveness_threshold` RSTs (0 disables that), which are tagged
+/// TCP_RST_METHOD instead of `discovery_method`. Hosts that sent any RST
+/// carry the count in `attributes["rst_count"]`. Open ports come out sorted
+/// and unique. `port_state_detail` holds the state of every port probed,
+/// and "open" for each open port.
+///
+/// Hosts no probe could be sent to are kept as status "error" with the
+/// reason in `error`, so a target that failed at setup can be told from one
+/// that was scanned and is down (absent).
+pub fn results_from_scan(
+    scanned: Vec<HostScan>,
+    discovery_method: &str,
+    scan_timestamp: f64,
+    liveness_threshold: u32,
+) -> Vec<ScanResult> {
+    scanned
+        .into_iter()
+        .filter(|host| {
+            host.setup_error.is_some()
+                || !host.open_ports.is_empty()
+                || (liveness_threshold > 0 && host.rst_count >= liveness_threshold)
+        })
+        .map(|mut host| {
+            if let Some(error) = host.setup_error {
+                return ScanResult {
+                    ip: host.ip,
+                    status: "error".to_string(),
+                    discovery_method: discovery_method.to_string(),
+                    scan_timestamp,
+                    sources: vec![discovery_method.to_string()],
+                    error,
+                    ..Default::default()
+                };
+            }
+            host.open_ports = sorted_ports(host.open_ports);
+            let method = match (host.open_ports.is_empty(), &host.syn_fallback) {
+                (true, _) => TCP_RST_METHOD,
+                (false, Some(_)) => "tcp_connect",
+                (false, None) => discovery_method,
+            };
+            let mut result = ScanResult {
+                ip: host.ip,
+                status: "up".to_string(),
+                response_time_ms: host.response_time_ms,
+                open_ports: host.open_ports,
+                discovery_method: method.to_string(),
+                scan_timestamp,
+                sources: vec![method.to_string()],
+                ..Default::default()
+            };
+            result.port_state_detail = host
+                .port_states
+                .iter()
+                .map(|(port, state)| (*port, state.as_str().to_string()))
+                .chain(result.open_ports.iter().map(|port| (*port, "open".to_string())))
+                .collect();
+            if host.rst_count > 0 {
+                result.attributes.insert("rst_count".to_string(), host.rst_count.to_string());
+            }
+            result
+        })
+        .collect()
+}
+
+/// Parse a port spec like "22,80,443", "1-1024" or "common" (COMMON_PORTS);
+/// returns sorted unique ports
+pub fn parse_port_spec(spec: &str) -> Result<Vec<u16>, String> {
+    let mut ports = Vec::new();
+    for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        if part.eq_ignore_ascii_case("common") {
+            ports.extend_from_slice(COMMON_PORTS);
+            continue;
+        }
+        let parse = |p: &str| -> Result<u16, String> {
+            match p.trim().parse::<u16>() {
+                Ok(0) | Err(_) => Err(format!("Invalid port '{}' in '{}'", p.trim(), spec)),
+                Ok(port) => Ok(port),
+            }
+        };
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (parse(start)?, parse(end)?);
+                if start > end {
+                    return Err(format!("Invalid port range '{}' in '{}'", part, spec));
+                }
+                ports.extend(start..=end);
+            }
+            None => ports.push(parse(part)?),
+        }
+    }
+    if ports.is_empty() {
+        return Err(format!("No ports in '{}'", spec));
+    }
+    ports.sort_unstable();
+    ports.dedup();
+    Ok(ports)
+}
+
+/// Parse a port spec ("22,80,8000-8100", "common") into sorted unique ports
+#[pyfunction]
+pub fn parse_ports(spec: &str) -> PyResult<Vec<u16>> {
+    parse_port_spec(spec).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+/// Port lists longer than this draw a warning: every port is probed on every host
+pub const LARGE_PORT_LIST: usize = 10_000;
+
+/// A caller's port list as the scan probes it: sorted, each port once
+///
+/// Raises ValueError for port 0 and warns (RuntimeWarning) when more than
+/// LARGE_PORT_LIST ports remain, which a spec like "1-65535" or a pasted
+/// list easily reaches by accident.
+pub fn checked_ports(py: Python, ports: Vec<u16>) -> PyResult<Vec<u16>> {
+    let ports = sorted_ports(ports);
+    if ports.first() == Some(&0) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid port 0: ports are 1-65535"));
+    }
+    if ports.len() > LARGE_PORT_LIST {
+        PyErr::warn(
+            py,
+            py.get_type::<pyo3::exceptions::PyRuntimeWarning>(),
+            &format!("Scanning {} ports per host; each one is probed on every target", ports.len()),
+            1,
+        )?;
+    }
+    Ok(ports)
+}
+
+fn sorted_ports(mut ports: Vec<u16>) -> Vec<u16> {
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+/// Walk two port lists in sorted order, keeping each port for which
+/// `keep(in_a, in_b)` holds; inputs need not be sorted or unique
+fn merge_ports(a: Vec<u16>, b: Vec<u16>, keep: fn(bool, bool) -> bool) -> Vec<u16> {
+    let (a, b) = (sorted_ports(a), sorted_ports(b));
+    let mut out = Vec::with_capacity(a.len().max(b.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        let (port, in_a, in_b) = match (a.get(i), b.get(j)) {
+            (Some(&x), Some(&y)) if x == y => (x, true, true),
+            (Some(&x), Some(&y)) if x < y => (x, true, false),
+            (Some(&x), None) => (x, true, false),
+            (_, Some(&y)) => (y, false, true),
+            (None, None) => unreachable!("loop condition"),
+        };
+        i += in_a as usize;
+        j += in_b as usize;
+        if keep(in_a, in_b) {
+            out.push(port);
+        }
+    }
+    out
+}
+
+/// Ports in `range` (inclusive, default (1, 65535)) that are not in
+/// `ports`, ascending: "everything except 80 and 443"
+#[pyfunction]
+#[pyo3(signature = (ports, range=None))]
+pub fn port_range_complement(ports: Vec<u16>, range: Option<(u16, u16)>) -> PyResult<Vec<u16>> {
+    let (start, end) = range.unwrap_or((1, 65535));
+    if start > end {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Invalid port range ({}, {})",
+            start, end
+        )));
+    }
+    let excluded = sorted_ports(ports);
+    let mut excluded = excluded.iter().peekable();
+    Ok((start..=end)
+        .filter(|port| {
+            while excluded.next_if(|e| **e < *port).is_some() {}
+            excluded.next_if_eq(&port).is_none()
+        })
+        .collect())
+}
+
+/// Ports in both lists, ascending
+#[pyfunction]
+pub fn port_range_intersection(a: Vec<u16>, b: Vec<u16>) -> Vec<u16> {
+    merge_ports(a, b, |in_a, in_b| in_a && in_b)
+}
+
+/// Ports in either list, ascending
+#[pyfunction]
+pub fn port_range_union(a: Vec<u16>, b: Vec<u16>) -> Vec<u16> {
+    merge_ports(a, b, |in_a, in_b| in_a || in_b)
+}
+
+/// Ports in `a` but not in `b`, ascending
+#[pyfunction]
+pub fn port_range_difference(a: Vec<u16>, b: Vec<u16>) -> Vec<u16> {
+    merge_ports(a, b, |in_a, in_b| in_a && !in_b)
+}
+
+/// Ports used for TCP-based liveness checks
+pub const TCP_PING_PORTS: &[u16] = &[80, 443, 22, 445, 139, 21, 23, 25, 3389];
+
+/// Fast ping sweep using raw sockets (requires root on Linux)
+///
+/// Returns ScanResults with source "tcp_ping", or "tcp_rst" for hosts that
+/// only answered with RSTs, and status "error" ones for targets that
+/// couldn't be probed (as in tcp_scan_batch); `legacy=True` returns the old
+/// tcp_scan_batch dicts. Authorized scopes and `force` work as in
+/// tcp_scan_batch.
+#[pyfunction]
+#[pyo3(signature = (ips, timeout_ms, max_concurrent, legacy=false, force=false))]
+pub fn ping_sweep_fast(
+    py: Python,
+    ips: &PyAny,
+    timeout_ms: u64,
+    max_concurrent: usize,
+    legacy: bool,
+    force: bool,
+) -> PyResult<PyObject> {
+    // Fall back to TCP ping on common ports
+    let ports = TCP_PING_PORTS.to_vec();
+    tcp_scan_hosts(
+        py, "ping_sweep_fast", "tcp_ping", ips, ports, timeout_ms, max_concurrent, false, legacy, 1, force, false,
+    )
+}
+
+fn parse_target_ip(ip: &str) -> PyResult<IpAddr> {
+    ip.trim().parse::<IpAddr>().map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IP: {}", e))
+    })
+}
+
+/// Check a single port, returning "open", "closed" or "filtered"
+///
+/// `force=True` probes a target outside `set_authorized_scopes`.
+#[pyfunction]
+#[pyo3(signature = (ip, port, timeout_ms=500, force=false))]
+pub fn check_port(py: Python, ip: &str, port: u16, timeout_ms: u64, force: bool) -> PyResult<String> {
+    let addr = parse_target_ip(ip)?.to_string();
+    authorize_targets(py, "check_port", &[&addr], force)?;
+    
+    let (state, _) = py.allow_threads(|| {
+        runtime().block_on(tcp_probe_state(&addr, port, timeout_ms))
+    });
+    Ok(state.as_str().to_string())
+}
+
+/// Re-check a single host, probing all ports concurrently
+/// (resolves within roughly one timeout window)
+///
+/// The host is "up" when any port answered, open or closed (RST), and
+/// "down" when every probe timed out. `force=True` probes a target outside `set_authorized_scopes`.
+#[pyfunction]
+#[pyo3(signature = (ip, ports=None, timeout_ms=500, force=false))]
+pub fn check_host(
+    py: Python,
+    ip: &str,
+    ports: Option<Vec<u16>>,
+    timeout_ms: u64,
+    force: bool,
+) -> PyResult<HashMap<String, PyObject>> {
+    let addr = parse_target_ip(ip)?.to_string();
+    authorize_targets(py, "check_host", &[&addr], force)?;
+    let ports = checked_ports(py, ports.unwrap_or_else(|| TCP_PING_PORTS.to_vec()))?;
+    
+    let probes: Vec<(u16, PortState, Option<f64>)> = py.allow_threads(|| {
+        runtime().block_on(async {
+            let handles: Vec<_> = ports
+                .iter()
+                .map(|&port| {
+                    let addr = addr.clone();
+                    (port, tokio::spawn(async move {
+                        tcp_probe_state(&addr, port, timeout_ms).await
+                    }))
+                })
+                .collect();
+            
+            let mut probes = Vec::with_capacity(handles.len());
+            for (port, handle) in handles {
+                let (state, rtt) = handle.await.unwrap_or((PortState::Filtered, None));
+                probes.push((port, state, rtt));
+            }
+            probes
+        })
+    });
+    
+    let open_ports: Vec<u16> = probes
+        .iter()
+        .filter(|(_, state, _)| *state == PortState::Open)
+        .map(|(port, _, _)| *port)
+        .collect();
+    let port_states: HashMap<u16, &str> = probes
+        .iter()
+        .map(|(port, state, _)| (*port, state.as_str()))
+        .collect();
+    let rtt_ms: HashMap<u16, f64> = probes
+        .iter()
+        .filter_map(|(port, _, rtt)| rtt.map(|r| (*port, r)))
+        .collect();
+    let answered = probes.iter().any(|(_, state, _)| *state != PortState::Filtered);
+    let status = if answered { "up" } else { "down" };
+    
+    let mut map = HashMap::new();
+    map.insert("ip".to_string(), addr.into_py(py));
+    map.insert("status".to_string(), status.into_py(py));
+    map.insert("open_ports".to_string(), open_ports.into_py(py));
+    map.insert("port_states".to_string(), port_states.into_py(py));
+    map.insert("rtt_ms".to_string(), rtt_ms.into_py(py));
+    Ok(map)
+}
+
+// Common port list for quick scans
+pub const COMMON_PORTS: &[u16] = &[
+    21,    // FTP
+    22,    // SSH
+    23,    // Telnet
+    25,    // SMTP
+    53,    // DNS
+    80,    // HTTP
+    110,   // POP3
+    111,   // RPC
+    135,   // MSRPC
+    139,   // NetBIOS
+    143,   // IMAP
+    443,   // HTTPS
+    445,   // SMB
     993,   // IMAPS
     995,   // POP3S
     1723,  // PPTP
@@ -167,3 +1686,1511 @@ pub const COMMON_PORTS: &[u16] = &[
 pub fn get_common_ports() -> Vec<u16> {
     COMMON_PORTS.to_vec()
 }
+
+// =============================================================================
+// Scanner
+// =============================================================================
+
+/// Scan configuration shared by the Scanner and config-driven entry points
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct ScanConfig {
+    #[pyo3(get, set)]
+    pub ports: Vec<u16>,
+    #[pyo3(get, set)]
+    pub timeout_ms: u64,
+    #[pyo3(get, set)]
+    pub max_concurrent: usize,
+    /// Reuse cached port results younger than this; 0 disables the cache
+    #[pyo3(get, set)]
+    pub cache_ttl_seconds: u64,
+    /// Load the cache from / save it to this JSON file around each scan
+    #[pyo3(get, set)]
+    pub cache_file: Option<String>,
+    /// Reverse-resolve hostnames of hosts found up (through the Scanner's DnsCache)
+    #[pyo3(get, set)]
+    pub resolve_hostnames: bool,
+    /// Stop with partial results rather than send more probes than this; 0 is unlimited
+    #[pyo3(get, set)]
+    pub max_total_probes: u64,
+    /// Targets scanned when `Scanner.scan` is called without IPs (IPs,
+    /// CIDRs, ranges and wildcards, as for `parse_targets`)
+    #[pyo3(get, set)]
+    pub ip_specs: Vec<String>,
+    /// Addresses or CIDRs removed from the expanded `ip_specs`
+    #[pyo3(get, set)]
+    pub exclusion_cidrs: Vec<String>,
+    /// Scan the expanded targets in random order
+    #[pyo3(get, set)]
+    pub randomize_order: bool,
+    /// Seed for `randomize_order`; None draws a new order on every expansion
+    #[pyo3(get, set)]
+    pub shuffle_seed: Option<u64>,
+    /// Report a host with no open ports as up (discovery_method "tcp_rst")
+    /// once this many probed ports answered with RST; 0 requires an open
+    /// port. Ports answered from the cache don't count.
+    #[pyo3(get, set)]
+    pub liveness_threshold: u32,
+    /// Send every probe out of this interface (e.g. "eth0.10") instead of
+    /// the routed one; results record it in `scanned_via`. The port cache
+    /// is not kept per interface, so leave it off when comparing interfaces.
+    #[pyo3(get, set)]
+    pub interface: Option<String>,
+    /// Make every TCP connect probe through this SOCKS5 proxy (host, port),
+    /// e.g. a pivot host; ARP, ICMP and DNS lookups still go out directly
+    #[pyo3(get, set)]
+    pub proxy: Option<(String, u16)>,
+    /// Order of target dispatch across groups: "none" (input order),
+    /// "round_robin" or "weighted" (see `interleave_targets`)
+    #[pyo3(get, set)]
+    pub interleave: String,
+    /// Group -> weight for `interleave="weighted"`; empty weighs groups by size
+    #[pyo3(get, set)]
+    pub group_weights: HashMap<String, f64>,
+    /// Group -> CIDRs, e.g. one group per site; other targets are grouped
+    /// by /24 (IPv6: /64)
+    #[pyo3(get, set)]
+    pub target_groups: HashMap<String, Vec<String>>,
+    /// Close connected probes with RST instead of FIN, so no TIME_WAIT
+    /// entries pile up on the scanner or stateful firewalls in the path;
+    /// through a proxy this applies to the connection to the proxy
+    #[pyo3(get, set)]
+    pub abort_close: bool,
+    /// How ports are probed: "connect" (TCP connect), "syn" (raw SYN
+    /// probes; needs the raw_tcp capability) or "auto" (SYN when the
+    /// capability report allows it and no proxy or interface is set,
+    /// otherwise connect)
+    #[pyo3(get, set)]
+    pub scan_method: String,
+    /// How hosts that answer no port probe are checked for liveness: "none",
+    /// "arp" (directly attached IPv4 subnets), "icmp" (echo) or "auto"
+    /// (each that the capability report allows)
+    #[pyo3(get, set)]
+    pub discovery: String,
+    /// New TCP connections started per second across the scan; 0 is
+    /// unlimited. Every attempt is a conntrack entry on a stateful firewall
+    /// in the path, so this caps how fast the scan fills its table.
+    #[pyo3(get, set)]
+    pub max_connection_rate: f64,
+}
+
+#[pymethods]
+impl ScanConfig {
+    #[new]
+    #[pyo3(signature = (ports=None, timeout_ms=1000, max_concurrent=500, cache_ttl_seconds=0, cache_file=None, resolve_hostnames=false, max_total_probes=0, ip_specs=Vec::new(), exclusion_cidrs=Vec::new(), randomize_order=false, shuffle_seed=None, liveness_threshold=1, interface=None, proxy=None, interleave="none".to_string(), group_weights=HashMap::new(), target_groups=HashMap::new(), abort_close=false, scan_method="connect".to_string(), discovery="none".to_string(), max_connection_rate=0.0))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        ports: Option<Vec<u16>>,
+        timeout_ms: u64,
+        max_concurrent: usize,
+        cache_ttl_seconds: u64,
+        cache_file: Option<String>,
+        resolve_hostnames: bool,
+        max_total_probes: u64,
+        ip_specs: Vec<String>,
+        exclusion_cidrs: Vec<String>,
+        randomize_order: bool,
+        shuffle_seed: Option<u64>,
+        liveness_threshold: u32,
+        interface: Option<String>,
+        proxy: Option<(String, u16)>,
+        interleave: String,
+        group_weights: HashMap<String, f64>,
+        target_groups: HashMap<String, Vec<String>>,
+        abort_close: bool,
+        scan_method: String,
+        discovery: String,
+        max_connection_rate: f64,
+    ) -> Self {
+        ScanConfig {
+            ports: ports.unwrap_or_else(|| COMMON_PORTS.to_vec()),
+            timeout_ms,
+            max_concurrent,
+            cache_ttl_seconds,
+            cache_file,
+            resolve_hostnames,
+            max_total_probes,
+            ip_specs,
+            exclusion_cidrs,
+            randomize_order,
+            shuffle_seed,
+            liveness_threshold,
+            interface,
+            proxy,
+            interleave,
+            group_weights,
+            target_groups,
+            abort_close,
+            scan_method,
+            discovery,
+            max_connection_rate,
+        }
+    }
+    
+    fn __repr__(&self) -> String {
+        format!(
+            "ScanConfig(ports=<{} ports>, timeout_ms={}, max_concurrent={}, cache_ttl_seconds={}, cache_file={}, resolve_hostnames={}, max_total_probes={}, ip_specs=<{} specs>, exclusion_cidrs=<{} exclusions>, randomize_order={}, shuffle_seed={}, liveness_threshold={}, interface={}, proxy={}, interleave='{}', abort_close={}, scan_method='{}', discovery='{}', max_connection_rate={})",
+            self.ports.len(), self.timeout_ms, self.max_concurrent, self.cache_ttl_seconds,
+            self.cache_file.as_ref().map(|f| format!("'{}'", f)).unwrap_or_else(|| "None".to_string()),
+            if self.resolve_hostnames { "True" } else { "False" },
+            self.max_total_probes,
+            self.ip_specs.len(),
+            self.exclusion_cidrs.len(),
+            if self.randomize_order { "True" } else { "False" },
+            self.shuffle_seed.map(|s| s.to_string()).unwrap_or_else(|| "None".to_string()),
+            self.liveness_threshold,
+            self.interface.as_ref().map(|i| format!("'{}'", i)).unwrap_or_else(|| "None".to_string()),
+            self.proxy.as_ref().map(|(h, p)| format!("('{}', {})", h, p)).unwrap_or_else(|| "None".to_string()),
+            self.interleave,
+            if self.abort_close { "True" } else { "False" },
+            self.scan_method,
+            self.discovery,
+            self.max_connection_rate
+        )
+    }
+
+    // Builder methods: each returns a modified copy and leaves `self` as it
+    // was, so a shared base config can be specialised per scan:
+    // `ScanConfig.default().with_timeout(500).with_ports([22, 80])`
+
+    /// The configuration `ScanConfig()` gives
+    #[staticmethod]
+    #[pyo3(name = "default")]
+    fn default_py() -> Self {
+        ScanConfig::default()
+    }
+
+    /// A named starting point, raising ValueError for an unknown name:
+    /// - "quick": the TCP ping ports, 500 ms timeout
+    /// - "default": same as `ScanConfig()`
+    /// - "thorough": ports 1-1024 plus the common ports, 2 s timeout
+    /// - "stealth": common ports in random order, 50 probes at a time
+    #[staticmethod]
+    fn from_profile(name: &str) -> PyResult<Self> {
+        let mut config = ScanConfig::default();
+        match name.trim().to_lowercase().as_str() {
+            "quick" => {
+                config.ports = TCP_PING_PORTS.to_vec();
+                config.timeout_ms = 500;
+            }
+            "default" => {}
+            "thorough" => {
+                config.ports = sorted_ports((1..=1024).chain(COMMON_PORTS.iter().copied()).collect());
+                config.timeout_ms = 2000;
+            }
+            "stealth" => {
+                config.max_concurrent = 50;
+                config.timeout_ms = 2000;
+                config.randomize_order = true;
+            }
+            other => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Unknown scan profile '{}': expected quick, default, thorough or stealth",
+                    other
+                )))
+            }
+        }
+        Ok(config)
+    }
+
+    /// Copy with these ports (sorted and deduplicated, as a scan uses them)
+    fn with_ports(&self, py: Python, ports: Vec<u16>) -> PyResult<Self> {
+        Ok(ScanConfig { ports: checked_ports(py, ports)?, ..self.clone() })
+    }
+
+    fn with_timeout(&self, timeout_ms: u64) -> Self {
+        ScanConfig { timeout_ms, ..self.clone() }
+    }
+
+    fn with_concurrency(&self, max_concurrent: usize) -> Self {
+        ScanConfig { max_concurrent, ..self.clone() }
+    }
+
+    #[pyo3(signature = (ttl_seconds, cache_file=None))]
+    fn with_cache(&self, ttl_seconds: u64, cache_file: Option<String>) -> Self {
+        ScanConfig { cache_ttl_seconds: ttl_seconds, cache_file, ..self.clone() }
+    }
+
+    fn with_hostname_resolution(&self, enabled: bool) -> Self {
+        ScanConfig { resolve_hostnames: enabled, ..self.clone() }
+    }
+
+    fn with_probe_limit(&self, max_total_probes: u64) -> Self {
+        ScanConfig { max_total_probes, ..self.clone() }
+    }
+
+    fn with_targets(&self, ip_specs: Vec<String>) -> Self {
+        ScanConfig { ip_specs, ..self.clone() }
+    }
+
+    fn with_exclusions(&self, exclusion_cidrs: Vec<String>) -> Self {
+        ScanConfig { exclusion_cidrs, ..self.clone() }
+    }
+
+    #[pyo3(signature = (enabled, seed=None))]
+    fn with_randomization(&self, enabled: bool, seed: Option<u64>) -> Self {
+        ScanConfig { randomize_order: enabled, shuffle_seed: seed, ..self.clone() }
+    }
+
+    fn with_liveness_threshold(&self, liveness_threshold: u32) -> Self {
+        ScanConfig { liveness_threshold, ..self.clone() }
+    }
+
+    fn with_interface(&self, interface: Option<String>) -> Self {
+        ScanConfig { interface, ..self.clone() }
+    }
+
+    fn with_proxy(&self, proxy: Option<(String, u16)>) -> Self {
+        ScanConfig { proxy, ..self.clone() }
+    }
+
+    #[pyo3(signature = (enabled=true))]
+    fn with_abort_close(&self, enabled: bool) -> Self {
+        ScanConfig { abort_close: enabled, ..self.clone() }
+    }
+
+    fn with_scan_method(&self, scan_method: String) -> Self {
+        ScanConfig { scan_method, ..self.clone() }
+    }
+
+    fn with_discovery(&self, discovery: String) -> Self {
+        ScanConfig { discovery, ..self.clone() }
+    }
+
+    fn with_connection_rate(&self, max_connection_rate: f64) -> Self {
+        ScanConfig { max_connection_rate, ..self.clone() }
+    }
+
+    /// Copy with this dispatch order; raises ValueError for an unknown
+    /// mode, a non-positive weight or an invalid CIDR
+    #[pyo3(signature = (mode, weights=None, groups=None))]
+    fn with_interleave(
+        &self,
+        mode: &str,
+        weights: Option<HashMap<String, f64>>,
+        groups: Option<HashMap<String, Vec<String>>>,
+    ) -> PyResult<Self> {
+        let config = ScanConfig {
+            interleave: mode.to_string(),
+            group_weights: weights.unwrap_or_default(),
+            target_groups: groups.unwrap_or_default(),
+            ..self.clone()
+        };
+        config.target_groups().map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+        Ok(config)
+    }
+}
+
+impl ScanConfig {
+    /// The targets a scan of this config covers, in scan order: `ip_specs`
+    /// expanded and deduplicated in input order, minus `exclusion_cidrs`,
+    /// shuffled when `randomize_order` is set, then interleaved
+    pub fn target_ips(&self) -> Result<Vec<String>, String> {
+        let excluded = CidrSet::parse(&self.exclusion_cidrs)?;
+        let ips: Vec<String> = expand_targets(&self.ip_specs)?
+            .into_iter()
+            .filter(|ip| !excluded.contains_str(ip))
+            .collect();
+        self.order_targets(ips)
+    }
+
+    /// `ips` shuffled when `randomize_order` is set, then interleaved
+    pub fn order_targets(&self, mut ips: Vec<String>) -> Result<Vec<String>, String> {
+        if self.randomize_order {
+            let seed = self.shuffle_seed.unwrap_or_else(|| (unix_now() * 1e9) as u64);
+            shuffle_targets(&mut ips, seed);
+        }
+        interleave_targets(ips, &self.target_groups()?, &self.interleave, &self.group_weights)
+    }
+
+    /// `target_groups` parsed, after checking the interleave settings
+    pub fn target_groups(&self) -> Result<TargetGroups, String> {
+        check_interleave(&self.interleave, &self.group_weights)?;
+        TargetGroups::parse(&self.target_groups)
+    }
+}
+
+/// Dry run: the IPs a scan of `config` would target, in order, without
+/// sending anything
+///
+/// Expands `ip_specs` (IPs, CIDRs, ranges, wildcards), drops addresses in
+/// `exclusion_cidrs`, deduplicates and, with `randomize_order`, shuffles,
+/// then applies `interleave`. With `shuffle_seed` set, `Scanner.scan()`
+/// visits targets in the same order as the preview.
+#[pyfunction]
+pub fn preview_scan_targets(config: &ScanConfig) -> PyResult<Vec<String>> {
+    config.target_ips().map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        ScanConfig::new(
+            None, 1000, 500, 0, None, false, 0, Vec::new(), Vec::new(), false, None, 1, None, None,
+            "none".to_string(), HashMap::new(), HashMap::new(), false, "connect".to_string(), "none".to_string(), 0.0,
+        )
+    }
+}
+
+// =============================================================================
+// Multi-Interface Scans
+// =============================================================================
+
+/// Scan the same targets out of several interfaces at once and merge the
+/// results per host, recording which interfaces reached it
+///
+/// `targets` take the `parse_targets` forms. `config` supplies ports,
+/// timeout, concurrency and `liveness_threshold` (its interface, target and
+/// cache settings are not used); `max_concurrent` is split between the
+/// interfaces. Each host found from any interface is returned once, with
+/// `reachability` naming every interface as True or False (a host reachable
+/// from VLAN 10 but not VLAN 20 shows as {"eth0.10": True, "eth0.20":
+/// False}) and `scanned_via` the first interface listed that reached it.
+/// Targets outside `set_authorized_scopes` are refused unless `force=True`.
+#[pyfunction]
+#[pyo3(signature = (targets, interfaces, config=None, strict=false, force=false))]
+pub fn scan_all_interfaces(
+    py: Python,
+    targets: Vec<String>,
+    interfaces: Vec<String>,
+    config: Option<ScanConfig>,
+    strict: bool,
+    force: bool,
+) -> PyResult<Vec<ScanResult>> {
+    let mut config = config.unwrap_or_default();
+    config.ports = checked_ports(py, config.ports)?;
+    if interfaces.is_empty() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("No interfaces given"));
+    }
+    for interface in &interfaces {
+        check_interface(interface)?;
+    }
+    let ips = expand_targets(&targets).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+    authorize_targets(py, "scan_all_interfaces", &ips, force)?;
+    let per_interface = (config.max_concurrent / interfaces.len()).max(1);
+    let scan_timestamp = unix_now();
+
+    let scans: Vec<(Vec<HostScan>, Vec<TaskError>)> = py.allow_threads(|| {
+        runtime().block_on(async {
+            // One pacer for all interfaces, so the rate cap holds for the scan
+            let base = ProbeRoute::from_config(&config);
+            let handles: Vec<_> = interfaces
+                .iter()
+                .map(|interface| {
+                    let targets = ips.iter().map(|ip| (ip.clone(), config.ports.clone())).collect();
+                    let route = ProbeRoute { interface: Some(interface.clone()), ..base.clone() };
+                    tokio::spawn(scan_targets(targets, config.timeout_ms, per_interface, route))
+                })
+                .collect();
+            let mut scans = Vec::with_capacity(handles.len());
+            for handle in handles {
+                scans.push(handle.await.unwrap_or_else(|e| {
+                    (Vec::new(), vec![TaskError { ip: String::new(), message: describe_join_error(e) }])
+                }));
+            }
+            scans
+        })
+    });
+
+    let mut merged: Vec<ScanResult> = Vec::new();
+    let mut by_ip: HashMap<String, usize> = HashMap::new();
+    let mut task_errors = Vec::new();
+    for (interface, (scanned, errors)) in interfaces.iter().zip(scans) {
+        task_errors.extend(errors);
+        for mut result in results_from_scan(scanned, "tcp_connect", scan_timestamp, config.liveness_threshold) {
+            if result.status == "error" {
+                // Unreachable from this interface; reachability marks it False below
+                continue;
+            }
+            result.scanned_via.clone_from(interface);
+            result.reachability.insert(interface.clone(), true);
+            match by_ip.get(&result.ip) {
+                Some(&i) => merged[i].merge(&result),
+                None => {
+                    by_ip.insert(result.ip.clone(), merged.len());
+                    merged.push(result);
+                }
+            }
+        }
+    }
+    for result in &mut merged {
+        for interface in &interfaces {
+            result.reachability.entry(interface.clone()).or_insert(false);
+        }
+    }
+    surface_task_errors(py, &task_errors, strict)?;
+    Ok(merged)
+}
+
+// =============================================================================
+// Subnet Scans with Exclusions
+// =============================================================================
+
+/// Drop excluded targets, then scan the rest with a `Scanner` built from
+/// `config` (its ports replaced by `ports`)
+fn scan_with_exclusions(
+    py: Python,
+    ips: Vec<String>,
+    exclusions: Vec<String>,
+    ports: Vec<u16>,
+    config: Option<ScanConfig>,
+    strict: bool,
+    force: bool,
+) -> PyResult<Vec<ScanResult>> {
+    let ips = crate::scope::filter_ips_by_exclusion_list(ips, exclusions)?;
+    let config = ScanConfig { ports, ..config.unwrap_or_default() };
+    let scanner = PyCell::new(py, Scanner::new(py, Some(config), None)?)?;
+    Scanner::scan(scanner, py, Some(pyo3::types::PyList::new(py, ips)), strict, None, force)
+}
+
+/// Scan every address of an IPv4 CIDR except the excluded ones
+///
+/// Shorthand for `expand_cidr`, `filter_ips_by_exclusion_list` and
+/// `Scanner(config).scan()`: `exclusions` take bare IPs, CIDRs or
+/// "a.b.c.d-e.f.g.h" ranges, and `ports` replaces the config's ports.
+/// CIDRs over `expand_ip_range`'s default limit are refused; errors from
+/// every step (bad CIDR or exclusion, unauthorized targets, `strict`) are
+/// raised as they would be by the step itself.
+#[pyfunction]
+#[pyo3(signature = (cidr, exclusions, ports, config=None, strict=false, force=false))]
+pub fn scan_cidr_with_exclusions(
+    py: Python,
+    cidr: &str,
+    exclusions: Vec<String>,
+    ports: Vec<u16>,
+    config: Option<ScanConfig>,
+    strict: bool,
+    force: bool,
+) -> PyResult<Vec<ScanResult>> {
+    let network: ipnetwork::Ipv4Network = cidr.trim().parse().map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid CIDR: {}", e))
+    })?;
+    let ips = crate::expand_ip_range_with_limit(
+        &network.network().to_string(),
+        &network.broadcast().to_string(),
+        crate::DEFAULT_MAX_IP_RANGE,
+    )?;
+    scan_with_exclusions(py, ips, exclusions, ports, config, strict, force)
+}
+
+/// Same as `scan_cidr_with_exclusions` for the inclusive range `start`-`end`
+#[pyfunction]
+#[pyo3(signature = (start, end, exclusions, ports, config=None, strict=false, force=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn scan_ip_range_with_exclusions(
+    py: Python,
+    start: &str,
+    end: &str,
+    exclusions: Vec<String>,
+    ports: Vec<u16>,
+    config: Option<ScanConfig>,
+    strict: bool,
+    force: bool,
+) -> PyResult<Vec<ScanResult>> {
+    let ips = crate::expand_ip_range_with_limit(start, end, crate::DEFAULT_MAX_IP_RANGE)?;
+    scan_with_exclusions(py, ips, exclusions, ports, config, strict, force)
+}
+
+// =============================================================================
+// Scan Duration Estimate
+// =============================================================================
+//
+// Worst case: every probe waits out its full timeout (all hosts down), and
+// probes run in waves of `max_concurrent`.
+
+/// Worst-case duration of scanning `ips_count` hosts on `ports_count` ports
+pub fn estimate_scan_duration(ips_count: usize, ports_count: usize, config: &ScanConfig) -> Duration {
+    let mut total_probes = (ips_count as u64).saturating_mul(ports_count as u64);
+    if config.max_total_probes > 0 {
+        total_probes = total_probes.min(config.max_total_probes);
+    }
+    let waves = total_probes.div_ceil(config.max_concurrent.max(1) as u64);
+    Duration::from_millis(waves.saturating_mul(config.timeout_ms))
+}
+
+/// As `estimate_scan_duration`, with `retries` extra attempts per unanswered
+/// probe and up to `jitter_ms` of random delay before each attempt
+pub fn estimate_scan_duration_with(
+    ips_count: usize,
+    ports_count: usize,
+    config: &ScanConfig,
+    retries: u32,
+    jitter_ms: u64,
+) -> Duration {
+    // Both only stretch the time each probe slot stays occupied
+    let stretched = ScanConfig {
+        timeout_ms: config.timeout_ms.saturating_add(jitter_ms).saturating_mul(1 + retries as u64),
+        ..config.clone()
+    };
+    estimate_scan_duration(ips_count, ports_count, &stretched)
+}
+
+/// "~2 minutes 30 seconds": the largest unit plus the next one when non-zero
+fn describe_duration(duration: Duration) -> String {
+    let secs = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+    if secs == 0 {
+        return "~0 seconds".to_string();
+    }
+    let units = [(86_400, "day"), (3_600, "hour"), (60, "minute"), (1, "second")];
+    let Some(first) = units.iter().position(|(size, _)| secs >= *size) else {
+        return "~0 seconds".to_string();
+    };
+    let part = |value: u64, name: &str| format!("{} {}{}", value, name, if value == 1 { "" } else { "s" });
+    let (size, name) = units[first];
+    let mut text = format!("~{}", part(secs / size, name));
+    if let Some(&(next_size, next_name)) = units.get(first + 1) {
+        let rest = secs % size / next_size;
+        if rest > 0 {
+            text.push(' ');
+            text.push_str(&part(rest, next_name));
+        }
+    }
+    text
+}
+
+/// Worst-case scan time in seconds: ceil(ips × ports / max_concurrent) waves,
+/// each taking (timeout_ms + jitter_ms) × (1 + retries)
+///
+/// `config` defaults to ScanConfig(); its `max_total_probes`, when set,
+/// caps the probe count.
+#[pyfunction]
+#[pyo3(name = "estimate_scan_duration", signature = (ips_count, ports_count, config=None, retries=0, jitter_ms=0))]
+pub fn estimate_scan_duration_py(
+    ips_count: usize,
+    ports_count: usize,
+    config: Option<ScanConfig>,
+    retries: u32,
+    jitter_ms: u64,
+) -> f64 {
+    let config = config.unwrap_or_default();
+    estimate_scan_duration_with(ips_count, ports_count, &config, retries, jitter_ms).as_secs_f64()
+}
+
+/// `estimate_scan_duration` as text, e.g. "~2 minutes 30 seconds"
+#[pyfunction]
+#[pyo3(signature = (ips_count, ports_count, config=None, retries=0, jitter_ms=0))]
+pub fn estimate_scan_duration_str(
+    ips_count: usize,
+    ports_count: usize,
+    config: Option<ScanConfig>,
+    retries: u32,
+    jitter_ms: u64,
+) -> String {
+    let config = config.unwrap_or_default();
+    describe_duration(estimate_scan_duration_with(ips_count, ports_count, &config, retries, jitter_ms))
+}
+
+/// Summary of the most recent Scanner run
+#[derive(Debug, Clone, Default)]
+pub struct ScanSummary {
+    pub targets: usize,
+    pub hosts_up: usize,
+    /// Targets probed that didn't answer
+    pub hosts_down: usize,
+    /// Targets no probe could be sent to (status "error" in the results)
+    pub setup_failed: usize,
+    pub duration_s: f64,
+    pub degradations: Vec<String>,
+    /// Methods the scan used: "tcp_connect" or "tcp_syn", plus "arp" and
+    /// "icmp_echo" when discovery ran
+    pub methods: Vec<String>,
+    pub probes_sent: u64,
+    /// Set when max_total_probes cut the scan short
+    pub aborted: bool,
+    pub traffic: TrafficStats,
+    /// Hosts whose scan task panicked or was cancelled (absent from results)
+    pub task_errors: Vec<TaskError>,
+    /// Targets outside the authorized scopes scanned because of force=True
+    pub forced_targets: usize,
+    /// Connect probes that completed a handshake, each one a connection
+    /// (and a conntrack entry on any stateful firewall in the path)
+    pub connections_opened: u64,
+    /// How many of those were closed with RST (`abort_close`, where the
+    /// zero linger could be set); the rest were closed with FIN and sit in
+    /// TIME_WAIT for a while
+    pub connections_reset: u64,
+}
+
+impl ScanSummary {
+    pub fn to_py_dict(&self, py: Python) -> HashMap<String, PyObject> {
+        let mut map = HashMap::new();
+        map.insert("targets".to_string(), self.targets.into_py(py));
+        map.insert("hosts_up".to_string(), self.hosts_up.into_py(py));
+        map.insert("hosts_down".to_string(), self.hosts_down.into_py(py));
+        map.insert("setup_failed".to_string(), self.setup_failed.into_py(py));
+        map.insert("duration_s".to_string(), self.duration_s.into_py(py));
+        map.insert("degradations".to_string(), self.degradations.clone().into_py(py));
+        map.insert("methods".to_string(), self.methods.clone().into_py(py));
+        map.insert("probes_sent".to_string(), self.probes_sent.into_py(py));
+        map.insert("aborted".to_string(), self.aborted.into_py(py));
+        map.insert("traffic".to_string(), self.traffic.to_py_dict(py).into_py(py));
+        let task_errors: Vec<_> = self.task_errors.iter().map(|e| e.to_py_dict(py)).collect();
+        map.insert("task_errors".to_string(), task_errors.into_py(py));
+        map.insert("forced_targets".to_string(), self.forced_targets.into_py(py));
+        let connections = HashMap::from([
+            ("opened", self.connections_opened),
+            ("closed_rst", self.connections_reset),
+            ("closed_fin", self.connections_opened - self.connections_reset),
+        ]);
+        map.insert("connections".to_string(), connections.into_py(py));
+        map
+    }
+}
+
+/// File descriptors reserved for the interpreter and non-scan sockets
+const FD_RESERVE: u64 = 64;
+
+/// Probe and discovery methods chosen for one scan
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct ScanMethods {
+    syn: bool,
+    arp: bool,
+    icmp: bool,
+}
+
+impl ScanMethods {
+    fn names(&self) -> Vec<String> {
+        let probe = if self.syn { "tcp_syn" } else { "tcp_connect" };
+        [(true, probe), (self.arp, "arp"), (self.icmp, "icmp_echo")]
+            .into_iter()
+            .filter(|(used, _)| *used)
+            .map(|(_, name)| name.to_string())
+            .collect()
+    }
+}
+
+fn missing_capability(setting: &str, capability: &str) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyPermissionError, _>(format!(
+        "{} needs the {} capability; {}",
+        setting,
+        capability,
+        crate::capabilities::grant_hint(capability)
+    ))
+}
+
+/// Pick methods for `config.scan_method` and `config.discovery` from the
+/// capability report. An explicit method the process can't use raises
+/// PermissionError; "auto" falls back and notes it in `degradations`.
+fn select_methods(
+    config: &ScanConfig,
+    caps: &crate::capabilities::Capabilities,
+    degradations: &mut Vec<String>,
+) -> PyResult<ScanMethods> {
+    // SYN probes bypass the kernel's connect, so they can't honour either
+    let routed = config.proxy.is_some() || config.interface.is_some();
+    let syn = match config.scan_method.as_str() {
+        "connect" => false,
+        "syn" if routed => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "scan_method='syn' can't be combined with proxy or interface",
+            ));
+        }
+        "syn" if !caps.raw_tcp => return Err(missing_capability("scan_method='syn'", "raw_tcp")),
+        "syn" => true,
+        "auto" if routed => false,
+        "auto" if !caps.raw_tcp => {
+            degradations.push(format!(
+                "SYN probes unavailable, using TCP connect ({})",
+                crate::capabilities::grant_hint("raw_tcp")
+            ));
+            false
+        }
+        "auto" => true,
+        other => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid scan_method '{}': expected 'connect', 'syn' or 'auto'",
+                other
+            )));
+        }
+    };
+
+    let icmp_usable = caps.raw_icmp || caps.unprivileged_icmp;
+    let (arp, icmp) = match config.discovery.as_str() {
+        "none" => (false, false),
+        "arp" if !caps.raw_packet => return Err(missing_capability("discovery='arp'", "raw_packet")),
+        "arp" => (true, false),
+        "icmp" if !icmp_usable => return Err(missing_capability("discovery='icmp'", "icmp")),
+        "icmp" => (false, true),
+        "auto" => {
+            if !caps.raw_packet {
+                degradations.push(format!(
+                    "ARP discovery unavailable ({})",
+                    crate::capabilities::grant_hint("raw_packet")
+                ));
+            }
+            if !icmp_usable {
+                degradations.push(format!("ICMP discovery unavailable ({})", crate::capabilities::grant_hint("icmp")));
+            }
+            (caps.raw_packet, icmp_usable)
+        }
+        other => {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Invalid discovery '{}': expected 'none', 'arp', 'icmp' or 'auto'",
+                other
+            )));
+        }
+    };
+    Ok(ScanMethods { syn, arp, icmp })
+}
+
+/// Check hosts that answered no port probe by ARP (those on a directly
+/// attached IPv4 subnet) and ICMP echo; returns those that replied, as up
+fn discover_silent(
+    silent: &[String],
+    methods: ScanMethods,
+    timeout_ms: u64,
+    scan_timestamp: f64,
+    degradations: &mut Vec<String>,
+) -> Vec<ScanResult> {
+    use rayon::prelude::*;
+
+    let mut remaining: Vec<Ipv4Addr> = silent.iter().filter_map(|ip| ip.parse().ok()).collect();
+    let mut found = Vec::new();
+    let up = |ip: Ipv4Addr, method: &str, mac: String, rtt: f64| ScanResult {
+        ip: ip.to_string(),
+        mac,
+        status: "up".to_string(),
+        response_time_ms: rtt,
+        discovery_method: method.to_string(),
+        scan_timestamp,
+        sources: vec![method.to_string()],
+        ..Default::default()
+    };
+
+    if methods.arp {
+        let replies: Vec<_> = remaining
+            .par_iter()
+            .filter(|ip| crate::arp::local_interface_for(**ip).is_some())
+            .map(|ip| (*ip, crate::arp::arp_ping(*ip, timeout_ms)))
+            .collect();
+        if let Some((_, Err(e))) = replies.iter().find(|(_, reply)| reply.is_err()) {
+            degradations.push(format!("ARP discovery failed for some hosts: {}", e));
+        }
+        for (ip, reply) in replies {
+            if let Ok(Some((mac, rtt))) = reply {
+                remaining.retain(|other| *other != ip);
+                found.push(up(ip, "arp", mac, rtt));
+            }
+        }
+    }
+    if methods.icmp && !remaining.is_empty() {
+        match crate::icmp::icmp_sweep(&remaining, &[crate::icmp::IcmpProbe::Echo], timeout_ms) {
+            Ok(replies) => {
+                for ip in &remaining {
+                    if let Some((probe, rtt)) = replies.get(ip) {
+                        found.push(up(*ip, probe.method(), String::new(), *rtt));
+                    }
+                }
+            }
+            Err(e) => degradations.push(format!("ICMP discovery failed: {}", e)),
+        }
+    }
+    found
+}
+
+/// Stateful scanner: picks methods from the capability report and records
+/// any degradations applied in its summary
+#[pyclass]
+pub struct Scanner {
+    #[pyo3(get, set)]
+    pub config: ScanConfig,
+    pub summary: ScanSummary,
+    #[pyo3(get)]
+    pub cache: Py<ScanCache>,
+    #[pyo3(get)]
+    pub monitor: Py<ScanRateMonitor>,
+    /// Reverse DNS cache; pass the same DnsCache to several Scanners to share it
+    #[pyo3(get, set)]
+    pub dns_cache: DnsCache,
+    /// Python probes run on each host found, in registration order
+    pub probes: Vec<CustomProbe>,
+    /// Progress and ETA of the current or last scan (see ScanProgress)
+    #[pyo3(get)]
+    pub progress: ScanProgress,
+}
+
+impl Scanner {
+    /// Clamp concurrency to what the fd limit allows, noting the degradation
+    fn effective_concurrency(&self, degradations: &mut Vec<String>) -> usize {
+        let requested = self.config.max_concurrent.max(1);
+        match crate::capabilities::cached().fd_limit {
+            Some(limit) if (requested as u64) + FD_RESERVE > limit => {
+                let allowed = limit.saturating_sub(FD_RESERVE).max(1) as usize;
+                degradations.push(format!(
+                    "max_concurrent reduced from {} to {} (open file limit {}; raise with `ulimit -n`)",
+                    requested, allowed, limit
+                ));
+                allowed
+            }
+            _ => requested,
+        }
+    }
+}
+
+#[pymethods]
+impl Scanner {
+    #[new]
+    #[pyo3(signature = (config=None, dns_cache=None))]
+    pub fn new(py: Python, config: Option<ScanConfig>, dns_cache: Option<DnsCache>) -> PyResult<Self> {
+        let config = config.unwrap_or_default();
+        let cache = match config.cache_file.as_deref() {
+            Some(path) if std::path::Path::new(path).exists() => ScanCache::load(path)?,
+            _ => ScanCache::default(),
+        };
+        Ok(Scanner {
+            config,
+            summary: ScanSummary::default(),
+            cache: Py::new(py, cache)?,
+            monitor: Py::new(py, ScanRateMonitor::default())?,
+            dns_cache: dns_cache.unwrap_or_else(|| shared_cache().clone()),
+            probes: Vec::new(),
+            progress: ScanProgress::default(),
+        })
+    }
+    
+    /// Port scan of the given targets; returns hosts with open ports
+    ///
+    /// Ports are probed by TCP connect, or by raw SYNs per
+    /// `config.scan_method`; with `config.discovery` set, hosts that answer
+    /// no probe are also tried by ARP and ICMP echo and reported up by the
+    /// one that got a reply. Methods are picked from `capabilities()`: the
+    /// summary's `methods` lists those used and `degradations` any "auto"
+    /// choice that fell back for lack of privileges.
+    ///
+    /// `ips` may be a list or any iterable, such as a generator yielding
+    /// targets as they are discovered: targets are pulled only as in-flight
+    /// hosts finish, and an exception raised by the iterable aborts the scan.
+    ///
+    /// With `cache_ttl_seconds` set, ports with a fresh cached result are not
+    /// probed again and their cached state is used instead.
+    ///
+    /// Hosts whose scan task fails are listed in the summary's `task_errors`;
+    /// with `strict=True` the scan raises RuntimeError instead. Targets no
+    /// probe could be sent to are returned as status "error" (see
+    /// `tcp_scan_batch`) and counted in the summary's `setup_failed`, apart
+    /// from `hosts_down`.
+    ///
+    /// Without `ips`, the config's targets are scanned (`ip_specs` minus
+    /// `exclusion_cidrs`, as listed by `preview_scan_targets`).
+    ///
+    /// `progress_callback` is called as `(done, total, eta_seconds,
+    /// rate_hosts_per_s)` after each host; `total` and `eta_seconds` are None
+    /// when `ips` has no length. An exception from it aborts the scan. The
+    /// same figures are readable from `scanner.progress` during the scan,
+    /// from the callback or another thread.
+    ///
+    /// With `config.interleave` set, targets are dispatched across groups
+    /// (see `interleave_targets`; `ips` is then read in full before the scan
+    /// starts), and `scanner.progress.groups` gives each group's completion.
+    ///
+    /// With `set_authorized_scopes` in effect, every target is checked before
+    /// the scan starts (`ips` is read in full first) and any outside the
+    /// scopes raise UnauthorizedTargetError. `force=True` scans them anyway;
+    /// the summary's `forced_targets` then counts them.
+    #[pyo3(signature = (ips=None, strict=false, progress_callback=None, force=false))]
+    pub fn scan(
+        slf: &PyCell<Self>,
+        py: Python,
+        ips: Option<&PyAny>,
+        strict: bool,
+        progress_callback: Option<PyObject>,
+        force: bool,
+    ) -> PyResult<Vec<ScanResult>> {
+        // Work from copies so the Scanner isn't borrowed while it scans:
+        // `scanner.progress` stays readable from the callback and other threads
+        let start = Instant::now();
+        let mut degradations = Vec::new();
+        let (mut config, cache, monitor, dns_cache, probes, progress, concurrency, methods) = {
+            let this = slf.borrow();
+            let concurrency = this.effective_concurrency(&mut degradations);
+            let methods = select_methods(&this.config, crate::capabilities::cached(), &mut degradations)?;
+            (
+                this.config.clone(),
+                this.cache.clone_ref(py),
+                this.monitor.clone_ref(py),
+                this.dns_cache.clone(),
+                this.probes.clone(),
+                this.progress.clone(),
+                concurrency,
+                methods,
+            )
+        };
+        let groups = config.target_groups().map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+        let (ips, forced_targets): (&PyAny, usize) = match ips {
+            Some(ips) => {
+                let (ips, forced) = authorize_iterable(py, "Scanner.scan", ips, force)?;
+                if config.interleave == "none" {
+                    (ips, forced)
+                } else {
+                    // Interleaving needs every target up front
+                    let ips = ips
+                        .iter()?
+                        .map(|ip| Ok(ip?.extract::<String>()?.trim().to_string()))
+                        .collect::<PyResult<Vec<String>>>()?;
+                    let ips = interleave_targets(ips, &groups, &config.interleave, &config.group_weights)
+                        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+                    (pyo3::types::PyList::new(py, ips), forced)
+                }
+            }
+            None if config.ip_specs.is_empty() => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                    "No targets: pass ips or set ScanConfig.ip_specs",
+                ));
+            }
+            None => {
+                let targets = preview_scan_targets(&config)?;
+                let forced = authorize_targets(py, "Scanner.scan", &targets, force)?;
+                (pyo3::types::PyList::new(py, targets), forced)
+            }
+        };
+        if let Some(interface) = config.interface.as_deref() {
+            check_interface(interface)?;
+        }
+        config.ports = checked_ports(py, config.ports)?;
+        let use_cache = config.cache_ttl_seconds > 0;
+        let source: Py<PyIterator> = ips.iter()?.into();
+        progress.begin(ips.len().ok(), concurrency, config.ports.len(), config.timeout_ms);
+        // Per-group progress needs the group sizes, so a list of targets
+        let grouped = (config.interleave != "none" || !config.target_groups.is_empty())
+            && ips.downcast::<pyo3::types::PyList>().is_ok();
+        if grouped {
+            let mut totals: HashMap<String, usize> = HashMap::new();
+            for ip in ips.iter()? {
+                *totals.entry(groups.group_of(&ip?.extract::<String>()?)).or_default() += 1;
+            }
+            progress.set_groups(totals);
+        }
+        let on_done = |host: &HostScan, probed: usize, elapsed: Duration| -> PyResult<()> {
+            let answered = !host.open_ports.is_empty() || host.rst_count > 0;
+            if grouped {
+                progress.record_group(&groups.group_of(&host.ip));
+            }
+            let (done, total, eta, rate) = progress.record(answered, probed, elapsed);
+            match &progress_callback {
+                Some(callback) => Python::with_gil(|py| callback.call1(py, (done, total, eta, rate)).map(drop)),
+                None => Ok(()),
+            }
+        };
+        
+        // Filled in per target as the feed pulls them
+        let now = unix_now();
+        let mut targets = 0usize;
+        let mut cached_open: HashMap<String, Vec<u16>> = HashMap::new();
+        let mut probed_ports: HashMap<String, Vec<u16>> = HashMap::new();
+        let (mut hits, mut misses) = (0u64, 0u64);
+        let mut probes_sent = 0u64;
+        let mut aborted = false;
+        
+        let feed = |want: usize| -> PyResult<Vec<(String, Vec<u16>)>> {
+            let mut batch = Vec::with_capacity(want);
+            // Guard rail: stop pulling targets once max_total_probes is spent
+            if aborted {
+                return Ok(batch);
+            }
+            let ips = pull_targets(&source, want)?;
+            Python::with_gil(|py| {
+                let cache = cache.borrow(py);
+                for ip in ips {
+                    // Split the host's ports into cached answers and ports to probe
+                    let mut probe = Vec::new();
+                    for &port in &config.ports {
+                        match use_cache.then(|| cache.lookup(&ip, port, config.cache_ttl_seconds, now)).flatten() {
+                            Some(is_open) => {
+                                hits += 1;
+                                if is_open {
+                                    cached_open.entry(ip.clone()).or_default().push(port);
+                                }
+                            }
+                            None => {
+                                misses += use_cache as u64;
+                                probe.push(port);
+                            }
+                        }
+                    }
+                    if config.max_total_probes > 0 {
+                        let budget = config.max_total_probes - probes_sent;
+                        if (probe.len() as u64) > budget {
+                            probe.truncate(budget as usize);
+                            aborted = true;
+                        }
+                    }
+                    targets += 1;
+                    probes_sent += probe.len() as u64;
+                    probed_ports.insert(ip.clone(), probe.clone());
+                    batch.push((ip, probe));
+                    if aborted {
+                        break;
+                    }
+                }
+            });
+            Ok(batch)
+        };
+        
+        let route = ProbeRoute { syn: methods.syn, ..ProbeRoute::from_config(&config) };
+        let scanned = py.allow_threads(|| {
+            runtime().block_on(scan_target_feed_observed(feed, on_done, config.timeout_ms, concurrency, &route))
+        });
+        progress.finish();
+        let (scanned, task_errors) = scanned?;
+        if aborted {
+            degradations.push(format!(
+                "max_total_probes ({}) reached; remaining probes not sent, results are partial",
+                config.max_total_probes
+            ));
+        }
+        let syn_fallbacks: Vec<&str> = scanned.iter().filter_map(|host| host.syn_fallback.as_deref()).collect();
+        let mut method_names = methods.names();
+        if let Some(reason) = syn_fallbacks.first() {
+            degradations.push(format!(
+                "SYN probes failed for {} host(s), which were connect-scanned instead: {}",
+                syn_fallbacks.len(),
+                reason
+            ));
+            method_names.insert(1, "tcp_connect".to_string());
+        }
+        let probes_per_host: HashMap<String, u64> = probed_ports
+            .iter()
+            .map(|(ip, ports)| (ip.clone(), ports.len() as u64))
+            .collect();
+        // Nothing went out to hosts that failed at setup
+        probes_sent -= scanned
+            .iter()
+            .filter(|host| host.setup_error.is_some())
+            .map(|host| probes_per_host.get(&host.ip).copied().unwrap_or(0))
+            .sum::<u64>();
+        let hosts_scanned = scanned.len();
+        
+        let scan_timestamp = unix_now();
+        if use_cache {
+            let mut cache = cache.borrow_mut(py);
+            for host in scanned.iter().filter(|host| host.setup_error.is_none()) {
+                for &port in probed_ports.get(&host.ip).into_iter().flatten() {
+                    cache.record(&host.ip, port, host.open_ports.binary_search(&port).is_ok(), scan_timestamp);
+                }
+            }
+            if let Some(path) = config.cache_file.as_deref() {
+                cache.save(path)?;
+            }
+        }
+        {
+            let mut monitor = monitor.borrow_mut(py);
+            monitor.record_probes(probes_sent);
+            monitor.record_cache(hits, misses);
+        }
+        
+        let open_probed: u64 = scanned
+            .iter()
+            .map(|host| {
+                let probed = probes_per_host.get(&host.ip).copied().unwrap_or(0);
+                (host.open_ports.len() as u64).min(probed)
+            })
+            .sum();
+        let scanned: Vec<HostScan> = scanned
+            .into_iter()
+            .map(|mut host: HostScan| {
+                if let Some(cached) = cached_open.remove(&host.ip) {
+                    host.open_ports.extend(cached);
+                }
+                host
+            })
+            .collect();
+        let mut traffic = TrafficStats::default();
+        if methods.syn {
+            let closed: u64 = scanned.iter().map(|host| host.rst_count as u64).sum();
+            traffic.record_syn(open_probed, closed, probes_sent.saturating_sub(open_probed + closed));
+        } else {
+            let reset = route.closes.reset.load(Ordering::Relaxed);
+            traffic.record_connect(open_probed, probes_sent - open_probed, reset.min(open_probed));
+        }
+        let probed_ips: Vec<String> = scanned
+            .iter()
+            .filter(|host| host.setup_error.is_none())
+            .map(|host| host.ip.clone())
+            .collect();
+        let probe_method = if methods.syn { "tcp_syn" } else { "tcp_connect" };
+        let (mut results, setup_failed): (Vec<ScanResult>, Vec<ScanResult>) =
+            results_from_scan(scanned, probe_method, scan_timestamp, config.liveness_threshold)
+                .into_iter()
+                .partition(|result| result.status != "error");
+        if methods.arp || methods.icmp {
+            let up: std::collections::HashSet<&str> = results.iter().map(|r| r.ip.as_str()).collect();
+            let silent: Vec<String> = probed_ips.into_iter().filter(|ip| !up.contains(ip.as_str())).collect();
+            let discovered = py.allow_threads(|| {
+                discover_silent(&silent, methods, config.timeout_ms, scan_timestamp, &mut degradations)
+            });
+            results.extend(discovered);
+        }
+        for result in &mut results {
+            result.probes_sent = probes_per_host.get(&result.ip).copied().unwrap_or(0);
+            if let Some(interface) = &config.interface {
+                result.scanned_via.clone_from(interface);
+                result.reachability.insert(interface.clone(), true);
+            }
+        }
+        
+        if config.resolve_hostnames && !results.is_empty() {
+            let addrs: Vec<IpAddr> = results.iter().filter_map(|r| r.ip.parse().ok()).collect();
+            let names = py.allow_threads(|| {
+                runtime().block_on(resolve_many(&dns_cache, addrs, config.timeout_ms.max(1000), 1, concurrency.min(64)))
+            });
+            for result in &mut results {
+                if let Some(Some(name)) = result.ip.parse().ok().and_then(|ip: IpAddr| names.get(&ip)) {
+                    result.hostname = name.clone();
+                    result.hostname_sources.insert("dns".to_string(), name.clone());
+                }
+            }
+        }
+        if !probes.is_empty() {
+            run_custom_probes(py, &probes, &mut results);
+        }
+        
+        let hosts_up = results.len();
+        let summary = ScanSummary {
+            targets,
+            hosts_up,
+            hosts_down: hosts_scanned - hosts_up - setup_failed.len(),
+            setup_failed: setup_failed.len(),
+            duration_s: start.elapsed().as_secs_f64(),
+            degradations,
+            methods: method_names,
+            probes_sent,
+            aborted,
+            traffic,
+            task_errors,
+            forced_targets,
+            connections_opened: route.closes.opened.load(Ordering::Relaxed),
+            connections_reset: route.closes.reset.load(Ordering::Relaxed),
+        };
+        let task_errors = summary.task_errors.clone();
+        slf.borrow_mut().summary = summary;
+        if strict {
+            surface_task_errors(py, &task_errors, true)?;
+        }
+        results.extend(setup_failed);
+        Ok(results)
+    }
+    
+    /// Register a Python probe, called as `callable(result)` on every host
+    /// the scan finds (or those matching the `only_if` filter expression)
+    ///
+    /// Probes run after the scan on a thread pool, each call limited to
+    /// `timeout_ms`. The dict a probe returns is merged into the host's
+    /// result: keys are read as for `to_canonical`, fields already set are
+    /// kept, unknown keys land in `attributes` and the probe's name is added
+    /// to `sources`; returning None adds nothing. An exception, timeout or
+    /// non-dict return is recorded in the host's `error` and the scan goes
+    /// on. Registering an existing name replaces that probe.
+    #[pyo3(signature = (name, callable, only_if=None, timeout_ms=5000))]
+    fn add_probe(
+        &mut self,
+        py: Python,
+        name: &str,
+        callable: PyObject,
+        only_if: Option<&str>,
+        timeout_ms: u64,
+    ) -> PyResult<()> {
+        if !callable.as_ref(py).is_callable() {
+            return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+                "Probe '{}' is not callable",
+                name
+            )));
+        }
+        let probe = CustomProbe {
+            name: name.to_string(),
+            callable,
+            only_if: only_if.map(crate::query::compile_filter_py).transpose()?,
+            timeout_ms,
+        };
+        match self.probes.iter_mut().find(|p| p.name == name) {
+            Some(existing) => *existing = probe,
+            None => self.probes.push(probe),
+        }
+        Ok(())
+    }
+    
+    /// Unregister a probe; returns whether it was registered
+    fn remove_probe(&mut self, name: &str) -> bool {
+        let before = self.probes.len();
+        self.probes.retain(|p| p.name != name);
+        self.probes.len() != before
+    }
+    
+    /// Summary of the last scan (targets, hosts_up, hosts_down, setup_failed,
+    /// duration_s, degradations, methods, probes_sent, aborted, traffic,
+    /// task_errors, forced_targets, connections)
+    fn summary(&self, py: Python) -> HashMap<String, PyObject> {
+        self.summary.to_py_dict(py)
+    }
+    
+    /// Capability report this Scanner uses to choose methods
+    fn capabilities(&self, py: Python) -> HashMap<String, PyObject> {
+        crate::capabilities::cached().to_py_dict(py)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::{PyDict, PyList};
+    use crate::capabilities::Capabilities;
+
+    fn caps(raw: bool) -> Capabilities {
+        Capabilities {
+            raw_icmp: raw,
+            unprivileged_icmp: false,
+            raw_tcp: raw,
+            raw_packet: raw,
+            pcap: false,
+            privileged: raw,
+            fd_limit: None,
+        }
+    }
+
+    /// What the accepting side reads from a probe's connection once the
+    /// probe has closed it
+    fn peer_sees(abort_close: bool) -> (std::io::Result<usize>, u64, u64) {
+        use std::io::Read;
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let route = ProbeRoute { abort_close, ..ProbeRoute::default() };
+        let (state, _) = runtime().block_on(tcp_probe_outcome("127.0.0.1", port, 1000, &route)).unwrap();
+        assert_eq!(state, PortState::Open);
+        let (mut peer, _) = listener.accept().unwrap();
+        peer.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let read = peer.read(&mut [0u8; 16]);
+        (read, route.closes.opened.load(Ordering::Relaxed), route.closes.reset.load(Ordering::Relaxed))
+    }
+
+    #[test]
+    fn abort_close_resets_the_connection() {
+        let (read, opened, reset) = peer_sees(true);
+        assert_eq!(read.unwrap_err().kind(), std::io::ErrorKind::ConnectionReset);
+        assert_eq!((opened, reset), (1, 1));
+
+        let (read, opened, reset) = peer_sees(false);
+        assert_eq!(read.unwrap(), 0, "FIN close reads as end of stream");
+        assert_eq!((opened, reset), (1, 0));
+    }
+
+    #[test]
+    fn connection_rate_is_capped() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let route = ProbeRoute { pacer: ConnectPacer::new(50.0), ..ProbeRoute::default() };
+        let started = Instant::now();
+        runtime().block_on(async {
+            for _ in 0..6 {
+                assert_eq!(tcp_probe_outcome("127.0.0.1", port, 1000, &route).await.unwrap().0, PortState::Open);
+            }
+        });
+        // Six connections at 50/s: the last starts 100 ms after the first
+        assert!(started.elapsed() >= Duration::from_millis(95), "{:?}", started.elapsed());
+        assert!(ConnectPacer::new(0.0).is_none());
+    }
+
+    #[test]
+    fn panicking_host_task_is_reported_not_dropped() {
+        // A pacer whose first slot fits an Instant and whose second does not:
+        // whichever host waits second panics inside its scan task
+        let now = Instant::now();
+        let (mut fits, mut overflows) = (0u64, u64::MAX);
+        while overflows - fits > 1 {
+            let mid = fits + (overflows - fits) / 2;
+            if now.checked_add(Duration::from_secs(mid)).is_some() { fits = mid } else { overflows = mid }
+        }
+        let pacer = ConnectPacer {
+            interval: Duration::from_secs(fits / 2 + 1),
+            next: parking_lot::Mutex::new(now),
+        };
+        let route = ProbeRoute { pacer: Some(Arc::new(pacer)), ..ProbeRoute::default() };
+        let targets = vec![("127.0.0.1".to_string(), vec![1]), ("127.0.0.2".to_string(), vec![1])];
+        let (scanned, errors) = runtime().block_on(scan_targets(targets, 500, 2, route));
+
+        assert_eq!(scanned.len(), 1);
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].message.starts_with("panicked: "), "{}", errors[0].message);
+        let mut ips = vec![scanned[0].ip.as_str(), errors[0].ip.as_str()];
+        ips.sort();
+        assert_eq!(ips, ["127.0.0.1", "127.0.0.2"]);
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let err = surface_task_errors(py, &errors, true).unwrap_err();
+            assert!(err.is_instance_of::<pyo3::exceptions::PyRuntimeError>(py));
+            assert!(err.to_string().contains(&errors[0].ip), "{}", err);
+        });
+    }
+
+    #[test]
+    fn full_port_range_is_deduplicated_without_quadratic_work() {
+        let ports = parse_port_spec("1-65535,22,80-90,65535").unwrap();
+        assert_eq!(ports.len(), 65535);
+        assert_eq!((ports[0], ports[65534]), (1, 65535));
+        assert!(ports.windows(2).all(|w| w[0] < w[1]));
+        for bad in ["0", "0-10", "65536", "10-1"] {
+            assert!(parse_port_spec(bad).is_err(), "{}", bad);
+        }
+
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let mut pasted: Vec<u16> = (1..=65535).rev().collect();
+            pasted.extend([443, 22, 65535]);
+            let (checked, warnings) = crate::tests::recorded_warnings(py, || checked_ports(py, pasted.clone()));
+            assert_eq!(checked.unwrap(), ports);
+            assert_eq!(warnings.len(), 1, "{:?}", warnings);
+
+            pasted.push(0);
+            let err = checked_ports(py, pasted).unwrap_err();
+            assert!(err.is_instance_of::<pyo3::exceptions::PyValueError>(py));
+        });
+
+        // A host answering on every port, listed backwards with repeats
+        let mut open_ports: Vec<u16> = (1..=65535).rev().collect();
+        open_ports.extend(1..=1000);
+        let host = HostScan { ip: "10.0.0.1".into(), open_ports, ..Default::default() };
+        let started = Instant::now();
+        let results = results_from_scan(vec![host], "tcp_connect", 0.0, 0);
+        assert!(started.elapsed() < Duration::from_secs(5), "{:?}", started.elapsed());
+        assert_eq!(results[0].open_ports, ports);
+        assert_eq!(results[0].port_state_detail.len(), 65535);
+    }
+
+    #[test]
+    fn every_probed_port_state_reaches_the_result() {
+        let host = HostScan {
+            ip: "10.0.0.2".into(),
+            open_ports: vec![443, 22],
+            port_states: HashMap::from([
+                (22, PortState::Open),
+                (443, PortState::Open),
+                (23, PortState::Closed),
+                (25, PortState::Closed),
+                (3389, PortState::Filtered),
+            ]),
+            rst_count: 2,
+            ..Default::default()
+        };
+        let results = results_from_scan(vec![host], "tcp_syn", 0.0, 0);
+        let detail = &results[0].port_state_detail;
+        assert_eq!(detail.len(), 5);
+        assert_eq!(detail[&22], "open");
+        assert_eq!(detail[&25], "closed");
+        assert_eq!(detail[&3389], "filtered");
+        assert_eq!(results[0].open_ports, vec![22, 443]);
+    }
+
+    #[test]
+    fn syn_fallback_is_recorded() {
+        let semaphore = Arc::new(Semaphore::new(4));
+        let route = ProbeRoute { syn: true, interface: Some("netscan-missing0".into()), ..ProbeRoute::default() };
+        let host = runtime().block_on(scan_host_ports("127.0.0.1", &[9], 200, semaphore, &route));
+        assert!(host.syn_fallback.is_some());
+
+        let host = HostScan {
+            ip: "10.0.0.3".into(),
+            open_ports: vec![22],
+            syn_fallback: Some("SYN probes need a raw socket".into()),
+            ..Default::default()
+        };
+        let results = results_from_scan(vec![host], "tcp_syn", 0.0, 0);
+        assert_eq!(results[0].discovery_method, "tcp_connect");
+    }
+
+    #[test]
+    fn blocking_senders_share_the_pace() {
+        let pacer = ConnectPacer::new(20.0).unwrap();
+        let started = Instant::now();
+        for _ in 0..3 {
+            pacer.wait_blocking();
+        }
+        runtime().block_on(pacer.wait());
+        assert!(started.elapsed() >= Duration::from_millis(150), "{:?}", started.elapsed());
+    }
+
+    #[test]
+    fn host_answering_only_with_rst_is_up() {
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let checked = check_host(py, "127.0.0.1", Some(vec![closed]), 500, true).unwrap();
+            assert_eq!(checked["status"].extract::<String>(py).unwrap(), "up");
+            assert!(checked["open_ports"].extract::<Vec<u16>>(py).unwrap().is_empty());
+            let states: HashMap<u16, String> = checked["port_states"].extract(py).unwrap();
+            assert_eq!(states[&closed], "closed");
+        });
+    }
+
+    #[test]
+    fn methods_follow_the_capability_report() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let auto = ScanConfig { scan_method: "auto".into(), discovery: "auto".into(), ..Default::default() };
+            let mut degradations = Vec::new();
+            let methods = select_methods(&auto, &caps(true), &mut degradations).unwrap();
+            assert_eq!(methods, ScanMethods { syn: true, arp: true, icmp: true });
+            assert!(degradations.is_empty());
+
+            let methods = select_methods(&auto, &caps(false), &mut degradations).unwrap();
+            assert_eq!(methods, ScanMethods::default());
+            assert_eq!(methods.names(), vec!["tcp_connect"]);
+            assert_eq!(degradations.len(), 3, "{:?}", degradations);
+
+            // A proxy keeps "auto" on connect without calling it a degradation
+            let proxied = ScanConfig { proxy: Some(("127.0.0.1".into(), 1080)), ..auto.clone() };
+            let mut degradations = Vec::new();
+            assert!(!select_methods(&proxied, &caps(true), &mut degradations).unwrap().syn);
+            assert!(degradations.is_empty());
+
+            let syn = ScanConfig { scan_method: "syn".into(), ..Default::default() };
+            let err = select_methods(&syn, &caps(false), &mut Vec::new()).unwrap_err();
+            assert!(err.is_instance_of::<pyo3::exceptions::PyPermissionError>(py));
+            let arp = ScanConfig { discovery: "arp".into(), ..Default::default() };
+            let err = select_methods(&arp, &caps(false), &mut Vec::new()).unwrap_err();
+            assert!(err.is_instance_of::<pyo3::exceptions::PyPermissionError>(py));
+            let bogus = ScanConfig { scan_method: "udp".into(), ..Default::default() };
+            let err = select_methods(&bogus, &caps(true), &mut Vec::new()).unwrap_err();
+            assert!(err.is_instance_of::<pyo3::exceptions::PyValueError>(py));
+        });
+    }
+
+    #[test]
+    fn progress_is_readable_during_scan() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let config = ScanConfig { ports: vec![port], timeout_ms: 300, ..Default::default() };
+            let scanner = PyCell::new(py, Scanner::new(py, Some(config), None).unwrap()).unwrap();
+
+            // From another thread, while the scan has released the GIL
+            let handle: Py<Scanner> = scanner.into();
+            let reader = std::thread::spawn(move || {
+                let mut reads = 0;
+                while reads < 20 {
+                    Python::with_gil(|py| {
+                        let progress = handle.as_ref(py).try_borrow().map(|s| s.progress.clone());
+                        assert!(progress.is_ok(), "Scanner borrowed during scan");
+                    });
+                    reads += 1;
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+            });
+
+            // And from the progress callback
+            let seen = PyList::empty(py);
+            let globals = PyDict::new(py);
+            globals.set_item("scanner", scanner).unwrap();
+            globals.set_item("seen", seen).unwrap();
+            let callback = py
+                .eval("lambda *args: seen.append((scanner.progress.done, scanner.progress.running))", Some(globals), None)
+                .unwrap();
+            let ips = PyList::new(py, ["127.0.0.1", "127.0.0.2"]);
+            Scanner::scan(scanner, py, Some(ips), false, Some(callback.into()), false).unwrap();
+            py.allow_threads(|| reader.join().unwrap());
+
+            let seen: Vec<(usize, bool)> = seen.extract().unwrap();
+            assert_eq!(seen, vec![(1, true), (2, true)]);
+            let running: bool = scanner.getattr("progress").unwrap().getattr("running").unwrap().extract().unwrap();
+            assert!(!running);
+        });
+    }
+}
@@ -0,0 +1,828 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use serde::{Serialize, Deserialize};
+
+use crate::importers::civil_from_days;
+use crate::scanner::{parse_port_spec, unix_now, ScanConfig, ScanResult, Scanner};
+use crate::scope::authorize_targets;
+use crate::targets::expand_targets;
+
+// =============================================================================
+// Scan Windows (allowed times of day / days of week)
+// =============================================================================
+//
+// A window is written "<days> HH:MM-HH:MM", evaluated in local time:
+//   "daily 22:00-06:00"   every night, crossing midnight
+//   "Mon-Fri 22:00-06:00" starts Monday..Friday evenings (ends Tue..Sat 06:00)
+//   "Sat,Sun 00:00-24:00" whole weekend days
+// A cross-midnight range belongs to the day it starts on.
+
+const DAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanWindow {
+    /// Allowed start days, Monday = 0
+    days: [bool; 7],
+    /// Minutes after midnight
+    start: u32,
+    end: u32,
+}
+
+fn parse_day(name: &str) -> Option<usize> {
+    let name = name.trim().to_lowercase();
+    DAY_NAMES.iter().position(|d| name.starts_with(d))
+}
+
+fn parse_clock(value: &str) -> Option<u32> {
+    let (h, m) = value.trim().split_once(':')?;
+    let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+    (m < 60 && (h < 24 || (h == 24 && m == 0))).then_some(h * 60 + m)
+}
+
+impl ScanWindow {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid scan window '{}' (expected e.g. 'Mon-Fri 22:00-06:00')", spec);
+        let (days_spec, range) = spec.trim().rsplit_once(char::is_whitespace).ok_or_else(invalid)?;
+        let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+        let (start, end) = (parse_clock(start).ok_or_else(invalid)?, parse_clock(end).ok_or_else(invalid)?);
+        if start == end {
+            return Err(invalid());
+        }
+
+        let mut days = [false; 7];
+        let days_spec = days_spec.trim().to_lowercase();
+        if matches!(days_spec.as_str(), "daily" | "*" | "all") {
+            days = [true; 7];
+        } else {
+            for part in days_spec.split(',') {
+                match part.split_once('-') {
+                    Some((from, to)) => {
+                        let (from, to) = (parse_day(from).ok_or_else(invalid)?, parse_day(to).ok_or_else(invalid)?);
+                        // Ranges may wrap the week ("Fri-Mon")
+                        let mut day = from;
+                        loop {
+                            days[day] = true;
+                            if day == to {
+                                break;
+                            }
+                            day = (day + 1) % 7;
+                        }
+                    }
+                    None => days[parse_day(part).ok_or_else(invalid)?] = true,
+                }
+            }
+        }
+        Ok(ScanWindow { days, start, end })
+    }
+
+    /// Whether (weekday, minute-of-day) falls inside this window
+    pub fn contains(&self, weekday: usize, minute: u32) -> bool {
+        if self.start < self.end {
+            self.days[weekday] && minute >= self.start && minute < self.end
+        } else {
+            let yesterday = (weekday + 6) % 7;
+            (self.days[weekday] && minute >= self.start) || (self.days[yesterday] && minute < self.end)
+        }
+    }
+}
+
+/// Local (weekday with Monday = 0, minute of day) for a Unix timestamp
+fn local_weekday_minute(ts: f64) -> (usize, u32) {
+    let t = local_time(ts);
+    (((t.weekday + 6) % 7) as usize, t.hour * 60 + t.minute)
+}
+
+/// Whether any window allows scanning at `ts` (no windows means always)
+pub fn in_window(windows: &[ScanWindow], ts: f64) -> bool {
+    if windows.is_empty() {
+        return true;
+    }
+    let (weekday, minute) = local_weekday_minute(ts);
+    windows.iter().any(|w| w.contains(weekday, minute))
+}
+
+/// Start of the next allowed minute at or after `ts`, searching one week ahead
+pub fn next_window_start(windows: &[ScanWindow], ts: f64) -> Option<f64> {
+    if in_window(windows, ts) {
+        return Some(ts);
+    }
+    let first = (ts / 60.0).floor() * 60.0 + 60.0;
+    (0..8 * 1440)
+        .map(|i| first + i as f64 * 60.0)
+        .find(|t| in_window(windows, *t))
+}
+
+fn parse_windows(specs: &[String]) -> PyResult<Vec<ScanWindow>> {
+    specs
+        .iter()
+        .map(|spec| ScanWindow::parse(spec).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>))
+        .collect()
+}
+
+// =============================================================================
+// Windowed Scanning with Checkpoints
+// =============================================================================
+
+/// Progress of a windowed scan; persisted to the checkpoint file after every batch
+#[pyclass]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WindowedScanState {
+    #[pyo3(get)]
+    pub targets: Vec<String>,
+    /// Targets already scanned, in completion order
+    #[pyo3(get)]
+    pub completed: Vec<String>,
+    #[pyo3(get)]
+    pub results: Vec<ScanResult>,
+    #[pyo3(get)]
+    pub windows: Vec<String>,
+    #[pyo3(get)]
+    pub checkpoint_path: String,
+    /// Unix time the scan may continue, when paused outside every window
+    #[pyo3(get)]
+    pub next_window_start: Option<f64>,
+}
+
+fn io_error(action: &str, e: impl std::fmt::Display) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot {} scan checkpoint: {}", action, e))
+}
+
+impl WindowedScanState {
+    fn save(&self) -> PyResult<()> {
+        // Write then rename so an interrupted save never leaves a torn checkpoint
+        let tmp = format!("{}.tmp", self.checkpoint_path);
+        let file = File::create(&tmp).map_err(|e| io_error("write", e))?;
+        serde_json::to_writer(BufWriter::new(file), self).map_err(|e| io_error("write", e))?;
+        std::fs::rename(&tmp, &self.checkpoint_path).map_err(|e| io_error("write", e))
+    }
+
+    fn pending(&self) -> Vec<String> {
+        let done: HashSet<&String> = self.completed.iter().collect();
+        let mut seen = HashSet::new();
+        self.targets
+            .iter()
+            .filter(|ip| !done.contains(ip) && seen.insert(*ip))
+            .cloned()
+            .collect()
+    }
+
+    /// Scan pending targets batch by batch while inside a window
+    ///
+    /// The window is checked between batches: a batch already in flight is
+    /// allowed to finish, then no new probes start until the next window.
+    /// Pending targets are checked against the authorized scopes first.
+    fn run(&mut self, py: Python, scanner: &PyCell<Scanner>, blocking: bool, batch_size: usize, force: bool) -> PyResult<()> {
+        let windows = parse_windows(&self.windows)?;
+        let batch_size = batch_size.max(1);
+        authorize_targets(py, "run_windowed_scan", &self.pending(), force)?;
+
+        loop {
+            let pending = self.pending();
+            if pending.is_empty() {
+                self.next_window_start = None;
+                return self.save();
+            }
+
+            let now = unix_now();
+            if !in_window(&windows, now) {
+                self.next_window_start = next_window_start(&windows, now);
+                self.save()?;
+                let Some(resume_at) = self.next_window_start else {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                        "Scan windows never open",
+                    ));
+                };
+                if !blocking {
+                    return Ok(());
+                }
+                while unix_now() < resume_at {
+                    py.check_signals()?;
+                    let wait = (resume_at - unix_now()).clamp(0.0, 1.0);
+                    py.allow_threads(|| std::thread::sleep(Duration::from_secs_f64(wait)));
+                }
+                continue;
+            }
+
+            self.next_window_start = None;
+            let batch: Vec<String> = pending.into_iter().take(batch_size).collect();
+            let results = Scanner::scan(scanner, py, Some(PyList::new(py, &batch)), false, None, force)?;
+            self.results.extend(results);
+            self.completed.extend(batch);
+            self.save()?;
+        }
+    }
+}
+
+#[pymethods]
+impl WindowedScanState {
+    /// Load a checkpoint written by `run_windowed_scan`
+    #[staticmethod]
+    pub fn load(path: &str) -> PyResult<WindowedScanState> {
+        let file = File::open(path).map_err(|e| io_error("read", e))?;
+        let mut state: WindowedScanState = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| io_error("read", e))?;
+        state.checkpoint_path = path.to_string();
+        Ok(state)
+    }
+
+    /// Continue scanning from this state (see `run_windowed_scan`)
+    #[pyo3(signature = (scanner, blocking=true, batch_size=256, force=false))]
+    fn resume(
+        &mut self,
+        py: Python,
+        scanner: &PyCell<Scanner>,
+        blocking: bool,
+        batch_size: usize,
+        force: bool,
+    ) -> PyResult<()> {
+        self.run(py, scanner, blocking, batch_size, force)
+    }
+
+    /// Targets not yet scanned
+    fn remaining(&self) -> Vec<String> {
+        self.pending()
+    }
+
+    #[getter]
+    fn is_complete(&self) -> bool {
+        self.pending().is_empty()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "WindowedScanState(<{}/{} targets done>, results={}, next_window_start={})",
+            self.completed.len(),
+            self.targets.len(),
+            self.results.len(),
+            self.next_window_start.map(|t| t.to_string()).unwrap_or_else(|| "None".to_string())
+        )
+    }
+}
+
+/// Scan `targets` only during the allowed `windows`, checkpointing progress
+///
+/// Windows use the "<days> HH:MM-HH:MM" format in local time (e.g.
+/// "Mon-Fri 22:00-06:00"); an empty list allows any time. If
+/// `checkpoint_path` already holds a checkpoint, targets it lists as completed
+/// are not scanned again. Outside a window the scan pauses after the current
+/// batch: with `blocking=True` it sleeps until the next window opens,
+/// otherwise it returns the state so the caller can `resume()` later.
+/// `force` is passed on to each batch's `Scanner.scan`.
+#[pyfunction]
+#[pyo3(signature = (scanner, targets, windows, checkpoint_path, blocking=true, batch_size=256, force=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn run_windowed_scan(
+    py: Python,
+    scanner: &PyCell<Scanner>,
+    targets: Vec<String>,
+    windows: Vec<String>,
+    checkpoint_path: &str,
+    blocking: bool,
+    batch_size: usize,
+    force: bool,
+) -> PyResult<WindowedScanState> {
+    parse_windows(&windows)?;
+
+    let previous = if std::path::Path::new(checkpoint_path).exists() {
+        Some(WindowedScanState::load(checkpoint_path)?)
+    } else {
+        None
+    };
+    let mut state = WindowedScanState {
+        targets,
+        windows,
+        checkpoint_path: checkpoint_path.to_string(),
+        ..Default::default()
+    };
+    if let Some(previous) = previous {
+        let wanted: HashSet<&String> = state.targets.iter().collect();
+        let done: HashSet<String> = previous.completed.iter().filter(|ip| wanted.contains(ip)).cloned().collect();
+        state.results = previous.results.into_iter().filter(|r| done.contains(&r.ip)).collect();
+        state.completed = previous.completed.into_iter().filter(|ip| done.contains(ip)).collect();
+    }
+
+    state.run(py, scanner, blocking, batch_size, force)?;
+    Ok(state)
+}
+
+/// Whether `timestamp` (default: now) falls inside any of the given windows
+#[pyfunction]
+#[pyo3(signature = (windows, timestamp=None))]
+pub fn in_scan_window(windows: Vec<String>, timestamp: Option<f64>) -> PyResult<bool> {
+    Ok(in_window(&parse_windows(&windows)?, timestamp.unwrap_or_else(unix_now)))
+}
+
+// =============================================================================
+// Cron Schedules
+// =============================================================================
+//
+// Standard five-field crontab expressions, "minute hour day-of-month month
+// day-of-week", in local time: `*`, lists, ranges and steps ("*/15",
+// "1-5", "0,30", "9-17/2"), month and day names ("jan", "mon-fri"), 0 or 7
+// for Sunday, and the @hourly/@daily/@weekly/@monthly/@yearly shorthands.
+// As in cron, when both day fields are restricted a day matching either
+// one fires.
+
+/// Broken-down local time
+#[derive(Debug, Clone, Copy)]
+struct LocalTime {
+    year: i64,
+    /// 1-12
+    month: u32,
+    /// 1-31
+    day: u32,
+    hour: u32,
+    minute: u32,
+    /// Sunday = 0, as cron counts
+    weekday: u32,
+}
+
+/// Broken-down local time for a Unix timestamp (UTC where the platform
+/// has no `localtime_r`, or it fails)
+#[cfg(unix)]
+fn local_time(ts: f64) -> LocalTime {
+    let secs = ts as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    // SAFETY: both pointers are valid for the duration of the call
+    if unsafe { libc::localtime_r(&secs, &mut tm) }.is_null() {
+        return utc_time(ts);
+    }
+    LocalTime {
+        year: tm.tm_year as i64 + 1900,
+        month: tm.tm_mon as u32 + 1,
+        day: tm.tm_mday as u32,
+        hour: tm.tm_hour as u32,
+        minute: tm.tm_min as u32,
+        weekday: tm.tm_wday as u32,
+    }
+}
+
+#[cfg(not(unix))]
+fn local_time(ts: f64) -> LocalTime {
+    utc_time(ts)
+}
+
+fn utc_time(ts: f64) -> LocalTime {
+    let secs = ts as i64;
+    let days = secs.div_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    let minute_of_day = (secs.rem_euclid(86_400) / 60) as u32;
+    LocalTime {
+        year,
+        month,
+        day,
+        hour: minute_of_day / 60,
+        minute: minute_of_day % 60,
+        // 1970-01-01 was a Thursday
+        weekday: (days + 4).rem_euclid(7) as u32,
+    }
+}
+
+const MONTH_NAMES: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const CRON_DAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    /// Bit n set when value n is allowed
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether the day-of-month / day-of-week field was `*`
+    any_day: bool,
+    any_weekday: bool,
+}
+
+/// Allowed values of one field as a bitmask; `names` are matched by their
+/// first three letters and numbered from `min`
+fn parse_cron_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |v: &str| -> Result<u32, String> {
+        let lower = v.to_lowercase();
+        let n = match names.iter().position(|name| lower.starts_with(name)) {
+            Some(i) if lower.len() >= 3 => i as u32 + min,
+            _ => v.parse().map_err(|_| format!("'{}' is not a number", v))?,
+        };
+        if n < min || n > max {
+            return Err(format!("{} is outside {}-{}", n, min, max));
+        }
+        Ok(n)
+    };
+
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("invalid step in '{}'", part)),
+            },
+            None => (part, 1),
+        };
+        let (from, to) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((from, to)) => (value(from)?, value(to)?),
+                // "5/15" runs from 5 to the end of the range
+                None if part.contains('/') => (value(range)?, max),
+                None => {
+                    let v = value(range)?;
+                    (v, v)
+                }
+            },
+        };
+        if from > to {
+            return Err(format!("range '{}' runs backwards", range));
+        }
+        for n in (from..=to).step_by(step as usize) {
+            mask |= 1 << n;
+        }
+    }
+    Ok(mask)
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expanded = match expr.trim().to_lowercase().as_str() {
+            "@hourly" => "0 * * * *".to_string(),
+            "@daily" | "@midnight" => "0 0 * * *".to_string(),
+            "@weekly" => "0 0 * * 0".to_string(),
+            "@monthly" => "0 0 1 * *".to_string(),
+            "@yearly" | "@annually" => "0 0 1 1 *".to_string(),
+            _ => expr.trim().to_string(),
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let invalid = |why: String| format!("Invalid cron expression '{}': {}", expr, why);
+        if fields.len() != 5 {
+            return Err(invalid(format!(
+                "expected 5 fields (minute hour day month weekday), got {}",
+                fields.len()
+            )));
+        }
+        let mut weekdays = parse_cron_field(fields[4], 0, 7, &CRON_DAY_NAMES).map_err(invalid)?;
+        // 7 is Sunday too
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(CronSchedule {
+            minutes: parse_cron_field(fields[0], 0, 59, &[]).map_err(invalid)?,
+            hours: parse_cron_field(fields[1], 0, 23, &[]).map_err(invalid)?,
+            days: parse_cron_field(fields[2], 1, 31, &[]).map_err(invalid)?,
+            months: parse_cron_field(fields[3], 1, 12, &MONTH_NAMES).map_err(invalid)?,
+            weekdays,
+            any_day: fields[2].starts_with('*'),
+            any_weekday: fields[4].starts_with('*'),
+        })
+    }
+
+    fn day_matches(&self, t: &LocalTime) -> bool {
+        let day = self.days & (1 << t.day) != 0;
+        let weekday = self.weekdays & (1 << t.weekday) != 0;
+        let date = if self.any_day || self.any_weekday { day && weekday } else { day || weekday };
+        date && self.months & (1 << t.month) != 0
+    }
+
+    /// First whole minute after `ts` the schedule fires, searching five
+    /// years ahead (long enough for "0 0 29 2 *")
+    pub fn next_after(&self, ts: f64) -> Option<f64> {
+        self.next_after_in(ts, local_time)
+    }
+
+    /// `next_after` reading wall-clock time through `clock`
+    fn next_after_in(&self, ts: f64, clock: fn(f64) -> LocalTime) -> Option<f64> {
+        let limit = ts + 5.0 * 366.0 * 86_400.0;
+        let mut t = (ts / 60.0).floor() * 60.0 + 60.0;
+        while t < limit {
+            let local = clock(t);
+            // Skip to the next day or hour rather than stepping minute by minute
+            if !self.day_matches(&local) {
+                t += ((24 - local.hour) * 60 - local.minute) as f64 * 60.0;
+            } else if self.hours & (1 << local.hour) == 0 {
+                t += (60 - local.minute) as f64 * 60.0;
+            } else if self.minutes & (1 << local.minute) == 0 {
+                t += 60.0;
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+}
+
+fn parse_cron(expr: &str) -> PyResult<CronSchedule> {
+    CronSchedule::parse(expr).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+/// Next times (Unix seconds) `cron_expr` fires after `after` (default: now)
+#[pyfunction]
+#[pyo3(signature = (cron_expr, count=1, after=None))]
+pub fn cron_next_runs(cron_expr: &str, count: usize, after: Option<f64>) -> PyResult<Vec<f64>> {
+    let schedule = parse_cron(cron_expr)?;
+    let mut runs = Vec::with_capacity(count);
+    let mut t = after.unwrap_or_else(unix_now);
+    while runs.len() < count {
+        match schedule.next_after(t) {
+            Some(next) => {
+                runs.push(next);
+                t = next;
+            }
+            None => break,
+        }
+    }
+    Ok(runs)
+}
+
+// =============================================================================
+// Scheduled Scans
+// =============================================================================
+//
+// A schedule stops when its `ScheduleCancel` token is cancelled: a scan in
+// progress is finished and saved, then the call returns. The crate installs
+// no signal handlers; to stop on SIGTERM (systemd, `docker stop`), cancel
+// the token from a Python handler, which runs between scans and while
+// waiting:
+//   cancel = ScheduleCancel()
+//   signal.signal(signal.SIGTERM, lambda *_: cancel.cancel())
+// Ctrl-C raises KeyboardInterrupt as usual, but only between scans.
+
+/// Stops the scheduled scans it is passed to; safe to cancel from any thread
+#[pyclass]
+#[derive(Debug, Clone, Default)]
+pub struct ScheduleCancel {
+    cancelled: Arc<AtomicBool>,
+}
+
+#[pymethods]
+impl ScheduleCancel {
+    #[new]
+    fn new() -> Self {
+        ScheduleCancel::default()
+    }
+
+    /// Stop the schedule after the scan in progress, if any
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether `cancel()` has been called
+    #[getter]
+    pub fn cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ScheduleCancel(cancelled={})", if self.cancelled() { "True" } else { "False" })
+    }
+}
+
+/// Sleep until `at` without holding the GIL, running Python signal
+/// handlers each second; false if `cancel` was cancelled first
+fn sleep_until(py: Python, at: f64, cancel: &ScheduleCancel) -> PyResult<bool> {
+    loop {
+        py.check_signals()?;
+        if cancel.cancelled() {
+            return Ok(false);
+        }
+        let wait = at - unix_now();
+        if wait <= 0.0 {
+            return Ok(true);
+        }
+        py.allow_threads(|| std::thread::sleep(Duration::from_secs_f64(wait.min(1.0))));
+    }
+}
+
+/// Targets and config of a scheduled scan, checked (authorized scopes
+/// included) before any waiting
+fn scheduled_scan_setup(
+    py: Python,
+    entry_point: &str,
+    cidr: &str,
+    port_spec: &str,
+    config: Option<ScanConfig>,
+    force: bool,
+) -> PyResult<(Vec<String>, ScanConfig)> {
+    let targets = expand_targets(&[cidr.to_string()]).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+    authorize_targets(py, entry_point, &targets, force)?;
+    let mut config = config.unwrap_or_default();
+    config.ports = parse_port_spec(port_spec).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+    Ok((targets, config))
+}
+
+/// Scan `targets` and write the results to `path` as JSON lines
+fn run_scheduled_scan(py: Python, scanner: &PyCell<Scanner>, targets: &[String], path: &str, force: bool) -> PyResult<usize> {
+    let results = Scanner::scan(scanner, py, Some(PyList::new(py, targets)), false, None, force)?;
+    crate::jsonl::write_scan_results_jsonl(results, path, false)
+}
+
+/// Scan `cidr` every time `cron_expr` fires, until SIGTERM
+///
+/// `cron_expr` is a five-field crontab line in local time ("0 2 * * *" is
+/// 02:00 daily, "*/30 8-18 * * mon-fri" every half hour of the working
+/// day). Each run scans `cidr` (any `expand_targets` spec) on `port_spec`
+/// ("22,80,443", "1-1024", "common") with `config`'s other settings and
+/// writes `output_dir/scan_YYYY-MM-DD_HH-MM.jsonl`, named for its scheduled
+/// time. A run that overruns later fire times skips them. The GIL is
+/// released while waiting. Cancelling `cancel` (see `ScheduleCancel`) lets
+/// a running scan finish and be saved, then returns; so does completing
+/// `max_runs` runs. Targets outside the
+/// authorized scopes are refused when the schedule is set up, not at the
+/// first run (`force=True` as in `Scanner.scan`).
+#[pyfunction]
+#[pyo3(signature = (cron_expr, cidr, port_spec, output_dir, config=None, max_runs=None, force=false, cancel=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn scan_schedule_cron(
+    py: Python,
+    cron_expr: &str,
+    cidr: &str,
+    port_spec: &str,
+    output_dir: &str,
+    config: Option<ScanConfig>,
+    max_runs: Option<usize>,
+    force: bool,
+    cancel: Option<ScheduleCancel>,
+) -> PyResult<()> {
+    let schedule = parse_cron(cron_expr)?;
+    let (targets, config) = scheduled_scan_setup(py, "scan_schedule_cron", cidr, port_spec, config, force)?;
+    std::fs::create_dir_all(output_dir).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot create {}: {}", output_dir, e))
+    })?;
+    let scanner = PyCell::new(py, Scanner::new(py, Some(config), None)?)?;
+    let cancel = cancel.unwrap_or_default();
+
+    let mut runs = 0usize;
+    while max_runs.is_none_or(|max| runs < max) {
+        let Some(at) = schedule.next_after(unix_now()) else {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Cron expression '{}' never fires",
+                cron_expr
+            )));
+        };
+        if !sleep_until(py, at, &cancel)? {
+            break;
+        }
+        let t = local_time(at);
+        let path = std::path::Path::new(output_dir).join(format!(
+            "scan_{:04}-{:02}-{:02}_{:02}-{:02}.jsonl",
+            t.year, t.month, t.day, t.hour, t.minute
+        ));
+        run_scheduled_scan(py, scanner, &targets, &path.to_string_lossy(), force)?;
+        runs += 1;
+        py.check_signals()?;
+        if cancel.cancelled() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Scan `cidr` once at Unix time `timestamp` (at once if it has passed),
+/// writing the results to `output_file` as JSON lines
+///
+/// Arguments as for `scan_schedule_cron`. Cancelling `cancel` while waiting
+/// returns without scanning.
+#[pyfunction]
+#[pyo3(signature = (timestamp, cidr, port_spec, output_file, config=None, force=false, cancel=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn scan_schedule_once_at(
+    py: Python,
+    timestamp: f64,
+    cidr: &str,
+    port_spec: &str,
+    output_file: &str,
+    config: Option<ScanConfig>,
+    force: bool,
+    cancel: Option<ScheduleCancel>,
+) -> PyResult<()> {
+    let (targets, config) = scheduled_scan_setup(py, "scan_schedule_once_at", cidr, port_spec, config, force)?;
+    let scanner = PyCell::new(py, Scanner::new(py, Some(config), None)?)?;
+    if sleep_until(py, timestamp, &cancel.unwrap_or_default())? {
+        run_scheduled_scan(py, scanner, &targets, output_file, force)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_clock_reads_the_cron_clock() {
+        // 2024-03-04 was a Monday
+        let t = utc_time(1_709_556_300.0);
+        assert_eq!((t.year, t.month, t.day, t.hour, t.minute, t.weekday), (2024, 3, 4, 12, 45, 1));
+        for ts in [0.0, 1_709_556_300.0, 1_709_856_000.0] {
+            let local = local_time(ts);
+            let (weekday, minute) = local_weekday_minute(ts);
+            assert_eq!((weekday as u32 + 1) % 7, local.weekday);
+            assert_eq!(minute, local.hour * 60 + local.minute);
+        }
+    }
+
+    fn bits(values: &[u32]) -> u64 {
+        values.iter().fold(0, |mask, v| mask | 1 << v)
+    }
+
+    #[test]
+    fn cron_fields_parse_to_bitmasks() {
+        let s = CronSchedule::parse("*/15 9-17/2 1,15 jan-mar mon-fri").unwrap();
+        assert_eq!(s.minutes, bits(&[0, 15, 30, 45]));
+        assert_eq!(s.hours, bits(&[9, 11, 13, 15, 17]));
+        assert_eq!(s.days, bits(&[1, 15]));
+        assert_eq!(s.months, bits(&[1, 2, 3]));
+        assert_eq!(s.weekdays, bits(&[1, 2, 3, 4, 5]));
+        assert!(!s.any_day && !s.any_weekday);
+
+        type Field = fn(&CronSchedule) -> u64;
+        let cases: &[(&str, Field, &[u32])] = &[
+            ("5/20 * * * *", |s| s.minutes, &[5, 25, 45]),
+            ("* * * * 0,7", |s| s.weekdays, &[0]),
+            ("* * * * 7", |s| s.weekdays, &[0]),
+            ("* * * * SAT,Sun", |s| s.weekdays, &[0, 6]),
+            ("* * * jun,Dec *", |s| s.months, &[6, 12]),
+            ("* 22-23 * * *", |s| s.hours, &[22, 23]),
+            ("@weekly", |s| s.weekdays, &[0]),
+            ("@hourly", |s| s.minutes, &[0]),
+        ];
+        for (expr, field, expected) in cases {
+            let s = CronSchedule::parse(expr).unwrap_or_else(|e| panic!("{}: {}", expr, e));
+            assert_eq!(field(&s), bits(expected), "{}", expr);
+        }
+        assert!(CronSchedule::parse("* * * * *").unwrap().any_day);
+    }
+
+    #[test]
+    fn bad_cron_fields_are_rejected() {
+        for expr in [
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * 32 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "* * * *",
+            "* * * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "abc * * * *",
+            "* * * foo *",
+            "* * * * funday",
+            "1,,2 * * * *",
+            "@yearly-ish",
+        ] {
+            assert!(CronSchedule::parse(expr).is_err(), "{} should be rejected", expr);
+        }
+    }
+
+    #[test]
+    fn next_fire_times_follow_cron_rules() {
+        // 2024-03-04 12:45 UTC, a Monday
+        let monday = 1_709_556_300.0;
+        let day = 86_400.0;
+        let cases = [
+            ("*/15 * * * *", monday, (2024, 3, 4, 13, 0)),
+            ("45 12 * * *", monday, (2024, 3, 5, 12, 45)),
+            ("0 9 * * mon-fri", monday + 4.0 * day - 2.75 * 3600.0, (2024, 3, 11, 9, 0)),
+            ("0 0 * * 7", monday, (2024, 3, 10, 0, 0)),
+            ("0 0 * * sun", monday, (2024, 3, 10, 0, 0)),
+            // Day of month and day of week both restricted: either may match
+            ("0 0 13 * fri", monday, (2024, 3, 8, 0, 0)),
+            ("0 0 13 * fri", monday + 4.0 * day, (2024, 3, 13, 0, 0)),
+            // With one of them `*`, only the other counts
+            ("0 0 13 * *", monday, (2024, 3, 13, 0, 0)),
+            ("0 0 29 2 *", monday, (2028, 2, 29, 0, 0)),
+            ("0 12 1 jan *", monday, (2025, 1, 1, 12, 0)),
+            ("@monthly", monday, (2024, 4, 1, 0, 0)),
+        ];
+        for (expr, from, (year, month, d, hour, minute)) in cases {
+            let at = CronSchedule::parse(expr).unwrap().next_after_in(from, utc_time).unwrap();
+            let t = utc_time(at);
+            assert_eq!((t.year, t.month, t.day, t.hour, t.minute), (year, month, d, hour, minute), "{}", expr);
+        }
+        // A date that never occurs is not found
+        assert_eq!(CronSchedule::parse("0 0 31 feb *").unwrap().next_after_in(monday, utc_time), None);
+    }
+
+    #[test]
+    fn cancelled_schedule_stops_waiting() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let cancel = ScheduleCancel::default();
+            let far = unix_now() + 3600.0;
+            let token = cancel.clone();
+            let canceller = std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(200));
+                token.cancel();
+            });
+            assert!(!sleep_until(py, far, &cancel).unwrap());
+            canceller.join().unwrap();
+            assert!(cancel.cancelled());
+            // A separate schedule is unaffected
+            assert!(sleep_until(py, unix_now(), &ScheduleCancel::default()).unwrap());
+        });
+    }
+}
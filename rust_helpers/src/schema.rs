@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use pyo3::prelude::*;
+use pyo3::types::{IntoPyDict, PyDict, PyString, PyTuple};
+
+use crate::scanner::{ScanResult, ScanResultDataclass, PY_FIELDS};
+
+// =============================================================================
+// Canonical Result Schema
+// =============================================================================
+//
+// Every producer of host records (scanners, device table parsers, lease and
+// pipe files, importers) returns ScanResult objects: the usual fields plus
+// `sources` (producers that contributed), `scan_timestamp` and `error`.
+// Fields a producer cannot observe keep their defaults; what it knows that
+// has no field of its own (switch port, VLAN, ARP age) goes to `attributes`.
+//
+// Producers that used to return their own dict or tuple layout take
+// `legacy=True` to keep returning it for one more release (with a
+// DeprecationWarning); `to_canonical` upgrades records in any of those
+// layouts.
+
+/// Keys older layouts used for canonical fields
+const FIELD_ALIASES: &[(&str, &str)] = &[
+    ("ip_address", "ip"),
+    ("address", "ip"),
+    ("mac_address", "mac"),
+    ("hw_address", "mac"),
+    ("host", "hostname"),
+    ("name", "hostname"),
+    ("rtt_ms", "response_time_ms"),
+    ("latency_ms", "response_time_ms"),
+    ("ports", "open_ports"),
+    ("timestamp", "scan_timestamp"),
+    ("last_seen", "scan_timestamp"),
+];
+
+/// Canonical field for a record key, and whether the key is the field's
+/// own name (which wins over aliases)
+fn canonical_field(key: &str) -> Option<(&'static str, bool)> {
+    let key = key.trim().to_lowercase();
+    if let Some(field) = PY_FIELDS.iter().find(|f| **f == key) {
+        return Some((field, true));
+    }
+    FIELD_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == key)
+        .map(|(_, field)| (*field, false))
+}
+
+fn text(value: &PyAny) -> PyResult<String> {
+    if value.is_none() {
+        return Ok(String::new());
+    }
+    match value.downcast::<PyString>() {
+        Ok(s) => Ok(s.to_str()?.trim().to_string()),
+        Err(_) => Ok(value.str()?.to_str()?.trim().to_string()),
+    }
+}
+
+fn number(value: &PyAny) -> PyResult<f64> {
+    match value.extract::<f64>() {
+        Ok(n) => Ok(n),
+        Err(_) => Ok(text(value)?.parse().unwrap_or(0.0)),
+    }
+}
+
+/// Comma-separated text or a list; unparseable items are dropped
+fn text_list(value: &PyAny) -> PyResult<Vec<String>> {
+    let items: Vec<String> = if value.is_instance_of::<PyString>() {
+        text(value)?.split(',').map(|s| s.trim().to_string()).collect()
+    } else {
+        value.iter()?.map(|item| text(item?)).collect::<PyResult<_>>()?
+    };
+    Ok(items.into_iter().filter(|s| !s.is_empty()).collect())
+}
+
+fn text_map(value: &PyAny) -> PyResult<HashMap<String, String>> {
+    value
+        .downcast::<PyDict>()?
+        .iter()
+        .map(|(k, v)| Ok((text(k)?, text(v)?)))
+        .collect()
+}
+
+/// Record `source` as a contributor; it also names the discovery method
+/// when the record has none
+fn add_source(result: &mut ScanResult, source: &str) {
+    let source = source.trim();
+    if source.is_empty() {
+        return;
+    }
+    if !result.sources.iter().any(|s| s == source) {
+        result.sources.push(source.to_string());
+    }
+    if result.discovery_method.is_empty() {
+        result.discovery_method = source.to_string();
+    }
+}
+
+/// Canonical result from a dict in any producer's layout
+///
+/// Canonical keys win over their aliases and None counts as missing;
+/// unknown keys are kept as text in `attributes`. Unparseable numbers and ports take their defaults, like
+/// `ScanResult::from_record`.
+pub fn canonical_from_dict(dict: &PyDict, source: &str) -> PyResult<ScanResult> {
+    let mut fields: HashMap<&'static str, &PyAny> = HashMap::new();
+    let mut result = ScanResult::default();
+    for (key, value) in dict.iter().filter(|(_, v)| !v.is_none()) {
+        let key = text(key)?;
+        match canonical_field(&key) {
+            Some((field, exact)) if exact || !fields.contains_key(field) => {
+                fields.insert(field, value);
+            }
+            Some(_) => {}
+            None => {
+                result.attributes.insert(key, text(value)?);
+            }
+        }
+    }
+
+    for (field, value) in fields {
+        match field {
+            "ip" => result.ip = text(value)?,
+            "mac" => result.mac = text(value)?,
+            "hostname" => result.hostname = text(value)?,
+            "vendor" => result.vendor = text(value)?,
+            "status" => result.status = text(value)?.to_lowercase(),
+            "discovery_method" => result.discovery_method = text(value)?,
+            "os" => result.os = text(value)?,
+            "error" => result.error = text(value)?,
+            "scanned_via" => result.scanned_via = text(value)?,
+            "response_time_ms" => result.response_time_ms = number(value)?,
+            "scan_timestamp" => result.scan_timestamp = number(value)?,
+            "probes_sent" => result.probes_sent = number(value)?.max(0.0) as u64,
+            "open_ports" => {
+                result.open_ports = text_list(value)?.iter().filter_map(|p| p.parse().ok()).collect();
+            }
+            "sources" => result.sources = text_list(value)?,
+            "ipv6_addresses" => result.ipv6_addresses = text_list(value)?,
+            "hostname_sources" => result.hostname_sources = text_map(value)?,
+            "port_state_detail" => {
+                result.port_state_detail = text_map(value)?
+                    .into_iter()
+                    .filter_map(|(port, state)| Some((port.parse().ok()?, state)))
+                    .collect();
+            }
+            "reachability" => {
+                result.reachability = text_map(value)?
+                    .into_iter()
+                    .map(|(interface, reachable)| {
+                        let reachable = matches!(reachable.to_lowercase().as_str(), "true" | "1" | "yes" | "up");
+                        (interface, reachable)
+                    })
+                    .collect();
+            }
+            "attributes" => {
+                for (key, value) in text_map(value)? {
+                    result.attributes.entry(key).or_insert(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !result.mac.is_empty() {
+        result.mac = crate::normalize_mac(&result.mac);
+    }
+    result.open_ports.sort_unstable();
+    result.open_ports.dedup();
+    add_source(&mut result, source);
+    Ok(result)
+}
+
+/// Upgrade a host record in any layout the crate has returned to a
+/// ScanResult, adding `source` to its `sources`
+///
+/// Accepts ScanResult objects, dicts from any producer (tcp_scan_batch,
+/// device table parsers, lease and pipe files, `ScanResult.to_dict`) and the
+/// (ip, mac, hostname) tuples of `parse_arp_output(legacy=True)`. Aliased
+/// keys ("rtt_ms", "timestamp", "mac_address", ...) map to their fields;
+/// other keys are kept in `attributes`.
+#[pyfunction]
+pub fn to_canonical(record: &PyAny, source: &str) -> PyResult<ScanResult> {
+    if let Ok(result) = record.extract::<PyRef<ScanResultDataclass>>() {
+        let mut result: ScanResult = result.clone().into();
+        add_source(&mut result, source);
+        return Ok(result);
+    }
+    if let Ok(tuple) = record.downcast::<PyTuple>() {
+        let (ip, mac, mut hostname): (String, String, String) = tuple.extract()?;
+        if hostname == "?" {
+            hostname.clear();
+        }
+        let dict = [("ip", ip), ("mac", mac), ("hostname", hostname)].into_py_dict(record.py());
+        return canonical_from_dict(dict, source);
+    }
+    match record.downcast::<PyDict>() {
+        Ok(dict) => canonical_from_dict(dict, source),
+        Err(_) => Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!(
+            "Cannot convert {} to ScanResult (expected ScanResult, dict or (ip, mac, hostname))",
+            record.get_type().name()?
+        ))),
+    }
+}
+
+/// Warn once per call that a producer's legacy layout is going away
+pub fn warn_legacy(py: Python, producer: &str) -> PyResult<()> {
+    PyErr::warn(
+        py,
+        py.get_type::<pyo3::exceptions::PyDeprecationWarning>(),
+        &format!(
+            "{}(legacy=True) returns the pre-ScanResult layout and will be removed in the next release; \
+             use the default ScanResult output or to_canonical()",
+            producer
+        ),
+        1,
+    )
+}
+
+/// Producer output for string records: canonical results, or with `legacy`
+/// the records as they are
+pub fn emit_records(
+    py: Python,
+    producer: &str,
+    records: Vec<HashMap<String, String>>,
+    source: &str,
+    legacy: bool,
+) -> PyResult<PyObject> {
+    if legacy {
+        warn_legacy(py, producer)?;
+        return Ok(records.into_py(py));
+    }
+    let results = records
+        .into_iter()
+        .map(|record| canonical_from_dict(record.into_py_dict(py), source))
+        .collect::<PyResult<Vec<ScanResult>>>()?;
+    Ok(results.into_py(py))
+}
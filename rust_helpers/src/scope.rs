@@ -0,0 +1,347 @@
+use std::net::Ipv4Addr;
+use ipnetwork::{IpNetwork, Ipv4Network, Ipv6Network};
+use parking_lot::RwLock;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+use crate::scanner::ScanResult;
+
+// =============================================================================
+// Scope Filtering (authorized IP ranges)
+// =============================================================================
+
+/// Sorted, merged set of IPv4 ranges built from a CIDR list
+#[derive(Debug, Clone, Default)]
+pub struct CidrSet {
+    ranges: Vec<(u32, u32)>,
+}
+
+impl CidrSet {
+    /// Parse CIDRs (bare IPs are treated as /32, "a.b.c.d-e.f.g.h" as an
+    /// inclusive address range) into disjoint sorted ranges
+    pub fn parse(cidrs: &[String]) -> Result<Self, String> {
+        let mut ranges: Vec<(u32, u32)> = Vec::with_capacity(cidrs.len());
+        for cidr in cidrs {
+            if let Some((start, end)) = cidr.trim().split_once('-') {
+                let parse = |ip: &str| {
+                    ip.trim().parse::<Ipv4Addr>().map(u32::from).map_err(|e| {
+                        format!("Invalid range '{}': {}", cidr, e)
+                    })
+                };
+                let (start, end) = (parse(start)?, parse(end)?);
+                if end < start {
+                    return Err(format!("Invalid range '{}': end IP must be >= start IP", cidr));
+                }
+                ranges.push((start, end));
+                continue;
+            }
+            let network: Ipv4Network = cidr.trim().parse().map_err(|e| {
+                format!("Invalid CIDR '{}': {}", cidr, e)
+            })?;
+            ranges.push((u32::from(network.network()), u32::from(network.broadcast())));
+        }
+
+        ranges.sort_unstable();
+        let mut merged: Vec<(u32, u32)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1.saturating_add(1) => {
+                    last.1 = last.1.max(end);
+                }
+                _ => merged.push((start, end)),
+            }
+        }
+
+        Ok(CidrSet { ranges: merged })
+    }
+
+    /// Binary search membership test
+    pub fn contains(&self, ip: Ipv4Addr) -> bool {
+        let n = u32::from(ip);
+        let idx = self.ranges.partition_point(|&(start, _)| start <= n);
+        idx > 0 && n <= self.ranges[idx - 1].1
+    }
+
+    /// Whether every address from `start` to `end` is in the set
+    pub fn contains_range(&self, start: u32, end: u32) -> bool {
+        let idx = self.ranges.partition_point(|&(first, _)| first <= start);
+        idx > 0 && end <= self.ranges[idx - 1].1
+    }
+
+    /// Membership test for a string address; unparseable addresses are out of scope
+    pub fn contains_str(&self, ip: &str) -> bool {
+        ip.trim().parse::<Ipv4Addr>().map(|addr| self.contains(addr)).unwrap_or(false)
+    }
+}
+
+fn parse_scope(cidrs: &[String]) -> PyResult<CidrSet> {
+    CidrSet::parse(cidrs).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(e)
+    })
+}
+
+/// Keep only results whose IP falls within any of the given CIDRs
+/// (or, with `invert=True`, only those outside them for auditing)
+#[pyfunction]
+#[pyo3(signature = (results, cidrs, invert=false))]
+pub fn filter_scan_results_by_cidr_list(
+    results: Vec<ScanResult>,
+    cidrs: Vec<String>,
+    invert: bool,
+) -> PyResult<Vec<ScanResult>> {
+    let scope = parse_scope(&cidrs)?;
+
+    Ok(results
+        .into_par_iter()
+        .filter(|r| scope.contains_str(&r.ip) != invert)
+        .collect())
+}
+
+/// Raise ValueError if any result lies outside the given CIDRs
+#[pyfunction]
+pub fn assert_results_in_scope(results: Vec<ScanResult>, cidrs: Vec<String>) -> PyResult<()> {
+    let scope = parse_scope(&cidrs)?;
+
+    let out_of_scope: Vec<String> = results
+        .par_iter()
+        .filter(|r| !scope.contains_str(&r.ip))
+        .map(|r| r.ip.clone())
+        .collect();
+
+    if out_of_scope.is_empty() {
+        return Ok(());
+    }
+
+    let shown: Vec<&str> = out_of_scope.iter().take(10).map(String::as_str).collect();
+    let more = out_of_scope.len().saturating_sub(shown.len());
+    Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+        "{} result(s) out of scope: {}{}",
+        out_of_scope.len(),
+        shown.join(", "),
+        if more > 0 { format!(" (+{} more)", more) } else { String::new() }
+    )))
+}
+
+// =============================================================================
+// Authorized Scopes (scan interlock)
+// =============================================================================
+//
+// `set_authorized_scopes` declares the only ranges this process may scan.
+// Every entry point that probes given targets checks them before the first
+// packet and raises UnauthorizedTargetError listing the ones outside the
+// scopes; `force=True` on that call lets them through with a RuntimeWarning
+// (and, for Scanner.scan, a count in the summary). While scopes are set a
+// hostname target is refused, as its address isn't known before it
+// resolves. Link-local multicast discovery (multicast_discovery, the NDP
+// sweeps) has no targets to check and is not affected.
+
+pyo3::create_exception!(
+    netscan_core,
+    UnauthorizedTargetError,
+    pyo3::exceptions::PyValueError,
+    "Scan targets outside the authorized scopes; the offending targets are in `targets`"
+);
+
+struct AuthorizedScopes {
+    cidrs: Vec<String>,
+    v4: CidrSet,
+    v6: Vec<Ipv6Network>,
+}
+
+impl AuthorizedScopes {
+    fn parse(cidrs: &[String]) -> Result<Self, String> {
+        let mut v4 = Vec::new();
+        let mut v6 = Vec::new();
+        for cidr in cidrs {
+            match cidr.trim().parse::<IpNetwork>() {
+                Ok(IpNetwork::V4(network)) => v4.push(network.to_string()),
+                Ok(IpNetwork::V6(network)) => v6.push(network),
+                Err(e) => return Err(format!("Invalid CIDR '{}': {}", cidr, e)),
+            }
+        }
+        Ok(AuthorizedScopes {
+            cidrs: cidrs.iter().map(|c| c.trim().to_string()).collect(),
+            v4: CidrSet::parse(&v4)?,
+            v6,
+        })
+    }
+
+    /// Whether a target address or CIDR lies wholly inside the scopes
+    fn covers(&self, target: &str) -> bool {
+        match target.trim().parse::<IpNetwork>() {
+            Ok(IpNetwork::V4(network)) => {
+                self.v4.contains_range(u32::from(network.network()), u32::from(network.broadcast()))
+            }
+            Ok(IpNetwork::V6(network)) => self
+                .v6
+                .iter()
+                .any(|scope| scope.prefix() <= network.prefix() && scope.contains(network.network())),
+            Err(_) => false,
+        }
+    }
+}
+
+/// None: no scopes declared, everything may be scanned
+static AUTHORIZED_SCOPES: RwLock<Option<AuthorizedScopes>> = parking_lot::const_rwlock(None);
+
+/// Declare the CIDRs (IPv4 or IPv6, bare addresses as host routes) this
+/// process may scan
+///
+/// From then on every scan entry point refuses targets outside them with
+/// UnauthorizedTargetError before any packet is sent, unless the call
+/// passes `force=True`. An empty list lifts the restriction. Raises
+/// ValueError, keeping the current scopes, if any CIDR is invalid.
+#[pyfunction]
+pub fn set_authorized_scopes(cidrs: Vec<String>) -> PyResult<()> {
+    let scopes = if cidrs.is_empty() {
+        None
+    } else {
+        Some(AuthorizedScopes::parse(&cidrs).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?)
+    };
+    *AUTHORIZED_SCOPES.write() = scopes;
+    Ok(())
+}
+
+/// The CIDRs given to `set_authorized_scopes`; empty when unrestricted
+#[pyfunction]
+pub fn get_authorized_scopes() -> Vec<String> {
+    AUTHORIZED_SCOPES.read().as_ref().map(|scopes| scopes.cidrs.clone()).unwrap_or_default()
+}
+
+/// Whether authorized scopes are set, so targets must be checked
+pub fn scopes_active() -> bool {
+    AUTHORIZED_SCOPES.read().is_some()
+}
+
+/// Check a scan's targets (addresses or CIDRs) against the authorized
+/// scopes before anything is sent
+///
+/// Returns how many targets `force` let through: 0 when all are in scope,
+/// as they always are with no scopes set. Without `force`, targets outside
+/// the scopes raise UnauthorizedTargetError with the full list in its
+/// `targets` attribute.
+pub fn authorize_targets<S: AsRef<str>>(py: Python, entry_point: &str, targets: &[S], force: bool) -> PyResult<usize> {
+    let outside: Vec<String> = match AUTHORIZED_SCOPES.read().as_ref() {
+        None => return Ok(0),
+        Some(scopes) => targets
+            .iter()
+            .map(|t| t.as_ref().trim())
+            .filter(|t| !scopes.covers(t))
+            .map(str::to_string)
+            .collect(),
+    };
+    if outside.is_empty() {
+        return Ok(0);
+    }
+
+    if force {
+        PyErr::warn(
+            py,
+            py.get_type::<pyo3::exceptions::PyRuntimeWarning>(),
+            &format!("{}: force=True, scanning {} target(s) outside the authorized scopes", entry_point, outside.len()),
+            1,
+        )?;
+        return Ok(outside.len());
+    }
+    let shown: Vec<&str> = outside.iter().take(10).map(String::as_str).collect();
+    let more = outside.len().saturating_sub(shown.len());
+    let err = UnauthorizedTargetError::new_err(format!(
+        "{}: {} target(s) outside the authorized scopes: {}{}; pass force=True to scan them anyway",
+        entry_point,
+        outside.len(),
+        shown.join(", "),
+        if more > 0 { format!(" (+{} more)", more) } else { String::new() }
+    ));
+    err.value(py).setattr("targets", outside)?;
+    Err(err)
+}
+
+/// `authorize_targets` for an iterable that may be a generator: with scopes
+/// set it is read in full first and a list of its targets stands in for it
+pub fn authorize_iterable<'py>(
+    py: Python<'py>,
+    entry_point: &str,
+    ips: &'py PyAny,
+    force: bool,
+) -> PyResult<(&'py PyAny, usize)> {
+    if !scopes_active() {
+        return Ok((ips, 0));
+    }
+    let targets = ips
+        .iter()?
+        .map(|ip| Ok(ip?.extract::<String>()?.trim().to_string()))
+        .collect::<PyResult<Vec<String>>>()?;
+    let forced = authorize_targets(py, entry_point, &targets, force)?;
+    Ok((pyo3::types::PyList::new(py, targets), forced))
+}
+
+// =============================================================================
+// Exclusion Lists (keep re-scans away from critical systems)
+// =============================================================================
+
+/// Ports that mark a host as critical infrastructure
+pub const CRITICAL_SERVICE_PORTS: &[(u16, &str)] = &[
+    // Domain controllers
+    (88, "kerberos"),
+    (389, "ldap"),
+    (464, "kpasswd"),
+    (636, "ldaps"),
+    (3268, "global-catalog"),
+    (3269, "global-catalog-ssl"),
+    // Industrial control systems
+    (102, "s7comm"),
+    (502, "modbus"),
+    (1911, "niagara-fox"),
+    (2404, "iec-104"),
+    (4840, "opc-ua"),
+    (20000, "dnp3"),
+    (44818, "ethernet-ip"),
+    (47808, "bacnet"),
+];
+
+fn is_critical_host(result: &ScanResult) -> bool {
+    result
+        .open_ports
+        .iter()
+        .any(|p| CRITICAL_SERVICE_PORTS.iter().any(|(port, _)| port == p))
+}
+
+/// IPs to exclude from a re-scan: hosts running critical services
+/// (domain controller / ICS ports) or, if `only_critical_services` is false,
+/// every scanned host. Order follows the results with duplicates removed.
+#[pyfunction]
+#[pyo3(signature = (results, only_critical_services=true))]
+pub fn generate_exclude_list_from_scan(results: Vec<ScanResult>, only_critical_services: bool) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    results
+        .into_iter()
+        .filter(|r| !only_critical_services || is_critical_host(r))
+        .map(|r| r.ip.trim().to_string())
+        .filter(|ip| !ip.is_empty() && seen.insert(ip.clone()))
+        .collect()
+}
+
+/// Same as `generate_exclude_list_from_scan`, as host CIDRs (/32, or /128 for IPv6)
+#[pyfunction]
+#[pyo3(signature = (results, only_critical_services=true))]
+pub fn generate_exclude_cidr_list(results: Vec<ScanResult>, only_critical_services: bool) -> Vec<String> {
+    generate_exclude_list_from_scan(results, only_critical_services)
+        .into_iter()
+        .map(|ip| match ip.parse::<std::net::IpAddr>() {
+            Ok(std::net::IpAddr::V6(_)) => format!("{}/128", ip),
+            _ => format!("{}/32", ip),
+        })
+        .collect()
+}
+
+/// Drop IPs covered by any exclusion (bare IPv4 addresses, CIDRs or
+/// "a.b.c.d-e.f.g.h" ranges)
+#[pyfunction]
+pub fn filter_ips_by_exclusion_list(ips: Vec<String>, exclusions: Vec<String>) -> PyResult<Vec<String>> {
+    let excluded = parse_scope(&exclusions)?;
+
+    Ok(ips
+        .into_par_iter()
+        .filter(|ip| !excluded.contains_str(ip))
+        .collect())
+}
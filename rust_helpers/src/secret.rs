@@ -0,0 +1,154 @@
+use std::fmt;
+use parking_lot::RwLock;
+use pyo3::prelude::*;
+use regex::Regex;
+use zeroize::Zeroizing;
+
+// =============================================================================
+// Secrets and Redaction
+// =============================================================================
+//
+// Credentials handed to the Rust layer (SNMP communities, proxy passwords,
+// anonymization keys) are held as `Secret`: formatting one prints a
+// placeholder, so no error, log line or panic message built with `{}` or
+// `{:?}` can carry the value, and the buffer is wiped when it is dropped.
+// The copy Python passed in is outside our control. Text that comes back
+// from the network is a separate problem: devices leak tokens in banners,
+// so probe output goes through the site's redaction patterns first.
+
+/// What redacted text and formatted secrets are replaced with
+pub const REDACTED: &str = "[REDACTED]";
+
+/// A credential that never formats as itself and is zeroized on drop
+///
+/// Credential parameters accept a Secret or a plain str; wrapping it on the
+/// Python side keeps it out of reprs, tracebacks and logging there too.
+#[pyclass]
+pub struct Secret(Zeroizing<String>);
+
+impl Secret {
+    /// The value itself, for the one place that must send it
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+#[pymethods]
+impl Secret {
+    #[new]
+    pub fn new(value: &str) -> Self {
+        Secret(Zeroizing::new(value.to_string()))
+    }
+
+    /// The wrapped value (named as in pydantic's SecretStr)
+    fn get_secret_value(&self) -> String {
+        self.expose().to_string()
+    }
+
+    /// `text` with every occurrence of the value replaced; for messages from
+    /// sockets or peers that may echo what they were sent
+    pub fn scrub(&self, text: &str) -> String {
+        if self.0.is_empty() {
+            return text.to_string();
+        }
+        text.replace(self.0.as_str(), REDACTED)
+    }
+
+    fn __bool__(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Secret({})", REDACTED)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<'source> FromPyObject<'source> for Secret {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        if let Ok(secret) = ob.extract::<PyRef<Secret>>() {
+            return Ok(Secret::new(secret.expose()));
+        }
+        Ok(Secret::new(ob.extract::<&str>()?))
+    }
+}
+
+static REDACTION_PATTERNS: RwLock<Vec<Regex>> = parking_lot::const_rwlock(Vec::new());
+
+/// `text` with every match of the configured redaction patterns replaced
+pub fn redact(text: &str) -> String {
+    let patterns = REDACTION_PATTERNS.read();
+    let mut text = text.to_string();
+    for pattern in patterns.iter() {
+        if pattern.is_match(&text) {
+            text = pattern.replace_all(&text, REDACTED).into_owned();
+        }
+    }
+    text
+}
+
+/// Replace the process-wide redaction patterns (regular expressions)
+///
+/// Banners and probe output (`scan_host_services`, `Scanner.add_probe`
+/// results) have every match replaced with "[REDACTED]" before they are
+/// returned. An empty list turns redaction off. Raises ValueError, keeping
+/// the current patterns, if any expression is invalid.
+#[pyfunction]
+pub fn set_redaction_patterns(patterns: Vec<String>) -> PyResult<()> {
+    let compiled = patterns
+        .iter()
+        .map(|p| Regex::new(p))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid redaction pattern: {}", e)))?;
+    *REDACTION_PATTERNS.write() = compiled;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SENTINEL: &str = "s3ntinel-community-value";
+
+    #[test]
+    fn secret_never_formats_as_itself() {
+        let secret = Secret::new(SENTINEL);
+        let formatted = [
+            format!("{}", secret),
+            format!("{:?}", secret),
+            format!("{:#?}", Some(&secret)),
+            secret.__repr__(),
+            secret.__str__(),
+            format!("probe failed with community {}", secret),
+        ];
+        for text in &formatted {
+            assert!(!text.contains(SENTINEL), "{}", text);
+            assert!(text.contains(REDACTED), "{}", text);
+        }
+        assert_eq!(secret.expose(), SENTINEL);
+        assert_eq!(secret.scrub(&format!("peer said: {}", SENTINEL)), format!("peer said: {}", REDACTED));
+    }
+
+    #[test]
+    fn secret_panic_message_is_redacted() {
+        let secret = Secret::new(SENTINEL);
+        let panic = std::panic::catch_unwind(|| panic!("scan failed: {:?}", secret)).unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(!message.contains(SENTINEL), "{}", message);
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::sync::OnceLock;
+use dashmap::DashMap;
+use memmap2::Mmap;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use regex::bytes::{Regex, RegexBuilder};
+
+use crate::scanner::parse_port_spec;
+
+// =============================================================================
+// nmap Service Probes
+// =============================================================================
+//
+// Reads the parts of nmap's `nmap-service-probes` that identify a service
+// from a response: each `Probe` block's name, the ports it is sent to and its
+// `match`/`softmatch` patterns. A pattern is kept as "<service> m<d>regex<d>
+// [flags]", the directive minus its version fields. nmap's patterns are
+// PCRE; the few that rely on backreferences or lookaround never match here.
+
+/// One `Probe` block
+#[derive(Debug, Default)]
+struct ServiceProbe {
+    name: String,
+    ports: Vec<u16>,
+    matches: Vec<String>,
+}
+
+/// "<service> m<d>regex<d>[flags]" from the rest of a match line
+fn match_pattern(rest: &str) -> Option<String> {
+    let (service, rest) = rest.trim().split_once(char::is_whitespace)?;
+    let rest = rest.trim_start().strip_prefix('m')?;
+    let delimiter = rest.chars().next()?;
+    let body = &rest[delimiter.len_utf8()..];
+    let end = body.find(delimiter)?;
+    let flags: String = body[end + delimiter.len_utf8()..]
+        .chars()
+        .take_while(|c| matches!(c, 'i' | 's'))
+        .collect();
+    Some(format!("{} m{}{}{}{}", service, delimiter, &body[..end], delimiter, flags))
+}
+
+fn parse_probe_block(block: &str) -> Option<ServiceProbe> {
+    let mut lines = block.lines();
+    // Probe <TCP|UDP> <name> q|payload|
+    let mut header = lines.next()?.split_whitespace().skip(2);
+    let mut probe = ServiceProbe { name: header.next()?.to_string(), ..Default::default() };
+    for line in lines {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("match ").or_else(|| line.strip_prefix("softmatch ")) {
+            probe.matches.extend(match_pattern(rest));
+        } else if let Some(spec) = line.strip_prefix("ports ") {
+            probe.ports.extend(parse_port_spec(spec).unwrap_or_default());
+        }
+    }
+    Some(probe)
+}
+
+fn read_service_probes(path: &str) -> PyResult<Vec<ServiceProbe>> {
+    let file = File::open(path).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot open file: {}", e))
+    })?;
+    let mmap = unsafe { Mmap::map(&file) }.map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Cannot mmap file: {}", e))
+    })?;
+    // Payloads and patterns may hold raw bytes; they only need to survive as text
+    let content = String::from_utf8_lossy(&mmap);
+
+    // Split at each Probe line, then parse the blocks in parallel
+    let mut blocks = Vec::new();
+    let mut start = None;
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if line.starts_with("Probe ") {
+            if let Some(s) = start {
+                blocks.push(&content[s..offset]);
+            }
+            start = Some(offset);
+        }
+        offset += line.len();
+    }
+    if let Some(s) = start {
+        blocks.push(&content[s..]);
+    }
+    Ok(blocks.par_iter().filter_map(|block| parse_probe_block(block)).collect())
+}
+
+/// Parse `nmap-service-probes` into {probe name: [match patterns]}
+///
+/// Patterns are in file order, `softmatch` lines included, each as
+/// "<service> m<d>regex<d>[flags]" (e.g. "ssh m|^SSH-([\d.]+)-|"); version
+/// fields are dropped. Raises IOError if the file can't be read.
+#[pyfunction]
+pub fn parse_nmap_service_probe_file(py: Python, path: &str) -> PyResult<HashMap<String, Vec<String>>> {
+    let probes = py.allow_threads(|| read_service_probes(path))?;
+    let mut mapping: HashMap<String, Vec<String>> = HashMap::new();
+    for probe in probes {
+        mapping.entry(probe.name).or_default().extend(probe.matches);
+    }
+    Ok(mapping)
+}
+
+/// {probe name: ports} from the `ports` lines of `nmap-service-probes`;
+/// probes without one (NULL is sent to every port) map to []
+#[pyfunction]
+pub fn parse_nmap_service_probe_ports(py: Python, path: &str) -> PyResult<HashMap<String, Vec<u16>>> {
+    let probes = py.allow_threads(|| read_service_probes(path))?;
+    let mut mapping: HashMap<String, Vec<u16>> = HashMap::new();
+    for probe in probes {
+        let ports = mapping.entry(probe.name).or_default();
+        ports.extend(probe.ports);
+        ports.sort_unstable();
+        ports.dedup();
+    }
+    Ok(mapping)
+}
+
+/// Compiled form of a stored pattern; None if it isn't one or doesn't compile
+fn compiled(pattern: &str) -> Option<Regex> {
+    static COMPILED: OnceLock<DashMap<String, Option<Regex>>> = OnceLock::new();
+    let cache = COMPILED.get_or_init(DashMap::new);
+    if let Some(regex) = cache.get(pattern) {
+        return regex.clone();
+    }
+    let regex = (|| {
+        let (_, rest) = pattern.split_once(" m")?;
+        let delimiter = rest.chars().next()?;
+        let body = &rest[delimiter.len_utf8()..];
+        let end = body.rfind(delimiter)?;
+        let flags = &body[end + delimiter.len_utf8()..];
+        RegexBuilder::new(&body[..end])
+            .unicode(false)
+            .octal(true)
+            .case_insensitive(flags.contains('i'))
+            .dot_matches_new_line(flags.contains('s'))
+            .build()
+            .ok()
+    })();
+    cache.insert(pattern.to_string(), regex.clone());
+    regex
+}
+
+/// Service named by the first pattern in `probes` that matches `banner`
+///
+/// The NULL probe's patterns (those for what a server sends unprompted) are
+/// tried first, then the other probes by name, each in file order. Match
+/// against the raw response: nmap's patterns often expect the "\r\n" that
+/// cleaned banners have collapsed.
+#[pyfunction]
+pub fn apply_nmap_probes(banner: &str, probes: HashMap<String, Vec<String>>) -> Option<String> {
+    let mut names: Vec<&String> = probes.keys().collect();
+    names.sort_by_key(|name| (name.as_str() != "NULL", name.as_str()));
+    names
+        .into_iter()
+        .flat_map(|name| &probes[name])
+        .find(|pattern| compiled(pattern).is_some_and(|regex| regex.is_match(banner.as_bytes())))
+        .and_then(|pattern| pattern.split_whitespace().next().map(str::to_string))
+}
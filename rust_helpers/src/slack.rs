@@ -0,0 +1,282 @@
+use std::collections::{BTreeMap, HashMap};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::{json, Value};
+
+use crate::probes::service_name;
+use crate::scanner::ScanResult;
+use crate::secret::Secret;
+
+// =============================================================================
+// Slack Notifications
+// =============================================================================
+//
+// Payloads follow Slack's Block Kit and go to an incoming webhook URL.
+// Posting needs the HTTP client of the "slack" feature (on by default);
+// `dry_run=True` works in every build and returns the payload instead of
+// sending it.
+
+/// Open ports worth calling out: remote admin, file sharing, databases and
+/// other services that rarely belong on an open network
+pub const NOTABLE_PORTS: &[u16] = &[
+    21, 23, 445, 1433, 2375, 3306, 3389, 5432, 5900, 6379, 9200, 11211, 27017,
+];
+
+/// Lines listed per section before the rest are summarised; Slack caps a
+/// section's text at 3000 characters
+const MAX_LINES: usize = 20;
+
+fn section(text: String) -> Value {
+    json!({"type": "section", "text": {"type": "mrkdwn", "text": text}})
+}
+
+/// "*heading*" and a bullet per line, the surplus folded into "...and N more"
+fn bullet_section(heading: &str, lines: &[String]) -> Value {
+    let mut text = format!("*{}*", heading);
+    for line in lines.iter().take(MAX_LINES) {
+        text.push_str("\n• ");
+        text.push_str(line);
+    }
+    if lines.len() > MAX_LINES {
+        text.push_str(&format!("\n…and {} more", lines.len() - MAX_LINES));
+    }
+    section(text)
+}
+
+/// Slack's mrkdwn only needs &, < and > escaped
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// A field value as code, or "(none)" when empty
+fn shown(value: &str) -> String {
+    if value.is_empty() {
+        "(none)".to_string()
+    } else {
+        format!("`{}`", escape(value))
+    }
+}
+
+fn summary_payload(results: &[ScanResult], title: &str) -> Value {
+    let up: Vec<&ScanResult> = results.iter().filter(|r| r.status.is_empty() || r.status == "up").collect();
+
+    let mut vendors: HashMap<&str, usize> = HashMap::new();
+    for result in &up {
+        let vendor = if result.vendor.is_empty() { "Unknown" } else { result.vendor.as_str() };
+        *vendors.entry(vendor).or_default() += 1;
+    }
+    let mut vendors: Vec<(&str, usize)> = vendors.into_iter().collect();
+    vendors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let vendor_lines: Vec<String> = vendors.iter().map(|(v, n)| format!("{}: {}", escape(v), n)).collect();
+
+    let mut notable: BTreeMap<u16, Vec<&str>> = BTreeMap::new();
+    for result in &up {
+        for port in result.open_ports.iter().filter(|p| NOTABLE_PORTS.contains(p)) {
+            notable.entry(*port).or_default().push(&result.ip);
+        }
+    }
+    let port_lines: Vec<String> = notable
+        .iter()
+        .map(|(port, ips)| format!("{} ({}): {}", port, service_name(*port), ips.join(", ")))
+        .collect();
+
+    let mut blocks = vec![
+        json!({"type": "header", "text": {"type": "plain_text", "text": title}}),
+        json!({"type": "section", "fields": [
+            {"type": "mrkdwn", "text": format!("*Hosts up*\n{}", up.len())},
+            {"type": "mrkdwn", "text": format!("*Hosts reported*\n{}", results.len())},
+        ]}),
+    ];
+    if !vendor_lines.is_empty() {
+        blocks.push(bullet_section("Vendors", &vendor_lines));
+    }
+    if !port_lines.is_empty() {
+        blocks.push(bullet_section("Notable open ports", &port_lines));
+    }
+    json!({"text": format!("{}: {} hosts up", title, up.len()), "blocks": blocks})
+}
+
+fn diff_payload(
+    joined: &[String],
+    changes: &BTreeMap<String, BTreeMap<String, (String, String)>>,
+    flapping: &BTreeMap<String, Vec<u16>>,
+    title: &str,
+) -> Value {
+    let mut blocks = vec![json!({"type": "header", "text": {"type": "plain_text", "text": title}})];
+    if joined.is_empty() && changes.is_empty() && flapping.is_empty() {
+        blocks.push(section("No changes".to_string()));
+    }
+    if !joined.is_empty() {
+        let lines: Vec<String> = joined.iter().map(|key| escape(key)).collect();
+        blocks.push(bullet_section(&format!("New devices ({})", joined.len()), &lines));
+    }
+    if !changes.is_empty() {
+        let lines: Vec<String> = changes
+            .iter()
+            .map(|(key, fields)| {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(field, (old, new))| format!("{} {} → {}", field, shown(old), shown(new)))
+                    .collect();
+                format!("{}: {}", escape(key), fields.join("; "))
+            })
+            .collect();
+        blocks.push(bullet_section(&format!("Changed devices ({})", changes.len()), &lines));
+    }
+    if !flapping.is_empty() {
+        let lines: Vec<String> = flapping
+            .iter()
+            .map(|(key, ports)| {
+                let ports: Vec<String> = ports.iter().map(u16::to_string).collect();
+                format!("{}: {}", escape(key), ports.join(", "))
+            })
+            .collect();
+        blocks.push(bullet_section("Flapping ports", &lines));
+    }
+    let text = format!("{}: {} new, {} changed", title, joined.len(), changes.len());
+    json!({"text": text, "blocks": blocks})
+}
+
+#[cfg(feature = "slack")]
+fn post(webhook_url: &str, payload: &str, timeout_ms: u64) -> PyResult<()> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(std::time::Duration::from_millis(timeout_ms.max(1)))
+        .build();
+    post_with(&agent, webhook_url, payload)
+}
+
+/// POST `payload`; errors name the status or failure kind, never the URL
+#[cfg(feature = "slack")]
+fn post_with(agent: &ureq::Agent, webhook_url: &str, payload: &str) -> PyResult<()> {
+    agent
+        .post(webhook_url)
+        .set("Content-Type", "application/json")
+        .send_string(payload)
+        .map_err(|e| match e {
+            ureq::Error::Status(code, _) => format!("Slack webhook returned HTTP {}", code),
+            ureq::Error::Transport(t) => format!("Slack webhook unreachable: {}", t.kind()),
+        })
+        .map_err(PyErr::new::<pyo3::exceptions::PyConnectionError, _>)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "slack"))]
+fn post(_webhook_url: &str, _payload: &str, _timeout_ms: u64) -> PyResult<()> {
+    Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+        "netscan_core was built without the \"slack\" feature; use dry_run=True and post the payload yourself",
+    ))
+}
+
+/// The payload with `dry_run`, else POST it to the webhook and return None
+fn send(py: Python, webhook_url: &Secret, payload: Value, dry_run: bool, timeout_ms: u64) -> PyResult<Option<String>> {
+    let payload = payload.to_string();
+    if dry_run {
+        return Ok(Some(payload));
+    }
+    if !webhook_url.expose().starts_with("https://") {
+        // The URL is the credential, so it stays out of the message
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+            "Invalid webhook URL: expected an https:// incoming webhook",
+        ));
+    }
+    py.allow_threads(|| post(webhook_url.expose(), &payload, timeout_ms))?;
+    Ok(None)
+}
+
+/// Post a summary of `results` to a Slack incoming webhook
+///
+/// The message has hosts up, a per-vendor count and the hosts with notable
+/// open ports (NOTABLE_PORTS: telnet, SMB, RDP, VNC, databases, ...). With
+/// `dry_run` the Block Kit JSON is returned instead of sent. The webhook
+/// URL is a credential: pass it as a `Secret` (a str also works). Raises
+/// ConnectionError when the post fails, RuntimeError in builds with the
+/// "slack" feature turned off.
+#[pyfunction]
+#[pyo3(signature = (results, webhook_url, title="Network scan summary", dry_run=false, timeout_ms=10000))]
+pub fn scan_summary_to_slack_message(
+    py: Python,
+    results: Vec<ScanResult>,
+    webhook_url: Secret,
+    title: &str,
+    dry_run: bool,
+    timeout_ms: u64,
+) -> PyResult<Option<String>> {
+    send(py, &webhook_url, summary_payload(&results, title), dry_run, timeout_ms)
+}
+
+/// Post the change summary `Inventory.apply_scan` returns (new devices,
+/// changed fields, flapping ports) to a Slack incoming webhook
+///
+/// Same `dry_run` and errors as `scan_summary_to_slack_message`.
+#[pyfunction]
+#[pyo3(signature = (diff, webhook_url, title="Network changes", dry_run=false, timeout_ms=10000))]
+pub fn scan_diff_to_slack_message(
+    py: Python,
+    diff: &PyDict,
+    webhook_url: Secret,
+    title: &str,
+    dry_run: bool,
+    timeout_ms: u64,
+) -> PyResult<Option<String>> {
+    let joined: Vec<String> = match diff.get_item("joined_keys")? {
+        Some(keys) => keys.extract()?,
+        None => Vec::new(),
+    };
+    let changes: BTreeMap<String, BTreeMap<String, (String, String)>> = match diff.get_item("changes")? {
+        Some(changes) => changes.extract()?,
+        None => BTreeMap::new(),
+    };
+    let flapping: BTreeMap<String, Vec<u16>> = match diff.get_item("flapping")? {
+        Some(flapping) => flapping.extract()?,
+        None => BTreeMap::new(),
+    };
+    send(py, &webhook_url, diff_payload(&joined, &changes, &flapping, title), dry_run, timeout_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SENTINEL: &str = "T0SENTINEL/B0SENTINEL/xoxSENTINEL";
+
+    #[test]
+    fn webhook_url_stays_out_of_errors() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            for url in [format!("http://hooks.slack.com/services/{}", SENTINEL), format!("https://127.0.0.1:1/{}", SENTINEL)] {
+                let secret = Secret::new(&url);
+                let err = send(py, &secret, json!({"text": "x"}), false, 200).unwrap_err();
+                assert!(!err.to_string().contains("SENTINEL"), "{}", err);
+                assert!(!format!("{:?}", err).contains("SENTINEL"));
+            }
+        });
+    }
+
+    #[cfg(feature = "slack")]
+    #[test]
+    fn payload_is_posted_to_the_webhook() {
+        use crate::probes::tests::tls_server;
+
+        pyo3::prepare_freethreaded_python();
+        let agent = ureq::AgentBuilder::new()
+            .tls_config(crate::probes::unverified_tls_config())
+            .timeout(std::time::Duration::from_secs(5))
+            .build();
+        let payload = summary_payload(&[], "Nightly").to_string();
+
+        let (port, requests) = tls_server("HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok");
+        let url = format!("https://127.0.0.1:{}/services/{}", port, SENTINEL);
+        post_with(&agent, &url, &payload).unwrap();
+        let request = String::from_utf8(requests.recv().unwrap()).unwrap();
+        assert!(request.starts_with(&format!("POST /services/{} HTTP/1.1\r\n", SENTINEL)), "{}", request);
+        assert!(request.to_lowercase().contains("content-type: application/json"));
+        assert!(request.ends_with(&payload));
+
+        let (port, _) = tls_server("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        let url = format!("https://127.0.0.1:{}/services/{}", port, SENTINEL);
+        let err = post_with(&agent, &url, &payload).unwrap_err();
+        Python::with_gil(|py| assert!(err.is_instance_of::<pyo3::exceptions::PyConnectionError>(py)));
+        assert!(err.to_string().contains("HTTP 404"), "{}", err);
+        assert!(!err.to_string().contains("SENTINEL"), "{}", err);
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+use std::mem::MaybeUninit;
+use std::net::{Ipv4Addr, SocketAddrV4, UdpSocket};
+use std::time::{Duration, Instant};
+use pyo3::prelude::*;
+use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+
+use crate::icmp::checksum;
+use crate::routes::parse_ipv4;
+use crate::scanner::{checked_ports, interface_address, unix_now, ConnectPacer, PortState, ScanResult};
+use crate::scope::authorize_targets;
+
+// =============================================================================
+// TCP SYN (half-open) Probing
+// =============================================================================
+//
+// A bare SYN is sent on a raw socket and the handshake is never completed:
+// SYN-ACK means open, RST means closed, silence means filtered. The kernel
+// answers the SYN-ACK with its own RST since no socket owns the connection.
+// Replies are read from a raw TCP socket, which Linux delivers; other
+// platforms need packet capture for this and report every port filtered.
+
+const TCP_SYN: u8 = 0x02;
+const TCP_RST: u8 = 0x04;
+const TCP_ACK: u8 = 0x10;
+
+/// Local address the kernel would use to reach `ip`
+fn source_address(ip: Ipv4Addr) -> std::io::Result<Ipv4Addr> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.connect(SocketAddrV4::new(ip, 9))?;
+    match socket.local_addr()? {
+        std::net::SocketAddr::V4(addr) => Ok(*addr.ip()),
+        std::net::SocketAddr::V6(_) => Err(std::io::Error::other("no IPv4 route")),
+    }
+}
+
+/// Why SYN probes to a host could not be sent
+#[derive(Debug)]
+pub enum SynError {
+    /// No raw TCP socket could be opened
+    Socket(std::io::Error),
+    /// No local address routes to the target
+    NoRoute(Ipv4Addr, std::io::Error),
+    /// The SYN to a port could not be sent
+    Send(Ipv4Addr, u16, std::io::Error),
+}
+
+impl SynError {
+    fn io_error(&self) -> &std::io::Error {
+        match self {
+            SynError::Socket(e) | SynError::NoRoute(_, e) | SynError::Send(_, _, e) => e,
+        }
+    }
+}
+
+impl std::fmt::Display for SynError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SynError::Socket(e) => write!(f, "SYN probes need a raw socket ({})", e),
+            SynError::NoRoute(ip, e) => write!(f, "No route to {}: {}", ip, e),
+            SynError::Send(ip, port, e) => write!(f, "Cannot send SYN to {}:{}: {}", ip, port, e),
+        }
+    }
+}
+
+/// EPERM/EACCES become PermissionError with how to get raw sockets; a bad
+/// address is a ValueError and anything else an OSError
+impl From<SynError> for PyErr {
+    fn from(error: SynError) -> PyErr {
+        match error.io_error().kind() {
+            std::io::ErrorKind::PermissionDenied => PyErr::new::<pyo3::exceptions::PyPermissionError, _>(format!(
+                "{}; {}",
+                error,
+                crate::capabilities::grant_hint("raw_tcp")
+            )),
+            std::io::ErrorKind::InvalidInput => PyErr::new::<pyo3::exceptions::PyValueError, _>(error.to_string()),
+            _ => PyErr::new::<pyo3::exceptions::PyOSError, _>(error.to_string()),
+        }
+    }
+}
+
+/// TCP SYN segment (with an MSS option) and its pseudo-header checksum
+fn build_syn(src: Ipv4Addr, dst: Ipv4Addr, sport: u16, dport: u16, seq: u32) -> Vec<u8> {
+    let mut segment = Vec::with_capacity(24);
+    segment.extend_from_slice(&sport.to_be_bytes());
+    segment.extend_from_slice(&dport.to_be_bytes());
+    segment.extend_from_slice(&seq.to_be_bytes());
+    segment.extend_from_slice(&[0, 0, 0, 0]); // ack
+    segment.push(6 << 4); // data offset: 6 words
+    segment.push(TCP_SYN);
+    segment.extend_from_slice(&64240u16.to_be_bytes()); // window
+    segment.extend_from_slice(&[0, 0, 0, 0]); // checksum, urgent
+    segment.extend_from_slice(&[2, 4, 0x05, 0xB4]); // MSS 1460
+
+    let mut pseudo = Vec::with_capacity(12 + segment.len());
+    pseudo.extend_from_slice(&src.octets());
+    pseudo.extend_from_slice(&dst.octets());
+    pseudo.extend_from_slice(&[0, 6]);
+    pseudo.extend_from_slice(&(segment.len() as u16).to_be_bytes());
+    pseudo.extend_from_slice(&segment);
+    let sum = checksum(&pseudo);
+    segment[16..18].copy_from_slice(&sum.to_be_bytes());
+    segment
+}
+
+/// Send one SYN per port and classify each from the replies (IPv4 only)
+pub fn syn_probe_ports(
+    ip: Ipv4Addr,
+    ports: &[u16],
+    timeout_ms: u64,
+) -> Result<HashMap<u16, PortState>, SynError> {
+    syn_probe_ports_via(ip, ports, timeout_ms, None, None)
+}
+
+/// `syn_probe_ports` leaving through `interface` (as `connect_via` binds
+/// connect probes), each SYN waiting for its slot from `pacer`
+pub fn syn_probe_ports_via(
+    ip: Ipv4Addr,
+    ports: &[u16],
+    timeout_ms: u64,
+    interface: Option<&str>,
+    pacer: Option<&ConnectPacer>,
+) -> Result<HashMap<u16, PortState>, SynError> {
+    let socket = Socket::new(Domain::IPV4, Type::RAW, Some(Protocol::TCP)).map_err(SynError::Socket)?;
+    let src = match interface {
+        None => source_address(ip).map_err(|e| SynError::NoRoute(ip, e))?,
+        Some(interface) => {
+            let address = interface_address(interface, &ip.into())
+                .map_err(|e| SynError::NoRoute(ip, std::io::Error::new(std::io::ErrorKind::AddrNotAvailable, e)))?;
+            #[cfg(target_os = "linux")]
+            socket.bind_device(Some(interface.as_bytes())).map_err(SynError::Socket)?;
+            #[cfg(not(target_os = "linux"))]
+            socket.bind(&SockAddr::from(std::net::SocketAddr::new(address, 0))).map_err(SynError::Socket)?;
+            let std::net::IpAddr::V4(address) = address else {
+                return Err(SynError::NoRoute(ip, std::io::Error::from(std::io::ErrorKind::AddrNotAvailable)));
+            };
+            address
+        }
+    };
+    let sport = 40000 + (std::process::id() % 20000) as u16;
+    let seq = (unix_now() * 1_000_000.0) as u32;
+
+    let mut states: HashMap<u16, PortState> = ports.iter().map(|p| (*p, PortState::Filtered)).collect();
+    let target = SockAddr::from(SocketAddrV4::new(ip, 0));
+    for &port in ports {
+        if let Some(pacer) = pacer {
+            pacer.wait_blocking();
+        }
+        socket
+            .send_to(&build_syn(src, ip, sport, port, seq), &target)
+            .map_err(|e| SynError::Send(ip, port, e))?;
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    let mut pending = states.len();
+    let mut buf = [MaybeUninit::<u8>::uninit(); 1500];
+    while pending > 0 {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() || socket.set_read_timeout(Some(remaining)).is_err() {
+            break;
+        }
+        let Ok(n) = socket.recv(&mut buf) else {
+            break;
+        };
+        // SAFETY: recv initialized the first n bytes
+        let packet: &[u8] = unsafe { std::slice::from_raw_parts(buf.as_ptr() as *const u8, n) };
+
+        if packet.len() < 20 || packet[9] != 6 || packet[12..16] != ip.octets() {
+            continue;
+        }
+        let ihl = (packet[0] & 0x0F) as usize * 4;
+        let Some(tcp) = packet.get(ihl..ihl + 14) else {
+            continue;
+        };
+        let from_port = u16::from_be_bytes([tcp[0], tcp[1]]);
+        if u16::from_be_bytes([tcp[2], tcp[3]]) != sport {
+            continue;
+        }
+        let flags = tcp[13];
+        let state = if flags & (TCP_SYN | TCP_ACK) == TCP_SYN | TCP_ACK {
+            PortState::Open
+        } else if flags & TCP_RST != 0 {
+            PortState::Closed
+        } else {
+            continue;
+        };
+        if let Some(slot) = states.get_mut(&from_port) {
+            if *slot == PortState::Filtered {
+                *slot = state;
+                pending -= 1;
+            }
+        }
+    }
+    Ok(states)
+}
+
+/// SYN-only probe: True for SYN-ACK; False for RST (closed) or no answer (filtered)
+///
+/// `force=True` probes a target outside `set_authorized_scopes`.
+#[pyfunction]
+#[pyo3(signature = (ip, port, timeout_ms=1000, force=false))]
+pub fn tcp_half_open_detection(py: Python, ip: &str, port: u16, timeout_ms: u64, force: bool) -> PyResult<bool> {
+    let addr = parse_ipv4(ip)?;
+    authorize_targets(py, "tcp_half_open_detection", &[addr.to_string()], force)?;
+    let states = py
+        .allow_threads(|| syn_probe_ports(addr, &[port], timeout_ms))?;
+    Ok(states.get(&port) == Some(&PortState::Open))
+}
+
+/// SYN scan one host; `port_state_detail` records open/closed/filtered per port
+///
+/// `force=True` scans a target outside `set_authorized_scopes`.
+#[pyfunction]
+#[pyo3(signature = (ip, ports, timeout_ms=1000, force=false))]
+pub fn tcp_half_open_scan(py: Python, ip: &str, ports: Vec<u16>, timeout_ms: u64, force: bool) -> PyResult<ScanResult> {
+    let addr = parse_ipv4(ip)?;
+    authorize_targets(py, "tcp_half_open_scan", &[addr.to_string()], force)?;
+    let ports = checked_ports(py, ports)?;
+    let start = Instant::now();
+    let states = py
+        .allow_threads(|| syn_probe_ports(addr, &ports, timeout_ms))?;
+
+    let mut open_ports: Vec<u16> = states
+        .iter()
+        .filter(|(_, state)| **state == PortState::Open)
+        .map(|(port, _)| *port)
+        .collect();
+    open_ports.sort_unstable();
+    let answered = states.values().any(|state| *state != PortState::Filtered);
+
+    Ok(ScanResult {
+        ip: addr.to_string(),
+        status: if answered { "up" } else { "down" }.to_string(),
+        response_time_ms: if answered { start.elapsed().as_secs_f64() * 1000.0 } else { 0.0 },
+        open_ports,
+        discovery_method: "tcp_syn".to_string(),
+        sources: vec!["tcp_syn".to_string()],
+        scan_timestamp: unix_now(),
+        probes_sent: states.len() as u64,
+        port_state_detail: states
+            .into_iter()
+            .map(|(port, state)| (port, state.as_str().to_string()))
+            .collect(),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Error, ErrorKind};
+
+    #[test]
+    fn syn_checksum_verifies() {
+        let (src, dst) = (Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2));
+        let segment = build_syn(src, dst, 40000, 443, 7);
+        let mut pseudo = Vec::new();
+        pseudo.extend_from_slice(&src.octets());
+        pseudo.extend_from_slice(&dst.octets());
+        pseudo.extend_from_slice(&[0, 6, 0, segment.len() as u8]);
+        pseudo.extend_from_slice(&segment);
+        assert_eq!(checksum(&pseudo), 0);
+    }
+
+    #[test]
+    fn only_denied_sockets_raise_permission_error() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let ip = Ipv4Addr::new(10, 0, 0, 2);
+            #[allow(unused_mut)]
+            let mut denied = vec![Error::from(ErrorKind::PermissionDenied)];
+            // EPERM, EACCES
+            #[cfg(target_os = "linux")]
+            denied.extend([Error::from_raw_os_error(1), Error::from_raw_os_error(13)]);
+            for e in denied {
+                let err = PyErr::from(SynError::Socket(e));
+                assert!(err.is_instance_of::<pyo3::exceptions::PyPermissionError>(py), "{}", err);
+                assert!(err.to_string().contains(crate::capabilities::grant_hint("raw_tcp")));
+            }
+
+            let err = PyErr::from(SynError::Send(ip, 80, Error::from(ErrorKind::InvalidInput)));
+            assert!(err.is_instance_of::<pyo3::exceptions::PyValueError>(py), "{}", err);
+            let err = PyErr::from(SynError::NoRoute(ip, Error::from(ErrorKind::NetworkUnreachable)));
+            assert!(err.is_instance_of::<pyo3::exceptions::PyOSError>(py), "{}", err);
+            assert!(!err.is_instance_of::<pyo3::exceptions::PyPermissionError>(py));
+        });
+    }
+}
@@ -0,0 +1,503 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::{IpAddr, Ipv4Addr};
+use ipnetwork::Ipv4Network;
+use pyo3::prelude::*;
+
+use crate::scope::CidrSet;
+
+// =============================================================================
+// Target Specification Parsing
+// =============================================================================
+//
+// Supported forms (may be mixed freely in one target list):
+//   192.168.1.10              single address
+//   192.168.1.0/24            CIDR
+//   192.168.1.10-192.168.1.20 full address range
+//   192.168.1.10-20           last-octet range
+//   192.168.1.*               wildcard octet (0-255)
+//   10.1.[1,5,9].0/24         bracketed octet list, composes with CIDR suffix
+//   10.1.1-3.0/24             octet range, composes with CIDR suffix
+//   10.[1-3,7].*.1            ranges inside octet lists
+//
+// Octet patterns expand to at most DEFAULT_MAX_IP_RANGE addresses, like
+// `expand_ip_range`; larger ones are rejected rather than materialised.
+
+/// Parsed octet-pattern target: allowed values per octet plus optional prefix
+#[derive(Debug, Clone)]
+pub struct OctetPattern {
+    pub octets: [Vec<u8>; 4],
+    pub prefix: u8,
+}
+
+impl OctetPattern {
+    /// Number of unique addresses this pattern expands to
+    pub fn count(&self) -> u64 {
+        let mut total: u64 = 1;
+        for (i, values) in self.octets.iter().enumerate() {
+            let mask = octet_mask(self.prefix, i);
+            let distinct: HashSet<u8> = values.iter().map(|v| v & mask).collect();
+            total *= distinct.len() as u64;
+        }
+        total * (1u64 << (32 - self.prefix as u32))
+    }
+
+    /// Error if the pattern covers more than `DEFAULT_MAX_IP_RANGE` addresses,
+    /// the same limit `expand_ip_range` applies
+    fn check_size(&self) -> Result<(), String> {
+        let count = self.count();
+        if count > crate::DEFAULT_MAX_IP_RANGE as u64 {
+            return Err(format!(
+                "Target pattern covers {} addresses, more than the limit of {}",
+                count,
+                crate::DEFAULT_MAX_IP_RANGE
+            ));
+        }
+        Ok(())
+    }
+
+    /// Distinct network blocks as inclusive `(first, last)` addresses, in
+    /// ascending base order
+    fn blocks(&self) -> Result<Vec<(u32, u32)>, String> {
+        self.check_size()?;
+        let host_bits = 32 - self.prefix as u32;
+        let mask = if host_bits == 32 { 0 } else { u32::MAX << host_bits };
+        let mut seen: HashSet<u32> = HashSet::new();
+        let mut out = Vec::new();
+        for &a in &self.octets[0] {
+            for &b in &self.octets[1] {
+                for &c in &self.octets[2] {
+                    for &d in &self.octets[3] {
+                        let network = u32::from(Ipv4Addr::new(a, b, c, d)) & mask;
+                        if seen.insert(network) {
+                            out.push((network, network | !mask));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Expand to addresses, deduplicated, in ascending base order
+    pub fn expand(&self) -> Result<Vec<Ipv4Addr>, String> {
+        Ok(self
+            .blocks()?
+            .into_iter()
+            .flat_map(|(first, last)| (first..=last).map(Ipv4Addr::from))
+            .collect())
+    }
+}
+
+/// Network-bit mask that applies to octet `index` for a given prefix length
+fn octet_mask(prefix: u8, index: usize) -> u8 {
+    let start = (index * 8) as u8;
+    if prefix >= start + 8 {
+        0xFF
+    } else if prefix <= start {
+        0x00
+    } else {
+        0xFFu8 << (8 - (prefix - start))
+    }
+}
+
+/// Parse a single octet value, reporting the octet position on failure
+fn parse_octet_value(value: &str, position: usize, spec: &str) -> Result<u8, String> {
+    value.trim().parse::<u8>().map_err(|_| {
+        format!(
+            "Invalid octet {} in '{}': '{}' is not a number 0-255",
+            position, spec, value
+        )
+    })
+}
+
+/// Parse `lo-hi` or a single value inside an octet
+fn parse_octet_item(item: &str, position: usize, spec: &str) -> Result<Vec<u8>, String> {
+    if let Some((lo, hi)) = item.split_once('-') {
+        let lo = parse_octet_value(lo, position, spec)?;
+        let hi = parse_octet_value(hi, position, spec)?;
+        if hi < lo {
+            return Err(format!(
+                "Invalid octet {} in '{}': range {}-{} is reversed",
+                position, spec, lo, hi
+            ));
+        }
+        Ok((lo..=hi).collect())
+    } else {
+        Ok(vec![parse_octet_value(item, position, spec)?])
+    }
+}
+
+/// Parse one dotted octet: `*`, `N`, `N-M`, or `[a,b,c-d]`
+fn parse_octet(octet: &str, position: usize, spec: &str) -> Result<Vec<u8>, String> {
+    let octet = octet.trim();
+    if octet == "*" {
+        return Ok((0..=255).collect());
+    }
+
+    let items: Vec<&str> = if let Some(inner) = octet.strip_prefix('[') {
+        let inner = inner.strip_suffix(']').ok_or_else(|| {
+            format!("Invalid octet {} in '{}': unclosed '['", position, spec)
+        })?;
+        inner.split(',').collect()
+    } else {
+        vec![octet]
+    };
+
+    let mut values: Vec<u8> = Vec::new();
+    for item in items {
+        if item.trim().is_empty() {
+            return Err(format!("Invalid octet {} in '{}': empty value", position, spec));
+        }
+        values.extend(parse_octet_item(item, position, spec)?);
+    }
+    values.sort_unstable();
+    values.dedup();
+    Ok(values)
+}
+
+/// Parse an octet-pattern spec such as `10.1.[1,5,9].0/24` or `192.168.1.*`
+pub fn parse_octet_pattern(spec: &str) -> Result<OctetPattern, String> {
+    let (addr_part, prefix) = match spec.split_once('/') {
+        Some((addr, p)) => {
+            let prefix: u8 = p.trim().parse().map_err(|_| {
+                format!("Invalid prefix length in '{}': '{}'", spec, p)
+            })?;
+            if prefix > 32 {
+                return Err(format!("Invalid prefix length in '{}': {} > 32", spec, prefix));
+            }
+            (addr, prefix)
+        }
+        None => (spec, 32),
+    };
+
+    let parts: Vec<&str> = addr_part.trim().split('.').collect();
+    if parts.len() != 4 {
+        return Err(format!(
+            "Invalid target '{}': expected 4 octets, found {}",
+            spec,
+            parts.len()
+        ));
+    }
+
+    let mut octets: [Vec<u8>; 4] = Default::default();
+    for (i, part) in parts.iter().enumerate() {
+        octets[i] = parse_octet(part, i + 1, spec)?;
+    }
+
+    Ok(OctetPattern { octets, prefix })
+}
+
+/// A single parsed target specification
+#[derive(Debug, Clone)]
+pub enum TargetSpec {
+    Range(Ipv4Addr, Ipv4Addr),
+    Pattern(OctetPattern),
+}
+
+impl TargetSpec {
+    pub fn expand(&self) -> Result<Vec<Ipv4Addr>, String> {
+        match self {
+            TargetSpec::Range(start, end) => {
+                Ok((u32::from(*start)..=u32::from(*end)).map(Ipv4Addr::from).collect())
+            }
+            TargetSpec::Pattern(p) => p.expand(),
+        }
+    }
+
+    /// Covered addresses as inclusive `(first, last)` intervals
+    fn intervals(&self) -> Result<Vec<(u32, u32)>, String> {
+        match self {
+            TargetSpec::Range(start, end) => Ok(vec![(u32::from(*start), u32::from(*end))]),
+            TargetSpec::Pattern(p) => p.blocks(),
+        }
+    }
+}
+
+/// Parse any supported target spec into its structured form
+pub fn parse_target_spec(spec: &str) -> Result<TargetSpec, String> {
+    let spec = spec.trim();
+    if spec.is_empty() {
+        return Err("Empty target specification".to_string());
+    }
+
+    // Full address range: a.b.c.d-e.f.g.h. Anything else with a '-', such
+    // as `192.168.1-3.1`, is an octet range and goes to the pattern parser.
+    if let Some((start, end)) = spec.split_once('-') {
+        if let (Ok(start_ip), Ok(end_ip)) = (start.trim().parse::<Ipv4Addr>(), end.trim().parse::<Ipv4Addr>()) {
+            if u32::from(end_ip) < u32::from(start_ip) {
+                return Err(format!("Invalid range '{}': end IP must be >= start IP", spec));
+            }
+            return Ok(TargetSpec::Range(start_ip, end_ip));
+        }
+    }
+
+    // Plain CIDR fast path keeps ipnetwork's error messages for the common case
+    if !spec.contains(['*', '[', '-']) && spec.contains('/') {
+        let network: Ipv4Network = spec.parse().map_err(|e| {
+            format!("Invalid CIDR '{}': {}", spec, e)
+        })?;
+        let (start, end) = (network.network(), network.broadcast());
+        return Ok(TargetSpec::Range(start, end));
+    }
+
+    parse_octet_pattern(spec).map(TargetSpec::Pattern)
+}
+
+/// Parse and expand a list of target specs, deduplicated in input order
+pub fn expand_targets(specs: &[String]) -> Result<Vec<String>, String> {
+    let mut seen: HashSet<u32> = HashSet::new();
+    let mut out = Vec::new();
+    for spec in specs {
+        for ip in parse_target_spec(spec)?.expand()? {
+            if seen.insert(u32::from(ip)) {
+                out.push(ip.to_string());
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Next value of a xorshift64 stream; the state must start non-zero
+pub fn xorshift64(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// Fisher-Yates shuffle driven by a xorshift64 stream; the same seed always
+/// gives the same order
+pub fn shuffle_targets<T>(items: &mut [T], seed: u64) {
+    let mut x = seed | 1;
+    for i in (1..items.len()).rev() {
+        items.swap(i, (xorshift64(&mut x) % (i as u64 + 1)) as usize);
+    }
+}
+
+// =============================================================================
+// Interleaved Target Order
+// =============================================================================
+//
+// Targets in input order scan one site after another. Interleaving groups
+// them (by an explicit group map, else by /24, or /64 for IPv6) and deals
+// them out across the groups: "round_robin" one per group in turn,
+// "weighted" in proportion to per-group weights by smooth weighted
+// round-robin, so a weight-2 group gets every other slot against two
+// weight-1 groups rather than bursts of two. Within a group the input order
+// (or shuffle) is kept.
+
+pub const INTERLEAVE_MODES: &[&str] = &["none", "round_robin", "weighted"];
+
+/// Named groups of CIDRs; a target belongs to the first group, by name,
+/// whose CIDRs contain it, otherwise to its /24 (IPv6: /64)
+#[derive(Debug, Clone, Default)]
+pub struct TargetGroups {
+    groups: Vec<(String, CidrSet)>,
+}
+
+impl TargetGroups {
+    pub fn parse(groups: &HashMap<String, Vec<String>>) -> Result<Self, String> {
+        let mut parsed = groups
+            .iter()
+            .map(|(name, cidrs)| Ok((name.clone(), CidrSet::parse(cidrs)?)))
+            .collect::<Result<Vec<_>, String>>()?;
+        parsed.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(TargetGroups { groups: parsed })
+    }
+
+    pub fn group_of(&self, ip: &str) -> String {
+        if let Some((name, _)) = self.groups.iter().find(|(_, cidrs)| cidrs.contains_str(ip)) {
+            return name.clone();
+        }
+        match ip.trim().parse::<IpAddr>() {
+            Ok(IpAddr::V4(addr)) => {
+                let [a, b, c, _] = addr.octets();
+                format!("{}.{}.{}.0/24", a, b, c)
+            }
+            Ok(IpAddr::V6(addr)) => {
+                let prefix = u128::from(addr) & !((1u128 << 64) - 1);
+                format!("{}/64", std::net::Ipv6Addr::from(prefix))
+            }
+            Err(_) => ip.trim().to_string(),
+        }
+    }
+}
+
+/// Check an interleave mode and its weights before anything is scanned
+pub fn check_interleave(mode: &str, weights: &HashMap<String, f64>) -> Result<(), String> {
+    if !INTERLEAVE_MODES.contains(&mode) {
+        return Err(format!(
+            "Invalid interleave '{}': expected 'none', 'round_robin' or 'weighted'",
+            mode
+        ));
+    }
+    match weights.iter().find(|(_, w)| !(w.is_finite() && **w > 0.0)) {
+        Some((group, weight)) => Err(format!("Invalid weight {} for group '{}': must be positive", weight, group)),
+        None => Ok(()),
+    }
+}
+
+/// `ips` reordered by `mode` (see above). In "weighted" mode groups missing
+/// from `weights` weigh 1, and with no weights at all each group weighs its
+/// size, so every group finishes at about the same time.
+pub fn interleave_targets(
+    ips: Vec<String>,
+    groups: &TargetGroups,
+    mode: &str,
+    weights: &HashMap<String, f64>,
+) -> Result<Vec<String>, String> {
+    check_interleave(mode, weights)?;
+    if mode == "none" {
+        return Ok(ips);
+    }
+    let total = ips.len();
+    let mut order: Vec<String> = Vec::new();
+    let mut queues: HashMap<String, VecDeque<String>> = HashMap::new();
+    for ip in ips {
+        let group = groups.group_of(&ip);
+        queues
+            .entry(group.clone())
+            .or_insert_with(|| {
+                order.push(group);
+                VecDeque::new()
+            })
+            .push_back(ip);
+    }
+    // (queue, weight, current) per group, in order of first appearance
+    let mut lanes: Vec<(VecDeque<String>, f64, f64)> = order
+        .iter()
+        .map(|group| {
+            let queue = queues.remove(group).unwrap_or_default();
+            let weight = match (mode, weights.is_empty()) {
+                ("weighted", true) => queue.len() as f64,
+                ("weighted", false) => weights.get(group).copied().unwrap_or(1.0),
+                _ => 1.0,
+            };
+            (queue, weight, 0.0)
+        })
+        .collect();
+
+    let mut out = Vec::with_capacity(total);
+    while out.len() < total {
+        let mut active = 0.0;
+        let mut best: Option<(usize, f64)> = None;
+        for (i, lane) in lanes.iter_mut().enumerate().filter(|(_, lane)| !lane.0.is_empty()) {
+            lane.2 += lane.1;
+            active += lane.1;
+            // Ties go to the group seen first
+            if best.is_none_or(|(_, current)| lane.2 > current) {
+                best = Some((i, lane.2));
+            }
+        }
+        let Some((best, _)) = best else { break };
+        lanes[best].2 -= active;
+        out.extend(lanes[best].0.pop_front());
+    }
+    Ok(out)
+}
+
+/// Dispatch order for `ips` across target groups
+///
+/// `mode` is "round_robin" (one target per group in turn) or "weighted"
+/// (`weights` maps group -> weight; missing groups weigh 1, and without
+/// weights each group weighs its size so all finish together); "none"
+/// returns `ips` as given. `groups` maps a group name to its CIDRs; targets
+/// outside every group are grouped by /24 (IPv6: /64).
+#[pyfunction]
+#[pyo3(name = "interleave_targets", signature = (ips, mode="round_robin", weights=None, groups=None))]
+pub fn interleave_targets_py(
+    ips: Vec<String>,
+    mode: &str,
+    weights: Option<HashMap<String, f64>>,
+    groups: Option<HashMap<String, Vec<String>>>,
+) -> PyResult<Vec<String>> {
+    let groups = TargetGroups::parse(&groups.unwrap_or_default()).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+    interleave_targets(ips, &groups, mode, &weights.unwrap_or_default())
+        .map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+/// Expand wildcard / octet-list notation (e.g. `192.168.1.*`, `10.1.[1,5,9].0/24`)
+#[pyfunction]
+pub fn expand_wildcard(spec: &str) -> PyResult<Vec<String>> {
+    let pattern = parse_octet_pattern(spec.trim()).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(e)
+    })?;
+
+    let ips = pattern.expand().map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)?;
+    Ok(ips.into_iter().map(|ip| ip.to_string()).collect())
+}
+
+/// Parse a list of target specs (IPs, CIDRs, ranges, wildcards) into unique IPs
+#[pyfunction]
+pub fn parse_targets(specs: Vec<String>) -> PyResult<Vec<String>> {
+    expand_targets(&specs).map_err(|e| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(e)
+    })
+}
+
+/// Count unique addresses the given target specs expand to, without
+/// expanding them; addresses covered by several specs count once, matching
+/// `len(parse_targets(specs))`. Patterns over the expansion limit raise
+/// ValueError here too.
+#[pyfunction]
+pub fn count_targets(specs: Vec<String>) -> PyResult<u64> {
+    unique_target_count(&specs).map_err(PyErr::new::<pyo3::exceptions::PyValueError, _>)
+}
+
+fn unique_target_count(specs: &[String]) -> Result<u64, String> {
+    let mut intervals: Vec<(u32, u32)> = Vec::new();
+    for spec in specs {
+        intervals.extend(parse_target_spec(spec)?.intervals()?);
+    }
+    intervals.sort_unstable();
+
+    let mut total: u64 = 0;
+    let mut current: Option<(u32, u32)> = None;
+    for (first, last) in intervals {
+        current = match current {
+            Some((start, end)) if first as u64 <= end as u64 + 1 => Some((start, end.max(last))),
+            Some((start, end)) => {
+                total += (end - start) as u64 + 1;
+                Some((first, last))
+            }
+            None => Some((first, last)),
+        };
+    }
+    if let Some((start, end)) = current {
+        total += (end - start) as u64 + 1;
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn specs(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn dotted_octet_range_is_a_pattern() {
+        let ips = expand_targets(&specs(&["192.168.1-3.1"])).unwrap();
+        assert_eq!(ips, vec!["192.168.1.1", "192.168.2.1", "192.168.3.1"]);
+        let ips = expand_targets(&specs(&["10.0.0.1-10.0.0.3"])).unwrap();
+        assert_eq!(ips, vec!["10.0.0.1", "10.0.0.2", "10.0.0.3"]);
+    }
+
+    #[test]
+    fn count_matches_expansion_for_overlapping_specs() {
+        let list = specs(&["10.0.0.0/24", "10.0.0.1-10.0.1.5", "10.0.[0,1].*", "10.0.0.7"]);
+        let expected = expand_targets(&list).unwrap().len() as u64;
+        assert_eq!(unique_target_count(&list).unwrap(), expected);
+        assert_eq!(expected, 512);
+    }
+
+    #[test]
+    fn oversized_pattern_is_rejected() {
+        let err = parse_octet_pattern("10.*.*.*").unwrap().expand().unwrap_err();
+        assert!(err.contains("limit"), "{}", err);
+        assert!(expand_targets(&specs(&["*.*.*.*"])).is_err());
+        assert_eq!(parse_octet_pattern("10.[1,2].*.*").unwrap().expand().unwrap().len(), 131_072);
+    }
+}
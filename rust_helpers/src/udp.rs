@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::sync::Semaphore;
+use tokio::time::timeout;
+use pyo3::prelude::*;
+
+use crate::dns;
+use crate::scanner::runtime;
+use crate::scope::authorize_targets;
+use crate::secret::Secret;
+
+// =============================================================================
+// UDP Service Discovery
+// =============================================================================
+//
+// One well-formed query per protocol; a reply of any kind proves the service
+// exists, and a minimal parse extracts the interesting bit of the answer.
+
+struct UdpProbe {
+    name: &'static str,
+    port: u16,
+    /// Query for (transaction id, target, SNMP community)
+    build: fn(u16, Ipv4Addr, &Secret) -> Vec<u8>,
+    parse: fn(&[u8]) -> Option<String>,
+}
+
+const UDP_PROBES: &[UdpProbe] = &[
+    UdpProbe { name: "dns", port: 53, build: build_dns_version, parse: parse_dns_version },
+    UdpProbe { name: "ntp", port: 123, build: build_ntp, parse: parse_ntp },
+    UdpProbe { name: "snmp", port: 161, build: build_snmp_sysdescr, parse: parse_snmp_sysdescr },
+    UdpProbe { name: "mdns", port: 5353, build: build_mdns_ptr, parse: parse_mdns_ptr },
+    UdpProbe { name: "nbns", port: 137, build: build_nbns_status, parse: parse_nbns_status },
+];
+
+/// DNS CHAOS TXT query for version.bind
+fn build_dns_version(id: u16, _ip: Ipv4Addr, _community: &Secret) -> Vec<u8> {
+    dns::build_query(id, "version.bind", dns::TYPE_TXT, dns::CLASS_CH, false)
+}
+
+fn parse_dns_version(packet: &[u8]) -> Option<String> {
+    let response = dns::parse_response(packet)?;
+    let version = response
+        .answers
+        .iter()
+        .find(|a| a.rtype == dns::TYPE_TXT)
+        .map(|a| dns::txt_strings(&a.rdata).join(" "));
+    Some(match version {
+        Some(v) if !v.is_empty() => format!("version: {}", v),
+        _ => format!("responded (rcode {})", response.rcode),
+    })
+}
+
+/// NTP v3 client (mode 3) request
+fn build_ntp(_id: u16, _ip: Ipv4Addr, _community: &Secret) -> Vec<u8> {
+    let mut packet = vec![0u8; 48];
+    packet[0] = 0x1B;
+    packet
+}
+
+fn parse_ntp(packet: &[u8]) -> Option<String> {
+    if packet.len() < 48 || packet[0] & 0x07 != 4 {
+        return None;
+    }
+    let stratum = packet[1];
+    let refid = &packet[12..16];
+    let refid = if stratum <= 1 {
+        String::from_utf8_lossy(refid).trim_end_matches('\0').to_string()
+    } else {
+        Ipv4Addr::new(refid[0], refid[1], refid[2], refid[3]).to_string()
+    };
+    Some(format!("stratum {}, refid {}", stratum, refid))
+}
+
+/// OID 1.3.6.1.2.1.1.1.0 (sysDescr.0), BER encoded
+const SYSDESCR_OID: &[u8] = &[0x06, 0x08, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x01, 0x00];
+
+/// BER type-length-value with a definite-form length
+fn ber(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    match content.len() {
+        len if len < 0x80 => out.push(len as u8),
+        len if len <= 0xFF => out.extend_from_slice(&[0x81, len as u8]),
+        len => out.extend_from_slice(&[0x82, (len >> 8) as u8, len as u8]),
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+/// SNMPv2c GetRequest for sysDescr.0
+fn build_snmp_sysdescr(id: u16, _ip: Ipv4Addr, community: &Secret) -> Vec<u8> {
+    let varbind = ber(0x30, &[SYSDESCR_OID, &[0x05, 0x00]].concat());
+    let pdu = ber(0xa0, &[
+        &[0x02, 0x04, 0x00, 0x00, (id >> 8) as u8, id as u8][..],
+        &[0x02, 0x01, 0x00],
+        &[0x02, 0x01, 0x00],
+        &ber(0x30, &varbind),
+    ].concat());
+    let version = [0x02, 0x01, 0x01];
+    ber(0x30, &[&version[..], &ber(0x04, community.expose().as_bytes()), &pdu].concat())
+}
+
+fn parse_snmp_sysdescr(packet: &[u8]) -> Option<String> {
+    if packet.first() != Some(&0x30) {
+        return None;
+    }
+    let pos = packet
+        .windows(SYSDESCR_OID.len())
+        .position(|w| w == SYSDESCR_OID)?;
+    let mut i = pos + SYSDESCR_OID.len();
+    if packet.get(i) != Some(&0x04) {
+        return Some("responded".to_string());
+    }
+    i += 1;
+    let mut len = *packet.get(i)? as usize;
+    i += 1;
+    if len & 0x80 != 0 {
+        let n = len & 0x7F;
+        len = packet.get(i..i + n)?.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        i += n;
+    }
+    let value = packet.get(i..(i + len).min(packet.len()))?;
+    Some(String::from_utf8_lossy(value).trim().to_string())
+}
+
+/// Unicast mDNS reverse (PTR) query; answering hosts return their .local name
+fn build_mdns_ptr(id: u16, ip: Ipv4Addr, _community: &Secret) -> Vec<u8> {
+    dns::build_query(id, &dns::reverse_name_v4(ip), dns::TYPE_PTR, dns::CLASS_IN, false)
+}
+
+fn parse_mdns_ptr(packet: &[u8]) -> Option<String> {
+    let response = dns::parse_response(packet)?;
+    let answer = response.answers.iter().find(|a| a.rtype == dns::TYPE_PTR);
+    Some(match answer {
+        Some(a) => dns::read_name(packet, a.rdata_offset)
+            .map(|(name, _)| name)
+            .unwrap_or_default(),
+        None => "responded".to_string(),
+    })
+}
+
+/// NetBIOS node status (NBSTAT) request for the wildcard name
+fn build_nbns_status(id: u16, _ip: Ipv4Addr, _community: &Secret) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(50);
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 0, 0, 0]);
+    packet.push(0x20);
+    let mut name = [0u8; 16];
+    name[0] = b'*';
+    for byte in name {
+        packet.push(b'A' + (byte >> 4));
+        packet.push(b'A' + (byte & 0x0F));
+    }
+    packet.push(0);
+    packet.extend_from_slice(&[0x00, 0x21, 0x00, 0x01]);
+    packet
+}
+
+fn parse_nbns_status(packet: &[u8]) -> Option<String> {
+    let response = dns::parse_response(packet)?;
+    let rdata = &response.answers.first()?.rdata;
+    let count = *rdata.first()? as usize;
+    let mut names = Vec::new();
+    for i in 0..count {
+        let entry = rdata.get(1 + i * 18..1 + (i + 1) * 18)?;
+        let suffix = entry[15];
+        let is_group = entry[16] & 0x80 != 0;
+        let name = String::from_utf8_lossy(&entry[..15]).trim().to_string();
+        if suffix == 0x00 && !is_group {
+            names.insert(0, name);
+        } else if suffix == 0x00 {
+            names.push(format!("group {}", name));
+        }
+    }
+    let mac = rdata.get(1 + count * 18..1 + count * 18 + 6).map(|m| {
+        m.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(":")
+    });
+    let mut detail = names.join(", ");
+    if let Some(mac) = mac {
+        detail.push_str(&format!(" (mac {})", mac));
+    }
+    Some(detail.trim().to_string())
+}
+
+/// Send one datagram and wait for a single reply
+async fn udp_query(ip: Ipv4Addr, port: u16, payload: &[u8], timeout_ms: u64) -> Option<Vec<u8>> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await.ok()?;
+    socket.connect(SocketAddr::from((ip, port))).await.ok()?;
+    socket.send(payload).await.ok()?;
+
+    let mut buf = vec![0u8; 2048];
+    match timeout(Duration::from_millis(timeout_ms), socket.recv(&mut buf)).await {
+        Ok(Ok(n)) => {
+            buf.truncate(n);
+            Some(buf)
+        }
+        _ => None,
+    }
+}
+
+/// Send one query per protocol (DNS, NTP, SNMP, mDNS, NBNS) to each host and
+/// report which protocols answered with what: {ip: {protocol: detail}}
+///
+/// SNMP is queried with `snmp_community` (a `Secret` or str, default
+/// "public"). Hosts outside `set_authorized_scopes` are refused unless
+/// `force=True`.
+#[pyfunction]
+#[pyo3(signature = (ips, timeout_ms=1000, max_concurrent=256, force=false, snmp_community=None))]
+pub fn udp_service_sweep(
+    py: Python,
+    ips: Vec<String>,
+    timeout_ms: u64,
+    max_concurrent: usize,
+    force: bool,
+    snmp_community: Option<Secret>,
+) -> PyResult<HashMap<String, HashMap<String, String>>> {
+    let community = Arc::new(snmp_community.unwrap_or_else(|| Secret::new("public")));
+    let targets: Vec<Ipv4Addr> = ips
+        .iter()
+        .map(|ip| ip.trim().parse::<Ipv4Addr>())
+        .collect::<Result<_, _>>()
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid IP: {}", e)))?;
+    authorize_targets(py, "udp_service_sweep", &ips, force)?;
+
+    let answers = py.allow_threads(|| {
+        runtime().block_on(async {
+            let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+            let mut handles = Vec::new();
+
+            for (n, ip) in targets.into_iter().enumerate() {
+                for probe in UDP_PROBES {
+                    let sem = semaphore.clone();
+                    let community = community.clone();
+                    let id = (n as u16).wrapping_mul(7).wrapping_add(probe.port);
+                    handles.push(tokio::spawn(async move {
+                        let _permit = sem.acquire_owned().await.ok()?;
+                        let reply = udp_query(ip, probe.port, &(probe.build)(id, ip, &community), timeout_ms).await?;
+                        let detail = (probe.parse)(&reply).unwrap_or_else(|| "responded".to_string());
+                        Some((ip.to_string(), probe.name, detail))
+                    }));
+                }
+            }
+
+            let mut answers: HashMap<String, HashMap<String, String>> = HashMap::new();
+            for handle in handles {
+                if let Ok(Some((ip, protocol, detail))) = handle.await {
+                    answers.entry(ip).or_default().insert(protocol.to_string(), detail);
+                }
+            }
+            answers
+        })
+    });
+
+    Ok(answers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snmp_request_carries_the_community() {
+        let ip = Ipv4Addr::LOCALHOST;
+        let public = build_snmp_sysdescr(0x1234, ip, &Secret::new("public"));
+        let mut expected = vec![
+            0x30, 0x29, 0x02, 0x01, 0x01, 0x04, 0x06, b'p', b'u', b'b', b'l', b'i', b'c',
+            0xa0, 0x1c, 0x02, 0x04, 0x00, 0x00, 0x12, 0x34, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00,
+            0x30, 0x0e, 0x30, 0x0c,
+        ];
+        expected.extend_from_slice(SYSDESCR_OID);
+        expected.extend_from_slice(&[0x05, 0x00]);
+        assert_eq!(public, expected);
+
+        let long = "c".repeat(200);
+        let packet = build_snmp_sysdescr(1, ip, &Secret::new(&long));
+        assert_eq!(&packet[..3], &[0x30, 0x81, (packet.len() - 3) as u8]);
+        assert!(packet.windows(long.len()).any(|w| w == long.as_bytes()));
+    }
+}